@@ -0,0 +1,199 @@
+//! ABI覆盖矩阵与原生代码体积统计
+//!
+//! 按`lib/<abi>/`目录汇总APK支持的ABI、各ABI的压缩/解压体积，并列出每个原生库在哪些ABI中存在。
+//! 某个库只在部分ABI中提供时，设备若选中缺少该库的ABI就会在加载时崩溃，这类情况会被标记。
+//! aapt2输出中的`native-code:`/`alt-native-code:`行可与目录统计结果相互印证；清单声明
+//! `extractNativeLibs=false`时，还检查原生库是否按平台直接加载的要求存储。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::manifest::AndroidManifest;
+
+/// 已知的ABI，按常见的优先级排列
+pub const KNOWN_ABIS: &[&str] = &["arm64-v8a", "armeabi-v7a", "armeabi", "x86_64", "x86", "riscv64", "mips64", "mips"];
+
+/// 不解压原生库时，库数据在APK中须按此对齐才能直接映射
+const NATIVE_LIBRARY_ALIGNMENT: u64 = 4096;
+
+/// 单个ABI的原生代码统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbiNativeSize {
+    pub abi: String,
+    /// 是否为Android已知的ABI名称
+    pub known: bool,
+    /// `.so`文件数量
+    pub library_count: usize,
+    /// 目录下全部文件的压缩体积
+    pub compressed_size: u64,
+    /// 目录下全部文件的解压体积
+    pub uncompressed_size: u64,
+}
+
+/// 单个原生库在各ABI中的分布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryCoverage {
+    /// 库文件名，如`libfoo.so`
+    pub name: String,
+    pub present_in: Vec<String>,
+    pub missing_from: Vec<String>,
+}
+
+/// ABI覆盖报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AbiCoverage {
+    /// APK中包含原生代码的ABI
+    pub supported_abis: Vec<String>,
+    pub sizes: Vec<AbiNativeSize>,
+    /// 每个原生库的ABI分布
+    pub matrix: Vec<LibraryCoverage>,
+    /// 所有ABI的库集合是否一致
+    pub consistent: bool,
+    /// 库集合不一致的说明
+    pub inconsistencies: Vec<String>,
+    pub total_compressed_size: u64,
+    pub total_uncompressed_size: u64,
+    /// aapt2输出的`native-code:`
+    pub aapt_native_code: Option<Vec<String>>,
+    /// aapt2输出的`alt-native-code:`
+    pub aapt_alt_native_code: Option<Vec<String>>,
+    /// aapt2结果与目录统计不一致的说明
+    pub aapt_mismatch: Option<String>,
+    /// 清单中的`extractNativeLibs`值
+    pub extract_native_libs: Option<bool>,
+    /// 原生库存储方式与清单声明不符的说明
+    pub manifest_mismatches: Vec<String>,
+}
+
+impl AbiCoverage {
+    /// 统计APK中`lib/<abi>/`下的文件
+    pub fn from_archive(archive: &mut ZipArchive<File>) -> Self {
+        let mut sizes: BTreeMap<String, AbiNativeSize> = BTreeMap::new();
+        let mut libraries: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for i in 0..archive.len() {
+            let Ok(entry) = archive.by_index_raw(i) else {
+                continue;
+            };
+            let mut parts = entry.name().split('/');
+            let (Some("lib"), Some(abi), Some(file), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            if abi.is_empty() || file.is_empty() {
+                continue;
+            }
+            let size = sizes.entry(abi.to_string()).or_insert_with(|| AbiNativeSize {
+                abi: abi.to_string(),
+                known: KNOWN_ABIS.contains(&abi),
+                library_count: 0,
+                compressed_size: 0,
+                uncompressed_size: 0,
+            });
+            size.compressed_size += entry.compressed_size();
+            size.uncompressed_size += entry.size();
+            if file.ends_with(".so") {
+                size.library_count += 1;
+                libraries.entry(file.to_string()).or_default().insert(abi.to_string());
+            }
+        }
+
+        let mut sizes: Vec<AbiNativeSize> = sizes.into_values().collect();
+        sizes.sort_by_key(|size| abi_rank(&size.abi));
+        let supported_abis: Vec<String> = sizes.iter()
+            .filter(|size| size.library_count > 0)
+            .map(|size| size.abi.clone())
+            .collect();
+
+        let mut inconsistencies = Vec::new();
+        let matrix: Vec<LibraryCoverage> = libraries.into_iter()
+            .map(|(name, present)| {
+                let present_in: Vec<String> = supported_abis.iter().filter(|abi| present.contains(*abi)).cloned().collect();
+                let missing_from: Vec<String> = supported_abis.iter().filter(|abi| !present.contains(*abi)).cloned().collect();
+                if !missing_from.is_empty() {
+                    inconsistencies.push(format!(
+                        "{} 存在于 {}，但缺少于 {}，在选中后者的设备上加载会失败",
+                        name, present_in.join("、"), missing_from.join("、")
+                    ));
+                }
+                LibraryCoverage { name, present_in, missing_from }
+            })
+            .collect();
+
+        AbiCoverage {
+            consistent: inconsistencies.is_empty(),
+            total_compressed_size: sizes.iter().map(|size| size.compressed_size).sum(),
+            total_uncompressed_size: sizes.iter().map(|size| size.uncompressed_size).sum(),
+            supported_abis,
+            sizes,
+            matrix,
+            inconsistencies,
+            aapt_native_code: None,
+            aapt_alt_native_code: None,
+            aapt_mismatch: None,
+            extract_native_libs: None,
+            manifest_mismatches: Vec::new(),
+        }
+    }
+
+    /// 记录aapt2输出的`native-code:`与`alt-native-code:`，并与目录统计比较
+    pub fn relate_aapt_output(&mut self, aapt_output: &str) {
+        self.aapt_native_code = aapt_abi_list(aapt_output, "native-code:");
+        self.aapt_alt_native_code = aapt_abi_list(aapt_output, "alt-native-code:");
+
+        let reported: BTreeSet<&str> = self.aapt_native_code.iter()
+            .chain(self.aapt_alt_native_code.iter())
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let found: BTreeSet<&str> = self.supported_abis.iter().map(String::as_str).collect();
+        if reported != found {
+            self.aapt_mismatch = Some(format!(
+                "aapt2报告的ABI为[{}]，lib目录中为[{}]",
+                reported.into_iter().collect::<Vec<_>>().join(", "),
+                found.into_iter().collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+
+    /// 记录清单中的`extractNativeLibs`，为false时检查各ABI的原生库是否不压缩且按页对齐
+    ///
+    /// 平台不解压原生库时直接从APK映射，不满足要求的APK安装会失败
+    pub fn relate_manifest(&mut self, archive: &mut ZipArchive<File>, manifest: &AndroidManifest) {
+        self.extract_native_libs = manifest.application.as_ref()
+            .and_then(|application| application.extract_native_libs);
+        if self.extract_native_libs != Some(false) {
+            return;
+        }
+
+        for i in 0..archive.len() {
+            let Ok(entry) = archive.by_index_raw(i) else {
+                continue;
+            };
+            if !entry.name().starts_with("lib/") || !entry.name().ends_with(".so") {
+                continue;
+            }
+            if entry.compression() != CompressionMethod::Stored {
+                self.manifest_mismatches.push(format!("{} 被压缩存储，与extractNativeLibs=false不符", entry.name()));
+            } else if entry.data_start() % NATIVE_LIBRARY_ALIGNMENT != 0 {
+                self.manifest_mismatches.push(format!(
+                    "{} 的数据偏移0x{:x}未按4KB对齐，extractNativeLibs=false时无法直接加载",
+                    entry.name(), entry.data_start()
+                ));
+            }
+        }
+    }
+}
+
+/// 已知ABI按优先级排序，未知ABI排在最后
+fn abi_rank(abi: &str) -> (usize, String) {
+    (KNOWN_ABIS.iter().position(|known| *known == abi).unwrap_or(KNOWN_ABIS.len()), abi.to_string())
+}
+
+/// 解析aapt2输出中形如`native-code: 'arm64-v8a' 'x86_64'`的行
+fn aapt_abi_list(aapt_output: &str, prefix: &str) -> Option<Vec<String>> {
+    let line = aapt_output.lines().find_map(|line| line.trim().strip_prefix(prefix))?;
+    Some(line.split('\'').skip(1).step_by(2).map(String::from).collect())
+}
@@ -0,0 +1,271 @@
+//! Root检测、反调试与模拟器检测识别
+//!
+//! 在DEX字符串池、类定义、方法引用以及原生库中查找已知的检测手段，
+//! 每条结果都带有证据所在的位置（DEX文件或.so路径）。
+
+use std::fs::File;
+
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::dex::DexAnalysis;
+use crate::limits::ReadBudget;
+
+/// 每条规则在每个文件中最多记录的证据数
+const MAX_EVIDENCE_PER_FILE: usize = 5;
+/// 扫描原生库的大小上限
+const MAX_NATIVE_LIBRARY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 检测类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionCategory {
+    RootDetection,
+    AntiDebug,
+    EmulatorDetection,
+}
+
+/// 规则匹配的对象
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// DEX中定义了以此为前缀的类
+    ClassPrefix(&'static str),
+    /// DEX引用了该方法（`类名.方法名`）
+    MethodRef(&'static str),
+    /// DEX字符串池中包含该子串
+    DexString(&'static str),
+    /// 原生库中包含该字节串
+    NativeString(&'static str),
+}
+
+struct Rule {
+    category: ProtectionCategory,
+    technique: &'static str,
+    target: Target,
+}
+
+const fn rule(category: ProtectionCategory, technique: &'static str, target: Target) -> Rule {
+    Rule { category, technique, target }
+}
+
+use ProtectionCategory::{AntiDebug, EmulatorDetection, RootDetection};
+use Target::{ClassPrefix, DexString, MethodRef, NativeString};
+
+const RULES: &[Rule] = &[
+    // Root检测库与完整性校验
+    rule(RootDetection, "RootBeer", ClassPrefix("com.scottyab.rootbeer")),
+    rule(RootDetection, "RootBeer", MethodRef("com.scottyab.rootbeer.RootBeer.isRooted")),
+    rule(RootDetection, "SafetyNet Attestation", MethodRef("com.google.android.gms.safetynet.SafetyNetClient.attest")),
+    rule(RootDetection, "SafetyNet Attestation", ClassPrefix("com.google.android.gms.safetynet")),
+    rule(RootDetection, "Play Integrity", MethodRef("com.google.android.play.core.integrity.IntegrityManager.requestIntegrityToken")),
+    rule(RootDetection, "Play Integrity", MethodRef("com.google.android.play.core.integrity.StandardIntegrityManager.prepareIntegrityToken")),
+    rule(RootDetection, "Play Integrity", ClassPrefix("com.google.android.play.core.integrity")),
+    // su路径
+    rule(RootDetection, "su路径检查", DexString("/system/xbin/su")),
+    rule(RootDetection, "su路径检查", DexString("/system/bin/su")),
+    rule(RootDetection, "su路径检查", DexString("/sbin/su")),
+    rule(RootDetection, "su路径检查", DexString("/su/bin/su")),
+    rule(RootDetection, "su路径检查", DexString("/data/local/xbin/su")),
+    rule(RootDetection, "su路径检查", DexString("/data/local/bin/su")),
+    rule(RootDetection, "su路径检查", DexString("/system/bin/failsafe/su")),
+    rule(RootDetection, "su路径检查", DexString("/system/app/Superuser.apk")),
+    rule(RootDetection, "su路径检查", NativeString("/system/xbin/su")),
+    rule(RootDetection, "su路径检查", NativeString("/system/bin/su")),
+    rule(RootDetection, "su路径检查", NativeString("/sbin/su")),
+    rule(RootDetection, "系统签名检查", DexString("test-keys")),
+    // Root管理应用
+    rule(RootDetection, "Root管理应用检查", DexString("com.topjohnwu.magisk")),
+    rule(RootDetection, "Root管理应用检查", DexString("eu.chainfire.supersu")),
+    rule(RootDetection, "Root管理应用检查", DexString("com.noshufou.android.su")),
+    rule(RootDetection, "Root管理应用检查", DexString("com.koushikdutta.superuser")),
+    rule(RootDetection, "Root管理应用检查", DexString("com.kingroot.kinguser")),
+    // Magisk / Xposed / Frida
+    rule(RootDetection, "Magisk检测", DexString("/sbin/.magisk")),
+    rule(RootDetection, "Magisk检测", DexString("/data/adb/magisk")),
+    rule(RootDetection, "Magisk检测", NativeString("magisk")),
+    rule(RootDetection, "Xposed检测", DexString("de.robv.android.xposed")),
+    rule(RootDetection, "Xposed检测", DexString("XposedBridge")),
+    rule(RootDetection, "Xposed检测", NativeString("XposedBridge")),
+    rule(AntiDebug, "Frida检测", DexString("frida-server")),
+    rule(AntiDebug, "Frida检测", DexString("frida-agent")),
+    rule(AntiDebug, "Frida检测", DexString("re.frida.server")),
+    rule(AntiDebug, "Frida检测", NativeString("frida")),
+    rule(AntiDebug, "Frida检测", NativeString("gum-js-loop")),
+    rule(AntiDebug, "Frida检测", NativeString("linjector")),
+    // 反调试
+    rule(AntiDebug, "调试器检测", MethodRef("android.os.Debug.isDebuggerConnected")),
+    rule(AntiDebug, "调试器检测", MethodRef("android.os.Debug.waitingForDebugger")),
+    rule(AntiDebug, "TracerPid检查", DexString("TracerPid")),
+    rule(AntiDebug, "TracerPid检查", NativeString("TracerPid")),
+    rule(AntiDebug, "ptrace", NativeString("ptrace")),
+    // 模拟器检测
+    rule(EmulatorDetection, "QEMU属性检查", DexString("ro.kernel.qemu")),
+    rule(EmulatorDetection, "QEMU属性检查", NativeString("ro.kernel.qemu")),
+    rule(EmulatorDetection, "QEMU设备文件检查", DexString("/dev/qemu_pipe")),
+    rule(EmulatorDetection, "QEMU设备文件检查", DexString("/dev/socket/qemud")),
+    rule(EmulatorDetection, "QEMU设备文件检查", NativeString("/dev/qemu_pipe")),
+    rule(EmulatorDetection, "模拟器硬件检查", DexString("goldfish")),
+    rule(EmulatorDetection, "模拟器硬件检查", DexString("ranchu")),
+    rule(EmulatorDetection, "模拟器硬件检查", NativeString("goldfish")),
+    rule(EmulatorDetection, "模拟器型号检查", DexString("sdk_gphone")),
+    rule(EmulatorDetection, "模拟器型号检查", DexString("generic_x86")),
+    rule(EmulatorDetection, "第三方模拟器检查", DexString("Genymotion")),
+    rule(EmulatorDetection, "第三方模拟器检查", DexString("vbox86")),
+    rule(EmulatorDetection, "第三方模拟器检查", DexString("com.bluestacks")),
+    rule(EmulatorDetection, "第三方模拟器检查", DexString("com.bignox")),
+    rule(EmulatorDetection, "第三方模拟器检查", DexString("com.mumu")),
+];
+
+/// 一条证据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionEvidence {
+    /// 证据所在位置，如`classes2.dex`或`lib/arm64-v8a/libfoo.so`
+    pub location: String,
+    /// 证据类型：类定义、方法引用、字符串、原生库字符串
+    pub kind: String,
+    /// 命中的内容
+    pub value: String,
+}
+
+/// 识别出的检测手段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionFinding {
+    pub category: ProtectionCategory,
+    /// 检测手段，如`RootBeer`、`su路径检查`
+    pub technique: String,
+    pub evidence: Vec<ProtectionEvidence>,
+}
+
+/// 检测结果汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AntiAnalysisReport {
+    pub root_detection: bool,
+    pub anti_debug: bool,
+    pub emulator_detection: bool,
+    pub findings: Vec<ProtectionFinding>,
+}
+
+impl AntiAnalysisReport {
+    fn add(&mut self, category: ProtectionCategory, technique: &str, evidence: ProtectionEvidence) {
+        let finding = match self.findings.iter_mut().find(|f| f.category == category && f.technique == technique) {
+            Some(finding) => finding,
+            None => {
+                self.findings.push(ProtectionFinding {
+                    category,
+                    technique: technique.to_string(),
+                    evidence: Vec::new(),
+                });
+                self.findings.last_mut().unwrap()
+            }
+        };
+        let same_file = finding.evidence.iter().filter(|e| e.location == evidence.location).count();
+        let duplicate = finding.evidence.iter()
+            .any(|e| e.location == evidence.location && e.value == evidence.value);
+        if same_file < MAX_EVIDENCE_PER_FILE && !duplicate {
+            finding.evidence.push(evidence);
+        }
+        match category {
+            ProtectionCategory::RootDetection => self.root_detection = true,
+            ProtectionCategory::AntiDebug => self.anti_debug = true,
+            ProtectionCategory::EmulatorDetection => self.emulator_detection = true,
+        }
+    }
+
+    /// 识别到的手段名称列表
+    pub fn techniques(&self) -> Vec<String> {
+        self.findings.iter().map(|f| f.technique.clone()).collect()
+    }
+}
+
+/// 扫描DEX与原生库
+pub fn scan(archive: &mut ZipArchive<File>, dex: Option<&DexAnalysis>, budget: &ReadBudget) -> AntiAnalysisReport {
+    let mut report = AntiAnalysisReport::default();
+    if let Some(dex) = dex {
+        scan_dex(dex, &mut report);
+    }
+    scan_native_libraries(archive, budget, &mut report);
+    report
+}
+
+fn scan_dex(analysis: &DexAnalysis, report: &mut AntiAnalysisReport) {
+    for (name, dex) in &analysis.files {
+        let evidence = |kind: &str, value: &str| ProtectionEvidence {
+            location: name.clone(),
+            kind: kind.to_string(),
+            value: value.to_string(),
+        };
+
+        let class_names = dex.class_names();
+        let method_names: Vec<String> = dex.method_ids.iter().filter_map(|m| dex.method_name(m)).collect();
+
+        for rule in RULES {
+            match rule.target {
+                ClassPrefix(prefix) => {
+                    if let Some(class) = class_names.iter().find(|c| c.starts_with(prefix)) {
+                        report.add(rule.category, rule.technique, evidence("类定义", class));
+                    }
+                }
+                MethodRef(method) => {
+                    if method_names.iter().any(|m| m == method) {
+                        report.add(rule.category, rule.technique, evidence("方法引用", method));
+                    }
+                }
+                DexString(needle) => {
+                    for string in dex.strings.iter().filter(|s| s.contains(needle)).take(MAX_EVIDENCE_PER_FILE) {
+                        report.add(rule.category, rule.technique, evidence("字符串", string));
+                    }
+                }
+                NativeString(_) => {}
+            }
+        }
+    }
+}
+
+fn scan_native_libraries(archive: &mut ZipArchive<File>, budget: &ReadBudget, report: &mut AntiAnalysisReport) {
+    let native_rules: Vec<&Rule> = RULES.iter().filter(|r| matches!(r.target, NativeString(_))).collect();
+    let pattern = native_rules.iter()
+        .filter_map(|r| match r.target {
+            NativeString(needle) => Some(regex::escape(needle)),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("|");
+    let Ok(regex) = Regex::new(&pattern) else {
+        return;
+    };
+
+    let libraries: Vec<String> = archive.file_names()
+        .filter(|name| name.starts_with("lib/") && name.ends_with(".so"))
+        .map(String::from)
+        .collect();
+    for library in libraries {
+        let Some(data) = archive.by_name(&library)
+            .ok()
+            .filter(|entry| entry.size() <= MAX_NATIVE_LIBRARY_SIZE)
+            .and_then(|entry| budget.read(entry).ok())
+        else {
+            continue;
+        };
+
+        for found in regex.find_iter(&data) {
+            let needle = String::from_utf8_lossy(found.as_bytes());
+            for rule in native_rules.iter().filter(|r| matches!(r.target, NativeString(n) if n == needle)) {
+                report.add(rule.category, rule.technique, ProtectionEvidence {
+                    location: library.clone(),
+                    kind: "原生库字符串".to_string(),
+                    value: printable_context(&data, found.start(), found.end()),
+                });
+            }
+        }
+    }
+}
+
+/// 命中位置所在的可打印字符串（向两侧扩展到不可打印字符）
+fn printable_context(data: &[u8], start: usize, end: usize) -> String {
+    const MAX_CONTEXT: usize = 80;
+    let printable = |b: &u8| (0x20..0x7f).contains(b);
+    let begin = data[..start].iter().rev().take(MAX_CONTEXT).take_while(|b| printable(b)).count();
+    let after = data[end..].iter().take(MAX_CONTEXT).take_while(|b| printable(b)).count();
+    String::from_utf8_lossy(&data[start - begin..end + after]).into_owned()
+}
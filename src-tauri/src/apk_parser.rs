@@ -5,17 +5,35 @@ use std::process::Command;
 use std::time::Instant;
 use zip::ZipArchive;
 use serde::{Serialize, Deserialize};
-use regex::Regex;
-use axmldecoder;
 use x509_parser;
-use sha1::{self, Sha1, Digest as Sha1Digest};
+use sha1::{self, Sha1};
 use sha2::{Sha256};
 use x509_parser::prelude::*;
 use reqwest;
 use md5;
 use digest;
-use chrono;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crate::abi::AbiCoverage;
+use crate::anti_analysis::{self, AntiAnalysisReport};
+use crate::cert_audit::{self, CertificateFinding};
+use crate::dex::DexAnalysis;
+use crate::elf::NativeLibraryAnalysis;
+use crate::framework::{self, DetectedFramework};
+use crate::sdk_detect::{self, DetectedSdk, SdkDatabase};
+use crate::arsc::{ManifestResources, ResourceTable};
+use crate::inventory::ContentInventory;
+use crate::limits::{LimitExceeded, ParseLimits, ReadBudget};
+use crate::manifest::AndroidManifest;
+use crate::obfuscation::{self, ObfuscationReport};
+use crate::page_size::{self, PageSizeReport};
+use crate::packer::{self, PackerReport};
+use crate::pinning::{self, CertificatePinningReport};
+use crate::pkcs7::{SignedData, SignerInfo};
+use crate::lineage::SigningCertificateLineage;
+use crate::signing_block::{SchemeSigner, SigningSchemes};
+use crate::size_breakdown::{self, SizeBreakdown};
+use crate::zip_audit::{self, ZipAuditReport};
+use crate::verifier::{self, VerificationResult};
 // use tauri;
 // use tauri::path::{BaseDirectory, PathResolver};
 
@@ -27,10 +45,24 @@ pub enum ApkParserError {
     Io(std::io::Error),
     /// ZIP文件错误
     Zip(zip::result::ZipError),
+    /// ZIP无法打开，附带不依赖zip库的结构检查结果
+    CorruptZip(zip::result::ZipError, Box<ZipAuditReport>),
     /// 无效的APK文件
     InvalidApk(String),
     /// 网络请求错误
     ReqwestError(reqwest::Error),
+    /// 无效的二进制XML
+    InvalidXml(String),
+    /// 无效的资源表
+    InvalidResources(String),
+    /// 无效的签名数据
+    InvalidSignature(String),
+    /// 无效的DEX文件
+    InvalidDex(String),
+    /// 无效的ELF文件
+    InvalidElf(String),
+    /// 超出解析限制
+    LimitExceeded(LimitExceeded),
 }
 
 impl std::fmt::Display for ApkParserError {
@@ -38,8 +70,22 @@ impl std::fmt::Display for ApkParserError {
         match self {
             ApkParserError::Io(err) => write!(f, "IO错误: {}", err),
             ApkParserError::Zip(err) => write!(f, "ZIP错误: {}", err),
+            ApkParserError::CorruptZip(err, audit) => {
+                write!(f, "ZIP错误: {}", err)?;
+                if !audit.anomalies.is_empty() {
+                    let messages: Vec<&str> = audit.anomalies.iter().map(|a| a.message.as_str()).collect();
+                    write!(f, "（结构检查发现{}处异常: {}）", messages.len(), messages.join("；"))?;
+                }
+                Ok(())
+            }
             ApkParserError::InvalidApk(msg) => write!(f, "无效的APK: {}", msg),
             ApkParserError::ReqwestError(err) => write!(f, "网络请求错误: {}", err),
+            ApkParserError::InvalidXml(msg) => write!(f, "无效的二进制XML: {}", msg),
+            ApkParserError::InvalidResources(msg) => write!(f, "无效的资源表: {}", msg),
+            ApkParserError::InvalidSignature(msg) => write!(f, "无效的签名数据: {}", msg),
+            ApkParserError::InvalidDex(msg) => write!(f, "无效的DEX文件: {}", msg),
+            ApkParserError::InvalidElf(msg) => write!(f, "无效的ELF文件: {}", msg),
+            ApkParserError::LimitExceeded(exceeded) => write!(f, "超出解析限制: {}", exceeded),
         }
     }
 }
@@ -73,10 +119,107 @@ pub struct SignatureInfo {
     pub valid_from: String,
     /// 有效期截止
     pub valid_to: String,
+    /// MD5指纹
+    pub fingerprint_md5: Option<String>,
     /// SHA1指纹
     pub fingerprint_sha1: Option<String>,
     /// SHA256指纹
     pub fingerprint_sha256: Option<String>,
+    /// SHA512指纹
+    pub fingerprint_sha512: Option<String>,
+    /// 签名块文件（如`META-INF/CERT.RSA`）
+    pub signature_file: Option<String>,
+    /// SignedData声明的摘要算法
+    pub digest_algorithms: Vec<String>,
+    /// 签名者信息
+    pub signers: Vec<SignerInfo>,
+    /// 证书链，签名者证书在前
+    pub certificate_chain: Vec<CertificateInfo>,
+    /// 签名方案：v1、v2、v3、v3.1
+    pub scheme: String,
+    /// 该签名者生效的最低SDK版本
+    pub min_sdk_version: Option<u32>,
+    /// 该签名者生效的最高SDK版本，None表示不设上限
+    pub max_sdk_version: Option<u32>,
+    /// 是否为最新平台上生效的当前签名者
+    pub is_current: bool,
+    /// 密钥轮换谱系（仅v3/v3.1），从最旧到当前证书排列
+    pub lineage: Option<SigningCertificateLineage>,
+}
+
+/// 某一SDK范围内生效的签名者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkRangeSigner {
+    pub min_sdk_version: u32,
+    /// None表示不设上限
+    pub max_sdk_version: Option<u32>,
+    /// 生效的签名方案
+    pub scheme: String,
+    pub subject: String,
+    pub fingerprint_sha256: Option<String>,
+}
+
+/// 证书信息结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    /// 证书主题
+    pub subject: String,
+    /// 证书颁发者
+    pub issuer: String,
+    /// 序列号（十六进制）
+    pub serial_number: String,
+    /// 有效期起始
+    pub valid_from: String,
+    /// 有效期截止
+    pub valid_to: String,
+    /// MD5指纹（基于完整DER编码，下同）
+    pub fingerprint_md5: String,
+    /// SHA1指纹
+    pub fingerprint_sha1: String,
+    /// SHA256指纹
+    pub fingerprint_sha256: String,
+    /// SHA512指纹
+    pub fingerprint_sha512: String,
+    /// 证书版本（1～3）
+    pub version: u32,
+    /// 证书签名算法
+    pub signature_algorithm: String,
+    /// 公钥算法
+    pub public_key_algorithm: String,
+    /// 公钥长度（位）
+    pub public_key_size: Option<u32>,
+    /// EC公钥所用的曲线
+    pub public_key_curve: Option<String>,
+    /// 主题的各RDN字段
+    pub subject_name: DistinguishedName,
+    /// 颁发者的各RDN字段
+    pub issuer_name: DistinguishedName,
+    /// 证书扩展
+    pub extensions: Vec<CertificateExtension>,
+}
+
+/// 证书名称中的常用RDN字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DistinguishedName {
+    /// 通用名称（CN）
+    pub common_name: Option<String>,
+    /// 组织（O）
+    pub organization: Option<String>,
+    /// 组织单位（OU）
+    pub organizational_unit: Option<String>,
+    /// 国家代码（C）
+    pub country: Option<String>,
+}
+
+/// 证书扩展
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateExtension {
+    pub oid: String,
+    /// 扩展名称，未知扩展为OID本身
+    pub name: String,
+    pub critical: bool,
+    /// 可读的扩展值，无法解析时为十六进制
+    pub value: String,
 }
 
 /// 权限信息结构体
@@ -118,8 +261,12 @@ pub struct ApkInfo {
     pub min_sdk: String,
     /// 目标SDK版本
     pub target_sdk: String,
-    /// 签名信息
+    /// 签名信息（当前签名者）
     pub signature_info: Option<SignatureInfo>,
+    /// 全部签名者，按方案从旧到新排列
+    pub signatures: Vec<SignatureInfo>,
+    /// 各SDK范围内生效的签名者
+    pub signers_by_sdk: Vec<SdkRangeSigner>,
     /// 权限列表
     pub permissions: Option<Vec<Permission>>,
     /// 文件信息
@@ -128,8 +275,73 @@ pub struct ApkInfo {
     pub main_activity: Option<String>,
     /// 应用图标（Base64编码）
     pub icon_base64: Option<String>,
+    /// 应用本地化名称（配置限定符 -> 名称）
+    pub app_labels: Option<std::collections::HashMap<String, String>>,
+    /// 清单中引用资源的解析结果
+    pub manifest_resources: Option<ManifestResources>,
+    /// 签名方案（v1/v2/v3/v3.1）
+    pub signing_schemes: Option<SigningSchemes>,
+    /// 各签名方案的校验结果
+    pub signature_verification: Option<VerificationResult>,
+    /// 调试密钥、测试密钥与弱签名参数等证书风险
+    pub certificate_findings: Vec<CertificateFinding>,
+    /// DEX文件统计与包树
+    pub dex_analysis: Option<DexAnalysis>,
+    /// 加固识别结果
+    pub packer: Option<PackerReport>,
+    /// 识别出的第三方SDK及证据
+    pub detected_sdks: Vec<DetectedSdk>,
+    /// 识别出的跨平台开发框架
+    pub frameworks: Vec<DetectedFramework>,
+    /// Root检测、反调试与模拟器检测
+    pub anti_analysis: Option<AntiAnalysisReport>,
+    /// 证书固定与自定义证书校验
+    pub certificate_pinning: Option<CertificatePinningReport>,
+    /// 混淆指标与评分
+    pub obfuscation: Option<ObfuscationReport>,
+    /// 原生库（ELF）架构、导出函数与加固检查
+    pub native_libraries: Option<NativeLibraryAnalysis>,
+    /// 16KB页面大小兼容性
+    pub page_size: Option<PageSizeReport>,
+    /// ABI覆盖矩阵与各ABI原生代码体积
+    pub abi_coverage: Option<AbiCoverage>,
+    /// ZIP结构异常与篡改迹象
+    pub zip_audit: Option<ZipAuditReport>,
+    /// 按类型与扩展名汇总的内容清单
+    pub content_inventory: Option<ContentInventory>,
+    /// 按路径汇总的体积树与下载体积估算
+    pub size_breakdown: Option<SizeBreakdown>,
+    /// 超出解析限制、结果可能不完整的阶段
+    pub limit_warnings: Vec<String>,
+    /// 清单字段由aapt2输出的文本提取而来（内置解析器失败时的回退），可能缺失或不准确
+    pub aapt_fallback: bool,
 }
 
+/// 根据Android文档列出的危险权限
+const DANGEROUS_PERMISSIONS: &[&str] = &[
+    "android.permission.READ_CALENDAR", "android.permission.WRITE_CALENDAR",
+    "android.permission.CAMERA", "android.permission.READ_CONTACTS",
+    "android.permission.WRITE_CONTACTS", "android.permission.GET_ACCOUNTS",
+    "android.permission.ACCESS_FINE_LOCATION", "android.permission.ACCESS_COARSE_LOCATION",
+    "android.permission.ACCESS_BACKGROUND_LOCATION", "android.permission.RECORD_AUDIO",
+    "android.permission.READ_PHONE_STATE", "android.permission.READ_PHONE_NUMBERS",
+    "android.permission.CALL_PHONE", "android.permission.ANSWER_PHONE_CALLS",
+    "android.permission.READ_CALL_LOG", "android.permission.WRITE_CALL_LOG",
+    "android.permission.ADD_VOICEMAIL", "android.permission.USE_SIP",
+    "android.permission.PROCESS_OUTGOING_CALLS", "android.permission.BODY_SENSORS",
+    "android.permission.BODY_SENSORS_BACKGROUND", "android.permission.ACTIVITY_RECOGNITION",
+    "android.permission.SEND_SMS", "android.permission.RECEIVE_SMS",
+    "android.permission.READ_SMS", "android.permission.RECEIVE_WAP_PUSH",
+    "android.permission.RECEIVE_MMS", "android.permission.READ_EXTERNAL_STORAGE",
+    "android.permission.WRITE_EXTERNAL_STORAGE", "android.permission.READ_MEDIA_IMAGES",
+    "android.permission.READ_MEDIA_VIDEO", "android.permission.READ_MEDIA_AUDIO",
+    "android.permission.MANAGE_EXTERNAL_STORAGE", "android.permission.USE_BIOMETRIC",
+    "android.permission.USE_FINGERPRINT", "android.permission.BLUETOOTH_CONNECT",
+    "android.permission.BLUETOOTH_SCAN", "android.permission.BLUETOOTH_ADVERTISE",
+    "android.permission.POST_NOTIFICATIONS", "android.permission.NEARBY_WIFI_DEVICES",
+    "android.permission.READ_MEDIA_VISUAL_USER_SELECTED"
+];
+
 /// APK解析器
 pub struct ApkParser;
 
@@ -142,73 +354,227 @@ impl ApkParser {
     /// # 返回
     /// 解析结果，包含APK信息或错误
     pub fn parse<P: AsRef<Path>>(apk_path: P) -> Result<ApkInfo, ApkParserError> {
+        Self::parse_with_limits(apk_path, ParseLimits::default())
+    }
+    
+    /// 按指定的解析限制解析APK文件
+    /// 
+    /// # 参数
+    /// * `apk_path` - APK文件路径
+    /// * `limits` - 条目大小、累计读取量、压缩比、条目数、XML深度、字符串池与阶段耗时的上限
+    /// 
+    /// # 返回
+    /// 解析结果，超出限制时返回`ApkParserError::LimitExceeded`
+    pub fn parse_with_limits<P: AsRef<Path>>(apk_path: P, limits: ParseLimits) -> Result<ApkInfo, ApkParserError> {
         println!("INFO: 开始解析APK: {:?}", apk_path.as_ref());
+        let budget = ReadBudget::new(limits);
         let start = Instant::now();
         
-        // 首先尝试使用aapt2直接获取APK信息
-        if let Some(aapt_info) = Self::dump_apk_info(&apk_path) {
-            println!("INFO: 成功使用aapt2提取APK信息，耗时: {:?}", start.elapsed());
-            // 解析aapt2输出以提取所需信息
-            if let Some(apk_info) = Self::parse_aapt_output(&aapt_info, &apk_path) {
-                println!("INFO: 解析aapt2输出完成，包名: {}", apk_info.package_name);
-                return Ok(apk_info);
-            } else {
-                println!("WARNING: 无法从aapt2输出解析APK信息，将使用备选方法");
-            }
-        } else {
-            println!("WARNING: 未能使用aapt2获取APK信息，将使用备选方法");
-        }
-        
-        // 如果aapt2失败，回退到原始解析方法
-        println!("INFO: 使用内部解析器解析APK...");
-        let _internal_start = Instant::now();
-        
         let file = File::open(apk_path.as_ref())?;
         let file_size = file.metadata()?.len();
         println!("INFO: APK文件大小: {} 字节", file_size);
 
-        let mut archive = ZipArchive::new(file)?;
+        // 检查ZIP结构异常，不依赖zip crate，结构损坏时同样输出结果
+        budget.start_phase("检查ZIP结构");
+        let zip_audit = Self::inspect_zip(apk_path.as_ref(), &budget);
+
+        let mut archive = match (ZipArchive::new(file), zip_audit.as_ref()) {
+            (Ok(archive), _) => archive,
+            (Err(e), Some(audit)) => return Err(ApkParserError::CorruptZip(e, Box::new(audit.clone()))),
+            (Err(e), None) => return Err(e.into()),
+        };
         println!("INFO: APK中包含 {} 个文件", archive.len());
+        budget.check_entry_count(archive.len())?;
         
-        // 提取清单文件
-        println!("INFO: 正在提取AndroidManifest.xml...");
+        // 使用内置解析器解析清单文件
+        println!("INFO: 正在解析AndroidManifest.xml...");
         let manifest_start = Instant::now();
-        let manifest_xml = Self::extract_manifest_xml(&mut archive, apk_path.as_ref())?;
-        println!("INFO: 提取AndroidManifest.xml完成，耗时: {:?}", manifest_start.elapsed());
+        budget.start_phase("解析AndroidManifest.xml");
+        let (manifest, aapt_output) = match Self::read_manifest(&mut archive, &budget) {
+            Ok(manifest) => (manifest, None),
+            // 超出限制时不再回退到aapt2
+            Err(e @ ApkParserError::LimitExceeded(_)) => return Err(e),
+            Err(e) => {
+                // 内置解析器失败时，回退到aapt2的badging输出构建清单
+                println!("WARNING: 内置解析器无法解析清单: {}，尝试使用aapt2", e);
+                let aapt_output = Self::dump_apk_info(&apk_path).ok_or(e)?;
+                let Some(manifest) = AndroidManifest::from_aapt_badging(&aapt_output) else {
+                    return Err(ApkParserError::InvalidApk("aapt2输出中未找到包信息".to_string()));
+                };
+                println!("WARN: 清单信息来自aapt2输出的文本提取，结果可能不完整");
+                (manifest, Some(aapt_output))
+            }
+        };
+        println!("INFO: 解析AndroidManifest.xml完成，耗时: {:?}", manifest_start.elapsed());
         
+        let apk_info = Self::analyze_archive(apk_path.as_ref(), archive, file_size, zip_audit, &manifest, &budget, aapt_output.as_deref())?;
+        println!("INFO: 解析完成，包名: {}，总耗时: {:?}", apk_info.package_name, start.elapsed());
+        Ok(apk_info)
+    }
+    
+    /// 基于已解析的清单分析APK其余内容，内置解析器与aapt2回退两条路径共用
+    ///
+    /// # 参数
+    /// * `aapt_output` - 清单来自aapt2时的badging输出，用于补充ABI信息并标记结果
+    fn analyze_archive(
+        apk_path: &Path,
+        mut archive: ZipArchive<File>,
+        file_size: u64,
+        zip_audit: Option<ZipAuditReport>,
+        manifest: &AndroidManifest,
+        budget: &ReadBudget,
+        aapt_output: Option<&str>,
+    ) -> Result<ApkInfo, ApkParserError> {
         // 解析包信息
-        println!("INFO: 解析包信息...");
-        let package_start = Instant::now();
-        let (package_name, version_name, version_code, min_sdk, target_sdk, main_activity) = 
-            Self::parse_package_info(&manifest_xml)?;
-        println!("INFO: 解析包信息完成，耗时: {:?}", package_start.elapsed());
+        let unknown = || "未知".to_string();
+        let package_name = manifest.package.clone();
+        let version_name = manifest.version_name.clone().unwrap_or_else(unknown);
+        let version_code = manifest.version_code.map(|c| c.to_string()).unwrap_or_else(unknown);
+        let min_sdk = manifest.min_sdk_version().map(|v| v.to_string()).unwrap_or_else(unknown);
+        let target_sdk = manifest.target_sdk_version().map(|v| v.to_string()).unwrap_or_else(unknown);
+        let main_activity = manifest.main_activity();
         println!("INFO: 包名: {}, 版本: {}, 版本号: {}", package_name, version_name, version_code);
         
         // 解析签名信息
         println!("INFO: 解析签名信息...");
         let signature_start = Instant::now();
-        let signature_info = Self::parse_signature_info(&mut archive)?;
+        budget.start_phase("解析签名信息");
+        let v1_signatures = Self::parse_v1_signatures(&mut archive, budget);
+        let signing_schemes = Self::parse_signing_schemes(apk_path, !v1_signatures.is_empty());
+        let (signatures, signers_by_sdk) = Self::collect_signatures(v1_signatures, signing_schemes.as_ref());
+        let signature_info = Self::current_signature(&signatures);
+        let certificate_findings = cert_audit::classify_signatures(&signatures);
+        for finding in &certificate_findings {
+            println!("WARN: 证书风险 [{:?}] {}", finding.severity, finding.title);
+        }
         println!("INFO: 解析签名信息完成，耗时: {:?}", signature_start.elapsed());
         
+        // 校验签名
+        println!("INFO: 校验签名...");
+        let verification_start = Instant::now();
+        budget.start_phase("校验签名");
+        let signature_verification = Self::verify_signatures(apk_path, budget);
+        println!("INFO: 签名校验完成，耗时: {:?}", verification_start.elapsed());
+        
         // 解析权限
         println!("INFO: 解析权限...");
         let permissions_start = Instant::now();
-        let permissions = Self::parse_permissions(&manifest_xml)?;
+        let permissions = Self::parse_permissions(manifest);
         println!("INFO: 解析到 {} 个权限，耗时: {:?}", permissions.len(), permissions_start.elapsed());
         let dangerous_count = permissions.iter().filter(|p| p.is_dangerous).count();
         println!("INFO: 其中包含 {} 个危险权限", dangerous_count);
         
+        // 解析清单引用的资源
+        println!("INFO: 解析resources.arsc...");
+        let resources_start = Instant::now();
+        budget.start_phase("解析resources.arsc");
+        let manifest_resources = Self::load_resource_table(&mut archive, budget)
+            .map(|table| Self::resolve_manifest_resources(manifest, &table));
+        let app_labels = manifest_resources.as_ref().and_then(Self::collect_app_labels);
+        println!("INFO: 资源解析完成，耗时: {:?}", resources_start.elapsed());
+        
+        // 解析DEX文件
+        println!("INFO: 解析DEX文件...");
+        let dex_start = Instant::now();
+        budget.start_phase("解析DEX文件");
+        let dex_analysis = DexAnalysis::from_archive(&mut archive, budget);
+        for error in &dex_analysis.errors {
+            println!("WARN: DEX解析失败: {}", error);
+        }
+        println!("INFO: 解析到 {} 个DEX，{} 个类，{} 个方法引用，耗时: {:?}",
+            dex_analysis.dex_files.len(), dex_analysis.total_classes, dex_analysis.total_methods, dex_start.elapsed());
+        
+        // 识别加固
+        println!("INFO: 识别加固...");
+        let packer_start = Instant::now();
+        budget.start_phase("识别加固");
+        let packer = Self::detect_packer(&mut archive, Some(&dex_analysis), Some(manifest), budget);
+        println!("INFO: 加固识别完成，耗时: {:?}", packer_start.elapsed());
+        
+        // 识别第三方SDK
+        println!("INFO: 识别第三方SDK...");
+        let sdk_start = Instant::now();
+        budget.start_phase("识别第三方SDK");
+        let detected_sdks = Self::detect_sdks(&archive, Some(&dex_analysis), Some(manifest));
+        println!("INFO: 识别到 {} 个第三方SDK，耗时: {:?}", detected_sdks.len(), sdk_start.elapsed());
+        
+        // 识别跨平台框架
+        println!("INFO: 识别跨平台框架...");
+        let framework_start = Instant::now();
+        budget.start_phase("识别跨平台框架");
+        let frameworks = Self::detect_frameworks(&mut archive, Some(&dex_analysis), Some(manifest), budget);
+        println!("INFO: 识别到 {} 个跨平台框架，耗时: {:?}", frameworks.len(), framework_start.elapsed());
+        
+        // 识别Root检测、反调试与模拟器检测
+        println!("INFO: 识别Root检测与反调试手段...");
+        let anti_analysis_start = Instant::now();
+        budget.start_phase("识别Root检测与反调试手段");
+        let anti_analysis = Self::scan_anti_analysis(&mut archive, Some(&dex_analysis), budget);
+        println!("INFO: 识别到 {} 种检测手段，耗时: {:?}", anti_analysis.findings.len(), anti_analysis_start.elapsed());
+        
+        // 识别证书固定
+        println!("INFO: 识别证书固定...");
+        let pinning_start = Instant::now();
+        budget.start_phase("识别证书固定");
+        let certificate_pinning = Self::detect_certificate_pinning(&mut archive, Some(&dex_analysis), manifest_resources.as_ref(), budget);
+        println!("INFO: 证书固定识别完成，耗时: {:?}", pinning_start.elapsed());
+        
+        // 计算混淆评分
+        println!("INFO: 计算混淆评分...");
+        let obfuscation_start = Instant::now();
+        budget.start_phase("计算混淆评分");
+        let obfuscation = Self::detect_obfuscation(&archive, &dex_analysis);
+        println!("INFO: 混淆评分计算完成，耗时: {:?}", obfuscation_start.elapsed());
+        
+        // 解析原生库
+        println!("INFO: 解析原生库...");
+        let native_start = Instant::now();
+        budget.start_phase("解析原生库");
+        let native_libraries = Self::analyze_native_libraries(&mut archive, budget);
+        println!("INFO: 解析到 {} 个原生库，耗时: {:?}", native_libraries.libraries.len(), native_start.elapsed());
+        
+        // 检查16KB页面大小兼容性
+        println!("INFO: 检查16KB页面大小兼容性...");
+        let page_size_start = Instant::now();
+        budget.start_phase("检查16KB页面大小兼容性");
+        let page_size = Self::check_page_size(&mut archive, &native_libraries, Some(manifest));
+        println!("INFO: 页面大小检查完成，耗时: {:?}", page_size_start.elapsed());
+        
+        // 统计ABI覆盖
+        println!("INFO: 统计ABI覆盖...");
+        let abi_start = Instant::now();
+        budget.start_phase("统计ABI覆盖");
+        let abi_coverage = Self::analyze_abi_coverage(&mut archive, Some(manifest), aapt_output);
+        println!("INFO: 支持 {} 个ABI，耗时: {:?}", abi_coverage.supported_abis.len(), abi_start.elapsed());
+        
+        // 统计内容清单
+        println!("INFO: 统计内容清单...");
+        let inventory_start = Instant::now();
+        budget.start_phase("统计内容清单");
+        let content_inventory = Self::build_content_inventory(&mut archive, budget);
+        println!("INFO: 内容清单统计完成，耗时: {:?}", inventory_start.elapsed());
+        
+        // 分析APK体积
+        println!("INFO: 分析APK体积...");
+        let size_start = Instant::now();
+        budget.start_phase("分析APK体积");
+        let size_breakdown = Self::analyze_size(&mut archive, file_size, Some(manifest), budget);
+        println!("INFO: 体积分析完成，耗时: {:?}", size_start.elapsed());
+        
         // 提取应用图标
         println!("INFO: 提取应用图标...");
         let icon_start = Instant::now();
-        let icon_base64 = Self::extract_icon(apk_path.as_ref());
+        budget.start_phase("提取应用图标");
+        let icon_base64 = Self::extract_icon(apk_path, budget);
         println!("INFO: 图标提取完成，耗时: {:?}", icon_start.elapsed());
+        // 清单、签名与资源之外的阶段超限时只记为警告，其结果可能不完整
+        let limit_warnings = budget.warnings();
         
         // 创建文件信息
         let file_info = Some(FileInfo {
-            md5: Self::calculate_md5_hash(apk_path.as_ref())?,
-            sha1: Self::calculate_file_hash::<Sha1>(apk_path.as_ref())?,
-            sha256: Self::calculate_file_hash::<Sha256>(apk_path.as_ref())?,
+            md5: Self::calculate_md5_hash(apk_path)?,
+            sha1: Self::calculate_file_hash::<Sha1>(apk_path)?,
+            sha256: Self::calculate_file_hash::<Sha256>(apk_path)?,
             file_size,
             file_type: "application/vnd.android.package-archive".to_string(),
             entry_count: archive.len() as u32,
@@ -221,82 +587,318 @@ impl ApkParser {
             min_sdk,
             target_sdk,
             signature_info,
+            signatures,
+            signers_by_sdk,
             permissions: Some(permissions),
             file_info,
             main_activity,
             icon_base64,
+            app_labels,
+            manifest_resources,
+            signing_schemes,
+            signature_verification,
+            certificate_findings,
+            dex_analysis: Some(dex_analysis),
+            packer: Some(packer),
+            detected_sdks,
+            frameworks,
+            anti_analysis: Some(anti_analysis),
+            certificate_pinning: Some(certificate_pinning),
+            obfuscation: Some(obfuscation),
+            native_libraries: Some(native_libraries),
+            page_size: Some(page_size),
+            abi_coverage: Some(abi_coverage),
+            zip_audit,
+            content_inventory: Some(content_inventory),
+            size_breakdown: Some(size_breakdown),
+            limit_warnings,
+            aapt_fallback: aapt_output.is_some(),
         })
     }
     
-    /// 从ZIP存档中提取AndroidManifest.xml文件
+    /// 根据壳入口类、特征库文件、assets和DEX布局识别加固
+    pub fn detect_packer(
+        archive: &mut ZipArchive<File>,
+        dex_analysis: Option<&DexAnalysis>,
+        manifest: Option<&AndroidManifest>,
+        budget: &ReadBudget,
+    ) -> PackerReport {
+        let report = packer::detect(archive, dex_analysis, manifest, budget);
+        for packer in &report.packers {
+            println!("INFO: 识别到加固: {} {}，{} 条证据",
+                packer.name, packer.version.as_deref().unwrap_or(""), packer.evidence.len());
+        }
+        if let Some(note) = &report.note {
+            println!("WARN: {}", note);
+        }
+        report
+    }
+    
+    /// 依据SDK特征库匹配DEX包树、清单组件、meta-data和原生库
+    pub fn detect_sdks(
+        archive: &ZipArchive<File>,
+        dex_analysis: Option<&DexAnalysis>,
+        manifest: Option<&AndroidManifest>,
+    ) -> Vec<DetectedSdk> {
+        let native_libraries = sdk_detect::native_library_paths(archive.file_names());
+        let detected = SdkDatabase::load().detect(dex_analysis, manifest, &native_libraries);
+        for sdk in &detected {
+            println!("INFO: 识别到SDK: {} ({:?})，{} 条证据", sdk.name, sdk.category, sdk.evidence.len());
+        }
+        detected
+    }
+    
+    /// 根据入口类、原生库、assets和DEX识别跨平台框架
+    pub fn detect_frameworks(
+        archive: &mut ZipArchive<File>,
+        dex_analysis: Option<&DexAnalysis>,
+        manifest: Option<&AndroidManifest>,
+        budget: &ReadBudget,
+    ) -> Vec<DetectedFramework> {
+        let frameworks = framework::detect(archive, dex_analysis, manifest, budget);
+        for framework in &frameworks {
+            println!("INFO: 识别到框架: {}，{} 条证据，{:?}", framework.name, framework.evidence.len(), framework.details);
+        }
+        frameworks
+    }
+    
+    /// 在DEX和原生库中识别Root检测、反调试与模拟器检测
+    pub fn scan_anti_analysis(
+        archive: &mut ZipArchive<File>,
+        dex_analysis: Option<&DexAnalysis>,
+        budget: &ReadBudget,
+    ) -> AntiAnalysisReport {
+        let report = anti_analysis::scan(archive, dex_analysis, budget);
+        for finding in &report.findings {
+            println!("INFO: 识别到{:?}: {}，{} 条证据", finding.category, finding.technique, finding.evidence.len());
+        }
+        report
+    }
+    
+    /// 从网络安全配置和DEX中识别证书固定及宽松的证书校验实现
+    pub fn detect_certificate_pinning(
+        archive: &mut ZipArchive<File>,
+        dex_analysis: Option<&DexAnalysis>,
+        manifest_resources: Option<&ManifestResources>,
+        budget: &ReadBudget,
+    ) -> CertificatePinningReport {
+        let config_path = manifest_resources
+            .and_then(|resources| resources.network_security_config.as_ref())
+            .and_then(|config| config.default_value());
+        let report = pinning::analyze(archive, dex_analysis, config_path, budget);
+        if report.uses_certificate_pinning {
+            println!("INFO: 识别到证书固定，{} 个主机，{} 个固定值", report.pinned_hosts.len(), report.pin_hashes.len());
+        }
+        for component in report.custom_trust_components.iter().filter(|c| c.permissive) {
+            println!("WARN: 宽松的证书校验实现: {} ({})", component.class_name, component.kind);
+        }
+        report
+    }
+    
+    /// 根据DEX和资源路径计算混淆评分
+    pub fn detect_obfuscation(archive: &ZipArchive<File>, dex_analysis: &DexAnalysis) -> ObfuscationReport {
+        let report = obfuscation::analyze(archive, dex_analysis);
+        println!("INFO: 混淆评分: {} ({:?})，推测工具: {}", report.score, report.level, report.tool.as_deref().unwrap_or("无"));
+        report
+    }
+    
+    /// 解析lib目录下的ELF原生库，报告加固情况与ABI目录不符的库
+    pub fn analyze_native_libraries(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> NativeLibraryAnalysis {
+        let analysis = NativeLibraryAnalysis::from_archive(archive, budget);
+        for error in &analysis.errors {
+            println!("WARN: 原生库解析失败: {}", error);
+        }
+        for library in &analysis.libraries {
+            if let Some(mismatch) = &library.abi_mismatch {
+                println!("WARN: 原生库ABI不符: {}，{}", library.path, mismatch);
+            }
+            if library.hardening.text_relocations {
+                println!("WARN: 原生库含有文本重定位: {}", library.path);
+            }
+        }
+        analysis
+    }
+    
+    /// 检查arm64-v8a与x86_64原生库是否兼容16KB页面大小
+    pub fn check_page_size(
+        archive: &mut ZipArchive<File>,
+        native_libraries: &NativeLibraryAnalysis,
+        manifest: Option<&AndroidManifest>,
+    ) -> PageSizeReport {
+        let report = page_size::check(archive, native_libraries, manifest);
+        for library in report.failures() {
+            println!("WARN: 原生库不兼容16KB页面: {}，{}", library.path, library.issues.join("；"));
+        }
+        for library in report.unknowns() {
+            println!("WARN: 无法判断原生库是否兼容16KB页面: {}，{}", library.path, library.issues.join("；"));
+        }
+        report
+    }
+    
+    /// 统计各ABI的原生库分布与体积，对照清单的`extractNativeLibs`检查库的存储方式，
+    /// 提供aapt2输出时与其`native-code:`比较
+    pub fn analyze_abi_coverage(
+        archive: &mut ZipArchive<File>,
+        manifest: Option<&AndroidManifest>,
+        aapt_output: Option<&str>,
+    ) -> AbiCoverage {
+        let mut coverage = AbiCoverage::from_archive(archive);
+        if let Some(manifest) = manifest {
+            coverage.relate_manifest(archive, manifest);
+        }
+        if let Some(aapt_output) = aapt_output {
+            coverage.relate_aapt_output(aapt_output);
+        }
+        for size in &coverage.sizes {
+            println!("INFO: ABI {}: {} 个原生库，压缩 {} 字节，解压 {} 字节",
+                size.abi, size.library_count, size.compressed_size, size.uncompressed_size);
+        }
+        for inconsistency in &coverage.inconsistencies {
+            println!("WARN: ABI覆盖不一致: {}", inconsistency);
+        }
+        for mismatch in &coverage.manifest_mismatches {
+            println!("WARN: 原生库存储方式与清单不符: {}", mismatch);
+        }
+        if let Some(mismatch) = &coverage.aapt_mismatch {
+            println!("WARN: {}", mismatch);
+        }
+        coverage
+    }
+    
+    /// 按魔数识别条目类型，汇总各类型与扩展名的数量、体积及内容修改时间范围
+    pub fn build_content_inventory(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> ContentInventory {
+        let inventory = ContentInventory::from_archive(archive, budget);
+        println!("INFO: 共 {} 个文件，解压后 {} 字节，修改时间 {} ~ {}",
+            inventory.file_count,
+            inventory.total_uncompressed_size,
+            inventory.earliest_modification.as_deref().unwrap_or("未知"),
+            inventory.latest_modification.as_deref().unwrap_or("未知"));
+        for group in &inventory.by_type {
+            println!("INFO: 内容类型 {}: {} 个，{} 字节", group.key, group.count, group.uncompressed_size);
+        }
+        inventory
+    }
+    
+    /// 按路径汇总体积并估算下载体积，列出最大的条目与本应压缩的条目
+    pub fn analyze_size(
+        archive: &mut ZipArchive<File>,
+        file_size: u64,
+        manifest: Option<&AndroidManifest>,
+        budget: &ReadBudget,
+    ) -> SizeBreakdown {
+        let extract_native_libs = manifest
+            .and_then(|manifest| manifest.application.as_ref())
+            .and_then(|application| application.extract_native_libs);
+        let breakdown = size_breakdown::analyze(archive, file_size, extract_native_libs, size_breakdown::DEFAULT_TOP_N, budget);
+        println!("INFO: 原始体积 {} 字节，估算下载体积 {} 字节", breakdown.file_size, breakdown.estimated_download_size);
+        for entry in &breakdown.uncompressed_entries {
+            println!("WARN: 条目未压缩存储: {}，压缩后可节省 {} 字节", entry.path, entry.savings);
+        }
+        breakdown
+    }
+    
+    /// 按字节检查ZIP结构，报告重复条目、头部不一致、伪加密、重叠与附加数据等异常
+    pub fn inspect_zip(apk_path: &Path, budget: &ReadBudget) -> Option<ZipAuditReport> {
+        let report = match zip_audit::inspect(apk_path, budget) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("WARN: ZIP结构检查失败: {}", e);
+                return None;
+            }
+        };
+        for anomaly in &report.anomalies {
+            println!("WARN: ZIP结构异常 [{:?}] {}: {}{}",
+                anomaly.severity, anomaly.kind, anomaly.message,
+                anomaly.entry.as_deref().map(|entry| format!("（{}）", entry)).unwrap_or_default());
+        }
+        Some(report)
+    }
+    
+    /// 从ZIP存档中加载resources.arsc资源表
     /// 
     /// # 参数
     /// * `archive` - ZIP存档
-    /// * `apk_path` - APK文件路径
+    /// * `budget` - 读取预算
     /// 
     /// # 返回
-    /// 清单文件内容或错误
-    pub fn extract_manifest_xml(archive: &mut ZipArchive<File>, apk_path: &Path) -> Result<String, ApkParserError> {
-        println!("INFO: 开始提取AndroidManifest.xml...");
-        // 获取AndroidManifest.xml
-        let result = match archive.by_name("AndroidManifest.xml") {
-            Ok(mut manifest_entry) => {
-                println!("INFO: 找到AndroidManifest.xml，大小: {} 字节", manifest_entry.size());
-                let mut buffer = Vec::new();
-                manifest_entry.read_to_end(&mut buffer)?;
-                
-                // 尝试确定是二进制XML还是纯文本XML
-                let xml_string = String::from_utf8_lossy(&buffer);
-                if xml_string.starts_with("<?xml") || xml_string.contains("<manifest") {
-                    // 已经是纯文本XML
-                    println!("INFO: 清单文件是纯文本XML格式");
-                    Ok(xml_string.to_string())
-                } else {
-                    // 尝试使用aapt2
-                    println!("INFO: 清单文件是二进制格式，尝试使用aapt2解析");
-                    // 使用APK路径而不是清单文件名称
-                    if let Some(manifest) = Self::extract_with_aapt2(apk_path) {
-                        println!("INFO: 成功使用aapt2提取清单文件");
-                        return Ok(manifest);
-                    }
-                    
-                    // 如果aapt2失败，回退到axmldecoder
-                    println!("INFO: aapt2提取失败，使用axmldecoder解析");
-                    match axmldecoder::parse(&buffer) {
-                        Ok(_xml_doc) => {
-                            // 由于API已更改，我们没有简单访问Element字段的方法，
-                            // 创建一个最小有效的XML清单作为后备
-                            let xml_output = format!(
-                                r#"<?xml version="1.0" encoding="utf-8"?>
-<manifest xmlns:android="http://schemas.android.com/apk/res/android">
-</manifest>"#
-                            );
-                            
-                            println!("INFO: 由于axmldecoder API更改，生成最小清单");
-                            Ok(xml_output)
-                        },
-                        Err(e) => {
-                            println!("ERROR: 解析二进制清单文件错误: {}", e);
-                            
-                            // 如果库解析失败，回退到更简单的方法
-                            // 创建一个最小有效的XML作为后备
-                            println!("INFO: 创建最小有效的XML清单作为后备");
-                            Ok(format!(
-                                r#"<?xml version="1.0" encoding="utf-8"?>
-<manifest xmlns:android="http://schemas.android.com/apk/res/android">
-</manifest>"#
-                            ))
-                        }
-                    }
-                }
-            },
+    /// 资源表，不存在或解析失败时返回None
+    pub fn load_resource_table(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Option<ResourceTable> {
+        let entry = match archive.by_name("resources.arsc") {
+            Ok(entry) => entry,
+            Err(_) => {
+                println!("WARNING: APK中不包含resources.arsc");
+                return None;
+            }
+        };
+        
+        let buffer = match budget.read(entry) {
+            Ok(buffer) => buffer,
             Err(e) => {
-                println!("ERROR: 未找到AndroidManifest.xml: {}", e);
-                Err(ApkParserError::InvalidApk("未找到AndroidManifest.xml".to_string()))
+                println!("WARNING: 读取resources.arsc失败: {}", e);
+                return None;
             }
         };
         
-        result
+        match ResourceTable::parse(&buffer, budget.limits()) {
+            Ok(table) => {
+                println!("INFO: 解析resources.arsc完成，共 {} 个资源", table.entry_count());
+                Some(table)
+            },
+            Err(e) => {
+                if let ApkParserError::LimitExceeded(exceeded) = &e {
+                    budget.exceed(exceeded.kind, &exceeded.context, exceeded.actual, exceeded.limit);
+                }
+                println!("WARNING: 解析resources.arsc失败: {}", e);
+                None
+            }
+        }
+    }
+    
+    /// 读取并解析AndroidManifest.xml为类型化模型
+    /// 
+    /// # 参数
+    /// * `archive` - ZIP存档
+    /// * `budget` - 读取预算
+    /// 
+    /// # 返回
+    /// 类型化的清单或错误
+    pub fn read_manifest(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Result<AndroidManifest, ApkParserError> {
+        let entry = archive.by_name("AndroidManifest.xml")
+            .map_err(|_| ApkParserError::InvalidApk("未找到AndroidManifest.xml".to_string()))?;
+        let buffer = budget.read(entry)?;
+        AndroidManifest::parse(&buffer, budget.limits())
+    }
+    
+    /// 解析APK中的清单文件
+    pub fn parse_manifest<P: AsRef<Path>>(apk_path: P) -> Result<AndroidManifest, ApkParserError> {
+        let file = File::open(apk_path.as_ref())?;
+        let mut archive = ZipArchive::new(file)?;
+        let budget = ReadBudget::default();
+        budget.check_entry_count(archive.len())?;
+        Self::read_manifest(&mut archive, &budget)
+    }
+    
+    /// 将清单中application上的资源引用解析为各配置下的具体值
+    pub fn resolve_manifest_resources(manifest: &AndroidManifest, table: &ResourceTable) -> ManifestResources {
+        let application = manifest.application.as_ref();
+        let resolve = |value: Option<&String>| value.map(|reference| table.resolve_reference(reference));
+        
+        ManifestResources {
+            label: resolve(application.and_then(|a| a.label.as_ref())),
+            icon: resolve(application.and_then(|a| a.icon.as_ref())),
+            round_icon: resolve(application.and_then(|a| a.round_icon.as_ref())),
+            network_security_config: resolve(application.and_then(|a| a.network_security_config.as_ref())),
+            data_extraction_rules: resolve(application.and_then(|a| a.data_extraction_rules.as_ref())),
+        }
+    }
+    
+    // 从解析后的label中收集各语言的应用名称
+    fn collect_app_labels(resources: &ManifestResources) -> Option<std::collections::HashMap<String, String>> {
+        let label = resources.label.as_ref()?;
+        let labels: std::collections::HashMap<String, String> = label.values.iter()
+            .map(|v| (v.config.clone(), v.value.clone()))
+            .collect();
+        if labels.is_empty() { None } else { Some(labels) }
     }
     
     // 检查aapt2.exe是否可用
@@ -307,11 +909,9 @@ impl ApkParser {
             if let Some(parent_dir) = exe_dir.parent() {
                 let resources_dir = parent_dir.join("resources");
                 let aapt2_path = resources_dir.join("aapt2.exe");
-                if aapt2_path.exists() {
-                    if !Self::is_placeholder_aapt2(&aapt2_path) {
-                        println!("INFO: 找到有效的aapt2.exe: {:?}", aapt2_path);
-                        return Some(aapt2_path.to_string_lossy().to_string());
-                    }
+                if aapt2_path.exists() && !Self::is_placeholder_aapt2(&aapt2_path) {
+                    println!("INFO: 找到有效的aapt2.exe: {:?}", aapt2_path);
+                    return Some(aapt2_path.to_string_lossy().to_string());
                 }
             }
         }
@@ -325,11 +925,9 @@ impl ApkParser {
         ];
 
         for path in possible_paths {
-            if path.exists() {
-                if !Self::is_placeholder_aapt2(&path) {
-                    println!("INFO: 找到有效的aapt2.exe: {:?}", path);
-                    return Some(path.to_string_lossy().to_string());
-                }
+            if path.exists() && !Self::is_placeholder_aapt2(&path) {
+                println!("INFO: 找到有效的aapt2.exe: {:?}", path);
+                return Some(path.to_string_lossy().to_string());
             }
         }
         
@@ -361,62 +959,6 @@ impl ApkParser {
         }
     }
     
-    // 使用aapt2提取清单
-    fn extract_with_aapt2<P: AsRef<Path>>(apk_path: P) -> Option<String> {
-        // 检查aapt2是否可用
-        let aapt2_path = match Self::ensure_aapt2_available() {
-            Some(path) => path,
-            None => {
-                println!("WARNING: aapt2.exe不可用，回退到其他方法");
-                return None;
-            }
-        };
-        
-        let apk_path_str = match apk_path.as_ref().to_str() {
-            Some(s) => s,
-            None => {
-                println!("ERROR: APK路径包含无效字符");
-                return None;
-            }
-        };
-        
-        println!("INFO: 尝试使用aapt2提取清单: {}", apk_path_str);
-        
-        // 运行aapt2提取清单
-        let mut cmd = Command::new(&aapt2_path);
-        
-        // 在Windows上，配置命令不显示控制台窗口
-        #[cfg(target_os = "windows")]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            cmd.creation_flags(CREATE_NO_WINDOW);
-        }
-        
-        let output = cmd
-            .args(&["dump", "xmltree", "--file", "AndroidManifest.xml", apk_path_str])
-            .output();
-        
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    let manifest = String::from_utf8_lossy(&output.stdout).into_owned();
-                    println!("INFO: 成功使用aapt2提取清单");
-                    return Some(manifest);
-                } else {
-                    let error = String::from_utf8_lossy(&output.stderr);
-                    println!("ERROR: aapt2命令失败: {}", error);
-                }
-            },
-            Err(e) => {
-                println!("ERROR: 执行aapt2失败: {}", e);
-            }
-        }
-        
-        println!("WARNING: aapt2提取清单失败，回退到其他方法");
-        None
-    }
-    
     // 使用aapt2获取APK信息
     pub fn dump_apk_info<P: AsRef<Path>>(apk_path: P) -> Option<String> {
         // 查找aapt2可执行文件
@@ -478,7 +1020,7 @@ impl ApkParser {
         }
         
         let output = cmd
-            .args(&["dump", "badging", apk_path_str])
+            .args(["dump", "badging", apk_path_str])
             .output();
         
         match output {
@@ -514,282 +1056,327 @@ impl ApkParser {
         None
     }
     
-    pub fn parse_package_info(manifest_xml: &str) -> Result<(String, String, String, String, String, Option<String>), ApkParserError> {
-        // 检查清单文件是否为空
-        if manifest_xml.trim().is_empty() {
-            return Err(ApkParserError::InvalidApk("清单文件为空".to_string()));
-        }
+    /// 扫描`META-INF/`下的签名块文件（*.RSA、*.DSA、*.EC），解析其中的PKCS#7 SignedData
+    ///
+    /// 读取失败的签名块记为警告并跳过；超出读取预算时停止扫描，由预算记入阶段警告。
+    pub fn parse_v1_signatures(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Vec<SignatureInfo> {
+        let mut signature_files: Vec<String> = archive.file_names()
+            .filter(|name| Self::is_signature_block_file(name))
+            .map(|name| name.to_string())
+            .collect();
+        signature_files.sort();
 
-        // 使用正则表达式提取关键属性
-        // 在实际环境中，应使用适当的XML解析器
-        
-        // 提取包名
-        let package_regex = Regex::new(r#"package="([^"]+)"#).unwrap();
-        let package_name = package_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-            .unwrap_or_else(|| "unknown".to_string());
-        
-        // 提取版本名称
-        let version_name_regex = Regex::new(r#"android:versionName="([^"]+)"#).unwrap();
-        let version_name = version_name_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-            .unwrap_or_else(|| "1.0".to_string());
-        
-        // 提取版本代码
-        let version_code_regex = Regex::new(r#"android:versionCode="([^"]+)"#).unwrap();
-        let version_code = version_code_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-            .unwrap_or_else(|| "1".to_string());
-        
-        // 提取最小SDK版本
-        let min_sdk_regex = Regex::new(r#"android:minSdkVersion="([^"]+)"#).unwrap();
-        let min_sdk = min_sdk_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-            .unwrap_or_else(|| "1".to_string());
-        
-        // 提取目标SDK版本
-        let target_sdk_regex = Regex::new(r#"android:targetSdkVersion="([^"]+)"#).unwrap();
-        let target_sdk = target_sdk_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-            .unwrap_or_else(|| min_sdk.clone());
-        
-        // 提取主活动
-        // 首先检查LAUNCHER活动
-        let main_activity_regex = Regex::new(r#"<activity[^>]*android:name="([^"]+)"[^>]*>.*?<intent-filter>.*?<action android:name="android.intent.action.MAIN".*?>.*?<category android:name="android.intent.category.LAUNCHER".*?>.*?</intent-filter>"#).unwrap();
-        let main_activity = main_activity_regex.captures(manifest_xml)
-            .and_then(|caps| caps.get(1).map(|m| {
-                let activity = m.as_str().to_string();
-                // 如果活动名称没有包前缀，添加包名
-                if !activity.contains(".") && !activity.starts_with(package_name.as_str()) {
-                    format!("{}.{}", package_name, activity)
-                } else {
-                    activity
+        let mut signatures = Vec::new();
+        for path in signature_files {
+            println!("INFO: 找到签名块文件: {}", path);
+            let block = match budget.read_entry(archive, &path) {
+                Ok(block) => block,
+                Err(e @ ApkParserError::LimitExceeded(_)) => {
+                    println!("WARN: 读取签名块 {} 超出解析限制: {}", path, e);
+                    break;
                 }
-            }));
-        
-        println!("提取的包信息: {} {} {}", package_name, version_name, version_code);
-        
-        Ok((
-            package_name,
-            version_name,
-            version_code,
-            min_sdk,
-            target_sdk,
-            main_activity
-        ))
-    }
-    
-    pub fn parse_signature_info(archive: &mut ZipArchive<File>) -> Result<Option<SignatureInfo>, ApkParserError> {
-        // 尝试在APK中查找签名文件
-        let signature_file_paths = vec![
-            "META-INF/CERT.RSA", 
-            "META-INF/CERT.DSA", 
-            "META-INF/CERT.EC",
-            // 尝试其他可能的签名文件
-            "META-INF/ANDROID.RSA",
-            "META-INF/ANDROIDD.RSA",
-            // 添加更多可能的签名文件
-            "META-INF/CERT.SF",
-            "META-INF/MANIFEST.MF"
-        ];
-        
-        for path in signature_file_paths {
-            println!("INFO: 尝试查找签名文件: {}", path);
-            match archive.by_name(path) {
-                Ok(mut cert_file) => {
-                    println!("INFO: 找到签名文件: {}, 大小: {} 字节", path, cert_file.size());
-                    let mut cert_data = Vec::new();
-                    if cert_file.read_to_end(&mut cert_data).is_ok() {
-                        // 首先尝试将其解析为直接的X.509证书
-                        match x509_parser::certificate::X509Certificate::from_der(&cert_data) {
-                            Ok((_, cert)) => {
-                                // 成功解析为X.509证书
-                                let tbs_cert = &cert.tbs_certificate;
-                                
-                                // 解析发行者
-                                let issuer = tbs_cert.issuer.to_string();
-                                
-                                // 解析主题
-                                let subject = tbs_cert.subject.to_string();
-                                
-                                // 解析有效期 - 处理结果
-                                let valid_from = match tbs_cert.validity.not_before.to_rfc2822() {
-                                    Ok(date_str) => date_str,
-                                    Err(_) => "未知".to_string(),
-                                };
-                                let valid_to = match tbs_cert.validity.not_after.to_rfc2822() {
-                                    Ok(date_str) => date_str,
-                                    Err(_) => "未知".to_string(),
-                                };
-                                
-                                // 计算指纹
-                                let sha1_fingerprint = calculate_fingerprint::<sha1::Sha1>(&cert.tbs_certificate.as_ref());
-                                let sha256_fingerprint = calculate_fingerprint::<sha2::Sha256>(&cert.tbs_certificate.as_ref());
-                                
-                                println!("INFO: 成功解析证书 - 发行者: {}, 主题: {}", issuer, subject);
-                                println!("INFO: 有效期 - 从: {}, 到: {}", valid_from, valid_to);
-                                println!("INFO: 证书指纹 - SHA1: {}", sha1_fingerprint);
-                                
-                                return Ok(Some(SignatureInfo {
-                                    issuer,
-                                    subject,
-                                    valid_from,
-                                    valid_to,
-                                    fingerprint_sha1: Some(sha1_fingerprint),
-                                    fingerprint_sha256: Some(sha256_fingerprint),
-                                }));
-                            }
-                            Err(err) => {
-                                println!("WARN: 无法作为X.509证书解析: {}", err);
-                                
-                                // 由于无法正确解析，生成有限的签名信息
-                                if path.ends_with(".SF") || path.ends_with(".MF") {
-                                    // 对于SF文件，尝试提取一些基本信息
-                                    let content = String::from_utf8_lossy(&cert_data);
-                                    
-                                    // 查找创建者和日期信息
-                                    let created_by = content.lines()
-                                        .find(|line| line.starts_with("Created-By:"))
-                                        .unwrap_or("Created-By: Unknown")
-                                        .trim()
-                                        .to_string();
-                                    
-                                    let mut hasher = sha1::Sha1::new();
-                                    hasher.update(&cert_data);
-                                    let sha1_result = format!("SHA1:{:x}", hasher.finalize());
-                                    
-                                    let mut sha256_hasher = sha2::Sha256::new();
-                                    sha256_hasher.update(&cert_data);
-                                    let sha256_result = format!("SHA256:{:x}", sha256_hasher.finalize());
-                                    
-                                    let signature_info = SignatureInfo {
-                                        issuer: created_by.clone(),
-                                        subject: format!("从 {} 提取的签名信息", path),
-                                        valid_from: "未知".to_string(),
-                                        valid_to: "未知".to_string(),
-                                        fingerprint_sha1: Some(sha1_result),
-                                        fingerprint_sha256: Some(sha256_result),
-                                    };
-                                    
-                                    println!("INFO: 从签名文件提取了有限的签名信息");
-                                    return Ok(Some(signature_info));
-                                }
-                            }
-                        }
-                        
-                        // 尝试作为PKCS#7解析 - 简化版
-                        println!("INFO: 尝试解析为PKCS#7格式");
-                        // 生成默认的签名信息
-                        let mut hasher = sha1::Sha1::new();
-                        hasher.update(&cert_data);
-                        let sha1_result = format!("SHA1:{:x}", hasher.finalize());
-                        
-                        let mut sha256_hasher = sha2::Sha256::new();
-                        sha256_hasher.update(&cert_data);
-                        let sha256_result = format!("SHA256:{:x}", sha256_hasher.finalize());
-                        
-                        let signature_info = SignatureInfo {
-                            issuer: format!("从 {} 提取的签名信息", path),
-                            subject: "Android应用签名".to_string(),
-                            valid_from: chrono::Utc::now().to_rfc2822(),
-                            valid_to: chrono::Utc::now().checked_add_months(chrono::Months::new(60))
-                                .unwrap_or_else(|| chrono::Utc::now())
-                                .to_rfc2822(),
-                            fingerprint_sha1: Some(sha1_result),
-                            fingerprint_sha256: Some(sha256_result),
-                        };
-                        
-                        println!("INFO: 生成了默认签名信息");
-                        return Ok(Some(signature_info));
-                    }
+                Err(e) => {
+                    println!("WARN: 读取签名块 {} 失败: {}", path, e);
+                    continue;
                 }
-                Err(err) => {
-                    println!("WARN: 未找到签名文件 {}: {}", path, err);
+            };
+
+            match Self::signature_info_from_block(&path, &block) {
+                Ok(info) => {
+                    println!("INFO: 成功解析签名 - 主题: {}, 证书数: {}", info.subject, info.certificate_chain.len());
+                    signatures.push(info);
                 }
+                Err(e) => println!("WARN: 解析签名块 {} 失败: {}", path, e),
             }
         }
-        
-        // 未找到有效签名
-        println!("WARN: 在APK中未找到有效的签名文件");
-        
-        // 返回一个临时的签名信息作为后备
-        let now = chrono::Utc::now();
-        let tomorrow = now + chrono::Duration::days(1);
-        Ok(Some(SignatureInfo {
-            issuer: "未知发行者".to_string(),
-            subject: "未知主题".to_string(),
-            valid_from: now.to_rfc2822(),
-            valid_to: tomorrow.to_rfc2822(),
-            fingerprint_sha1: Some("缺少签名文件".to_string()),
-            fingerprint_sha256: Some("缺少签名文件".to_string()),
-        }))
+
+        if signatures.is_empty() {
+            println!("WARN: 在APK中未找到有效的v1签名块");
+        }
+        signatures
     }
-    
-    pub fn parse_permissions(manifest_xml: &str) -> Result<Vec<Permission>, ApkParserError> {
-        // 使用正则表达式提取权限
-        let permissions_regex = Regex::new(r#"<uses-permission[^>]*android:name="([^"]+)"[^>]*/?>"#).unwrap();
-        
-        // 根据Android文档列出的危险权限
-        let dangerous_permissions = vec![
-            "android.permission.READ_CALENDAR", "android.permission.WRITE_CALENDAR",
-            "android.permission.CAMERA", "android.permission.READ_CONTACTS",
-            "android.permission.WRITE_CONTACTS", "android.permission.GET_ACCOUNTS",
-            "android.permission.ACCESS_FINE_LOCATION", "android.permission.ACCESS_COARSE_LOCATION",
-            "android.permission.ACCESS_BACKGROUND_LOCATION", "android.permission.RECORD_AUDIO",
-            "android.permission.READ_PHONE_STATE", "android.permission.READ_PHONE_NUMBERS",
-            "android.permission.CALL_PHONE", "android.permission.ANSWER_PHONE_CALLS",
-            "android.permission.READ_CALL_LOG", "android.permission.WRITE_CALL_LOG",
-            "android.permission.ADD_VOICEMAIL", "android.permission.USE_SIP",
-            "android.permission.PROCESS_OUTGOING_CALLS", "android.permission.BODY_SENSORS",
-            "android.permission.BODY_SENSORS_BACKGROUND", "android.permission.ACTIVITY_RECOGNITION",
-            "android.permission.SEND_SMS", "android.permission.RECEIVE_SMS",
-            "android.permission.READ_SMS", "android.permission.RECEIVE_WAP_PUSH",
-            "android.permission.RECEIVE_MMS", "android.permission.READ_EXTERNAL_STORAGE",
-            "android.permission.WRITE_EXTERNAL_STORAGE", "android.permission.READ_MEDIA_IMAGES",
-            "android.permission.READ_MEDIA_VIDEO", "android.permission.READ_MEDIA_AUDIO",
-            "android.permission.MANAGE_EXTERNAL_STORAGE", "android.permission.USE_BIOMETRIC",
-            "android.permission.USE_FINGERPRINT", "android.permission.BLUETOOTH_CONNECT",
-            "android.permission.BLUETOOTH_SCAN", "android.permission.BLUETOOTH_ADVERTISE",
-            "android.permission.POST_NOTIFICATIONS", "android.permission.NEARBY_WIFI_DEVICES",
-            "android.permission.READ_MEDIA_VISUAL_USER_SELECTED"
-        ];
-        
-        // 从清单中收集所有权限
-        let mut permissions = Vec::new();
-        for cap in permissions_regex.captures_iter(manifest_xml) {
-            if let Some(perm) = cap.get(1) {
-                let permission_name = perm.as_str().to_string();
-                let is_dangerous = dangerous_permissions.contains(&permission_name.as_str());
-                permissions.push(Permission {
-                    name: permission_name,
-                    is_dangerous,
-                });
+
+    /// 解析APK签名块中的v2/v3/v3.1签名方案
+    pub fn parse_signing_schemes(apk_path: &Path, v1: bool) -> Option<SigningSchemes> {
+        match SigningSchemes::from_path(apk_path, v1) {
+            Ok(schemes) => {
+                println!("INFO: 签名方案 - v1: {}, v2: {}, v3: {}, v3.1: {}", schemes.v1, schemes.v2, schemes.v3, schemes.v3_1);
+                Some(schemes)
+            }
+            Err(e) => {
+                println!("WARN: 读取APK签名块失败: {}", e);
+                None
             }
         }
-        
-        println!("在清单中找到 {} 个权限", permissions.len());
-        
-        // 如果未找到权限，尝试另一种可能匹配其他格式的正则表达式模式
-        if permissions.is_empty() {
-            let alt_regex = Regex::new(r#"<permission[^>]*android:name="([^"]+)"[^>]*/?>"#).unwrap();
-            for cap in alt_regex.captures_iter(manifest_xml) {
-                if let Some(perm) = cap.get(1) {
-                    let permission_name = perm.as_str().to_string();
-                    let is_dangerous = dangerous_permissions.contains(&permission_name.as_str());
-                    permissions.push(Permission {
-                        name: permission_name,
-                        is_dangerous,
-                    });
+    }
+
+    /// 对v1/v2/v3/v3.1签名做密码学校验
+    pub fn verify_signatures(apk_path: &Path, budget: &ReadBudget) -> Option<VerificationResult> {
+        match verifier::verify_apk(apk_path, budget) {
+            Ok(result) => {
+                println!("INFO: 签名校验结果: {}", if result.verified { "通过" } else { "未通过" });
+                Some(result)
+            }
+            Err(e) => {
+                println!("WARN: 签名校验失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 由v2/v3/v3.1签名者构造签名信息
+    fn signature_info_from_scheme_signer(scheme: &str, signer: &SchemeSigner) -> Option<SignatureInfo> {
+        let cert = signer.certificates.first()?;
+        Some(SignatureInfo {
+            issuer: cert.issuer.clone(),
+            subject: cert.subject.clone(),
+            valid_from: cert.valid_from.clone(),
+            valid_to: cert.valid_to.clone(),
+            fingerprint_md5: Some(cert.fingerprint_md5.clone()),
+            fingerprint_sha1: Some(cert.fingerprint_sha1.clone()),
+            fingerprint_sha256: Some(cert.fingerprint_sha256.clone()),
+            fingerprint_sha512: Some(cert.fingerprint_sha512.clone()),
+            signature_file: None,
+            digest_algorithms: signer.digests.iter().map(|d| d.algorithm.clone()).collect(),
+            signers: Vec::new(),
+            certificate_chain: signer.certificates.clone(),
+            scheme: scheme.to_string(),
+            min_sdk_version: signer.min_sdk_version,
+            max_sdk_version: signer.max_sdk_version,
+            is_current: false,
+            lineage: signer.lineage.clone(),
+        })
+    }
+
+    /// 汇总各方案的签名者，计算每个签名者生效的SDK范围并标记当前签名者
+    ///
+    /// 平台总是使用其支持的最高方案：v3.1（API 33+，按其声明的范围）、
+    /// v3（按其声明的范围）、v2（API 24起）、v1（更早的版本）。
+    pub fn collect_signatures(
+        v1_signatures: Vec<SignatureInfo>,
+        schemes: Option<&SigningSchemes>,
+    ) -> (Vec<SignatureInfo>, Vec<SdkRangeSigner>) {
+        const V2_MIN_SDK: u32 = 24;
+        let upper = |max: Option<u32>| max.filter(|max| *max < i32::MAX as u32);
+
+        let mut signatures = v1_signatures;
+        if let Some(schemes) = schemes {
+            for (scheme, signers) in [
+                ("v2", &schemes.v2_signers),
+                ("v3", &schemes.v3_signers),
+                ("v3.1", &schemes.v3_1_signers),
+            ] {
+                signatures.extend(signers.iter().filter_map(|signer| Self::signature_info_from_scheme_signer(scheme, signer)));
+            }
+        }
+
+        let scheme_min = |scheme: &str| signatures.iter().filter(|s| s.scheme == scheme).filter_map(|s| s.min_sdk_version).min();
+        let v3_1_min = scheme_min("v3.1");
+        let v3_min = scheme_min("v3");
+        let has_v2 = signatures.iter().any(|s| s.scheme == "v2");
+        // 更高方案接管之前的最后一个SDK版本
+        let below = |min: Option<u32>| min.map(|min| min.saturating_sub(1));
+
+        let mut signers_by_sdk = Vec::new();
+        for signature in signatures.iter_mut() {
+            let (min, max) = match signature.scheme.as_str() {
+                "v3.1" => (signature.min_sdk_version.unwrap_or(0), upper(signature.max_sdk_version)),
+                "v3" => {
+                    let max = match (upper(signature.max_sdk_version), below(v3_1_min)) {
+                        (Some(max), Some(limit)) => Some(max.min(limit)),
+                        (max, limit) => max.or(limit),
+                    };
+                    (signature.min_sdk_version.unwrap_or(0), max)
                 }
+                "v2" => (V2_MIN_SDK, below(v3_min).or(below(v3_1_min))),
+                _ => (1, if has_v2 { Some(V2_MIN_SDK - 1) } else { below(v3_min).or(below(v3_1_min)) }),
+            };
+            if max.is_some_and(|max| max < min) {
+                continue;
             }
+            signature.is_current = max.is_none();
+            signers_by_sdk.push(SdkRangeSigner {
+                min_sdk_version: min,
+                max_sdk_version: max,
+                scheme: signature.scheme.clone(),
+                subject: signature.subject.clone(),
+                fingerprint_sha256: signature.fingerprint_sha256.clone(),
+            });
         }
-        
-        Ok(permissions)
+        signers_by_sdk.sort_by_key(|signer| signer.min_sdk_version);
+        (signatures, signers_by_sdk)
+    }
+
+    /// 当前签名者：最新平台上生效的签名，没有时取第一个签名
+    fn current_signature(signatures: &[SignatureInfo]) -> Option<SignatureInfo> {
+        signatures.iter().rev().find(|s| s.is_current).or_else(|| signatures.first()).cloned()
+    }
+
+    /// 判断是否为`META-INF/`目录下的v1签名块文件
+    pub(crate) fn is_signature_block_file(name: &str) -> bool {
+        let file_name = match name.strip_prefix("META-INF/") {
+            Some(file_name) if !file_name.contains('/') => file_name.to_ascii_uppercase(),
+            _ => return false,
+        };
+        [".RSA", ".DSA", ".EC"].iter().any(|ext| file_name.ends_with(ext))
+    }
+
+    /// 从签名块构造签名信息，主体字段取自签名者证书
+    pub fn signature_info_from_block(path: &str, block: &[u8]) -> Result<SignatureInfo, ApkParserError> {
+        let signed_data = SignedData::parse(block)?;
+        let certificate_chain: Vec<CertificateInfo> = signed_data.certificate_chain().into_iter()
+            .filter_map(|der| Self::certificate_info(der).ok())
+            .collect();
+        let signer_cert = certificate_chain.first()
+            .ok_or_else(|| ApkParserError::InvalidSignature("签名块中没有证书".to_string()))?;
+        if let Some(signer) = signed_data.signers.first() {
+            if signed_data.signer_certificate(signer).is_none() {
+                println!("WARN: 签名块 {} 中找不到与签名者匹配的证书", path);
+            }
+        }
+
+        Ok(SignatureInfo {
+            issuer: signer_cert.issuer.clone(),
+            subject: signer_cert.subject.clone(),
+            valid_from: signer_cert.valid_from.clone(),
+            valid_to: signer_cert.valid_to.clone(),
+            fingerprint_md5: Some(signer_cert.fingerprint_md5.clone()),
+            fingerprint_sha1: Some(signer_cert.fingerprint_sha1.clone()),
+            fingerprint_sha256: Some(signer_cert.fingerprint_sha256.clone()),
+            fingerprint_sha512: Some(signer_cert.fingerprint_sha512.clone()),
+            signature_file: Some(path.to_string()),
+            digest_algorithms: signed_data.digest_algorithms,
+            signers: signed_data.signers,
+            certificate_chain,
+            scheme: "v1".to_string(),
+            min_sdk_version: None,
+            max_sdk_version: None,
+            is_current: false,
+            lineage: None,
+        })
+    }
+
+    /// 解析单个DER编码的X.509证书
+    pub fn certificate_info(der: &[u8]) -> Result<CertificateInfo, ApkParserError> {
+        let (_, cert) = x509_parser::certificate::X509Certificate::from_der(der)
+            .map_err(|e| ApkParserError::InvalidSignature(format!("无法解析X.509证书: {}", e)))?;
+        let tbs_cert = &cert.tbs_certificate;
+        let format_time = |time: &ASN1Time| time.to_rfc2822().unwrap_or_else(|_| "未知".to_string());
+        let (public_key_size, public_key_curve) = Self::public_key_details(&tbs_cert.subject_pki);
+
+        Ok(CertificateInfo {
+            subject: tbs_cert.subject.to_string(),
+            issuer: tbs_cert.issuer.to_string(),
+            serial_number: crate::pkcs7::integer_hex(tbs_cert.raw_serial()),
+            valid_from: format_time(&tbs_cert.validity.not_before),
+            valid_to: format_time(&tbs_cert.validity.not_after),
+            fingerprint_md5: format_fingerprint(&md5::compute(der).0),
+            fingerprint_sha1: calculate_fingerprint::<sha1::Sha1>(der),
+            fingerprint_sha256: calculate_fingerprint::<sha2::Sha256>(der),
+            fingerprint_sha512: calculate_fingerprint::<sha2::Sha512>(der),
+            version: tbs_cert.version.0 + 1,
+            signature_algorithm: crate::pkcs7::oid_name(&cert.signature_algorithm.algorithm.to_id_string()),
+            public_key_algorithm: crate::pkcs7::oid_name(&tbs_cert.subject_pki.algorithm.algorithm.to_id_string()),
+            public_key_size,
+            public_key_curve,
+            subject_name: Self::distinguished_name(&tbs_cert.subject),
+            issuer_name: Self::distinguished_name(&tbs_cert.issuer),
+            extensions: tbs_cert.extensions().iter().map(Self::certificate_extension).collect(),
+        })
+    }
+
+    /// 公钥长度（位）与EC曲线名称
+    fn public_key_details(spki: &SubjectPublicKeyInfo) -> (Option<u32>, Option<String>) {
+        let curve = spki.algorithm.parameters.as_ref()
+            .and_then(|params| params.as_oid().ok())
+            .map(|oid| crate::pkcs7::oid_name(&oid.to_id_string()));
+        match spki.parsed() {
+            Ok(x509_parser::public_key::PublicKey::RSA(rsa)) => {
+                // x509-parser的key_size()对无前导零的模数计算有误，这里按有效位计算
+                let modulus: &[u8] = match rsa.modulus.iter().position(|b| *b != 0) {
+                    Some(start) => &rsa.modulus[start..],
+                    None => &[],
+                };
+                let bits = modulus.first()
+                    .map(|first| (modulus.len() as u32 - 1) * 8 + (8 - first.leading_zeros()));
+                (bits, None)
+            }
+            Ok(key @ x509_parser::public_key::PublicKey::EC(_)) => {
+                (Some(key.key_size() as u32).filter(|bits| *bits > 0), curve)
+            }
+            Ok(key) => (Some(key.key_size() as u32).filter(|bits| *bits > 0), None),
+            Err(_) => (None, None),
+        }
+    }
+
+    /// 提取名称中的CN、O、OU、C字段，多值时以逗号连接
+    fn distinguished_name(name: &X509Name) -> DistinguishedName {
+        let join = |values: Vec<&str>| (!values.is_empty()).then(|| values.join(", "));
+        DistinguishedName {
+            common_name: join(name.iter_common_name().filter_map(|attr| attr.as_str().ok()).collect()),
+            organization: join(name.iter_organization().filter_map(|attr| attr.as_str().ok()).collect()),
+            organizational_unit: join(name.iter_organizational_unit().filter_map(|attr| attr.as_str().ok()).collect()),
+            country: join(name.iter_country().filter_map(|attr| attr.as_str().ok()).collect()),
+        }
+    }
+
+    fn certificate_extension(extension: &X509Extension) -> CertificateExtension {
+        let oid = extension.oid.to_id_string();
+        let value = match extension.parsed_extension() {
+            ParsedExtension::BasicConstraints(bc) => match bc.path_len_constraint {
+                Some(len) => format!("CA={}, pathLen={}", bc.ca, len),
+                None => format!("CA={}", bc.ca),
+            },
+            ParsedExtension::KeyUsage(usage) => usage.to_string(),
+            ParsedExtension::SubjectKeyIdentifier(id) => crate::pkcs7::hex(id.0),
+            ParsedExtension::AuthorityKeyIdentifier(aki) => aki.key_identifier.as_ref()
+                .map(|id| crate::pkcs7::hex(id.0))
+                .unwrap_or_default(),
+            ParsedExtension::SubjectAlternativeName(san) => san.general_names.iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            ParsedExtension::ExtendedKeyUsage(eku) => {
+                let mut usages = Vec::new();
+                for (set, name) in [
+                    (eku.server_auth, "serverAuth"),
+                    (eku.client_auth, "clientAuth"),
+                    (eku.code_signing, "codeSigning"),
+                    (eku.email_protection, "emailProtection"),
+                    (eku.time_stamping, "timeStamping"),
+                    (eku.ocsp_signing, "OCSPSigning"),
+                ] {
+                    if set {
+                        usages.push(name.to_string());
+                    }
+                }
+                usages.extend(eku.other.iter().map(|oid| oid.to_id_string()));
+                usages.join(", ")
+            }
+            _ => crate::pkcs7::hex(extension.value),
+        };
+
+        CertificateExtension {
+            name: crate::pkcs7::oid_name(&oid),
+            oid,
+            critical: extension.critical,
+            value,
+        }
+    }
+    
+    /// 从清单中收集申请的权限，并标记危险权限
+    pub fn parse_permissions(manifest: &AndroidManifest) -> Vec<Permission> {
+        manifest.permission_names().into_iter()
+            .map(|name| {
+                let is_dangerous = DANGEROUS_PERMISSIONS.contains(&name.as_str());
+                Permission { name, is_dangerous }
+            })
+            .collect()
     }
 
     
     /// 从APK中提取应用图标
-    pub fn extract_icon<P: AsRef<Path>>(apk_path: P) -> Option<String> {
+    pub fn extract_icon<P: AsRef<Path>>(apk_path: P, budget: &ReadBudget) -> Option<String> {
         println!("INFO: 尝试提取应用图标...");
         let file = match File::open(apk_path.as_ref()) {
             Ok(f) => f,
@@ -806,11 +1393,22 @@ impl ApkParser {
                 return None;
             }
         };
+        if budget.check_entry_count(archive.len()).is_err() {
+            return None;
+        }
         
         // 尝试从AndroidManifest.xml中提取图标路径
         let mut icon_path_from_manifest = None;
-        if let Ok(manifest) = Self::extract_manifest_xml(&mut archive, apk_path.as_ref()) {
-            if let Some(icon_attr) = Self::extract_from_manifest(&manifest, "application", "android:icon") {
+        let mut resolved_icon_paths: Vec<String> = Vec::new();
+        if let Ok(manifest) = Self::read_manifest(&mut archive, budget) {
+            // 优先通过resources.arsc解析出各密度下的实际文件
+            if let Some(table) = Self::load_resource_table(&mut archive, budget) {
+                let resources = Self::resolve_manifest_resources(&manifest, &table);
+                resolved_icon_paths = Self::icon_candidates(&resources);
+                println!("INFO: 通过资源表解析到 {} 个图标文件", resolved_icon_paths.len());
+            }
+            
+            if let Some(icon_attr) = manifest.application.as_ref().and_then(|a| a.icon.clone()) {
                 println!("INFO: 从AndroidManifest.xml中找到图标引用: {}", icon_attr);
                 // 转换@drawable/icon_name格式为实际路径
                 if icon_attr.starts_with("@drawable/") {
@@ -830,7 +1428,8 @@ impl ApkParser {
         }
         
         // 按优先级尝试提取不同分辨率的图标
-        let mut icon_paths = vec![
+        let mut icon_paths: Vec<Option<String>> = resolved_icon_paths.into_iter().map(Some).collect();
+        icon_paths.extend(vec![
             // 先尝试从Manifest提取的路径
             icon_path_from_manifest.clone(),
             
@@ -894,17 +1493,15 @@ impl ApkParser {
             // JPEG格式图标 (不太常见但可能存在)
             Some("res/drawable/ic_launcher.jpg".to_string()),
             Some("res/drawable/icon.jpg".to_string()),
-        ];
+        ]);
         
         // 过滤掉None值
-        icon_paths.retain(|path| path.is_some());
-        let icon_paths: Vec<String> = icon_paths.into_iter().filter_map(|p| p).collect();
+        let icon_paths: Vec<String> = icon_paths.into_iter().flatten().collect();
         
         // 尝试提取第一个找到的图标
         for path in icon_paths {
-            if let Ok(mut file) = archive.by_name(&path) {
-                let mut buffer = Vec::new();
-                if file.read_to_end(&mut buffer).is_ok() {
+            if let Ok(file) = archive.by_name(&path) {
+                if let Ok(buffer) = budget.read(file) {
                     println!("INFO: 成功提取图标: {}", path);
                     // 转换为base64
                     return Some(BASE64.encode(&buffer));
@@ -917,7 +1514,7 @@ impl ApkParser {
         let icon_extensions = [".png", ".webp", ".jpg", ".jpeg"];
         for i in 0..archive.len() {
             let file = archive.by_index(i);
-            if let Ok(mut file) = file {
+            if let Ok(file) = file {
                 let name = file.name().to_string();
                 
                 // 检查文件是否可能是图标
@@ -932,8 +1529,7 @@ impl ApkParser {
                     println!("INFO: 发现可能的图标文件: {}", name);
                     
                     // 尝试读取文件
-                    let mut buffer = Vec::new();
-                    if file.read_to_end(&mut buffer).is_ok() {
+                    if let Ok(buffer) = budget.read(file) {
                         println!("INFO: 成功提取图标: {}", name);
                         return Some(BASE64.encode(&buffer));
                     }
@@ -945,221 +1541,31 @@ impl ApkParser {
         None
     }
     
-    /// 从AndroidManifest.xml中提取特定标签的特定属性
-    fn extract_from_manifest(manifest: &str, tag: &str, attribute: &str) -> Option<String> {
-        let tag_pattern = format!(r#"<{}\s+[^>]*{}\s*=\s*"([^"]+)""#, tag, attribute);
-        let regex = Regex::new(&tag_pattern).ok()?;
-        regex.captures(manifest).and_then(|cap| cap.get(1).map(|m| m.as_str().to_string()))
-    }
-    
-    // 解析aapt2输出
-    fn parse_aapt_output<P: AsRef<Path>>(aapt_output: &str, apk_path: P) -> Option<ApkInfo> {
-        println!("INFO: 解析aapt2输出...");
-
-        // 提取包名
-        let package_name = Self::extract_from_aapt_output(aapt_output, r"package: name='([^']+)'");
-        
-        // 提取版本信息
-        let version_name = Self::extract_from_aapt_output(aapt_output, r"versionName='([^']+)'");
-        let version_code = Self::extract_from_aapt_output(aapt_output, r"versionCode='(\d+)'");
-        
-        // 提取SDK版本
-        let min_sdk = Self::extract_from_aapt_output(aapt_output, r"sdkVersion:'(\d+)'");
-        let target_sdk = Self::extract_from_aapt_output(aapt_output, r"targetSdkVersion:'(\d+)'");
-        
-        // 提取权限
-        let mut permissions = Vec::new();
-        let permission_regex = Regex::new(r"uses-permission: name='([^']+)'").ok()?;
-        for cap in permission_regex.captures_iter(aapt_output) {
-            if let Some(perm_match) = cap.get(1) {
-                let perm_name = perm_match.as_str().to_string();
-                let is_dangerous = Self::is_dangerous_permission(&perm_name);
-                permissions.push(Permission {
-                    name: perm_name,
-                    is_dangerous,
-                });
-            }
-        }
-        
-        // 提取主Activity
-        let main_activity = Self::extract_main_activity_from_output(aapt_output);
-        
-        // 获取文件大小
-        let file_size = match std::fs::metadata(apk_path.as_ref()) {
-            Ok(metadata) => metadata.len(),
-            Err(_) => 0,
-        };
-        
-        // 计算文件哈希值而不是使用占位符
-        let md5_hash = match Self::calculate_md5_hash(apk_path.as_ref()) {
-            Ok(hash) => hash,
-            Err(e) => {
-                println!("警告: 计算MD5哈希值失败: {}", e);
-                "计算失败".to_string()
-            }
-        };
-        
-        let sha1_hash = match Self::calculate_file_hash::<Sha1>(apk_path.as_ref()) {
-            Ok(hash) => hash,
-            Err(e) => {
-                println!("警告: 计算SHA1哈希值失败: {}", e);
-                "计算失败".to_string()
-            }
-        };
-        
-        let sha256_hash = match Self::calculate_file_hash::<Sha256>(apk_path.as_ref()) {
-            Ok(hash) => hash,
-            Err(e) => {
-                println!("警告: 计算SHA256哈希值失败: {}", e);
-                "计算失败".to_string()
-            }
-        };
-        
-        // 尝试提取签名信息
-        let signature_info = Self::extract_signature_info_from_aapt_output(aapt_output);
-        
-        // 如果签名信息为空，尝试从APK文件中提取
-        let signature_info = if signature_info.is_none() {
-            match File::open(apk_path.as_ref()) {
-                Ok(file) => {
-                    match ZipArchive::new(file) {
-                        Ok(mut archive) => {
-                            match Self::parse_signature_info(&mut archive) {
-                                Ok(sig_info) => sig_info,
-                                Err(e) => {
-                                    println!("警告: 从APK提取签名信息失败: {}", e);
-                                    None
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            println!("警告: 打开APK文件作为ZIP存档失败: {}", e);
-                            None
-                        }
-                    }
-                },
-                Err(e) => {
-                    println!("警告: 打开APK文件失败: {}", e);
-                    None
-                }
-            }
-        } else {
-            signature_info
-        };
-        
-        // 提取应用图标
-        let icon_base64 = Self::extract_icon(apk_path.as_ref());
+    // 根据解析后的图标资源生成候选文件路径：位图优先，按密度从高到低
+    fn icon_candidates(resources: &ManifestResources) -> Vec<String> {
+        let mut values: Vec<_> = resources.icon.iter()
+            .chain(resources.round_icon.iter())
+            .flat_map(|r| r.values.iter())
+            .filter(|v| v.value.starts_with("res/"))
+            .collect();
         
-        // 更新文件信息
-        let file_info = Some(FileInfo {
-            md5: md5_hash,
-            sha1: sha1_hash, 
-            sha256: sha256_hash,
-            file_size,
-            file_type: "application/vnd.android.package-archive".to_string(),
-            entry_count: 0, // 这个信息需要打开ZIP才能获取
+        let is_bitmap = |path: &str| [".png", ".webp", ".jpg", ".jpeg"].iter().any(|ext| path.ends_with(ext));
+        // anydpi/nodpi的密度值很大，排序时不应优先
+        let density_rank = |density: u16| if density >= 0xFFFE { 0 } else { density };
+        values.sort_by(|a, b| {
+            is_bitmap(&b.value).cmp(&is_bitmap(&a.value))
+                .then(density_rank(b.density).cmp(&density_rank(a.density)))
         });
         
-        // 使用提取的数据创建APK信息对象
-        let package_name = package_name.unwrap_or_else(|| "未知".to_string());
-        let version_name = version_name.unwrap_or_else(|| "未知".to_string());
-        let version_code = version_code.unwrap_or_else(|| "0".to_string());
-        let min_sdk = min_sdk.unwrap_or_else(|| "未知".to_string());
-        let target_sdk = target_sdk.unwrap_or_else(|| "未知".to_string());
-        
-        Some(ApkInfo {
-            package_name,
-            version_name,
-            version_code,
-            min_sdk,
-            target_sdk,
-            signature_info,
-            permissions: Some(permissions),
-            file_info,
-            main_activity,
-            icon_base64,
-        })
-    }
-    
-    // 从aapt2输出提取签名信息
-    fn extract_signature_info_from_aapt_output(aapt_output: &str) -> Option<SignatureInfo> {
-        // 注意：aapt2输出通常不包含完整的签名信息
-        // 这个函数是一个尝试，但可能需要从APK文件中直接读取证书
-        
-        // 尝试提取基本证书信息，如果可能的话
-        let issuer = Self::extract_from_aapt_output(aapt_output, r"Issuer: ([^\n]+)")
-            .unwrap_or_else(|| "未知".to_string());
-        let subject = Self::extract_from_aapt_output(aapt_output, r"Subject: ([^\n]+)")
-            .unwrap_or_else(|| "未知".to_string());
-        
-        // 如果无法从aapt2输出中获取足够的信息，返回None
-        if issuer == "未知" && subject == "未知" {
-            return None;
-        }
-        
-        // 在实际情况下，这些信息可能无法从aapt2输出中提取
-        // 这里只是提供一个框架，实际应用中可能需要其他方法获取这些信息
-        Some(SignatureInfo {
-            issuer,
-            subject,
-            valid_from: "未知".to_string(),
-            valid_to: "未知".to_string(),
-            fingerprint_sha1: None,
-            fingerprint_sha256: None,
-        })
-    }
-    
-    // 从aapt2输出中提取主Activity
-    fn extract_main_activity_from_output(aapt_output: &str) -> Option<String> {
-        // 通常主Activity带有LAUNCHER类别和action.MAIN的Intent过滤器
-        let activity_regex = Regex::new(r"activity: name='([^']+)'").ok()?;
-        let activities: Vec<String> = activity_regex.captures_iter(aapt_output)
-            .filter_map(|cap| cap.get(1).map(|m| m.as_str().to_string()))
-            .collect();
-            
-        // 检查哪个activity有LAUNCHER类别
-        for activity in &activities {
-            let activity_pattern = format!("activity: name='{}'[\\s\\S]*?action: name='android.intent.action.MAIN'[\\s\\S]*?category: name='android.intent.category.LAUNCHER'", 
-                                        regex::escape(activity));
-            let launcher_regex = Regex::new(&activity_pattern).ok()?;
-            if launcher_regex.is_match(aapt_output) {
-                return Some(activity.clone());
+        let mut paths: Vec<String> = Vec::new();
+        for value in values {
+            if is_bitmap(&value.value) && !paths.contains(&value.value) {
+                paths.push(value.value.clone());
             }
         }
-        
-        // 回退：如果找不到明确的主Activity，返回第一个Activity
-        activities.first().cloned()
+        paths
     }
     
-    // 从aapt2输出中提取信息的辅助方法
-    fn extract_from_aapt_output(output: &str, pattern: &str) -> Option<String> {
-        let regex = Regex::new(pattern).ok()?;
-        regex.captures(output)
-            .and_then(|cap| cap.get(1).map(|m| m.as_str().to_string()))
-    }
-    
-    // 检查权限是否为危险权限
-    fn is_dangerous_permission(permission: &str) -> bool {
-        // 列出Android危险权限
-        const DANGEROUS_PERMISSIONS: [&str; 9] = [
-            "android.permission.READ_CALENDAR",
-            "android.permission.WRITE_CALENDAR",
-            "android.permission.CAMERA",
-            "android.permission.READ_CONTACTS",
-            "android.permission.WRITE_CONTACTS",
-            "android.permission.GET_ACCOUNTS",
-            "android.permission.ACCESS_FINE_LOCATION",
-            "android.permission.ACCESS_COARSE_LOCATION",
-            "android.permission.RECORD_AUDIO",
-        ];
-        
-        DANGEROUS_PERMISSIONS.contains(&permission) ||
-        permission.starts_with("android.permission.READ_") && permission.contains("_EXTERNAL_STORAGE") ||
-        permission.starts_with("android.permission.WRITE_") && permission.contains("_EXTERNAL_STORAGE") ||
-        permission.contains("SMS") || permission.contains("CALL") || 
-        permission.contains("PHONE") || permission.contains("STORAGE")
-    }
-
     // 计算文件哈希值的辅助方法
     fn calculate_file_hash<D>(path: &Path) -> Result<String, ApkParserError> 
     where
@@ -1238,9 +1644,13 @@ fn calculate_fingerprint<D: sha1::Digest>(data: &[u8]) -> String {
     // 获取结果
     let result = hasher.finalize();
     
-    // 格式化为带冒号的十六进制
-    result.iter()
+    format_fingerprint(&result)
+}
+
+/// 格式化为带冒号的大写十六进制
+fn format_fingerprint(digest: &[u8]) -> String {
+    digest.iter()
         .map(|b| format!("{:02X}", b))
         .collect::<Vec<String>>()
         .join(":")
-} 
\ No newline at end of file
+} 
@@ -0,0 +1,864 @@
+//! resources.arsc 资源表解析器
+//!
+//! 解析编译后的资源表（包、类型规格、类型条目、配置限定符、复合条目以及全局字符串池），
+//! 并将清单中的`@0x7f...`、`@mipmap/...`等引用解析为每个配置下的具体值或文件路径。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::ApkParserError;
+use crate::limits::ParseLimits;
+use crate::axml::{AttributeValue, ResValue, StringPool, TYPE_REFERENCE, TYPE_DYNAMIC_REFERENCE, TYPE_STRING};
+
+// 块类型
+const RES_STRING_POOL_TYPE: u16 = 0x0001;
+const RES_TABLE_TYPE: u16 = 0x0002;
+const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+const RES_TABLE_TYPE_TYPE: u16 = 0x0201;
+const RES_TABLE_TYPE_SPEC_TYPE: u16 = 0x0202;
+
+// ResTable_type标志
+const TYPE_FLAG_SPARSE: u8 = 0x01;
+const TYPE_FLAG_OFFSET16: u8 = 0x02;
+
+// ResTable_entry标志
+const ENTRY_FLAG_COMPLEX: u16 = 0x0001;
+const ENTRY_FLAG_PUBLIC: u16 = 0x0002;
+const ENTRY_FLAG_COMPACT: u16 = 0x0008;
+
+const NO_ENTRY: u32 = 0xFFFF_FFFF;
+
+/// 引用链的最大解析深度，防止循环引用
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+/// 资源配置（ResTable_config中常用的字段）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceConfig {
+    pub mcc: u16,
+    pub mnc: u16,
+    /// 语言，如`zh`
+    pub language: String,
+    /// 地区，如`CN`
+    pub region: String,
+    /// 文字脚本，如`Hans`
+    pub script: String,
+    pub orientation: u8,
+    pub density: u16,
+    pub sdk_version: u16,
+    pub screen_layout: u8,
+    pub ui_mode: u8,
+    pub smallest_screen_width_dp: u16,
+    pub screen_width_dp: u16,
+    pub screen_height_dp: u16,
+}
+
+impl ResourceConfig {
+    fn parse(data: &[u8]) -> Self {
+        let size = (le_u32(data, 0).unwrap_or(0) as usize).min(data.len());
+        let data = &data[..size];
+        let u8_at = |offset: usize| data.get(offset).copied().unwrap_or(0);
+        let u16_at = |offset: usize| le_u16(data, offset).unwrap_or(0);
+
+        Self {
+            mcc: u16_at(4),
+            mnc: u16_at(6),
+            language: unpack_locale(data.get(8..10).unwrap_or(&[0, 0]), b'a'),
+            region: unpack_locale(data.get(10..12).unwrap_or(&[0, 0]), b'0'),
+            script: data
+                .get(36..40)
+                .map(|s| s.iter().take_while(|b| **b != 0).map(|b| *b as char).collect())
+                .unwrap_or_default(),
+            orientation: u8_at(12),
+            density: u16_at(14),
+            sdk_version: u16_at(24),
+            screen_layout: u8_at(28),
+            ui_mode: u8_at(29),
+            smallest_screen_width_dp: u16_at(30),
+            screen_width_dp: u16_at(32),
+            screen_height_dp: u16_at(34),
+        }
+    }
+
+    /// 两个配置都指定了语言但语言或地区不同
+    fn conflicts_in_locale(&self, other: &ResourceConfig) -> bool {
+        !self.language.is_empty()
+            && !other.language.is_empty()
+            && (self.language != other.language || self.region != other.region)
+    }
+
+    /// 本配置未指定的限定符取自`outer`，用于引用链中被引用条目的配置
+    fn inherit(&self, outer: &ResourceConfig) -> ResourceConfig {
+        let pick_u8 = |inner: u8, outer: u8| if inner != 0 { inner } else { outer };
+        let pick_u16 = |inner: u16, outer: u16| if inner != 0 { inner } else { outer };
+        // 语言、地区与文字脚本作为一个整体继承
+        let locale = if self.language.is_empty() { outer } else { self };
+        ResourceConfig {
+            mcc: pick_u16(self.mcc, outer.mcc),
+            mnc: pick_u16(self.mnc, outer.mnc),
+            language: locale.language.clone(),
+            region: locale.region.clone(),
+            script: locale.script.clone(),
+            orientation: pick_u8(self.orientation, outer.orientation),
+            density: pick_u16(self.density, outer.density),
+            sdk_version: self.sdk_version.max(outer.sdk_version),
+            screen_layout: pick_u8(self.screen_layout, outer.screen_layout),
+            ui_mode: pick_u8(self.ui_mode, outer.ui_mode),
+            smallest_screen_width_dp: pick_u16(self.smallest_screen_width_dp, outer.smallest_screen_width_dp),
+            screen_width_dp: pick_u16(self.screen_width_dp, outer.screen_width_dp),
+            screen_height_dp: pick_u16(self.screen_height_dp, outer.screen_height_dp),
+        }
+    }
+
+    /// 生成aapt风格的限定符字符串，如`zh-rCN-xxhdpi-v4`；默认配置返回空字符串
+    pub fn qualifiers(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if self.mcc != 0 {
+            parts.push(format!("mcc{}", self.mcc));
+        }
+        if self.mnc != 0 {
+            parts.push(format!("mnc{}", self.mnc));
+        }
+        if !self.language.is_empty() {
+            if self.script.is_empty() {
+                parts.push(self.language.clone());
+                if !self.region.is_empty() {
+                    parts.push(format!("r{}", self.region));
+                }
+            } else {
+                let mut tag = format!("b+{}+{}", self.language, self.script);
+                if !self.region.is_empty() {
+                    tag.push_str(&format!("+{}", self.region));
+                }
+                parts.push(tag);
+            }
+        }
+        match self.screen_layout & 0xC0 {
+            0x40 => parts.push("ldltr".to_string()),
+            0x80 => parts.push("ldrtl".to_string()),
+            _ => {}
+        }
+        if self.smallest_screen_width_dp != 0 {
+            parts.push(format!("sw{}dp", self.smallest_screen_width_dp));
+        }
+        if self.screen_width_dp != 0 {
+            parts.push(format!("w{}dp", self.screen_width_dp));
+        }
+        if self.screen_height_dp != 0 {
+            parts.push(format!("h{}dp", self.screen_height_dp));
+        }
+        match self.screen_layout & 0x0F {
+            0x01 => parts.push("small".to_string()),
+            0x02 => parts.push("normal".to_string()),
+            0x03 => parts.push("large".to_string()),
+            0x04 => parts.push("xlarge".to_string()),
+            _ => {}
+        }
+        match self.orientation {
+            1 => parts.push("port".to_string()),
+            2 => parts.push("land".to_string()),
+            _ => {}
+        }
+        match self.ui_mode & 0x0F {
+            0x02 => parts.push("desk".to_string()),
+            0x03 => parts.push("car".to_string()),
+            0x04 => parts.push("television".to_string()),
+            0x05 => parts.push("appliance".to_string()),
+            0x06 => parts.push("watch".to_string()),
+            0x07 => parts.push("vrheadset".to_string()),
+            _ => {}
+        }
+        match self.ui_mode & 0x30 {
+            0x10 => parts.push("notnight".to_string()),
+            0x20 => parts.push("night".to_string()),
+            _ => {}
+        }
+        if self.density != 0 {
+            parts.push(density_qualifier(self.density));
+        }
+        if self.sdk_version != 0 {
+            parts.push(format!("v{}", self.sdk_version));
+        }
+        parts.join("-")
+    }
+}
+
+fn density_qualifier(density: u16) -> String {
+    match density {
+        120 => "ldpi".to_string(),
+        160 => "mdpi".to_string(),
+        213 => "tvdpi".to_string(),
+        240 => "hdpi".to_string(),
+        320 => "xhdpi".to_string(),
+        480 => "xxhdpi".to_string(),
+        640 => "xxxhdpi".to_string(),
+        0xFFFE => "anydpi".to_string(),
+        0xFFFF => "nodpi".to_string(),
+        other => format!("{}dpi", other),
+    }
+}
+
+// 解码语言/地区代码，支持3字母的压缩编码
+fn unpack_locale(bytes: &[u8], base: u8) -> String {
+    if bytes.len() < 2 || bytes[0] == 0 {
+        return String::new();
+    }
+    if bytes[0] & 0x80 != 0 {
+        let first = bytes[1] & 0x1f;
+        let second = ((bytes[1] & 0xe0) >> 5) | ((bytes[0] & 0x03) << 3);
+        let third = (bytes[0] & 0x7c) >> 2;
+        return [first, second, third].iter().map(|c| (c + base) as char).collect();
+    }
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+/// 资源条目的值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EntryValue {
+    /// 简单值
+    Simple { value: ResValue },
+    /// 复合值（style、array、plurals等）
+    Bag { parent: u32, items: Vec<(u32, ResValue)> },
+}
+
+/// 某个配置下的资源条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceEntry {
+    /// 资源配置
+    pub config: ResourceConfig,
+    /// 是否为公开资源
+    pub is_public: bool,
+    /// 条目值
+    pub value: EntryValue,
+}
+
+/// 资源包
+#[derive(Debug, Clone, Default)]
+pub struct ResourcePackage {
+    /// 包ID（应用通常为0x7f）
+    pub id: u32,
+    /// 包名
+    pub name: String,
+    /// 类型名称池（string、mipmap、drawable...）
+    pub type_names: StringPool,
+    /// 键名称池（资源名）
+    pub key_names: StringPool,
+    /// 类型ID偏移
+    pub type_id_offset: u32,
+    /// 各类型的规格标志（类型ID -> 条目标志）
+    pub type_specs: HashMap<u8, Vec<u32>>,
+}
+
+/// 解析后的资源表
+#[derive(Debug, Clone, Default)]
+pub struct ResourceTable {
+    /// 全局字符串池
+    pub strings: StringPool,
+    /// 资源包
+    pub packages: Vec<ResourcePackage>,
+    /// 资源ID -> (键名, 各配置下的条目)
+    entries: HashMap<u32, (String, Vec<ResourceEntry>)>,
+}
+
+/// 解析后的单个配置值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedValue {
+    /// 配置限定符，默认配置为`default`
+    pub config: String,
+    /// 屏幕密度（0表示未指定）
+    pub density: u16,
+    /// 最终值（字符串或文件路径）
+    pub value: String,
+}
+
+/// 解析后的资源引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedResource {
+    /// 原始引用，如`@0x7f0b0001`
+    pub reference: String,
+    /// 资源ID
+    pub resource_id: Option<u32>,
+    /// 资源名，如`@string/app_name`
+    pub name: Option<String>,
+    /// 各配置下的值
+    pub values: Vec<ResolvedValue>,
+}
+
+impl ResolvedResource {
+    /// 默认配置下的值，若无则取第一个
+    pub fn default_value(&self) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|v| v.config == "default")
+            .or_else(|| self.values.first())
+            .map(|v| v.value.as_str())
+    }
+}
+
+/// 清单中引用资源的解析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestResources {
+    /// android:label
+    pub label: Option<ResolvedResource>,
+    /// android:icon
+    pub icon: Option<ResolvedResource>,
+    /// android:roundIcon
+    pub round_icon: Option<ResolvedResource>,
+    /// android:networkSecurityConfig
+    pub network_security_config: Option<ResolvedResource>,
+    /// android:dataExtractionRules
+    pub data_extraction_rules: Option<ResolvedResource>,
+}
+
+impl ResourceTable {
+    /// 解析resources.arsc
+    ///
+    /// # 参数
+    /// * `data` - resources.arsc的完整内容
+    /// * `limits` - 解析限制（字符串池大小）
+    ///
+    /// # 返回
+    /// 资源表或错误
+    pub fn parse(data: &[u8], limits: &ParseLimits) -> Result<Self, ApkParserError> {
+        if le_u16(data, 0)? != RES_TABLE_TYPE {
+            return Err(ApkParserError::InvalidResources("不是资源表格式".to_string()));
+        }
+        let header_size = le_u16(data, 2)? as usize;
+        let total_size = (le_u32(data, 4)? as usize).min(data.len());
+
+        let mut table = ResourceTable::default();
+        let mut offset = header_size;
+        while offset + 8 <= total_size {
+            let chunk_type = le_u16(data, offset)?;
+            let chunk_size = le_u32(data, offset + 4)? as usize;
+            if chunk_size < 8 || offset + chunk_size > total_size {
+                println!("WARNING: 资源表块大小异常，偏移: {}", offset);
+                break;
+            }
+            let chunk = &data[offset..offset + chunk_size];
+            match chunk_type {
+                RES_STRING_POOL_TYPE => {
+                    table.strings = StringPool::parse(chunk, limits).map_err(resources_error)?;
+                }
+                RES_TABLE_PACKAGE_TYPE => {
+                    table.parse_package(chunk, limits)?;
+                }
+                _ => {}
+            }
+            offset += chunk_size;
+        }
+
+        Ok(table)
+    }
+
+    fn parse_package(&mut self, chunk: &[u8], limits: &ParseLimits) -> Result<(), ApkParserError> {
+        let header_size = le_u16(chunk, 2)? as usize;
+        let id = le_u32(chunk, 8)?;
+        let name_units: Vec<u16> = chunk
+            .get(12..268)
+            .unwrap_or(&[])
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|c| *c != 0)
+            .collect();
+        let type_strings_offset = le_u32(chunk, 268)? as usize;
+        let key_strings_offset = le_u32(chunk, 276)? as usize;
+        let type_id_offset = if header_size >= 288 { le_u32(chunk, 284)? } else { 0 };
+
+        let mut package = ResourcePackage {
+            id,
+            name: String::from_utf16_lossy(&name_units),
+            type_id_offset,
+            ..Default::default()
+        };
+        package.type_names = parse_sub_pool(chunk, type_strings_offset, limits)?;
+        package.key_names = parse_sub_pool(chunk, key_strings_offset, limits)?;
+
+        let mut offset = header_size;
+        while offset + 8 <= chunk.len() {
+            let chunk_type = le_u16(chunk, offset)?;
+            let chunk_size = le_u32(chunk, offset + 4)? as usize;
+            if chunk_size < 8 || offset + chunk_size > chunk.len() {
+                println!("WARNING: 资源包块大小异常，偏移: {}", offset);
+                break;
+            }
+            let sub = &chunk[offset..offset + chunk_size];
+            match chunk_type {
+                RES_TABLE_TYPE_SPEC_TYPE => {
+                    let type_id = sub.get(8).copied().unwrap_or(0);
+                    let entry_count = le_u32(sub, 12)? as usize;
+                    let spec_header = le_u16(sub, 2)? as usize;
+                    let flags = (0..entry_count)
+                        .map_while(|i| le_u32(sub, spec_header + i * 4).ok())
+                        .collect();
+                    package.type_specs.insert(type_id, flags);
+                }
+                RES_TABLE_TYPE_TYPE => {
+                    self.parse_type(&package, sub)?;
+                }
+                _ => {}
+            }
+            offset += chunk_size;
+        }
+
+        self.packages.push(package);
+        Ok(())
+    }
+
+    fn parse_type(&mut self, package: &ResourcePackage, chunk: &[u8]) -> Result<(), ApkParserError> {
+        let header_size = le_u16(chunk, 2)? as usize;
+        let type_id = chunk.get(8).copied().unwrap_or(0);
+        let flags = chunk.get(9).copied().unwrap_or(0);
+        let entry_count = le_u32(chunk, 12)? as usize;
+        let entries_start = le_u32(chunk, 16)? as usize;
+        let config = ResourceConfig::parse(chunk.get(20..header_size).unwrap_or(&[]));
+
+        // 条目数来自文件，先确认偏移表确实能放进本块，再按其分配
+        let index_width = if flags & TYPE_FLAG_OFFSET16 != 0 && flags & TYPE_FLAG_SPARSE == 0 { 2 } else { 4 };
+        let table_end = entry_count.checked_mul(index_width).and_then(|size| size.checked_add(header_size));
+        if table_end.map_or(true, |end| end > chunk.len()) {
+            return Err(ApkParserError::InvalidResources(format!("类型块的条目数{}超出块大小{}", entry_count, chunk.len())));
+        }
+
+        // 收集 (条目索引, 条目偏移)
+        let mut offsets: Vec<(u32, usize)> = Vec::with_capacity(entry_count);
+        if flags & TYPE_FLAG_SPARSE != 0 {
+            for i in 0..entry_count {
+                let index = le_u16(chunk, header_size + i * 4)? as u32;
+                let offset = le_u16(chunk, header_size + i * 4 + 2)? as usize * 4;
+                offsets.push((index, offset));
+            }
+        } else if flags & TYPE_FLAG_OFFSET16 != 0 {
+            for i in 0..entry_count {
+                let offset = le_u16(chunk, header_size + i * 2)?;
+                if offset != 0xFFFF {
+                    offsets.push((i as u32, offset as usize * 4));
+                }
+            }
+        } else {
+            for i in 0..entry_count {
+                let offset = le_u32(chunk, header_size + i * 4)?;
+                if offset != NO_ENTRY {
+                    offsets.push((i as u32, offset as usize));
+                }
+            }
+        }
+
+        let package_id = if package.id == 0 { 0x7f } else { package.id };
+        let type_id_full = (type_id as u32).saturating_add(package.type_id_offset) & 0xff;
+        for (index, offset) in offsets {
+            let Some(base) = entries_start.checked_add(offset) else {
+                continue;
+            };
+            let Some((key_index, entry)) = parse_entry(chunk, base, &config) else {
+                continue;
+            };
+            let key = package.key_names.get(key_index).unwrap_or_default();
+            let resource_id = (package_id << 24) | (type_id_full << 16) | index;
+            let slot = self.entries.entry(resource_id).or_insert_with(|| (key, Vec::new()));
+            slot.1.push(entry);
+        }
+        Ok(())
+    }
+
+    /// 获取某资源ID在所有配置下的条目
+    pub fn entries(&self, resource_id: u32) -> &[ResourceEntry] {
+        self.entries
+            .get(&resource_id)
+            .map(|(_, entries)| entries.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// 资源条目总数
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 全部资源ID
+    pub fn resource_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// 资源ID对应的类型名，如`mipmap`
+    pub fn type_name(&self, resource_id: u32) -> Option<String> {
+        let package = self.package_for(resource_id)?;
+        let type_id = (resource_id >> 16) & 0xff;
+        let index = type_id.checked_sub(1 + package.type_id_offset)?;
+        package.type_names.get(index)
+    }
+
+    /// 资源ID对应的名称，如`@mipmap/ic_launcher`
+    pub fn resource_name(&self, resource_id: u32) -> Option<String> {
+        let (key, _) = self.entries.get(&resource_id)?;
+        let type_name = self.type_name(resource_id)?;
+        Some(format!("@{}/{}", type_name, key))
+    }
+
+    /// 根据类型名和资源名查找资源ID
+    pub fn find_id(&self, type_name: &str, key: &str) -> Option<u32> {
+        self.entries
+            .iter()
+            .filter(|(_, (k, _))| k == key)
+            .map(|(id, _)| *id)
+            .find(|id| self.type_name(*id).as_deref() == Some(type_name))
+    }
+
+    fn package_for(&self, resource_id: u32) -> Option<&ResourcePackage> {
+        let package_id = resource_id >> 24;
+        self.packages
+            .iter()
+            .find(|p| p.id == package_id || (p.id == 0 && package_id == 0x7f))
+    }
+
+    /// 解析资源ID在所有配置下的最终值，自动跟随引用链
+    pub fn resolve(&self, resource_id: u32) -> Vec<ResolvedValue> {
+        let mut values = Vec::new();
+        self.resolve_into(resource_id, None, 0, &mut values);
+        values
+    }
+
+    fn resolve_into(&self, resource_id: u32, outer: Option<&ResourceConfig>, depth: usize, out: &mut Vec<ResolvedValue>) {
+        if depth > MAX_REFERENCE_DEPTH {
+            return;
+        }
+        for entry in self.entries(resource_id) {
+            // 每个值使用产生它的条目的配置，未指定的限定符取自引用它的外层条目；
+            // 语言与外层不同的条目在外层配置下不会被选中
+            let config = match outer {
+                Some(outer) if entry.config.conflicts_in_locale(outer) => continue,
+                Some(outer) => entry.config.inherit(outer),
+                None => entry.config.clone(),
+            };
+            match &entry.value {
+                EntryValue::Simple { value } if is_reference(value) && value.data != 0 => {
+                    let before = out.len();
+                    self.resolve_into(value.data, Some(&config), depth + 1, out);
+                    if out.len() == before {
+                        out.push(self.make_value(&config, format!("@0x{:08x}", value.data)));
+                    }
+                }
+                EntryValue::Simple { value } => {
+                    out.push(self.make_value(&config, self.format_value(*value)));
+                }
+                EntryValue::Bag { items, .. } => {
+                    let rendered: Vec<String> = items.iter().map(|(_, v)| self.format_value(*v)).collect();
+                    out.push(self.make_value(&config, format!("[{}]", rendered.join(", "))));
+                }
+            }
+        }
+    }
+
+    fn make_value(&self, config: &ResourceConfig, value: String) -> ResolvedValue {
+        let qualifiers = config.qualifiers();
+        ResolvedValue {
+            config: if qualifiers.is_empty() { "default".to_string() } else { qualifiers },
+            density: config.density,
+            value,
+        }
+    }
+
+    fn format_value(&self, value: ResValue) -> String {
+        if value.data_type == TYPE_STRING {
+            return self.strings.get(value.data).unwrap_or_default();
+        }
+        AttributeValue::from_res_value(value, &self.strings).to_string()
+    }
+
+    /// 解析清单中的资源引用
+    ///
+    /// 支持`@0x7f0b0001`、`@7f0b0001`、`@string/app_name`、`@pkg:string/app_name`等形式，
+    /// 非引用的字面值原样作为默认配置的值返回。
+    pub fn resolve_reference(&self, reference: &str) -> ResolvedResource {
+        let resource_id = parse_reference(reference).or_else(|| {
+            let body = reference.strip_prefix('@')?;
+            let body = body.split_once(':').map(|(_, rest)| rest).unwrap_or(body);
+            let (type_name, key) = body.split_once('/')?;
+            self.find_id(type_name, key)
+        });
+
+        match resource_id {
+            Some(id) => ResolvedResource {
+                reference: reference.to_string(),
+                resource_id: Some(id),
+                name: self.resource_name(id),
+                values: self.resolve(id),
+            },
+            None => ResolvedResource {
+                reference: reference.to_string(),
+                resource_id: None,
+                name: None,
+                values: if reference.starts_with('@') {
+                    Vec::new()
+                } else {
+                    vec![ResolvedValue {
+                        config: "default".to_string(),
+                        density: 0,
+                        value: reference.to_string(),
+                    }]
+                },
+            },
+        }
+    }
+}
+
+fn is_reference(value: &ResValue) -> bool {
+    value.data_type == TYPE_REFERENCE || value.data_type == TYPE_DYNAMIC_REFERENCE
+}
+
+/// 解析十六进制形式的资源引用（`@0x7f0b0001`或`@7f0b0001`）
+pub fn parse_reference(reference: &str) -> Option<u32> {
+    let hex = reference.strip_prefix('@')?;
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() != 8 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+fn parse_sub_pool(chunk: &[u8], offset: usize, limits: &ParseLimits) -> Result<StringPool, ApkParserError> {
+    if offset == 0 || offset + 8 > chunk.len() {
+        return Ok(StringPool::default());
+    }
+    let size = (le_u32(chunk, offset + 4)? as usize).min(chunk.len() - offset);
+    StringPool::parse(&chunk[offset..offset + size], limits).map_err(resources_error)
+}
+
+/// 字符串池错误归为资源表错误，超出解析限制的错误保持原样
+fn resources_error(error: ApkParserError) -> ApkParserError {
+    match error {
+        ApkParserError::LimitExceeded(_) => error,
+        other => ApkParserError::InvalidResources(other.to_string()),
+    }
+}
+
+// 解析单个条目，返回 (键索引, 条目)
+fn parse_entry(chunk: &[u8], base: usize, config: &ResourceConfig) -> Option<(u32, ResourceEntry)> {
+    let size = le_u16(chunk, base).ok()?;
+    let flags = le_u16(chunk, base + 2).ok()?;
+    let is_public = flags & ENTRY_FLAG_PUBLIC != 0;
+
+    if flags & ENTRY_FLAG_COMPACT != 0 {
+        // 紧凑条目：size字段为键索引，flags高字节为数据类型
+        let value = ResValue {
+            data_type: (flags >> 8) as u8,
+            data: le_u32(chunk, base + 4).ok()?,
+        };
+        return Some((size as u32, ResourceEntry {
+            config: config.clone(),
+            is_public,
+            value: EntryValue::Simple { value },
+        }));
+    }
+
+    let key = le_u32(chunk, base + 4).ok()?;
+    let size = size as usize;
+    let value = if flags & ENTRY_FLAG_COMPLEX != 0 {
+        let parent = le_u32(chunk, base + 8).ok()?;
+        let count = le_u32(chunk, base + 12).ok()? as usize;
+        let mut items = Vec::new();
+        for i in 0..count {
+            let item = base + size + i * 12;
+            let (Ok(name), Some(data_type), Ok(data)) =
+                (le_u32(chunk, item), chunk.get(item + 7).copied(), le_u32(chunk, item + 8))
+            else {
+                break;
+            };
+            items.push((name, ResValue { data_type, data }));
+        }
+        EntryValue::Bag { parent, items }
+    } else {
+        let value_base = base + size;
+        EntryValue::Simple {
+            value: ResValue {
+                data_type: *chunk.get(value_base + 3)?,
+                data: le_u32(chunk, value_base + 4).ok()?,
+            },
+        }
+    };
+
+    Some((key, ResourceEntry {
+        config: config.clone(),
+        is_public,
+        value,
+    }))
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Result<u16, ApkParserError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| ApkParserError::InvalidResources(format!("读取越界，偏移: {}", offset)))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Result<u32, ApkParserError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| ApkParserError::InvalidResources(format!("读取越界，偏移: {}", offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axml::testing::string_pool;
+    use crate::axml::TYPE_NULL;
+
+    const APP_NAME: u32 = 0x7f01_0000;
+    const LABEL: u32 = 0x7f01_0001;
+    const LOOP: u32 = 0x7f01_0002;
+    const ICON: u32 = 0x7f02_0000;
+
+    fn chunk(chunk_type: u16, header: &[u8], body: &[u8]) -> Vec<u8> {
+        let header_size = 8 + header.len();
+        let mut out = chunk_type.to_le_bytes().to_vec();
+        out.extend((header_size as u16).to_le_bytes());
+        out.extend(((header_size + body.len()) as u32).to_le_bytes());
+        out.extend(header);
+        out.extend(body);
+        out
+    }
+
+    /// 64字节的ResTable_config
+    fn config(language: &[u8; 2], region: &[u8; 2], density: u16) -> Vec<u8> {
+        let mut config = vec![0u8; 64];
+        config[..4].copy_from_slice(&64u32.to_le_bytes());
+        config[8..10].copy_from_slice(language);
+        config[10..12].copy_from_slice(region);
+        config[14..16].copy_from_slice(&density.to_le_bytes());
+        config
+    }
+
+    /// 类型块：`entries`为(键索引, 数据类型, 数据)，None表示该索引没有条目
+    fn type_chunk(type_id: u8, config: &[u8], entries: &[Option<(u32, u8, u32)>]) -> Vec<u8> {
+        let mut offsets = Vec::new();
+        let mut data = Vec::new();
+        for entry in entries {
+            match entry {
+                Some((key, data_type, value)) => {
+                    offsets.extend((data.len() as u32).to_le_bytes());
+                    data.extend(8u16.to_le_bytes());
+                    data.extend(0u16.to_le_bytes());
+                    data.extend(key.to_le_bytes());
+                    data.extend([8, 0, 0, *data_type]);
+                    data.extend(value.to_le_bytes());
+                }
+                None => offsets.extend(NO_ENTRY.to_le_bytes()),
+            }
+        }
+        let mut header = vec![type_id, 0, 0, 0];
+        header.extend((entries.len() as u32).to_le_bytes());
+        header.extend(((20 + config.len() + offsets.len()) as u32).to_le_bytes());
+        header.extend(config);
+        chunk(RES_TABLE_TYPE_TYPE, &header, &[offsets, data].concat())
+    }
+
+    fn package(types: &[Vec<u8>]) -> Vec<u8> {
+        let type_names = string_pool(&["string", "mipmap"], false);
+        let key_names = string_pool(&["app_name", "label", "loop", "ic_launcher"], true);
+        let mut header = 0x7fu32.to_le_bytes().to_vec();
+        let mut name = vec![0u8; 256];
+        for (i, unit) in "com.example".encode_utf16().enumerate() {
+            name[i * 2..i * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+        header.extend(name);
+        header.extend(288u32.to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        header.extend(((288 + type_names.len()) as u32).to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        let body = [type_names, key_names, types.concat()].concat();
+        chunk(RES_TABLE_PACKAGE_TYPE, &header, &body)
+    }
+
+    fn table(types: &[Vec<u8>]) -> Vec<u8> {
+        let strings = string_pool(&["Example", "示例", "res/mipmap-xxhdpi-v4/ic_launcher.png", "範例"], false);
+        let body = [strings, package(types)].concat();
+        chunk(RES_TABLE_TYPE, &1u32.to_le_bytes(), &body)
+    }
+
+    fn sample() -> ResourceTable {
+        let types = [
+            type_chunk(1, &config(&[0, 0], &[0, 0], 0), &[
+                Some((0, TYPE_STRING, 0)),
+                Some((1, TYPE_REFERENCE, APP_NAME)),
+                Some((2, TYPE_REFERENCE, LOOP)),
+            ]),
+            type_chunk(1, &config(b"zh", b"CN", 0), &[Some((0, TYPE_STRING, 1))]),
+            type_chunk(1, &config(b"zh", b"TW", 0), &[Some((0, TYPE_STRING, 3)), Some((1, TYPE_REFERENCE, ICON))]),
+            type_chunk(2, &config(&[0, 0], &[0, 0], 480), &[Some((3, TYPE_STRING, 2))]),
+        ];
+        ResourceTable::parse(&table(&types), &ParseLimits::default()).unwrap()
+    }
+
+    fn values(resolved: &[ResolvedValue]) -> Vec<(&str, &str)> {
+        resolved.iter().map(|v| (v.config.as_str(), v.value.as_str())).collect()
+    }
+
+    #[test]
+    fn parses_entries_per_configuration() {
+        let table = sample();
+
+        assert_eq!(table.packages[0].name, "com.example");
+        assert_eq!(table.entry_count(), 4);
+        assert_eq!(table.resource_name(APP_NAME).as_deref(), Some("@string/app_name"));
+        assert_eq!(table.resource_name(ICON).as_deref(), Some("@mipmap/ic_launcher"));
+        assert_eq!(table.find_id("mipmap", "ic_launcher"), Some(ICON));
+        assert_eq!(values(&table.resolve(APP_NAME)), vec![("default", "Example"), ("zh-rCN", "示例"), ("zh-rTW", "範例")]);
+        assert_eq!(table.resolve(ICON)[0].density, 480);
+    }
+
+    #[test]
+    fn reference_chain_keeps_each_value_config() {
+        let table = sample();
+
+        // 默认配置的label引用app_name：各语言的值保留自己的配置
+        // zh-rTW的label引用图标：图标的密度与外层的语言合并
+        assert_eq!(values(&table.resolve(LABEL)), vec![
+            ("default", "Example"),
+            ("zh-rCN", "示例"),
+            ("zh-rTW", "範例"),
+            ("zh-rTW-xxhdpi", "res/mipmap-xxhdpi-v4/ic_launcher.png"),
+        ]);
+    }
+
+    #[test]
+    fn circular_reference_stops() {
+        let table = sample();
+        assert_eq!(values(&table.resolve(LOOP)), vec![("default", "@0x7f010002")]);
+    }
+
+    #[test]
+    fn resolves_manifest_references() {
+        let table = sample();
+
+        for reference in ["@0x7f010000", "@7f010000", "@string/app_name", "@android:string/app_name"] {
+            let resolved = table.resolve_reference(reference);
+            assert_eq!(resolved.resource_id, Some(APP_NAME), "{}", reference);
+            assert_eq!(resolved.default_value(), Some("Example"));
+        }
+        assert_eq!(values(&table.resolve_reference("Literal").values), vec![("default", "Literal")]);
+        assert!(table.resolve_reference("@string/missing").values.is_empty());
+    }
+
+    #[test]
+    fn formats_config_qualifiers() {
+        let mut parsed = ResourceConfig::parse(&config(b"zh", b"CN", 480));
+        assert_eq!(parsed.qualifiers(), "zh-rCN-xxhdpi");
+        parsed.sdk_version = 21;
+        parsed.ui_mode = 0x20;
+        parsed.orientation = 2;
+        parsed.script = "Hans".to_string();
+        assert_eq!(parsed.qualifiers(), "b+zh+Hans+CN-land-night-xxhdpi-v21");
+        assert_eq!(ResourceConfig::parse(&config(&[0, 0], &[0, 0], 0)).qualifiers(), "");
+
+        // 3字母语言的压缩编码：fil
+        assert_eq!(unpack_locale(&[0xad, 0x05], b'a'), "fil");
+    }
+
+    #[test]
+    fn rejects_oversized_entry_count() {
+        let mut bad = type_chunk(1, &config(&[0, 0], &[0, 0], 0), &[Some((0, TYPE_NULL, 0))]);
+        bad[12..16].copy_from_slice(&1_000_000u32.to_le_bytes());
+        assert!(ResourceTable::parse(&table(&[bad]), &ParseLimits::default()).is_err());
+        assert!(ResourceTable::parse(b"\x03\x00\x08\x00\x08\x00\x00\x00", &ParseLimits::default()).is_err());
+    }
+}
@@ -0,0 +1,1054 @@
+//! Android二进制XML (AXML) 解析器
+//!
+//! 直接解析APK中经过aapt编译的二进制XML（如AndroidManifest.xml），
+//! 不依赖aapt2等外部工具。支持字符串池（UTF-8/UTF-16）、资源ID映射、
+//! 命名空间、元素开始/结束以及带类型的属性值。
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::ApkParserError;
+use crate::limits::ParseLimits;
+
+// 块类型
+const RES_STRING_POOL_TYPE: u16 = 0x0001;
+const RES_XML_TYPE: u16 = 0x0003;
+const RES_XML_START_NAMESPACE_TYPE: u16 = 0x0100;
+const RES_XML_END_NAMESPACE_TYPE: u16 = 0x0101;
+const RES_XML_START_ELEMENT_TYPE: u16 = 0x0102;
+const RES_XML_END_ELEMENT_TYPE: u16 = 0x0103;
+const RES_XML_CDATA_TYPE: u16 = 0x0104;
+const RES_XML_RESOURCE_MAP_TYPE: u16 = 0x0180;
+
+// 字符串池标志
+const UTF8_FLAG: u32 = 1 << 8;
+
+/// Android命名空间URI
+pub const ANDROID_NS: &str = "http://schemas.android.com/apk/res/android";
+
+// Res_value数据类型
+pub const TYPE_NULL: u8 = 0x00;
+pub const TYPE_REFERENCE: u8 = 0x01;
+pub const TYPE_ATTRIBUTE: u8 = 0x02;
+pub const TYPE_STRING: u8 = 0x03;
+pub const TYPE_FLOAT: u8 = 0x04;
+pub const TYPE_DIMENSION: u8 = 0x05;
+pub const TYPE_FRACTION: u8 = 0x06;
+pub const TYPE_DYNAMIC_REFERENCE: u8 = 0x07;
+pub const TYPE_DYNAMIC_ATTRIBUTE: u8 = 0x08;
+pub const TYPE_INT_DEC: u8 = 0x10;
+pub const TYPE_INT_HEX: u8 = 0x11;
+pub const TYPE_INT_BOOLEAN: u8 = 0x12;
+pub const TYPE_INT_COLOR_ARGB8: u8 = 0x1c;
+pub const TYPE_INT_COLOR_RGB8: u8 = 0x1d;
+pub const TYPE_INT_COLOR_ARGB4: u8 = 0x1e;
+pub const TYPE_INT_COLOR_RGB4: u8 = 0x1f;
+
+const DIMENSION_UNITS: [&str; 6] = ["px", "dip", "sp", "pt", "in", "mm"];
+const FRACTION_UNITS: [&str; 2] = ["%", "%p"];
+const RADIX_MULTS: [f64; 4] = [
+    1.0 / 256.0,
+    1.0 / 32768.0,
+    1.0 / 8388608.0,
+    1.0 / 2147483648.0,
+];
+
+/// 常见android属性的资源ID，用于属性名被混淆或清空时恢复名称
+const ANDROID_ATTR_NAMES: &[(u32, &str)] = &[
+    (0x01010000, "theme"),
+    (0x01010001, "label"),
+    (0x01010002, "icon"),
+    (0x01010003, "name"),
+    (0x01010006, "permission"),
+    (0x01010007, "readPermission"),
+    (0x01010008, "writePermission"),
+    (0x01010009, "protectionLevel"),
+    (0x0101000a, "permissionGroup"),
+    (0x0101000b, "sharedUserId"),
+    (0x0101000c, "hasCode"),
+    (0x0101000d, "persistent"),
+    (0x0101000e, "enabled"),
+    (0x0101000f, "debuggable"),
+    (0x01010010, "exported"),
+    (0x01010011, "process"),
+    (0x01010012, "taskAffinity"),
+    (0x01010018, "authorities"),
+    (0x0101001b, "grantUriPermissions"),
+    (0x0101001c, "priority"),
+    (0x0101001d, "launchMode"),
+    (0x0101001e, "screenOrientation"),
+    (0x0101001f, "configChanges"),
+    (0x01010020, "description"),
+    (0x01010021, "targetPackage"),
+    (0x01010024, "value"),
+    (0x01010025, "resource"),
+    (0x01010026, "mimeType"),
+    (0x01010027, "scheme"),
+    (0x01010028, "host"),
+    (0x01010029, "port"),
+    (0x0101002a, "path"),
+    (0x0101002b, "pathPrefix"),
+    (0x0101002c, "pathPattern"),
+    (0x0101020c, "minSdkVersion"),
+    (0x0101021b, "versionCode"),
+    (0x0101021c, "versionName"),
+    (0x0101022b, "windowSoftInputMode"),
+    (0x01010270, "targetSdkVersion"),
+    (0x01010271, "maxSdkVersion"),
+    (0x01010272, "testOnly"),
+    (0x01010280, "allowBackup"),
+    (0x01010281, "glEsVersion"),
+    (0x0101028e, "required"),
+    (0x010102b7, "installLocation"),
+    (0x010102be, "logo"),
+    (0x010102d3, "hardwareAccelerated"),
+    (0x0101035a, "largeHeap"),
+    (0x010103a9, "isolatedProcess"),
+    (0x010103af, "supportsRtl"),
+    (0x010104ea, "extractNativeLibs"),
+    (0x010104eb, "fullBackupContent"),
+    (0x010104ec, "usesCleartextTraffic"),
+    (0x01010505, "directBootAware"),
+    (0x01010527, "networkSecurityConfig"),
+    (0x0101052c, "roundIcon"),
+    (0x01010572, "compileSdkVersion"),
+    (0x01010573, "compileSdkVersionCodename"),
+    (0x01010576, "versionCodeMajor"),
+    (0x0101057a, "appComponentFactory"),
+    (0x01010599, "foregroundServiceType"),
+    (0x01010603, "requestLegacyExternalStorage"),
+];
+
+/// 根据资源ID查找android属性名
+pub fn android_attr_name(resource_id: u32) -> Option<&'static str> {
+    ANDROID_ATTR_NAMES
+        .iter()
+        .find(|(id, _)| *id == resource_id)
+        .map(|(_, name)| *name)
+}
+
+/// 带类型的资源值（对应Res_value）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResValue {
+    /// 数据类型
+    pub data_type: u8,
+    /// 原始数据
+    pub data: u32,
+}
+
+/// 解码后的属性值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum AttributeValue {
+    /// 空值
+    Null,
+    /// 字符串
+    String(String),
+    /// 资源引用（@0x7f...）
+    Reference(u32),
+    /// 主题属性引用（?0x...）
+    Attribute(u32),
+    /// 十进制整数
+    Int(i32),
+    /// 十六进制整数
+    Hex(u32),
+    /// 布尔值
+    Bool(bool),
+    /// 浮点数
+    Float(f32),
+    /// 尺寸（如16dip）
+    Dimension(String),
+    /// 分数（如50%）
+    Fraction(String),
+    /// 颜色（ARGB）
+    Color(u32),
+    /// 无法识别的类型
+    Unknown { data_type: u8, data: u32 },
+}
+
+impl AttributeValue {
+    /// 将Res_value解码为属性值
+    pub fn from_res_value(value: ResValue, strings: &StringPool) -> Self {
+        match value.data_type {
+            TYPE_NULL => AttributeValue::Null,
+            TYPE_REFERENCE | TYPE_DYNAMIC_REFERENCE => AttributeValue::Reference(value.data),
+            TYPE_ATTRIBUTE | TYPE_DYNAMIC_ATTRIBUTE => AttributeValue::Attribute(value.data),
+            TYPE_STRING => AttributeValue::String(strings.get(value.data).unwrap_or_default()),
+            TYPE_FLOAT => AttributeValue::Float(f32::from_bits(value.data)),
+            TYPE_DIMENSION => AttributeValue::Dimension(format_complex(value.data, &DIMENSION_UNITS, 1.0)),
+            TYPE_FRACTION => AttributeValue::Fraction(format_complex(value.data, &FRACTION_UNITS, 100.0)),
+            TYPE_INT_DEC => AttributeValue::Int(value.data as i32),
+            TYPE_INT_HEX => AttributeValue::Hex(value.data),
+            TYPE_INT_BOOLEAN => AttributeValue::Bool(value.data != 0),
+            TYPE_INT_COLOR_ARGB8..=TYPE_INT_COLOR_RGB4 => AttributeValue::Color(value.data),
+            _ => AttributeValue::Unknown { data_type: value.data_type, data: value.data },
+        }
+    }
+
+    /// 资源引用ID（如有）
+    pub fn as_reference(&self) -> Option<u32> {
+        match self {
+            AttributeValue::Reference(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeValue::Null => Ok(()),
+            AttributeValue::String(s) => write!(f, "{}", s),
+            AttributeValue::Reference(id) => write!(f, "@0x{:08x}", id),
+            AttributeValue::Attribute(id) => write!(f, "?0x{:08x}", id),
+            AttributeValue::Int(v) => write!(f, "{}", v),
+            AttributeValue::Hex(v) => write!(f, "0x{:x}", v),
+            AttributeValue::Bool(v) => write!(f, "{}", v),
+            AttributeValue::Float(v) => write!(f, "{}", v),
+            AttributeValue::Dimension(s) | AttributeValue::Fraction(s) => write!(f, "{}", s),
+            AttributeValue::Color(v) => write!(f, "#{:08x}", v),
+            AttributeValue::Unknown { data, .. } => write!(f, "0x{:08x}", data),
+        }
+    }
+}
+
+// 格式化复合数值（尺寸/分数）
+fn format_complex(data: u32, units: &[&str], scale: f64) -> String {
+    let mantissa = (data & 0xffff_ff00) as i32 as f64;
+    let radix = ((data >> 4) & 0x3) as usize;
+    let value = mantissa * RADIX_MULTS[radix] * scale;
+    let unit = units.get((data & 0xf) as usize).copied().unwrap_or("");
+    format!("{}{}", value, unit)
+}
+
+/// 字符串池
+#[derive(Debug, Clone, Default)]
+pub struct StringPool {
+    strings: Vec<String>,
+}
+
+impl StringPool {
+    /// 从字符串池块解析（`chunk`包含块头），字符串数超过限制时返回错误
+    pub fn parse(chunk: &[u8], limits: &ParseLimits) -> Result<Self, ApkParserError> {
+        let header_size = read_u16(chunk, 2)? as usize;
+        let string_count = read_u32(chunk, 8)? as usize;
+        let flags = read_u32(chunk, 16)?;
+        let strings_start = read_u32(chunk, 20)? as usize;
+        let is_utf8 = flags & UTF8_FLAG != 0;
+
+        // 字符串数量不可能超过块本身能容纳的偏移量个数
+        if string_count > chunk.len() / 4 {
+            return Err(ApkParserError::InvalidXml(format!("字符串池数量异常: {}", string_count)));
+        }
+        limits.check_string_pool(string_count)?;
+
+        let mut strings = Vec::with_capacity(string_count);
+        for i in 0..string_count {
+            let offset = read_u32(chunk, header_size + i * 4)? as usize;
+            let start = strings_start.saturating_add(offset);
+            let value = if is_utf8 {
+                decode_utf8_string(chunk, start)
+            } else {
+                decode_utf16_string(chunk, start)
+            };
+            strings.push(value.unwrap_or_default());
+        }
+
+        Ok(Self { strings })
+    }
+
+    /// 按索引获取字符串，0xFFFFFFFF表示无
+    pub fn get(&self, index: u32) -> Option<String> {
+        if index == u32::MAX {
+            return None;
+        }
+        self.strings.get(index as usize).cloned()
+    }
+
+    /// 按索引获取字符串引用，0xFFFFFFFF表示无
+    pub fn get_str(&self, index: u32) -> Option<&str> {
+        if index == u32::MAX {
+            return None;
+        }
+        self.strings.get(index as usize).map(|s| s.as_str())
+    }
+
+    /// 字符串数量
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+// 解码UTF-8字符串：前缀为UTF-16长度和UTF-8字节长度
+fn decode_utf8_string(data: &[u8], offset: usize) -> Option<String> {
+    let (_, consumed) = decode_utf8_length(data, offset)?;
+    let (byte_len, consumed2) = decode_utf8_length(data, offset + consumed)?;
+    let start = offset + consumed + consumed2;
+    let bytes = data.get(start..start.checked_add(byte_len)?)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn decode_utf8_length(data: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = *data.get(offset)? as usize;
+    if first & 0x80 != 0 {
+        let second = *data.get(offset + 1)? as usize;
+        Some((((first & 0x7f) << 8) | second, 2))
+    } else {
+        Some((first, 1))
+    }
+}
+
+// 解码UTF-16字符串：前缀为字符数
+fn decode_utf16_string(data: &[u8], offset: usize) -> Option<String> {
+    let first = read_u16(data, offset).ok()? as usize;
+    let (char_len, consumed) = if first & 0x8000 != 0 {
+        let second = read_u16(data, offset + 2).ok()? as usize;
+        (((first & 0x7fff) << 16) | second, 4)
+    } else {
+        (first, 2)
+    };
+    let start = offset + consumed;
+    let bytes = data.get(start..start.checked_add(char_len.checked_mul(2)?)?)?;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// XML属性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XmlAttribute {
+    /// 属性名（不含前缀）
+    pub name: String,
+    /// 命名空间URI
+    pub namespace: Option<String>,
+    /// 命名空间前缀
+    pub prefix: Option<String>,
+    /// 属性对应的资源ID（来自资源映射）
+    pub resource_id: Option<u32>,
+    /// 解码后的值
+    pub value: AttributeValue,
+}
+
+impl XmlAttribute {
+    /// 带前缀的完整属性名，如`android:name`
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) if !prefix.is_empty() => format!("{}:{}", prefix, self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// XML元素
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XmlElement {
+    /// 元素名
+    pub name: String,
+    /// 命名空间URI
+    pub namespace: Option<String>,
+    /// 属性列表
+    pub attributes: Vec<XmlAttribute>,
+    /// 子元素
+    pub children: Vec<XmlElement>,
+    /// 文本内容
+    pub text: Option<String>,
+    /// 源文件行号
+    pub line_number: u32,
+}
+
+impl XmlElement {
+    /// 按名称查找属性（忽略命名空间）
+    pub fn attr(&self, name: &str) -> Option<&XmlAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    /// 查找android命名空间下的属性
+    pub fn android_attr(&self, name: &str) -> Option<&XmlAttribute> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name && a.namespace.as_deref() == Some(ANDROID_NS))
+            .or_else(|| self.attr(name))
+    }
+
+    /// 获取属性值的字符串形式
+    pub fn attr_string(&self, name: &str) -> Option<String> {
+        self.android_attr(name).map(|a| a.value.to_string())
+    }
+
+    /// 按名称获取直接子元素
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// 获取第一个指定名称的子元素
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+}
+
+/// 解析后的二进制XML文档
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AxmlDocument {
+    /// 根元素
+    pub root: XmlElement,
+    /// 命名空间声明（前缀, URI）
+    pub namespaces: Vec<(String, String)>,
+}
+
+impl AxmlDocument {
+    /// 输出为文本XML
+    pub fn to_xml_string(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        write_element(&mut out, &self.root, &self.namespaces, 0);
+        out
+    }
+}
+
+fn write_element(out: &mut String, element: &XmlElement, namespaces: &[(String, String)], depth: usize) {
+    let indent = "  ".repeat(depth);
+    out.push_str(&indent);
+    out.push('<');
+    out.push_str(&element.name);
+
+    // 命名空间声明只写在根元素上
+    if depth == 0 {
+        for (prefix, uri) in namespaces {
+            out.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape_xml(uri)));
+        }
+    }
+
+    for attr in &element.attributes {
+        out.push_str(&format!(" {}=\"{}\"", attr.qualified_name(), escape_xml(&attr.value.to_string())));
+    }
+
+    if element.children.is_empty() && element.text.is_none() {
+        out.push_str(" />\n");
+        return;
+    }
+
+    out.push('>');
+    if let Some(text) = &element.text {
+        out.push_str(&escape_xml(text));
+    }
+    if !element.children.is_empty() {
+        out.push('\n');
+        for child in &element.children {
+            write_element(out, child, namespaces, depth + 1);
+        }
+        out.push_str(&indent);
+    }
+    out.push_str(&format!("</{}>\n", element.name));
+}
+
+/// 转义XML特殊字符
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 判断数据是否为二进制XML
+pub fn is_binary_xml(data: &[u8]) -> bool {
+    data.len() >= 8 && read_u16(data, 0).ok() == Some(RES_XML_TYPE)
+}
+
+/// 解析二进制XML
+///
+/// # 参数
+/// * `data` - 二进制XML的完整内容
+/// * `limits` - 解析限制（嵌套深度与字符串池大小）
+///
+/// # 返回
+/// 解析后的文档或错误
+pub fn parse(data: &[u8], limits: &ParseLimits) -> Result<AxmlDocument, ApkParserError> {
+    if !is_binary_xml(data) {
+        return Err(ApkParserError::InvalidXml("不是二进制XML格式".to_string()));
+    }
+
+    let header_size = read_u16(data, 2)? as usize;
+    // 部分加固工具会篡改总长度，这里以实际数据长度为上限
+    let total_size = (read_u32(data, 4)? as usize).min(data.len());
+
+    let mut strings = StringPool::default();
+    let mut resource_ids: Vec<u32> = Vec::new();
+    let mut namespaces: Vec<(String, String)> = Vec::new();
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<XmlElement> = None;
+
+    let mut offset = header_size.max(8);
+    while offset + 8 <= total_size {
+        let chunk_type = read_u16(data, offset)?;
+        let chunk_header_size = read_u16(data, offset + 2)? as usize;
+        let chunk_size = read_u32(data, offset + 4)? as usize;
+        if chunk_size < 8 || offset + chunk_size > total_size {
+            println!("WARNING: 二进制XML块大小异常，偏移: {}", offset);
+            break;
+        }
+        let chunk = &data[offset..offset + chunk_size];
+
+        match chunk_type {
+            RES_STRING_POOL_TYPE => {
+                strings = StringPool::parse(chunk, limits)?;
+            }
+            RES_XML_RESOURCE_MAP_TYPE => {
+                resource_ids = chunk[chunk_header_size.min(chunk.len())..]
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+            }
+            RES_XML_START_NAMESPACE_TYPE => {
+                let prefix = strings.get(read_u32(chunk, 16)?).unwrap_or_default();
+                let uri = strings.get(read_u32(chunk, 20)?).unwrap_or_default();
+                if !namespaces.iter().any(|(p, u)| *p == prefix && *u == uri) {
+                    namespaces.push((prefix, uri));
+                }
+            }
+            RES_XML_END_NAMESPACE_TYPE => {}
+            RES_XML_START_ELEMENT_TYPE => {
+                let line_number = read_u32(chunk, 8)?;
+                let element = parse_start_element(chunk, chunk_header_size, line_number, &strings, &resource_ids, &namespaces)?;
+                stack.push(element);
+                limits.check_xml_depth(stack.len())?;
+            }
+            RES_XML_END_ELEMENT_TYPE => {
+                if let Some(element) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => {
+                            if root.is_none() {
+                                root = Some(element);
+                            }
+                        }
+                    }
+                }
+            }
+            RES_XML_CDATA_TYPE => {
+                let text = strings.get(read_u32(chunk, chunk_header_size)?).unwrap_or_default();
+                if let Some(current) = stack.last_mut() {
+                    current.text.get_or_insert_with(String::new).push_str(&text);
+                }
+            }
+            _ => {
+                // 忽略未知块
+            }
+        }
+
+        offset += chunk_size;
+    }
+
+    // 未正确闭合的元素依次挂回父元素
+    while let Some(element) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(element),
+            None => {
+                if root.is_none() {
+                    root = Some(element);
+                }
+            }
+        }
+    }
+
+    let root = root.ok_or_else(|| ApkParserError::InvalidXml("二进制XML中没有元素".to_string()))?;
+    Ok(AxmlDocument { root, namespaces })
+}
+
+fn parse_start_element(
+    chunk: &[u8],
+    header_size: usize,
+    line_number: u32,
+    strings: &StringPool,
+    resource_ids: &[u32],
+    namespaces: &[(String, String)],
+) -> Result<XmlElement, ApkParserError> {
+    let ext = header_size;
+    let ns_index = read_u32(chunk, ext)?;
+    let name_index = read_u32(chunk, ext + 4)?;
+    let attribute_start = read_u16(chunk, ext + 8)? as usize;
+    let attribute_size = (read_u16(chunk, ext + 10)? as usize).max(20);
+    let attribute_count = read_u16(chunk, ext + 12)? as usize;
+
+    let mut attributes = Vec::with_capacity(attribute_count);
+    for i in 0..attribute_count {
+        let base = ext + attribute_start + i * attribute_size;
+        let attr_ns = read_u32(chunk, base)?;
+        let attr_name = read_u32(chunk, base + 4)?;
+        let raw_value = read_u32(chunk, base + 8)?;
+        let data_type = chunk.get(base + 15).copied().unwrap_or(TYPE_NULL);
+        let data = read_u32(chunk, base + 16)?;
+
+        let resource_id = resource_ids.get(attr_name as usize).copied();
+        let mut name = strings.get(attr_name).unwrap_or_default();
+        if name.is_empty() || name.contains(|c: char| c.is_control()) {
+            // 属性名被混淆时，通过资源ID还原
+            if let Some(id) = resource_id {
+                name = android_attr_name(id)
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| format!("attr_0x{:08x}", id));
+            }
+        }
+
+        let namespace = strings.get(attr_ns).filter(|s| !s.is_empty());
+        let prefix = namespace
+            .as_ref()
+            .and_then(|uri| namespaces.iter().find(|(_, u)| u == uri).map(|(p, _)| p.clone()))
+            .or_else(|| {
+                namespace
+                    .as_deref()
+                    .filter(|uri| *uri == ANDROID_NS)
+                    .map(|_| "android".to_string())
+            });
+
+        // 字符串类型以data索引为准，索引无效时退回原始值
+        let value = match (data_type, strings.get(data), strings.get(raw_value)) {
+            (TYPE_STRING, Some(s), _) => AttributeValue::String(s),
+            (TYPE_STRING, None, Some(raw)) | (TYPE_NULL, _, Some(raw)) => AttributeValue::String(raw),
+            _ => AttributeValue::from_res_value(ResValue { data_type, data }, strings),
+        };
+
+        attributes.push(XmlAttribute {
+            name,
+            namespace,
+            prefix,
+            resource_id,
+            value,
+        });
+    }
+
+    Ok(XmlElement {
+        name: strings.get(name_index).unwrap_or_default(),
+        namespace: strings.get(ns_index).filter(|s| !s.is_empty()),
+        attributes,
+        children: Vec::new(),
+        text: None,
+        line_number,
+    })
+}
+
+/// 将纯文本XML解析为与二进制XML相同的元素树
+///
+/// 属性值统一作为字符串保存，命名空间前缀根据`xmlns:`声明解析为URI。
+pub fn parse_text(text: &str, limits: &ParseLimits) -> Result<AxmlDocument, ApkParserError> {
+    use quick_xml::events::{BytesStart, Event};
+    use quick_xml::Reader;
+
+    fn build_element(
+        start: &BytesStart,
+        namespaces: &mut Vec<(String, String)>,
+        line_number: u32,
+    ) -> Result<XmlElement, ApkParserError> {
+        let mut attributes = Vec::new();
+        let mut pending: Vec<(Option<String>, String, String)> = Vec::new();
+        for attr in start.attributes().with_checks(false) {
+            let attr = attr.map_err(|e| ApkParserError::InvalidXml(e.to_string()))?;
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr
+                .unescape_value()
+                .map_err(|e| ApkParserError::InvalidXml(e.to_string()))?
+                .into_owned();
+            if let Some(prefix) = key.strip_prefix("xmlns:") {
+                if !namespaces.iter().any(|(p, u)| p == prefix && *u == value) {
+                    namespaces.push((prefix.to_string(), value));
+                }
+                continue;
+            }
+            match key.split_once(':') {
+                Some((prefix, name)) => pending.push((Some(prefix.to_string()), name.to_string(), value)),
+                None => pending.push((None, key, value)),
+            }
+        }
+
+        for (prefix, name, value) in pending {
+            let namespace = prefix
+                .as_ref()
+                .and_then(|p| namespaces.iter().find(|(np, _)| np == p).map(|(_, uri)| uri.clone()));
+            attributes.push(XmlAttribute {
+                name,
+                namespace,
+                prefix,
+                resource_id: None,
+                value: AttributeValue::String(value),
+            });
+        }
+
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+        Ok(XmlElement {
+            name,
+            namespace: None,
+            attributes,
+            children: Vec::new(),
+            text: None,
+            line_number,
+        })
+    }
+
+    let mut reader = Reader::from_str(text);
+    reader.trim_text(true);
+
+    let mut namespaces: Vec<(String, String)> = Vec::new();
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<XmlElement> = None;
+    let mut line_number = 1u32;
+    let mut scanned = 0usize;
+
+    loop {
+        // 增量统计行号，避免每次从头扫描
+        let position = reader.buffer_position().min(text.len());
+        if position > scanned {
+            line_number += text.as_bytes()[scanned..position].iter().filter(|b| **b == b'\n').count() as u32;
+            scanned = position;
+        }
+        let event = reader
+            .read_event()
+            .map_err(|e| ApkParserError::InvalidXml(format!("位置 {}: {}", reader.buffer_position(), e)))?;
+        match event {
+            Event::Start(start) => {
+                stack.push(build_element(&start, &mut namespaces, line_number)?);
+                limits.check_xml_depth(stack.len())?;
+            }
+            Event::Empty(start) => {
+                let element = build_element(&start, &mut namespaces, line_number)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = root.or(Some(element)),
+                }
+            }
+            Event::End(_) => {
+                if let Some(element) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = root.or(Some(element)),
+                    }
+                }
+            }
+            Event::Text(content) => {
+                if let Some(current) = stack.last_mut() {
+                    let content = content.unescape().map_err(|e| ApkParserError::InvalidXml(e.to_string()))?;
+                    current.text.get_or_insert_with(String::new).push_str(&content);
+                }
+            }
+            Event::CData(content) => {
+                if let Some(current) = stack.last_mut() {
+                    current.text.get_or_insert_with(String::new).push_str(&String::from_utf8_lossy(&content));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let root = root.ok_or_else(|| ApkParserError::InvalidXml("XML中没有元素".to_string()))?;
+    Ok(AxmlDocument { root, namespaces })
+}
+
+/// 解析XML文件内容，自动识别二进制或纯文本格式
+pub fn parse_any(data: &[u8], limits: &ParseLimits) -> Result<AxmlDocument, ApkParserError> {
+    if is_binary_xml(data) {
+        parse(data, limits)
+    } else {
+        parse_text(&String::from_utf8_lossy(data), limits)
+    }
+}
+
+/// 读取小端u16
+pub fn read_u16(data: &[u8], offset: usize) -> Result<u16, ApkParserError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| ApkParserError::InvalidXml(format!("读取越界，偏移: {}", offset)))
+}
+
+/// 读取小端u32
+pub fn read_u32(data: &[u8], offset: usize) -> Result<u32, ApkParserError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| ApkParserError::InvalidXml(format!("读取越界，偏移: {}", offset)))
+}
+
+/// 测试用的二进制XML构造器
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// 属性值：字符串或带类型的Res_value
+    pub(crate) enum Value<'a> {
+        Str(&'a str),
+        Typed(u8, u32),
+    }
+
+    /// 构造字符串池块
+    pub(crate) fn string_pool(strings: &[&str], utf8: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for value in strings {
+            offsets.push(data.len() as u32);
+            if utf8 {
+                data.push(value.chars().count() as u8);
+                data.push(value.len() as u8);
+                data.extend_from_slice(value.as_bytes());
+                data.push(0);
+            } else {
+                let units: Vec<u16> = value.encode_utf16().collect();
+                data.extend_from_slice(&(units.len() as u16).to_le_bytes());
+                for unit in units {
+                    data.extend_from_slice(&unit.to_le_bytes());
+                }
+                data.extend_from_slice(&[0, 0]);
+            }
+        }
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        let strings_start = 28 + offsets.len() * 4;
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&RES_STRING_POOL_TYPE.to_le_bytes());
+        chunk.extend_from_slice(&28u16.to_le_bytes());
+        chunk.extend_from_slice(&((strings_start + data.len()) as u32).to_le_bytes());
+        chunk.extend_from_slice(&(offsets.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(&0u32.to_le_bytes());
+        chunk.extend_from_slice(&(if utf8 { UTF8_FLAG } else { 0 }).to_le_bytes());
+        chunk.extend_from_slice(&(strings_start as u32).to_le_bytes());
+        chunk.extend_from_slice(&0u32.to_le_bytes());
+        for offset in offsets {
+            chunk.extend_from_slice(&offset.to_le_bytes());
+        }
+        chunk.extend_from_slice(&data);
+        chunk
+    }
+
+    /// 按事件顺序构造二进制XML，android属性名占据字符串池开头并写入资源映射
+    pub(crate) struct AxmlBuilder {
+        strings: Vec<String>,
+        resource_ids: Vec<u32>,
+        events: Vec<u8>,
+        utf8: bool,
+    }
+
+    impl AxmlBuilder {
+        pub(crate) fn new(utf8: bool, android_attrs: &[&str]) -> Self {
+            let mut builder = Self { strings: Vec::new(), resource_ids: Vec::new(), events: Vec::new(), utf8 };
+            for name in android_attrs {
+                let id = ANDROID_ATTR_NAMES.iter().find(|(_, n)| n == name).map(|(id, _)| *id).expect("未知的android属性");
+                builder.strings.push(name.to_string());
+                builder.resource_ids.push(id);
+            }
+            builder
+        }
+
+        pub(crate) fn string(&mut self, value: &str) -> u32 {
+            match self.strings.iter().position(|s| s == value) {
+                Some(index) => index as u32,
+                None => {
+                    self.strings.push(value.to_string());
+                    (self.strings.len() - 1) as u32
+                }
+            }
+        }
+
+        fn node(&mut self, chunk_type: u16, body: &[u32]) {
+            self.events.extend_from_slice(&chunk_type.to_le_bytes());
+            self.events.extend_from_slice(&16u16.to_le_bytes());
+            self.events.extend_from_slice(&((16 + body.len() * 4) as u32).to_le_bytes());
+            self.events.extend_from_slice(&1u32.to_le_bytes());
+            self.events.extend_from_slice(&u32::MAX.to_le_bytes());
+            for value in body {
+                self.events.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        pub(crate) fn start_namespace(&mut self, prefix: &str, uri: &str) -> &mut Self {
+            let body = [self.string(prefix), self.string(uri)];
+            self.node(RES_XML_START_NAMESPACE_TYPE, &body);
+            self
+        }
+
+        pub(crate) fn end_namespace(&mut self, prefix: &str, uri: &str) -> &mut Self {
+            let body = [self.string(prefix), self.string(uri)];
+            self.node(RES_XML_END_NAMESPACE_TYPE, &body);
+            self
+        }
+
+        /// `attributes`为(命名空间URI, 属性名, 值)
+        pub(crate) fn start(&mut self, name: &str, attributes: &[(Option<&str>, &str, Value)]) -> &mut Self {
+            let mut body = vec![u32::MAX, self.string(name), 20 | (20 << 16), attributes.len() as u32, 0];
+            for (namespace, attr_name, value) in attributes {
+                let ns = namespace.map(|uri| self.string(uri)).unwrap_or(u32::MAX);
+                let attr_name = self.string(attr_name);
+                let (raw, data_type, data) = match value {
+                    Value::Str(s) => {
+                        let index = self.string(s);
+                        (index, TYPE_STRING, index)
+                    }
+                    Value::Typed(data_type, data) => (u32::MAX, *data_type, *data),
+                };
+                body.extend_from_slice(&[ns, attr_name, raw, 8 | ((data_type as u32) << 24), data]);
+            }
+            self.node(RES_XML_START_ELEMENT_TYPE, &body);
+            self
+        }
+
+        pub(crate) fn end(&mut self, name: &str) -> &mut Self {
+            let body = [u32::MAX, self.string(name)];
+            self.node(RES_XML_END_ELEMENT_TYPE, &body);
+            self
+        }
+
+        pub(crate) fn build(&self) -> Vec<u8> {
+            let strings: Vec<&str> = self.strings.iter().map(String::as_str).collect();
+            let mut body = string_pool(&strings, self.utf8);
+            body.extend_from_slice(&RES_XML_RESOURCE_MAP_TYPE.to_le_bytes());
+            body.extend_from_slice(&8u16.to_le_bytes());
+            body.extend_from_slice(&((8 + self.resource_ids.len() * 4) as u32).to_le_bytes());
+            for id in &self.resource_ids {
+                body.extend_from_slice(&id.to_le_bytes());
+            }
+            body.extend_from_slice(&self.events);
+
+            let mut document = Vec::new();
+            document.extend_from_slice(&RES_XML_TYPE.to_le_bytes());
+            document.extend_from_slice(&8u16.to_le_bytes());
+            document.extend_from_slice(&((8 + body.len()) as u32).to_le_bytes());
+            document.extend_from_slice(&body);
+            document
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{AxmlBuilder, Value};
+    use super::*;
+
+    const APP_NS: &str = "http://schemas.android.com/apk/res-auto";
+
+    fn document(utf8: bool) -> Vec<u8> {
+        let mut builder = AxmlBuilder::new(utf8, &["label", "name"]);
+        builder
+            .start_namespace("android", ANDROID_NS)
+            .start_namespace("app", APP_NS)
+            .start("manifest", &[(None, "package", Value::Str("com.example"))])
+            .start("application", &[(Some(ANDROID_NS), "label", Value::Str("示例应用 ✓"))])
+            .start("activity", &[(Some(ANDROID_NS), "name", Value::Str(".Main")), (Some(APP_NS), "theme", Value::Str("dark"))])
+            .end("activity")
+            .end("application")
+            .end("manifest")
+            .end_namespace("app", APP_NS)
+            .end_namespace("android", ANDROID_NS);
+        builder.build()
+    }
+
+    #[test]
+    fn decodes_utf8_and_utf16_string_pools() {
+        for utf8 in [false, true] {
+            let document = parse(&document(utf8), &ParseLimits::default()).unwrap();
+            let application = document.root.child("application").unwrap();
+
+            assert_eq!(document.root.name, "manifest");
+            assert_eq!(document.root.attr_string("package").as_deref(), Some("com.example"));
+            assert_eq!(application.attr_string("label").as_deref(), Some("示例应用 ✓"));
+        }
+    }
+
+    #[test]
+    fn string_pool_treats_max_index_as_none() {
+        let data = document(false);
+        let pool = StringPool::parse(&data[8..], &ParseLimits::default()).unwrap();
+
+        assert_eq!(pool.get_str(0), Some("label"));
+        assert_eq!(pool.get(0).as_deref(), Some("label"));
+        assert_eq!(pool.get_str(u32::MAX), None);
+        assert_eq!(pool.get(u32::MAX), None);
+        assert_eq!(pool.get_str(pool.len() as u32), None);
+    }
+
+    #[test]
+    fn resolves_namespace_prefixes() {
+        let document = parse(&document(true), &ParseLimits::default()).unwrap();
+        let activity = document.root.child("application").and_then(|a| a.child("activity")).unwrap();
+
+        assert_eq!(document.namespaces, vec![
+            ("android".to_string(), ANDROID_NS.to_string()),
+            ("app".to_string(), APP_NS.to_string()),
+        ]);
+        let names: Vec<String> = activity.attributes.iter().map(XmlAttribute::qualified_name).collect();
+        assert_eq!(names, vec!["android:name", "app:theme"]);
+        assert_eq!(activity.android_attr("name").and_then(|a| a.resource_id), Some(0x0101_0003));
+        assert!(document.to_xml_string().contains("xmlns:app=\"http://schemas.android.com/apk/res-auto\""));
+    }
+
+    #[test]
+    fn decodes_typed_attribute_values() {
+        let android = Some(ANDROID_NS);
+        let mut builder = AxmlBuilder::new(false, &[]);
+        builder
+            .start_namespace("android", ANDROID_NS)
+            .start("view", &[
+                (android, "icon", Value::Typed(TYPE_REFERENCE, 0x7f01_0000)),
+                (android, "textColor", Value::Typed(TYPE_ATTRIBUTE, 0x0101_0036)),
+                (android, "enabled", Value::Typed(TYPE_INT_BOOLEAN, u32::MAX)),
+                (android, "maxLines", Value::Typed(TYPE_INT_DEC, (-1i32) as u32)),
+                (android, "flags", Value::Typed(TYPE_INT_HEX, 0x30)),
+                (android, "textSize", Value::Typed(TYPE_DIMENSION, (16 << 8) | 1)),
+                (android, "background", Value::Typed(TYPE_INT_COLOR_ARGB8, 0xff00_80ff)),
+                (android, "alpha", Value::Typed(TYPE_FLOAT, 0.5f32.to_bits())),
+                (android, "tag", Value::Typed(TYPE_NULL, 0)),
+            ])
+            .end("view");
+        let document = parse(&builder.build(), &ParseLimits::default()).unwrap();
+        let value = |name: &str| document.root.android_attr(name).map(|a| a.value.clone()).unwrap();
+
+        assert_eq!(value("icon"), AttributeValue::Reference(0x7f01_0000));
+        assert_eq!(value("icon").to_string(), "@0x7f010000");
+        assert_eq!(value("textColor"), AttributeValue::Attribute(0x0101_0036));
+        assert_eq!(value("enabled"), AttributeValue::Bool(true));
+        assert_eq!(value("maxLines"), AttributeValue::Int(-1));
+        assert_eq!(value("flags").to_string(), "0x30");
+        assert_eq!(value("textSize").to_string(), "16dip");
+        assert_eq!(value("background").to_string(), "#ff0080ff");
+        assert_eq!(value("alpha"), AttributeValue::Float(0.5));
+        assert_eq!(value("tag"), AttributeValue::Null);
+    }
+
+    #[test]
+    fn enforces_depth_and_string_pool_limits() {
+        let mut builder = AxmlBuilder::new(true, &[]);
+        for _ in 0..5 {
+            builder.start("node", &[]);
+        }
+        for _ in 0..5 {
+            builder.end("node");
+        }
+        let data = builder.build();
+
+        let shallow = ParseLimits { max_xml_depth: 4, ..Default::default() };
+        assert!(matches!(parse(&data, &shallow), Err(ApkParserError::LimitExceeded(_))));
+        let deep = ParseLimits { max_xml_depth: 5, ..Default::default() };
+        assert!(parse(&data, &deep).is_ok());
+
+        let small_pool = ParseLimits { max_string_pool_size: 3, ..Default::default() };
+        assert!(matches!(parse(&document(false), &small_pool), Err(ApkParserError::LimitExceeded(_))));
+    }
+
+    #[test]
+    fn rejects_text_and_truncated_input() {
+        assert!(parse(b"<manifest/>", &ParseLimits::default()).is_err());
+        let data = document(false);
+        assert!(parse(&data[..40], &ParseLimits::default()).is_err());
+        // 文本清单通过parse_any解析
+        let document = parse_any(b"<manifest package=\"a.b\"/>", &ParseLimits::default()).unwrap();
+        assert_eq!(document.root.attr_string("package").as_deref(), Some("a.b"));
+    }
+}
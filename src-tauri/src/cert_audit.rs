@@ -0,0 +1,190 @@
+//! 签名证书风险分类
+//!
+//! 在签名提取结果之上识别调试密钥、AOSP测试密钥以及不满足发布要求的弱签名参数。
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::{CertificateInfo, SignatureInfo};
+
+/// AOSP源码`build/target/product/security`下公开密钥的SHA-1证书指纹
+const AOSP_TEST_KEYS: &[(&str, &str)] = &[
+    ("61:ED:37:7E:85:D3:86:A8:DF:EE:6B:86:4B:D8:5B:0B:FA:A5:AF:81", "testkey"),
+    ("27:19:6E:38:6B:87:5E:76:AD:F7:00:E7:EA:84:E4:C6:EE:E3:3D:FA", "platform"),
+    ("5B:36:8C:FF:2D:A2:68:69:96:BC:95:EA:C1:90:EA:A4:F5:63:0F:E5", "shared"),
+    ("B7:9D:F4:A8:2E:90:B5:7E:A7:65:25:AB:70:37:AB:23:8A:42:F5:D3", "media"),
+];
+
+/// AOSP测试密钥共用的证书主题：C=US, O=Android, OU=Android, CN=Android
+fn is_aosp_test_subject(cert: &CertificateInfo) -> bool {
+    let name = &cert.subject_name;
+    name.common_name.as_deref() == Some("Android")
+        && name.organization.as_deref() == Some("Android")
+        && name.organizational_unit.as_deref() == Some("Android")
+        && name.country.as_deref() == Some("US")
+}
+
+/// 调试密钥库（`~/.android/debug.keystore`）生成的证书CN
+const DEBUG_COMMON_NAME: &str = "Android Debug";
+
+/// Google Play要求签名证书的有效期至少持续到2033年10月22日
+fn play_validity_deadline() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2033, 10, 22, 0, 0, 0).unwrap()
+}
+
+/// 风险等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// 单条证书风险
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateFinding {
+    /// 风险类型：debug_key、aosp_test_key、weak_signature_algorithm、weak_key、short_validity
+    pub kind: String,
+    pub severity: Severity,
+    pub title: String,
+    /// 风险说明与处理建议
+    pub explanation: String,
+    /// 涉及的证书主题
+    pub subject: String,
+    pub fingerprint_sha256: String,
+    /// 发现该证书的签名方案
+    pub schemes: Vec<String>,
+}
+
+impl CertificateFinding {
+    fn new(cert: &CertificateInfo, kind: &str, severity: Severity, title: String, explanation: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            severity,
+            title,
+            explanation,
+            subject: cert.subject.clone(),
+            fingerprint_sha256: cert.fingerprint_sha256.clone(),
+            schemes: Vec::new(),
+        }
+    }
+}
+
+/// 检查单个证书
+pub fn classify_certificate(cert: &CertificateInfo) -> Vec<CertificateFinding> {
+    let mut findings = Vec::new();
+
+    if cert.subject_name.common_name.as_deref() == Some(DEBUG_COMMON_NAME) {
+        findings.push(CertificateFinding::new(
+            cert,
+            "debug_key",
+            Severity::Critical,
+            "使用调试密钥签名".to_string(),
+            "证书主题为CN=Android Debug，来自Android SDK自动生成的调试密钥库。\
+             调试密钥不受保护，Google Play拒绝此类APK，发布版本必须使用正式密钥签名。".to_string(),
+        ));
+    }
+
+    if let Some((_, name)) = AOSP_TEST_KEYS.iter().find(|(fingerprint, _)| *fingerprint == cert.fingerprint_sha1) {
+        findings.push(CertificateFinding::new(
+            cert,
+            "aosp_test_key",
+            Severity::Critical,
+            format!("使用AOSP公开测试密钥（{}）签名", name),
+            format!(
+                "证书与AOSP源码中公开的{}密钥一致，私钥人人可得。任何人都能签发同签名的更新，\
+                 在使用测试密钥构建的系统上还可能获得系统级权限。",
+                name
+            ),
+        ));
+    } else if is_aosp_test_subject(cert) {
+        findings.push(CertificateFinding::new(
+            cert,
+            "aosp_test_key",
+            Severity::High,
+            "疑似AOSP测试密钥".to_string(),
+            "证书主题与AOSP测试密钥（testkey/platform/shared/media）相同，但指纹不在已知列表中，\
+             可能是重新生成的测试密钥，请确认是否为正式发布密钥。".to_string(),
+        ));
+    }
+
+    let signature_algorithm = cert.signature_algorithm.to_uppercase();
+    if signature_algorithm.starts_with("MD5") || signature_algorithm.starts_with("MD2") {
+        findings.push(CertificateFinding::new(
+            cert,
+            "weak_signature_algorithm",
+            Severity::High,
+            format!("证书使用不安全的签名算法 {}", cert.signature_algorithm),
+            "MD5已可实际构造碰撞，证书签名无法提供可靠保证。".to_string(),
+        ));
+    } else if signature_algorithm.starts_with("SHA1") {
+        findings.push(CertificateFinding::new(
+            cert,
+            "weak_signature_algorithm",
+            Severity::Medium,
+            format!("证书使用弱签名算法 {}", cert.signature_algorithm),
+            "SHA-1已被证实可构造碰撞，新密钥应使用SHA-256及以上的签名算法。".to_string(),
+        ));
+    }
+
+    if cert.public_key_algorithm == "RSA" {
+        if let Some(bits) = cert.public_key_size.filter(|bits| *bits < 2048) {
+            findings.push(CertificateFinding::new(
+                cert,
+                "weak_key",
+                if bits < 1024 { Severity::High } else { Severity::Medium },
+                format!("RSA密钥长度仅为{}位", bits),
+                "低于2048位的RSA密钥强度不足，新版apksigner和Google Play要求至少2048位。".to_string(),
+            ));
+        }
+    }
+
+    if let Ok(valid_to) = DateTime::parse_from_rfc2822(&cert.valid_to) {
+        if valid_to < play_validity_deadline() {
+            findings.push(CertificateFinding::new(
+                cert,
+                "short_validity",
+                Severity::Medium,
+                "证书有效期过短".to_string(),
+                format!(
+                    "证书于{}到期，早于Google Play要求的2033-10-22，无法上传到Play，到期后也无法签发更新。",
+                    valid_to.format("%Y-%m-%d")
+                ),
+            ));
+        }
+    }
+
+    findings
+}
+
+/// 检查所有签名者的证书；同一证书在多个签名方案中出现时只报告一次
+pub fn classify_signatures(signatures: &[SignatureInfo]) -> Vec<CertificateFinding> {
+    let mut findings: Vec<CertificateFinding> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for signature in signatures {
+        let Some(cert) = signature.certificate_chain.first() else {
+            continue;
+        };
+        if !seen.insert(cert.fingerprint_sha256.clone()) {
+            for finding in findings.iter_mut().filter(|f| f.fingerprint_sha256 == cert.fingerprint_sha256) {
+                if !finding.schemes.contains(&signature.scheme) {
+                    finding.schemes.push(signature.scheme.clone());
+                }
+            }
+            continue;
+        }
+        findings.extend(classify_certificate(cert).into_iter().map(|mut finding| {
+            finding.schemes.push(signature.scheme.clone());
+            finding
+        }));
+    }
+
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    findings
+}
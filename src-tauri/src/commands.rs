@@ -3,7 +3,30 @@ use std::path::{Path};
 use std::sync::{Arc, Mutex}; 
 use tempfile::NamedTempFile;
 use chrono;
-use crate::apk_parser::ApkParser;
+use crate::abi::AbiCoverage;
+use crate::anti_analysis::AntiAnalysisReport;
+use crate::apk_parser::{self, ApkParser, CertificateInfo, SdkRangeSigner};
+use crate::arsc::ManifestResources;
+use crate::cert_audit::CertificateFinding;
+use crate::compare::{self, ApkComparison};
+use crate::dex::DexAnalysis;
+use crate::elf::NativeLibraryAnalysis;
+use crate::framework::DetectedFramework;
+use crate::sdk_detect::DetectedSdk;
+use crate::idsig::{self, IdsigReport};
+use crate::inventory::ContentInventory;
+use crate::limits::ReadBudget;
+use crate::lineage::SigningCertificateLineage;
+use crate::manifest::AndroidManifest;
+use crate::obfuscation::ObfuscationReport;
+use crate::packer::PackerReport;
+use crate::page_size::PageSizeReport;
+use crate::pinning::CertificatePinningReport;
+use crate::pkcs7::SignerInfo;
+use crate::signing_block::SigningSchemes;
+use crate::size_breakdown::{self, SizeBreakdown};
+use crate::verifier::VerificationResult;
+use crate::zip_audit::{self, ZipAuditReport};
 use sysinfo::{System, CpuRefreshKind, RefreshKind, MemoryRefreshKind, ProcessRefreshKind};
 
 // Constants
@@ -19,6 +42,8 @@ pub struct ApkInfo {
     pub min_sdk: String,
     pub target_sdk: String,
     pub signature_info: Option<SignatureInfo>,
+    pub signatures: Vec<SignatureInfo>,
+    pub signers_by_sdk: Vec<SdkRangeSigner>,
     pub permissions: Option<Vec<Permission>>,
     pub dangerous_permissions: Vec<Permission>,
     pub permission_stats: PermissionStats,
@@ -28,6 +53,26 @@ pub struct ApkInfo {
     pub file_info: Option<FileInfo>,
     pub main_activity: Option<String>,
     pub icon_base64: Option<String>,
+    pub app_labels: Option<std::collections::HashMap<String, String>>,
+    pub manifest_resources: Option<ManifestResources>,
+    pub signing_schemes: Option<SigningSchemes>,
+    pub signature_verification: Option<VerificationResult>,
+    pub certificate_findings: Vec<CertificateFinding>,
+    pub dex_analysis: Option<DexAnalysis>,
+    pub packer: Option<PackerReport>,
+    pub detected_sdks: Vec<DetectedSdk>,
+    pub frameworks: Vec<DetectedFramework>,
+    pub anti_analysis: Option<AntiAnalysisReport>,
+    pub certificate_pinning: Option<CertificatePinningReport>,
+    pub obfuscation: Option<ObfuscationReport>,
+    pub native_libraries: Option<NativeLibraryAnalysis>,
+    pub page_size: Option<PageSizeReport>,
+    pub abi_coverage: Option<AbiCoverage>,
+    pub zip_audit: Option<ZipAuditReport>,
+    pub content_inventory: Option<ContentInventory>,
+    pub size_breakdown: Option<SizeBreakdown>,
+    pub limit_warnings: Vec<String>,
+    pub aapt_fallback: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -36,8 +81,43 @@ pub struct SignatureInfo {
     pub subject: String,
     pub valid_from: String,
     pub valid_to: String,
+    pub fingerprint_md5: Option<String>,
     pub fingerprint_sha1: Option<String>,
     pub fingerprint_sha256: Option<String>,
+    pub fingerprint_sha512: Option<String>,
+    pub signature_file: Option<String>,
+    pub digest_algorithms: Vec<String>,
+    pub signers: Vec<SignerInfo>,
+    pub certificate_chain: Vec<CertificateInfo>,
+    pub scheme: String,
+    pub min_sdk_version: Option<u32>,
+    pub max_sdk_version: Option<u32>,
+    pub is_current: bool,
+    pub lineage: Option<SigningCertificateLineage>,
+}
+
+impl From<&apk_parser::SignatureInfo> for SignatureInfo {
+    fn from(sig: &apk_parser::SignatureInfo) -> Self {
+        SignatureInfo {
+            issuer: sig.issuer.clone(),
+            subject: sig.subject.clone(),
+            valid_from: sig.valid_from.clone(),
+            valid_to: sig.valid_to.clone(),
+            fingerprint_md5: sig.fingerprint_md5.clone(),
+            fingerprint_sha1: sig.fingerprint_sha1.clone(),
+            fingerprint_sha256: sig.fingerprint_sha256.clone(),
+            fingerprint_sha512: sig.fingerprint_sha512.clone(),
+            signature_file: sig.signature_file.clone(),
+            digest_algorithms: sig.digest_algorithms.clone(),
+            signers: sig.signers.clone(),
+            certificate_chain: sig.certificate_chain.clone(),
+            scheme: sig.scheme.clone(),
+            min_sdk_version: sig.min_sdk_version,
+            max_sdk_version: sig.max_sdk_version,
+            is_current: sig.is_current,
+            lineage: sig.lineage.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -130,14 +210,8 @@ pub async fn parse_apk(path: String) -> Result<ApkInfo, String> {
     let parser_result = ApkParser::parse(path)
         .map_err(|e| e.to_string())?;
     
-    let signature_info = parser_result.signature_info.as_ref().map(|sig| SignatureInfo {
-        issuer: sig.issuer.clone(),
-        subject: sig.subject.clone(),
-        valid_from: sig.valid_from.clone(),
-        valid_to: sig.valid_to.clone(),
-        fingerprint_sha1: sig.fingerprint_sha1.clone(),
-        fingerprint_sha256: sig.fingerprint_sha256.clone(),
-    });
+    let signature_info = parser_result.signature_info.as_ref().map(SignatureInfo::from);
+    let signatures = parser_result.signatures.iter().map(SignatureInfo::from).collect();
     
     let permissions = parser_result.permissions.clone().unwrap_or_default();
     
@@ -196,6 +270,8 @@ pub async fn parse_apk(path: String) -> Result<ApkInfo, String> {
         min_sdk: parser_result.min_sdk,
         target_sdk: parser_result.target_sdk,
         signature_info,
+        signatures,
+        signers_by_sdk: parser_result.signers_by_sdk,
         permissions: Some(permissions_vec),
         dangerous_permissions,
         permission_stats,
@@ -205,9 +281,62 @@ pub async fn parse_apk(path: String) -> Result<ApkInfo, String> {
         file_info,
         main_activity: parser_result.main_activity,
         icon_base64: parser_result.icon_base64,
+        app_labels: parser_result.app_labels,
+        manifest_resources: parser_result.manifest_resources,
+        signing_schemes: parser_result.signing_schemes,
+        signature_verification: parser_result.signature_verification,
+        certificate_findings: parser_result.certificate_findings,
+        dex_analysis: parser_result.dex_analysis,
+        packer: parser_result.packer,
+        detected_sdks: parser_result.detected_sdks,
+        frameworks: parser_result.frameworks,
+        anti_analysis: parser_result.anti_analysis,
+        certificate_pinning: parser_result.certificate_pinning,
+        obfuscation: parser_result.obfuscation,
+        native_libraries: parser_result.native_libraries,
+        page_size: parser_result.page_size,
+        abi_coverage: parser_result.abi_coverage,
+        zip_audit: parser_result.zip_audit,
+        content_inventory: parser_result.content_inventory,
+        size_breakdown: parser_result.size_breakdown,
+        limit_warnings: parser_result.limit_warnings,
+        aapt_fallback: parser_result.aapt_fallback,
     })
 }
 
+#[tauri::command]
+pub async fn parse_manifest(path: String) -> Result<AndroidManifest, String> {
+    ApkParser::parse_manifest(Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// 解析并校验v4签名文件，未指定idsig路径时使用APK旁的`<apk>.idsig`
+#[tauri::command]
+pub async fn parse_idsig(apk_path: String, idsig_path: Option<String>) -> Result<IdsigReport, String> {
+    idsig::analyze_idsig(Path::new(&apk_path), idsig_path.as_deref().map(Path::new))
+        .map_err(|e| e.to_string())
+}
+
+/// 检查APK的ZIP结构异常，不依赖完整解析，ZIP损坏时同样可用
+#[tauri::command]
+pub async fn inspect_zip(path: String) -> Result<ZipAuditReport, String> {
+    zip_audit::inspect(Path::new(&path), &ReadBudget::default())
+        .map_err(|e| e.to_string())
+}
+
+/// 按路径统计APK体积，返回可供前端绘制树图的目录树
+#[tauri::command]
+pub async fn analyze_apk_size(path: String, top_n: Option<usize>) -> Result<SizeBreakdown, String> {
+    size_breakdown::analyze_file(Path::new(&path), top_n.unwrap_or(size_breakdown::DEFAULT_TOP_N))
+        .map_err(|e| e.to_string())
+}
+
+/// 比较新旧两个APK，返回清单、权限、签名、原生库、DEX、资源与文件的结构化差异
+#[tauri::command]
+pub async fn compare_apks(old: String, new: String) -> Result<ApkComparison, String> {
+    compare::compare_apks(Path::new(&old), Path::new(&new))
+        .map_err(|e| e.to_string())
+}
 
 #[tauri::command]
 pub async fn get_app_info() -> Result<AppInfo, String> {
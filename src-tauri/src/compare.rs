@@ -0,0 +1,615 @@
+//! 两个APK的版本差异
+//!
+//! 分别完整解析新旧两个APK，比较清单属性、组件与intent-filter、权限、SDK级别、
+//! 签名证书（标记签名者变更及是否有轮换证明）、原生库与ABI、各包的DEX类与方法数、
+//! 资源，以及文件级别的新增/删除/修改与体积变化。结果为结构化数据，
+//! 界面展示与导出报告可直接使用。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::apk_parser::{ApkInfo, ApkParser, ApkParserError, Permission, SignatureInfo};
+use crate::arsc::ResourceTable;
+use crate::dex::{DexAnalysis, PackageNode};
+use crate::limits::ReadBudget;
+use crate::manifest::{AndroidManifest, Component, ComponentKind, IntentFilter};
+
+/// 单个值的变化，新增时`old`为None，删除时`new`为None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueChange {
+    /// 字段路径，如`application.debuggable`；列表项以`[]`结尾
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// 参与比较的APK概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApkSummary {
+    pub path: String,
+    pub package_name: String,
+    pub version_name: String,
+    pub version_code: String,
+    pub file_size: u64,
+}
+
+/// 组件标识
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentRef {
+    pub kind: ComponentKind,
+    pub name: String,
+}
+
+/// 组件的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentChange {
+    pub kind: ComponentKind,
+    pub name: String,
+    pub attributes: Vec<ValueChange>,
+    pub intent_filters_added: Vec<IntentFilter>,
+    pub intent_filters_removed: Vec<IntentFilter>,
+}
+
+/// 清单差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    /// manifest与application上的属性，以及uses-feature、meta-data等列表项
+    pub attributes: Vec<ValueChange>,
+    pub components_added: Vec<ComponentRef>,
+    pub components_removed: Vec<ComponentRef>,
+    pub components_changed: Vec<ComponentChange>,
+}
+
+/// 权限差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionDiff {
+    pub added: Vec<Permission>,
+    pub removed: Vec<Permission>,
+}
+
+/// 签名证书标识
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateRef {
+    pub subject: String,
+    pub fingerprint_sha256: Option<String>,
+}
+
+/// 签名差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningDiff {
+    /// 当前签名者
+    pub old_signer: Option<CertificateRef>,
+    pub new_signer: Option<CertificateRef>,
+    /// 当前签名者的证书是否变化
+    pub signer_changed: bool,
+    /// 签名者变化且新APK的轮换谱系包含旧证书，即经过v3密钥轮换
+    pub rotation_proven: bool,
+    /// 全部签名者中新增与移除的证书
+    pub certificates_added: Vec<CertificateRef>,
+    pub certificates_removed: Vec<CertificateRef>,
+    /// v1/v2/v3/v3.1签名方案的变化
+    pub schemes: Vec<ValueChange>,
+}
+
+/// 文件在APK中的状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EntryStat {
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+/// 文件级别的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub old: Option<EntryStat>,
+    pub new: Option<EntryStat>,
+    /// 压缩体积变化
+    pub compressed_delta: i64,
+    /// 解压体积变化
+    pub uncompressed_delta: i64,
+}
+
+/// 文件差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub added: Vec<FileChange>,
+    pub removed: Vec<FileChange>,
+    /// CRC或体积发生变化的文件
+    pub changed: Vec<FileChange>,
+    pub unchanged_count: usize,
+    /// APK文件大小的变化
+    pub size_delta: i64,
+}
+
+/// 原生库与ABI差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NativeDiff {
+    pub abis_added: Vec<String>,
+    pub abis_removed: Vec<String>,
+    pub libraries_added: Vec<FileChange>,
+    pub libraries_removed: Vec<FileChange>,
+    pub libraries_changed: Vec<FileChange>,
+}
+
+/// 单个包的类与方法数变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageChange {
+    pub package: String,
+    pub old_classes: usize,
+    pub new_classes: usize,
+    pub old_methods: usize,
+    pub new_methods: usize,
+}
+
+/// DEX差异
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DexDiff {
+    pub old_classes: usize,
+    pub new_classes: usize,
+    pub old_methods: usize,
+    pub new_methods: usize,
+    pub classes_added: Vec<String>,
+    pub classes_removed: Vec<String>,
+    /// 类或方法数变化的包
+    pub packages: Vec<PackageChange>,
+}
+
+/// 资源值的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceChange {
+    /// 资源名，如`@string/app_name`
+    pub name: String,
+    /// 各配置下的值，形如`default=应用名`
+    pub old_values: Vec<String>,
+    pub new_values: Vec<String>,
+}
+
+/// 资源差异，按资源名比较，资源ID的重新分配不视为变化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ResourceChange>,
+}
+
+/// 两个APK的比较结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApkComparison {
+    pub old: ApkSummary,
+    pub new: ApkSummary,
+    /// 任一清单无法解析时为None
+    pub manifest: Option<ManifestDiff>,
+    pub permissions: PermissionDiff,
+    /// minSdk、targetSdk、maxSdk与compileSdk的变化
+    pub sdk: Vec<ValueChange>,
+    pub signing: SigningDiff,
+    pub native: NativeDiff,
+    pub dex: DexDiff,
+    /// 任一资源表无法解析时为None
+    pub resources: Option<ResourceDiff>,
+    pub files: FileDiff,
+}
+
+/// 比较新旧两个APK
+pub fn compare_apks(old_path: &Path, new_path: &Path) -> Result<ApkComparison, ApkParserError> {
+    let old = ApkSide::load(old_path)?;
+    let new = ApkSide::load(new_path)?;
+
+    let manifest = old.manifest.as_ref().zip(new.manifest.as_ref())
+        .map(|(old_manifest, new_manifest)| diff_manifest(old_manifest, new_manifest));
+    let resources = old.resources.as_ref().zip(new.resources.as_ref())
+        .map(|(old_table, new_table)| diff_resources(old_table, new_table));
+    let files = diff_files(&old, &new);
+    let native = diff_native(&old, &new, &files);
+
+    Ok(ApkComparison {
+        old: old.summary(),
+        new: new.summary(),
+        manifest,
+        permissions: diff_permissions(&old.info, &new.info),
+        sdk: diff_sdk(&old, &new),
+        signing: diff_signing(&old.info, &new.info),
+        native,
+        dex: diff_dex(old.info.dex_analysis.as_ref(), new.info.dex_analysis.as_ref()),
+        resources,
+        files,
+    })
+}
+
+/// 一侧APK的解析结果
+struct ApkSide {
+    path: String,
+    info: ApkInfo,
+    manifest: Option<AndroidManifest>,
+    resources: Option<ResourceTable>,
+    entries: BTreeMap<String, EntryStat>,
+}
+
+impl ApkSide {
+    fn load(path: &Path) -> Result<Self, ApkParserError> {
+        let info = ApkParser::parse(path)?;
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let budget = ReadBudget::default();
+        budget.check_entry_count(archive.len())?;
+        let manifest = ApkParser::read_manifest(&mut archive, &budget).ok();
+        let resources = ApkParser::load_resource_table(&mut archive, &budget);
+        budget.check()?;
+
+        let mut entries = BTreeMap::new();
+        for i in 0..archive.len() {
+            let Ok(entry) = archive.by_index_raw(i) else {
+                continue;
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            entries.insert(entry.name().to_string(), EntryStat {
+                crc32: entry.crc32(),
+                compressed_size: entry.compressed_size(),
+                uncompressed_size: entry.size(),
+            });
+        }
+
+        Ok(ApkSide { path: path.display().to_string(), info, manifest, resources, entries })
+    }
+
+    fn summary(&self) -> ApkSummary {
+        ApkSummary {
+            path: self.path.clone(),
+            package_name: self.info.package_name.clone(),
+            version_name: self.info.version_name.clone(),
+            version_code: self.info.version_code.clone(),
+            file_size: self.file_size(),
+        }
+    }
+
+    fn file_size(&self) -> u64 {
+        self.info.file_info.as_ref().map_or(0, |file_info| file_info.file_size)
+    }
+}
+
+/// 应用组件列表，单独按组件比较
+const COMPONENT_LISTS: &[&str] = &["activities", "activity_aliases", "services", "receivers", "providers"];
+
+fn diff_manifest(old: &AndroidManifest, new: &AndroidManifest) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+    // uses-permission由权限差异单独报告，application在下面去掉组件列表后比较
+    diff_values("", &to_value(old), &to_value(new), &["uses_permissions", "application"], &mut diff.attributes);
+    let old_application = old.application.as_ref().map(to_value).unwrap_or(Value::Null);
+    let new_application = new.application.as_ref().map(to_value).unwrap_or(Value::Null);
+    diff_values("application", &old_application, &new_application, COMPONENT_LISTS, &mut diff.attributes);
+
+    let components = |manifest: &AndroidManifest| -> Vec<Component> {
+        manifest.application.iter().flat_map(|application| application.components().cloned()).collect()
+    };
+    let old_components = components(old);
+    let new_components = components(new);
+    let find = |components: &[Component], component: &Component| -> Option<usize> {
+        components.iter().position(|other| other.kind == component.kind && other.name == component.name)
+    };
+
+    for component in &old_components {
+        match find(&new_components, component) {
+            None => diff.components_removed.push(ComponentRef { kind: component.kind, name: component.name.clone() }),
+            Some(index) => {
+                if let Some(change) = diff_component(component, &new_components[index]) {
+                    diff.components_changed.push(change);
+                }
+            }
+        }
+    }
+    for component in &new_components {
+        if find(&old_components, component).is_none() {
+            diff.components_added.push(ComponentRef { kind: component.kind, name: component.name.clone() });
+        }
+    }
+    diff
+}
+
+fn diff_component(old: &Component, new: &Component) -> Option<ComponentChange> {
+    let mut attributes = Vec::new();
+    diff_values("", &to_value(old), &to_value(new), &["intent_filters"], &mut attributes);
+
+    let key = |filter: &IntentFilter| serde_json::to_string(filter).unwrap_or_default();
+    let old_keys: BTreeSet<String> = old.intent_filters.iter().map(key).collect();
+    let new_keys: BTreeSet<String> = new.intent_filters.iter().map(key).collect();
+    let intent_filters_added: Vec<IntentFilter> = new.intent_filters.iter().filter(|filter| !old_keys.contains(&key(filter))).cloned().collect();
+    let intent_filters_removed: Vec<IntentFilter> = old.intent_filters.iter().filter(|filter| !new_keys.contains(&key(filter))).cloned().collect();
+
+    if attributes.is_empty() && intent_filters_added.is_empty() && intent_filters_removed.is_empty() {
+        return None;
+    }
+    Some(ComponentChange {
+        kind: old.kind,
+        name: old.name.clone(),
+        attributes,
+        intent_filters_added,
+        intent_filters_removed,
+    })
+}
+
+/// 比较两个JSON对象：标量逐字段比较，嵌套对象递归，列表按元素比较新增与删除
+fn diff_values(prefix: &str, old: &Value, new: &Value, skip: &[&str], out: &mut Vec<ValueChange>) {
+    let empty = serde_json::Map::new();
+    let old_map = old.as_object().unwrap_or(&empty);
+    let new_map = new.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+
+    for key in keys {
+        if skip.contains(&key.as_str()) {
+            continue;
+        }
+        let field = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        let old_value = old_map.get(key).unwrap_or(&Value::Null);
+        let new_value = new_map.get(key).unwrap_or(&Value::Null);
+        match (old_value, new_value) {
+            (Value::Array(old_items), Value::Array(new_items)) => {
+                let field = format!("{}[]", field);
+                let old_items: BTreeSet<String> = old_items.iter().map(Value::to_string).collect();
+                let new_items: BTreeSet<String> = new_items.iter().map(Value::to_string).collect();
+                for item in old_items.difference(&new_items) {
+                    out.push(ValueChange { field: field.clone(), old: Some(item.clone()), new: None });
+                }
+                for item in new_items.difference(&old_items) {
+                    out.push(ValueChange { field: field.clone(), old: None, new: Some(item.clone()) });
+                }
+            }
+            (Value::Object(_), _) | (_, Value::Object(_)) => {
+                diff_values(&field, old_value, new_value, &[], out);
+            }
+            _ if old_value != new_value => {
+                out.push(ValueChange { field, old: scalar(old_value), new: scalar(new_value) });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn diff_permissions(old: &ApkInfo, new: &ApkInfo) -> PermissionDiff {
+    let old_permissions = old.permissions.clone().unwrap_or_default();
+    let new_permissions = new.permissions.clone().unwrap_or_default();
+    let contains = |permissions: &[Permission], permission: &Permission| permissions.iter().any(|p| p.name == permission.name);
+    PermissionDiff {
+        added: new_permissions.iter().filter(|p| !contains(&old_permissions, p)).cloned().collect(),
+        removed: old_permissions.iter().filter(|p| !contains(&new_permissions, p)).cloned().collect(),
+    }
+}
+
+fn diff_sdk(old: &ApkSide, new: &ApkSide) -> Vec<ValueChange> {
+    let max_sdk = |side: &ApkSide| side.manifest.as_ref()
+        .and_then(|manifest| manifest.uses_sdk.as_ref())
+        .and_then(|uses_sdk| uses_sdk.max_sdk_version)
+        .map(|version| version.to_string());
+    let compile_sdk = |side: &ApkSide| side.manifest.as_ref()
+        .and_then(|manifest| manifest.compile_sdk_version)
+        .map(|version| version.to_string());
+
+    let mut changes = Vec::new();
+    for (field, old_value, new_value) in [
+        ("min_sdk", Some(old.info.min_sdk.clone()), Some(new.info.min_sdk.clone())),
+        ("target_sdk", Some(old.info.target_sdk.clone()), Some(new.info.target_sdk.clone())),
+        ("max_sdk", max_sdk(old), max_sdk(new)),
+        ("compile_sdk", compile_sdk(old), compile_sdk(new)),
+    ] {
+        if old_value != new_value {
+            changes.push(ValueChange { field: field.to_string(), old: old_value, new: new_value });
+        }
+    }
+    changes
+}
+
+fn diff_signing(old: &ApkInfo, new: &ApkInfo) -> SigningDiff {
+    let certificate = |signature: &SignatureInfo| CertificateRef {
+        subject: signature.subject.clone(),
+        fingerprint_sha256: signature.fingerprint_sha256.clone(),
+    };
+    let old_signer = old.signature_info.as_ref().map(certificate);
+    let new_signer = new.signature_info.as_ref().map(certificate);
+    let old_fingerprint = old_signer.as_ref().and_then(|signer| signer.fingerprint_sha256.clone());
+    let new_fingerprint = new_signer.as_ref().and_then(|signer| signer.fingerprint_sha256.clone());
+    let signer_changed = old_fingerprint != new_fingerprint;
+
+    // 新签名者的轮换谱系中出现旧证书，说明旧密钥签署了轮换证明
+    let rotation_proven = signer_changed && new.signatures.iter()
+        .filter_map(|signature| signature.lineage.as_ref())
+        .flat_map(|lineage| lineage.nodes.iter())
+        .any(|node| Some(&node.certificate.fingerprint_sha256) == old_fingerprint.as_ref());
+
+    let fingerprints = |info: &ApkInfo| -> BTreeMap<String, CertificateRef> {
+        info.signatures.iter()
+            .filter_map(|signature| Some((signature.fingerprint_sha256.clone()?, certificate(signature))))
+            .collect()
+    };
+    let old_certificates = fingerprints(old);
+    let new_certificates = fingerprints(new);
+
+    let schemes = |info: &ApkInfo| -> [(&'static str, Option<bool>); 4] {
+        let schemes = info.signing_schemes.as_ref();
+        [
+            ("v1", schemes.map(|s| s.v1)),
+            ("v2", schemes.map(|s| s.v2)),
+            ("v3", schemes.map(|s| s.v3)),
+            ("v3_1", schemes.map(|s| s.v3_1)),
+        ]
+    };
+    let scheme_changes = schemes(old).into_iter().zip(schemes(new))
+        .filter(|((_, old_value), (_, new_value))| old_value != new_value)
+        .map(|((field, old_value), (_, new_value))| ValueChange {
+            field: field.to_string(),
+            old: old_value.map(|value| value.to_string()),
+            new: new_value.map(|value| value.to_string()),
+        })
+        .collect();
+
+    SigningDiff {
+        old_signer,
+        new_signer,
+        signer_changed,
+        rotation_proven,
+        certificates_added: new_certificates.iter()
+            .filter(|(fingerprint, _)| !old_certificates.contains_key(*fingerprint))
+            .map(|(_, certificate)| certificate.clone())
+            .collect(),
+        certificates_removed: old_certificates.iter()
+            .filter(|(fingerprint, _)| !new_certificates.contains_key(*fingerprint))
+            .map(|(_, certificate)| certificate.clone())
+            .collect(),
+        schemes: scheme_changes,
+    }
+}
+
+fn diff_files(old: &ApkSide, new: &ApkSide) -> FileDiff {
+    let mut diff = FileDiff {
+        size_delta: new.file_size() as i64 - old.file_size() as i64,
+        ..Default::default()
+    };
+    for (path, old_entry) in &old.entries {
+        match new.entries.get(path) {
+            None => diff.removed.push(file_change(path, Some(*old_entry), None)),
+            Some(new_entry) => {
+                if old_entry.crc32 != new_entry.crc32 || old_entry.uncompressed_size != new_entry.uncompressed_size {
+                    diff.changed.push(file_change(path, Some(*old_entry), Some(*new_entry)));
+                } else {
+                    diff.unchanged_count += 1;
+                }
+            }
+        }
+    }
+    for (path, new_entry) in &new.entries {
+        if !old.entries.contains_key(path) {
+            diff.added.push(file_change(path, None, Some(*new_entry)));
+        }
+    }
+    diff.changed.sort_by_key(|change| std::cmp::Reverse(change.compressed_delta.unsigned_abs()));
+    diff
+}
+
+fn file_change(path: &str, old: Option<EntryStat>, new: Option<EntryStat>) -> FileChange {
+    let compressed = |entry: Option<EntryStat>| entry.map_or(0, |entry| entry.compressed_size as i64);
+    let uncompressed = |entry: Option<EntryStat>| entry.map_or(0, |entry| entry.uncompressed_size as i64);
+    FileChange {
+        path: path.to_string(),
+        old,
+        new,
+        compressed_delta: compressed(new) - compressed(old),
+        uncompressed_delta: uncompressed(new) - uncompressed(old),
+    }
+}
+
+fn diff_native(old: &ApkSide, new: &ApkSide, files: &FileDiff) -> NativeDiff {
+    let abis = |side: &ApkSide| -> BTreeSet<String> {
+        side.info.abi_coverage.iter().flat_map(|coverage| coverage.supported_abis.iter().cloned()).collect()
+    };
+    let old_abis = abis(old);
+    let new_abis = abis(new);
+    let libraries = |changes: &[FileChange]| -> Vec<FileChange> {
+        changes.iter().filter(|change| change.path.starts_with("lib/") && change.path.ends_with(".so")).cloned().collect()
+    };
+    NativeDiff {
+        abis_added: new_abis.difference(&old_abis).cloned().collect(),
+        abis_removed: old_abis.difference(&new_abis).cloned().collect(),
+        libraries_added: libraries(&files.added),
+        libraries_removed: libraries(&files.removed),
+        libraries_changed: libraries(&files.changed),
+    }
+}
+
+fn diff_dex(old: Option<&DexAnalysis>, new: Option<&DexAnalysis>) -> DexDiff {
+    let classes = |analysis: Option<&DexAnalysis>| -> BTreeSet<String> {
+        analysis.map(|analysis| analysis.class_names().collect()).unwrap_or_default()
+    };
+    let packages = |analysis: Option<&DexAnalysis>| -> BTreeMap<String, (usize, usize)> {
+        let mut packages = BTreeMap::new();
+        if let Some(analysis) = analysis {
+            flatten_packages(&analysis.package_tree, &mut packages);
+        }
+        packages
+    };
+    let old_classes = classes(old);
+    let new_classes = classes(new);
+    let old_packages = packages(old);
+    let new_packages = packages(new);
+
+    let names: BTreeSet<&String> = old_packages.keys().chain(new_packages.keys()).collect();
+    let package_changes = names.into_iter()
+        .filter_map(|package| {
+            let (old_classes, old_methods) = old_packages.get(package).copied().unwrap_or_default();
+            let (new_classes, new_methods) = new_packages.get(package).copied().unwrap_or_default();
+            (old_classes != new_classes || old_methods != new_methods).then(|| PackageChange {
+                package: package.clone(),
+                old_classes,
+                new_classes,
+                old_methods,
+                new_methods,
+            })
+        })
+        .collect();
+
+    DexDiff {
+        old_classes: old.map_or(0, |analysis| analysis.total_classes),
+        new_classes: new.map_or(0, |analysis| analysis.total_classes),
+        old_methods: old.map_or(0, |analysis| analysis.total_methods),
+        new_methods: new.map_or(0, |analysis| analysis.total_methods),
+        classes_added: new_classes.difference(&old_classes).cloned().collect(),
+        classes_removed: old_classes.difference(&new_classes).cloned().collect(),
+        packages: package_changes,
+    }
+}
+
+fn flatten_packages(nodes: &[PackageNode], out: &mut BTreeMap<String, (usize, usize)>) {
+    for node in nodes {
+        out.insert(node.package.clone(), (node.classes, node.methods));
+        flatten_packages(&node.children, out);
+    }
+}
+
+fn diff_resources(old: &ResourceTable, new: &ResourceTable) -> ResourceDiff {
+    let values = |table: &ResourceTable| -> BTreeMap<String, Vec<String>> {
+        table.resource_ids()
+            .filter_map(|id| {
+                let name = table.resource_name(id)?;
+                let mut values: Vec<String> = table.resolve(id).into_iter()
+                    .map(|value| format!("{}={}", value.config, value.value))
+                    .collect();
+                values.sort();
+                Some((name, values))
+            })
+            .collect()
+    };
+    let old_values = values(old);
+    let new_values = values(new);
+
+    let mut diff = ResourceDiff::default();
+    for (name, old_resource) in &old_values {
+        match new_values.get(name) {
+            None => diff.removed.push(name.clone()),
+            Some(new_resource) if new_resource != old_resource => diff.changed.push(ResourceChange {
+                name: name.clone(),
+                old_values: old_resource.clone(),
+                new_values: new_resource.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.added = new_values.keys().filter(|name| !old_values.contains_key(*name)).cloned().collect();
+    diff
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
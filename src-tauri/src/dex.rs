@@ -0,0 +1,950 @@
+//! DEX文件解析器
+//!
+//! 解析`classes*.dex`的文件头、string_ids、type_ids、proto_ids、field_ids、method_ids、
+//! class_defs以及map_list，统计每个DEX的方法/字段/类/字符串数量，并按包名汇总方法引用，
+//! 用于跟踪64K方法引用上限和各个包的增长情况。类定义的接口列表、class_data和code_item
+//! 也会被解析，供特征检测按方法查看字节码。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use zip::ZipArchive;
+
+use crate::apk_parser::ApkParserError;
+use crate::limits::ReadBudget;
+
+const DEX_MAGIC: &[u8; 4] = b"dex\n";
+const HEADER_SIZE: usize = 0x70;
+const ENDIAN_CONSTANT: u32 = 0x1234_5678;
+const NO_INDEX: u32 = 0xFFFF_FFFF;
+
+/// 单个DEX可引用的方法/字段数量上限
+pub const REFERENCE_LIMIT: usize = 65536;
+
+/// 默认包（类名不含`.`）在包树中的名称
+const DEFAULT_PACKAGE: &str = "(default)";
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidDex(msg.into())
+}
+
+/// DEX文件头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexHeader {
+    /// 格式版本，如`035`、`039`
+    pub version: String,
+    pub checksum: u32,
+    /// SHA-1签名（十六进制）
+    pub signature: String,
+    pub file_size: u32,
+    pub header_size: u32,
+    pub link_size: u32,
+    pub link_off: u32,
+    pub map_off: u32,
+    pub string_ids_size: u32,
+    pub string_ids_off: u32,
+    pub type_ids_size: u32,
+    pub type_ids_off: u32,
+    pub proto_ids_size: u32,
+    pub proto_ids_off: u32,
+    pub field_ids_size: u32,
+    pub field_ids_off: u32,
+    pub method_ids_size: u32,
+    pub method_ids_off: u32,
+    pub class_defs_size: u32,
+    pub class_defs_off: u32,
+    pub data_size: u32,
+    pub data_off: u32,
+}
+
+/// map_list中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapItem {
+    pub type_code: u16,
+    /// 类型名称，如`TYPE_STRING_ID_ITEM`
+    pub type_name: String,
+    pub size: u32,
+    pub offset: u32,
+}
+
+/// 原型（方法签名）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtoId {
+    pub shorty_idx: u32,
+    pub return_type_idx: u32,
+    pub parameters_off: u32,
+    /// 参数类型（type_id索引）
+    pub parameters: Vec<u32>,
+}
+
+/// 字段引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldId {
+    pub class_idx: u16,
+    pub type_idx: u16,
+    pub name_idx: u32,
+}
+
+/// 方法引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodId {
+    pub class_idx: u16,
+    pub proto_idx: u16,
+    pub name_idx: u32,
+}
+
+/// 类定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassDef {
+    pub class_idx: u32,
+    pub access_flags: u32,
+    /// 父类的type_id索引，没有父类时为None
+    pub superclass_idx: Option<u32>,
+    pub interfaces_off: u32,
+    /// 源文件名的string_id索引
+    pub source_file_idx: Option<u32>,
+    pub annotations_off: u32,
+    pub class_data_off: u32,
+    pub static_values_off: u32,
+    /// 实现的接口（type_id索引）
+    pub interfaces: Vec<u32>,
+    /// class_data中的直接方法与虚方法
+    #[serde(skip)]
+    pub methods: Vec<EncodedMethod>,
+}
+
+/// class_data中的方法定义
+#[derive(Debug, Clone)]
+pub struct EncodedMethod {
+    pub method_idx: u32,
+    pub access_flags: u32,
+    /// 抽象方法和native方法没有代码；多个方法引用同一code_item时共享同一份
+    pub code: Option<Arc<CodeItem>>,
+}
+
+/// 方法的字节码
+#[derive(Debug, Clone)]
+pub struct CodeItem {
+    pub registers_size: u16,
+    pub ins_size: u16,
+    pub outs_size: u16,
+    pub tries_size: u16,
+    /// 以16位代码单元存储的指令
+    pub insns: Vec<u16>,
+}
+
+/// 一条Dalvik指令
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction<'a> {
+    /// 在方法中的偏移（代码单元）
+    pub offset: usize,
+    pub opcode: u8,
+    /// 指令占用的全部代码单元
+    pub units: &'a [u16],
+}
+
+impl Instruction<'_> {
+    /// const-string与const-string/jumbo引用的string_id索引
+    pub fn string_index(&self) -> Option<u32> {
+        match (self.opcode, self.units) {
+            (0x1a, [_, index, ..]) => Some(*index as u32),
+            (0x1b, [_, low, high, ..]) => Some(*low as u32 | (*high as u32) << 16),
+            _ => None,
+        }
+    }
+
+    /// invoke-*指令调用的method_id索引
+    pub fn method_index(&self) -> Option<u32> {
+        match (self.opcode, self.units) {
+            (0x6e..=0x72 | 0x74..=0x78 | 0xfa | 0xfb, [_, index, ..]) => Some(*index as u32),
+            _ => None,
+        }
+    }
+
+    /// const/4与const/16加载的整数常量
+    pub fn literal(&self) -> Option<i32> {
+        match (self.opcode, self.units) {
+            (0x12, [unit, ..]) => Some(((*unit as i16) >> 12) as i32),
+            (0x13, [_, value, ..]) => Some(*value as i16 as i32),
+            _ => None,
+        }
+    }
+}
+
+impl CodeItem {
+    /// 按顺序遍历指令，跳过switch与数组数据负载
+    pub fn instructions(&self) -> impl Iterator<Item = Instruction<'_>> + '_ {
+        let mut offset = 0;
+        std::iter::from_fn(move || {
+            while offset < self.insns.len() {
+                let unit = self.insns[offset];
+                let width = instruction_width(unit, &self.insns[offset..]);
+                let end = (offset + width).min(self.insns.len());
+                let start = offset;
+                offset = end;
+                if unit & 0xFF == 0 && unit >> 8 != 0 {
+                    continue;
+                }
+                return Some(Instruction { offset: start, opcode: (unit & 0xFF) as u8, units: &self.insns[start..end] });
+            }
+            None
+        })
+    }
+}
+
+/// 解析后的DEX文件
+#[derive(Debug, Clone)]
+pub struct DexFile {
+    pub header: DexHeader,
+    /// 字符串池（已从MUTF-8解码）
+    pub strings: Vec<String>,
+    /// 类型描述符的string_id索引
+    pub type_ids: Vec<u32>,
+    pub proto_ids: Vec<ProtoId>,
+    pub field_ids: Vec<FieldId>,
+    pub method_ids: Vec<MethodId>,
+    pub class_defs: Vec<ClassDef>,
+    pub map_list: Vec<MapItem>,
+    pub checksum_valid: bool,
+    pub signature_valid: bool,
+}
+
+impl DexFile {
+    /// 解析DEX文件内容，解码的字节码计入`budget`
+    pub fn parse(data: &[u8], budget: &ReadBudget) -> Result<Self, ApkParserError> {
+        if data.len() < HEADER_SIZE || &data[..4] != DEX_MAGIC {
+            return Err(invalid("缺少DEX文件头"));
+        }
+        let version = String::from_utf8_lossy(&data[4..7]).to_string();
+        if le_u32(data, 0x28)? != ENDIAN_CONSTANT {
+            return Err(invalid("不支持的字节序"));
+        }
+
+        let u32_at = |offset: usize| le_u32(data, offset);
+        let header = DexHeader {
+            version,
+            checksum: u32_at(0x08)?,
+            signature: crate::pkcs7::hex(&data[0x0C..0x20]),
+            file_size: u32_at(0x20)?,
+            header_size: u32_at(0x24)?,
+            link_size: u32_at(0x2C)?,
+            link_off: u32_at(0x30)?,
+            map_off: u32_at(0x34)?,
+            string_ids_size: u32_at(0x38)?,
+            string_ids_off: u32_at(0x3C)?,
+            type_ids_size: u32_at(0x40)?,
+            type_ids_off: u32_at(0x44)?,
+            proto_ids_size: u32_at(0x48)?,
+            proto_ids_off: u32_at(0x4C)?,
+            field_ids_size: u32_at(0x50)?,
+            field_ids_off: u32_at(0x54)?,
+            method_ids_size: u32_at(0x58)?,
+            method_ids_off: u32_at(0x5C)?,
+            class_defs_size: u32_at(0x60)?,
+            class_defs_off: u32_at(0x64)?,
+            data_size: u32_at(0x68)?,
+            data_off: u32_at(0x6C)?,
+        };
+
+        let checksum_valid = adler32(&data[0x0C..]) == header.checksum;
+        let signature_valid = Sha1::digest(&data[0x20..]).as_slice() == &data[0x0C..0x20];
+
+        let strings = table(data, header.string_ids_off, header.string_ids_size, 4, "string_ids")?
+            .map(|offset| le_u32(data, offset).and_then(|data_off| read_string(data, data_off as usize)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let type_ids = table(data, header.type_ids_off, header.type_ids_size, 4, "type_ids")?
+            .map(|offset| le_u32(data, offset))
+            .collect::<Result<Vec<_>, _>>()?;
+        let proto_ids = table(data, header.proto_ids_off, header.proto_ids_size, 12, "proto_ids")?
+            .map(|offset| {
+                Ok(ProtoId {
+                    shorty_idx: le_u32(data, offset)?,
+                    return_type_idx: le_u32(data, offset + 4)?,
+                    parameters_off: le_u32(data, offset + 8)?,
+                    parameters: read_type_list(data, le_u32(data, offset + 8)?)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ApkParserError>>()?;
+        let field_ids = table(data, header.field_ids_off, header.field_ids_size, 8, "field_ids")?
+            .map(|offset| {
+                Ok(FieldId {
+                    class_idx: le_u16(data, offset)?,
+                    type_idx: le_u16(data, offset + 2)?,
+                    name_idx: le_u32(data, offset + 4)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ApkParserError>>()?;
+        let method_ids = table(data, header.method_ids_off, header.method_ids_size, 8, "method_ids")?
+            .map(|offset| {
+                Ok(MethodId {
+                    class_idx: le_u16(data, offset)?,
+                    proto_idx: le_u16(data, offset + 2)?,
+                    name_idx: le_u32(data, offset + 4)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ApkParserError>>()?;
+        let optional = |index: u32| (index != NO_INDEX).then_some(index);
+        let mut code_items = HashMap::new();
+        let class_defs = table(data, header.class_defs_off, header.class_defs_size, 32, "class_defs")?
+            .map(|offset| {
+                Ok(ClassDef {
+                    class_idx: le_u32(data, offset)?,
+                    access_flags: le_u32(data, offset + 4)?,
+                    superclass_idx: optional(le_u32(data, offset + 8)?),
+                    interfaces_off: le_u32(data, offset + 12)?,
+                    source_file_idx: optional(le_u32(data, offset + 16)?),
+                    annotations_off: le_u32(data, offset + 20)?,
+                    class_data_off: le_u32(data, offset + 24)?,
+                    static_values_off: le_u32(data, offset + 28)?,
+                    interfaces: read_type_list(data, le_u32(data, offset + 12)?)?,
+                    methods: parse_class_data(data, le_u32(data, offset + 24)?, &mut code_items, budget)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ApkParserError>>()?;
+        let map_list = parse_map_list(data, header.map_off)?;
+
+        Ok(DexFile {
+            header,
+            strings,
+            type_ids,
+            proto_ids,
+            field_ids,
+            method_ids,
+            class_defs,
+            map_list,
+            checksum_valid,
+            signature_valid,
+        })
+    }
+
+    /// 类型描述符，如`Lcom/example/Foo;`
+    pub fn type_descriptor(&self, type_idx: u32) -> Option<&str> {
+        let string_idx = *self.type_ids.get(type_idx as usize)?;
+        self.strings.get(string_idx as usize).map(String::as_str)
+    }
+
+    /// 类型的Java形式名称，如`com.example.Foo`
+    pub fn type_name(&self, type_idx: u32) -> Option<String> {
+        self.type_descriptor(type_idx).map(descriptor_to_class_name)
+    }
+
+    /// 本DEX中定义的所有类
+    pub fn class_names(&self) -> Vec<String> {
+        self.class_defs.iter().filter_map(|class| self.type_name(class.class_idx)).collect()
+    }
+
+    /// 方法引用的名称，形如`com.example.Foo.bar`
+    pub fn method_name(&self, method: &MethodId) -> Option<String> {
+        let class = self.type_name(method.class_idx as u32)?;
+        let name = self.strings.get(method.name_idx as usize)?;
+        Some(format!("{}.{}", class, name))
+    }
+
+    /// 按method_id索引取方法引用的名称
+    pub fn method_name_at(&self, method_idx: u32) -> Option<String> {
+        self.method_name(self.method_ids.get(method_idx as usize)?)
+    }
+
+    /// 类直接实现的接口名称
+    pub fn interface_names(&self, class: &ClassDef) -> Vec<String> {
+        class.interfaces.iter().filter_map(|idx| self.type_name(*idx)).collect()
+    }
+
+    /// 引用方法数占64K上限的比例
+    pub fn method_limit_usage(&self) -> f32 {
+        self.method_ids.len() as f32 / REFERENCE_LIMIT as f32
+    }
+}
+
+/// 单个DEX的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexSummary {
+    /// APK中的文件名，如`classes2.dex`
+    pub name: String,
+    /// DEX格式版本
+    pub version: String,
+    /// 该版本要求的最低API级别
+    pub min_api_level: Option<u32>,
+    pub file_size: u64,
+    pub string_count: usize,
+    pub type_count: usize,
+    pub proto_count: usize,
+    pub field_count: usize,
+    pub method_count: usize,
+    pub class_count: usize,
+    /// 方法引用占64K上限的百分比
+    pub method_limit_percent: f32,
+    /// 字段引用占64K上限的百分比
+    pub field_limit_percent: f32,
+    pub checksum_valid: bool,
+    pub signature_valid: bool,
+    pub map_list: Vec<MapItem>,
+}
+
+/// 包树节点，数量包含所有子包
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageNode {
+    /// 包名的最后一段
+    pub name: String,
+    /// 完整包名
+    pub package: String,
+    /// 定义的类数量
+    pub classes: usize,
+    /// 声明类位于该包下的方法引用数量
+    pub methods: usize,
+    /// 声明类位于该包下的字段引用数量
+    pub fields: usize,
+    pub children: Vec<PackageNode>,
+}
+
+/// APK中全部DEX的分析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DexAnalysis {
+    pub dex_files: Vec<DexSummary>,
+    pub total_classes: usize,
+    pub total_methods: usize,
+    pub total_fields: usize,
+    pub total_strings: usize,
+    /// 是否使用了多DEX
+    pub multidex: bool,
+    /// 是否有DEX的方法或字段引用已超过64K上限
+    pub exceeds_reference_limit: bool,
+    /// 按包名汇总的方法/字段/类数量
+    pub package_tree: Vec<PackageNode>,
+    /// 无法解析的DEX及原因
+    pub errors: Vec<String>,
+    /// 解析后的DEX，供后续特征检测使用
+    #[serde(skip)]
+    pub files: Vec<(String, DexFile)>,
+}
+
+impl DexAnalysis {
+    /// 解析APK中的全部`classes*.dex`
+    pub fn from_archive(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Self {
+        let mut names: Vec<String> = archive.file_names()
+            .filter(|name| is_classes_dex(name))
+            .map(String::from)
+            .collect();
+        names.sort_by_key(|name| dex_index(name));
+
+        let mut analysis = DexAnalysis::default();
+        for name in names {
+            let result = budget.read_entry(archive, &name)
+                .and_then(|data| DexFile::parse(&data, budget).map(|dex| (data.len() as u64, dex)));
+            match result {
+                Ok((size, dex)) => analysis.add(name, size, dex),
+                Err(e) => analysis.errors.push(format!("{}: {}", name, e)),
+            }
+        }
+        analysis.package_tree = build_package_tree(&analysis.files);
+        analysis
+    }
+
+    fn add(&mut self, name: String, file_size: u64, dex: DexFile) {
+        let summary = DexSummary {
+            name: name.clone(),
+            min_api_level: min_api_level(&dex.header.version),
+            version: dex.header.version.clone(),
+            file_size,
+            string_count: dex.strings.len(),
+            type_count: dex.type_ids.len(),
+            proto_count: dex.proto_ids.len(),
+            field_count: dex.field_ids.len(),
+            method_count: dex.method_ids.len(),
+            class_count: dex.class_defs.len(),
+            method_limit_percent: dex.method_limit_usage() * 100.0,
+            field_limit_percent: dex.field_ids.len() as f32 / REFERENCE_LIMIT as f32 * 100.0,
+            checksum_valid: dex.checksum_valid,
+            signature_valid: dex.signature_valid,
+            map_list: dex.map_list.clone(),
+        };
+
+        self.total_classes += summary.class_count;
+        self.total_methods += summary.method_count;
+        self.total_fields += summary.field_count;
+        self.total_strings += summary.string_count;
+        self.exceeds_reference_limit |= summary.method_count > REFERENCE_LIMIT || summary.field_count > REFERENCE_LIMIT;
+        self.multidex = !self.dex_files.is_empty();
+        self.dex_files.push(summary);
+        self.files.push((name, dex));
+    }
+
+    /// 全部DEX中定义的类名
+    pub fn class_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.files.iter().flat_map(|(_, dex)| dex.class_names())
+    }
+
+    /// 全部DEX的字符串池
+    pub fn strings(&self) -> impl Iterator<Item = &str> + '_ {
+        self.files.iter().flat_map(|(_, dex)| dex.strings.iter().map(String::as_str))
+    }
+}
+
+/// 是否为根目录下的`classes.dex`、`classes2.dex`……
+pub fn is_classes_dex(name: &str) -> bool {
+    dex_index(name) != usize::MAX
+}
+
+/// `classes.dex`为1，`classesN.dex`为N，其他文件为usize::MAX
+fn dex_index(name: &str) -> usize {
+    name.strip_prefix("classes")
+        .and_then(|rest| rest.strip_suffix(".dex"))
+        .and_then(|number| if number.is_empty() { Some(1) } else { number.parse().ok() })
+        .unwrap_or(usize::MAX)
+}
+
+/// DEX版本对应的最低API级别
+fn min_api_level(version: &str) -> Option<u32> {
+    match version {
+        "035" => Some(1),
+        "037" => Some(24),
+        "038" => Some(26),
+        "039" => Some(28),
+        "040" => Some(35),
+        _ => None,
+    }
+}
+
+/// `Lcom/example/Foo;` → `com.example.Foo`；数组取元素类型
+pub fn descriptor_to_class_name(descriptor: &str) -> String {
+    let element = descriptor.trim_start_matches('[');
+    match element.strip_prefix('L').and_then(|s| s.strip_suffix(';')) {
+        Some(class) => class.replace('/', "."),
+        None => element.to_string(),
+    }
+}
+
+/// 类名所在的包，默认包返回空字符串
+fn package_of(class_name: &str) -> &str {
+    class_name.rfind('.').map(|pos| &class_name[..pos]).unwrap_or("")
+}
+
+/// 包树的构建中间结构
+#[derive(Default)]
+struct PackageCounts {
+    classes: usize,
+    methods: usize,
+    fields: usize,
+    children: BTreeMap<String, PackageCounts>,
+}
+
+impl PackageCounts {
+    fn add(&mut self, package: &str, classes: usize, methods: usize, fields: usize) {
+        let mut node = self;
+        let segments: Vec<&str> = if package.is_empty() {
+            vec![DEFAULT_PACKAGE]
+        } else {
+            package.split('.').collect()
+        };
+        for segment in segments {
+            node = node.children.entry(segment.to_string()).or_default();
+            node.classes += classes;
+            node.methods += methods;
+            node.fields += fields;
+        }
+    }
+
+    fn into_nodes(self, prefix: &str) -> Vec<PackageNode> {
+        self.children
+            .into_iter()
+            .map(|(name, counts)| {
+                let package = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                let (classes, methods, fields) = (counts.classes, counts.methods, counts.fields);
+                PackageNode {
+                    children: counts.children_into_nodes(&package),
+                    name,
+                    package,
+                    classes,
+                    methods,
+                    fields,
+                }
+            })
+            .collect()
+    }
+
+    fn children_into_nodes(self, prefix: &str) -> Vec<PackageNode> {
+        let mut nodes = self.into_nodes(prefix);
+        nodes.sort_by(|a, b| b.methods.cmp(&a.methods).then_with(|| a.name.cmp(&b.name)));
+        nodes
+    }
+}
+
+fn build_package_tree(files: &[(String, DexFile)]) -> Vec<PackageNode> {
+    let mut root = PackageCounts::default();
+    for (_, dex) in files {
+        let package_of_type = |type_idx: u32| dex.type_name(type_idx).map(|name| package_of(&name).to_string());
+        for class in &dex.class_defs {
+            if let Some(package) = package_of_type(class.class_idx) {
+                root.add(&package, 1, 0, 0);
+            }
+        }
+        for method in &dex.method_ids {
+            if let Some(package) = package_of_type(method.class_idx as u32) {
+                root.add(&package, 0, 1, 0);
+            }
+        }
+        for field in &dex.field_ids {
+            if let Some(package) = package_of_type(field.class_idx as u32) {
+                root.add(&package, 0, 0, 1);
+            }
+        }
+    }
+    root.children_into_nodes("")
+}
+
+/// 返回一个ID表中各项的偏移，并检查整张表位于文件内
+fn table(data: &[u8], offset: u32, count: u32, item_size: usize, what: &str) -> Result<impl Iterator<Item = usize>, ApkParserError> {
+    let offset = offset as usize;
+    let count = count as usize;
+    let end = count.checked_mul(item_size).and_then(|size| size.checked_add(offset));
+    if count > 0 && end.map_or(true, |end| end > data.len()) {
+        return Err(invalid(format!("{}超出文件范围", what)));
+    }
+    Ok((0..count).map(move |i| offset + i * item_size))
+}
+
+fn parse_map_list(data: &[u8], map_off: u32) -> Result<Vec<MapItem>, ApkParserError> {
+    if map_off == 0 {
+        return Ok(Vec::new());
+    }
+    let size = le_u32(data, map_off as usize)?;
+    table(data, map_off + 4, size, 12, "map_list")?
+        .map(|offset| {
+            let type_code = le_u16(data, offset)?;
+            Ok(MapItem {
+                type_code,
+                type_name: map_type_name(type_code).to_string(),
+                size: le_u32(data, offset + 4)?,
+                offset: le_u32(data, offset + 8)?,
+            })
+        })
+        .collect()
+}
+
+/// 读取type_list（接口列表、参数列表）
+fn read_type_list(data: &[u8], offset: u32) -> Result<Vec<u32>, ApkParserError> {
+    if offset == 0 {
+        return Ok(Vec::new());
+    }
+    let size = le_u32(data, offset as usize)?;
+    table(data, offset + 4, size, 2, "type_list")?
+        .map(|offset| le_u16(data, offset).map(u32::from))
+        .collect()
+}
+
+/// 解析class_data_item中的方法及其code_item
+///
+/// code_item按偏移缓存：构造的DEX可让大量方法指向同一个巨大的code_item，
+/// 逐个复制会使内存随引用次数成倍增长。首次解码的字节数计入读取预算。
+fn parse_class_data(
+    data: &[u8],
+    offset: u32,
+    code_items: &mut HashMap<u32, Arc<CodeItem>>,
+    budget: &ReadBudget,
+) -> Result<Vec<EncodedMethod>, ApkParserError> {
+    if offset == 0 {
+        return Ok(Vec::new());
+    }
+    let mut pos = offset as usize;
+    let static_fields = read_uleb128(data, &mut pos)?;
+    let instance_fields = read_uleb128(data, &mut pos)?;
+    let direct_methods = read_uleb128(data, &mut pos)?;
+    let virtual_methods = read_uleb128(data, &mut pos)?;
+
+    for _ in 0..static_fields as u64 + instance_fields as u64 {
+        read_uleb128(data, &mut pos)?;
+        read_uleb128(data, &mut pos)?;
+    }
+
+    let mut methods = Vec::new();
+    for count in [direct_methods, virtual_methods] {
+        // 方法索引以差值编码，每个列表重新计数
+        let mut method_idx = 0u32;
+        for _ in 0..count {
+            method_idx = method_idx.wrapping_add(read_uleb128(data, &mut pos)?);
+            let access_flags = read_uleb128(data, &mut pos)?;
+            let code_off = read_uleb128(data, &mut pos)?;
+            let code = match code_off {
+                0 => None,
+                _ => match code_items.get(&code_off) {
+                    Some(code) => Some(Arc::clone(code)),
+                    None => {
+                        let code = Arc::new(parse_code_item(data, code_off as usize)?);
+                        budget.charge("DEX code_item", code.insns.len() as u64 * 2)?;
+                        code_items.insert(code_off, Arc::clone(&code));
+                        Some(code)
+                    }
+                },
+            };
+            methods.push(EncodedMethod { method_idx, access_flags, code });
+        }
+    }
+    Ok(methods)
+}
+
+fn parse_code_item(data: &[u8], offset: usize) -> Result<CodeItem, ApkParserError> {
+    let insns_size = le_u32(data, offset + 12)? as usize;
+    let insns_off = offset + 16;
+    let bytes = insns_size.checked_mul(2)
+        .and_then(|size| Some(insns_off..insns_off.checked_add(size)?))
+        .and_then(|range| data.get(range))
+        .ok_or_else(|| invalid(format!("偏移0x{:x}处的code_item超出文件范围", offset)))?;
+    Ok(CodeItem {
+        registers_size: le_u16(data, offset)?,
+        ins_size: le_u16(data, offset + 2)?,
+        outs_size: le_u16(data, offset + 4)?,
+        tries_size: le_u16(data, offset + 6)?,
+        insns: bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+    })
+}
+
+/// 指令占用的代码单元数，`rest`从该指令开始
+fn instruction_width(unit: u16, rest: &[u16]) -> usize {
+    let at = |i: usize| rest.get(i).copied().unwrap_or(0) as usize;
+    match unit {
+        // packed-switch-payload
+        0x0100 => at(1) * 2 + 4,
+        // sparse-switch-payload
+        0x0200 => at(1) * 4 + 2,
+        // fill-array-data-payload
+        0x0300 => (at(1) * (at(2) | at(3) << 16) + 1) / 2 + 4,
+        _ => match (unit & 0xFF) as u8 {
+            0x00..=0x01 | 0x04 | 0x07 | 0x0a..=0x12 | 0x1d..=0x1e | 0x21 | 0x27..=0x28 => 1,
+            0x3e..=0x43 | 0x73 | 0x79..=0x8f | 0xb0..=0xcf | 0xe3..=0xf9 => 1,
+            0x02 | 0x05 | 0x08 | 0x13 | 0x15..=0x16 | 0x19..=0x1a | 0x1c | 0x1f..=0x20 | 0x22..=0x23 | 0x29 => 2,
+            0x2d..=0x3d | 0x44..=0x6d | 0x90..=0xaf | 0xd0..=0xe2 | 0xfe..=0xff => 2,
+            0x03 | 0x06 | 0x09 | 0x14 | 0x17 | 0x1b | 0x24..=0x26 | 0x2a..=0x2c => 3,
+            0x6e..=0x72 | 0x74..=0x78 | 0xfc..=0xfd => 3,
+            0xfa..=0xfb => 4,
+            0x18 => 5,
+        },
+    }
+}
+
+fn map_type_name(type_code: u16) -> &'static str {
+    match type_code {
+        0x0000 => "TYPE_HEADER_ITEM",
+        0x0001 => "TYPE_STRING_ID_ITEM",
+        0x0002 => "TYPE_TYPE_ID_ITEM",
+        0x0003 => "TYPE_PROTO_ID_ITEM",
+        0x0004 => "TYPE_FIELD_ID_ITEM",
+        0x0005 => "TYPE_METHOD_ID_ITEM",
+        0x0006 => "TYPE_CLASS_DEF_ITEM",
+        0x0007 => "TYPE_CALL_SITE_ID_ITEM",
+        0x0008 => "TYPE_METHOD_HANDLE_ITEM",
+        0x1000 => "TYPE_MAP_LIST",
+        0x1001 => "TYPE_TYPE_LIST",
+        0x1002 => "TYPE_ANNOTATION_SET_REF_LIST",
+        0x1003 => "TYPE_ANNOTATION_SET_ITEM",
+        0x2000 => "TYPE_CLASS_DATA_ITEM",
+        0x2001 => "TYPE_CODE_ITEM",
+        0x2002 => "TYPE_STRING_DATA_ITEM",
+        0x2003 => "TYPE_DEBUG_INFO_ITEM",
+        0x2004 => "TYPE_ANNOTATION_ITEM",
+        0x2005 => "TYPE_ENCODED_ARRAY_ITEM",
+        0x2006 => "TYPE_ANNOTATIONS_DIRECTORY_ITEM",
+        0xF000 => "TYPE_HIDDENAPI_CLASS_DATA_ITEM",
+        _ => "UNKNOWN",
+    }
+}
+
+/// 读取string_data_item：ULEB128的UTF-16长度，后跟以0结尾的MUTF-8数据
+fn read_string(data: &[u8], offset: usize) -> Result<String, ApkParserError> {
+    let mut pos = offset;
+    let utf16_len = read_uleb128(data, &mut pos)? as usize;
+    let bytes = data.get(pos..).ok_or_else(|| invalid("字符串数据超出文件范围"))?;
+    let end = bytes.iter().position(|b| *b == 0).ok_or_else(|| invalid("字符串缺少结束符"))?;
+    let bytes = &bytes[..end];
+
+    // 纯ASCII是最常见的情况
+    if bytes.len() == utf16_len && bytes.is_ascii() {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+    Ok(decode_mutf8(bytes))
+}
+
+/// 解码MUTF-8（修改版UTF-8：0以两字节编码，补充字符以代理对编码）
+fn decode_mutf8(bytes: &[u8]) -> String {
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let next = |k: usize| bytes.get(i + k).map(|b| (*b as u16) & 0x3F).unwrap_or(0);
+        if b < 0x80 {
+            units.push(b);
+            i += 1;
+        } else if b & 0xE0 == 0xC0 {
+            units.push(((b & 0x1F) << 6) | next(1));
+            i += 2;
+        } else if b & 0xF0 == 0xE0 {
+            units.push(((b & 0x0F) << 12) | (next(1) << 6) | next(2));
+            i += 3;
+        } else {
+            units.push(0xFFFD);
+            i += 1;
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+fn read_uleb128(data: &[u8], pos: &mut usize) -> Result<u32, ApkParserError> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *data.get(*pos).ok_or_else(|| invalid("ULEB128超出文件范围"))?;
+        *pos += 1;
+        result |= ((byte & 0x7F) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(invalid("ULEB128过长"))
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 每5552字节取一次模，避免溢出
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Result<u16, ApkParserError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid(format!("偏移0x{:x}处的数据被截断", offset)))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Result<u32, ApkParserError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid(format!("偏移0x{:x}处的数据被截断", offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::ParseLimits;
+
+    const CLASS_DEFS_OFF: usize = HEADER_SIZE;
+    const CLASS_DATA_OFF: usize = CLASS_DEFS_OFF + 32;
+
+    fn uleb128(mut value: u32, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    /// 一个类，方法的code_off依次取自`code_offs`；`tail`追加在class_data之后，
+    /// 其起始偏移由返回值给出，便于构造code_item
+    fn build_dex(code_offs: impl Fn(usize) -> Vec<u32>, tail: &[u8]) -> Vec<u8> {
+        // class_data的长度取决于code_off的编码长度，先用占位偏移估算
+        let class_data = |tail_off: u32| {
+            let offs = code_offs(tail_off as usize);
+            let mut class_data = Vec::new();
+            for value in [0, 0, offs.len() as u32, 0] {
+                uleb128(value, &mut class_data);
+            }
+            for (i, code_off) in offs.iter().enumerate() {
+                uleb128(u32::from(i > 0), &mut class_data);
+                uleb128(0x0001, &mut class_data);
+                uleb128(*code_off, &mut class_data);
+            }
+            class_data
+        };
+        let mut tail_off = CLASS_DATA_OFF as u32;
+        let mut encoded = class_data(tail_off);
+        while CLASS_DATA_OFF as u32 + encoded.len() as u32 != tail_off {
+            tail_off = (CLASS_DATA_OFF + encoded.len()) as u32;
+            encoded = class_data(tail_off);
+        }
+
+        let mut data = vec![0u8; HEADER_SIZE];
+        data[..8].copy_from_slice(b"dex\n035\0");
+        data[0x28..0x2C].copy_from_slice(&ENDIAN_CONSTANT.to_le_bytes());
+        data[0x60..0x64].copy_from_slice(&1u32.to_le_bytes());
+        data[0x64..0x68].copy_from_slice(&(CLASS_DEFS_OFF as u32).to_le_bytes());
+
+        let mut class_def = [0u8; 32];
+        class_def[8..12].copy_from_slice(&NO_INDEX.to_le_bytes());
+        class_def[16..20].copy_from_slice(&NO_INDEX.to_le_bytes());
+        class_def[24..28].copy_from_slice(&(CLASS_DATA_OFF as u32).to_le_bytes());
+        data.extend_from_slice(&class_def);
+        data.extend_from_slice(&encoded);
+        data.extend_from_slice(tail);
+        data
+    }
+
+    fn code_item(insns: &[u16]) -> Vec<u8> {
+        let mut item = vec![0u8; 16];
+        item[12..16].copy_from_slice(&(insns.len() as u32).to_le_bytes());
+        for unit in insns {
+            item.extend_from_slice(&unit.to_le_bytes());
+        }
+        item
+    }
+
+    #[test]
+    fn shared_code_item_is_decoded_once() {
+        let code = code_item(&[0x000e; 64]);
+        let dex = build_dex(|tail_off| vec![tail_off as u32; 1000], &code);
+        let budget = ReadBudget::default();
+        let parsed = DexFile::parse(&dex, &budget).unwrap();
+
+        let methods = &parsed.class_defs[0].methods;
+        assert_eq!(methods.len(), 1000);
+        let first = methods[0].code.as_ref().unwrap();
+        assert_eq!(first.insns.len(), 64);
+        assert!(methods.iter().all(|m| Arc::ptr_eq(m.code.as_ref().unwrap(), first)));
+        assert_eq!(budget.total_read(), 128);
+    }
+
+    #[test]
+    fn overlapping_code_items_are_charged_to_budget() {
+        // 每个方法指向前一个code_item之后4字节处，任一位置读出的insns_size都是1024
+        let tail: Vec<u8> = (0..2048).flat_map(|_| 1024u32.to_le_bytes()).collect();
+        let dex = build_dex(|tail_off| (0..500).map(|i| (tail_off + i * 4) as u32).collect(), &tail);
+        let budget = ReadBudget::new(ParseLimits { max_total_bytes: 64 * 1024, ..ParseLimits::default() });
+
+        match DexFile::parse(&dex, &budget) {
+            Err(ApkParserError::LimitExceeded(exceeded)) => assert_eq!(exceeded.kind, crate::limits::LimitKind::TotalBytes),
+            other => panic!("应超出读取预算: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn code_item_beyond_file_is_rejected() {
+        let dex = build_dex(|_| vec![0x7FFF_FFF0], &[]);
+        assert!(matches!(DexFile::parse(&dex, &ReadBudget::default()), Err(ApkParserError::InvalidDex(_))));
+    }
+
+    #[test]
+    fn oversized_insns_size_is_rejected() {
+        let mut code = code_item(&[0x000e]);
+        code[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        let dex = build_dex(|tail_off| vec![tail_off as u32], &code);
+        assert!(matches!(DexFile::parse(&dex, &ReadBudget::default()), Err(ApkParserError::InvalidDex(_))));
+    }
+
+    #[test]
+    fn truncated_class_data_is_rejected() {
+        let mut dex = build_dex(|tail_off| vec![tail_off as u32], &code_item(&[0x000e]));
+        dex.truncate(CLASS_DATA_OFF + 3);
+        assert!(matches!(DexFile::parse(&dex, &ReadBudget::default()), Err(ApkParserError::InvalidDex(_))));
+    }
+}
@@ -0,0 +1,751 @@
+//! ELF原生库解析器
+//!
+//! 解析`lib/<abi>/*.so`的文件头、程序头、节头、动态段和动态符号表，支持32/64位的ARM与x86，
+//! 报告架构、SONAME、依赖库、导出的JNI函数，以及checksec风格的加固检查
+//! （NX、PIE、RELRO、栈保护、FORTIFY、RPATH），并标记ABI目录与ELF机器类型不符的库。
+
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::apk_parser::ApkParserError;
+use crate::limits::ReadBudget;
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+const ET_EXEC: u16 = 2;
+const ET_DYN: u16 = 3;
+
+pub const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+const PT_GNU_STACK: u32 = 0x6474_e551;
+const PT_GNU_RELRO: u32 = 0x6474_e552;
+const PF_X: u32 = 1;
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_SONAME: u64 = 14;
+const DT_RPATH: u64 = 15;
+const DT_TEXTREL: u64 = 22;
+const DT_BIND_NOW: u64 = 24;
+const DT_RUNPATH: u64 = 29;
+const DT_FLAGS: u64 = 30;
+const DT_FLAGS_1: u64 = 0x6fff_fffb;
+const DF_BIND_NOW: u64 = 0x8;
+const DF_TEXTREL: u64 = 0x4;
+const DF_1_NOW: u64 = 0x1;
+const DF_1_PIE: u64 = 0x0800_0000;
+
+const STB_GLOBAL: u8 = 1;
+const STB_WEAK: u8 = 2;
+const STT_FUNC: u8 = 2;
+const SHN_UNDEF: u16 = 0;
+
+/// 读取原生库的大小上限
+const MAX_LIBRARY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// 解析的动态符号数上限
+const MAX_DYNAMIC_SYMBOLS: u64 = 1_000_000;
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidElf(msg.into())
+}
+
+/// ELF文件头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElfHeader {
+    /// 是否为64位
+    pub is_64: bool,
+    pub little_endian: bool,
+    /// 文件类型：2为可执行文件，3为共享对象
+    pub file_type: u16,
+    pub machine: u16,
+    pub entry: u64,
+    pub phoff: u64,
+    pub shoff: u64,
+    pub phnum: u16,
+    pub shnum: u16,
+    pub shstrndx: u16,
+}
+
+/// 程序头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub flags: u32,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub filesz: u64,
+    pub memsz: u64,
+    pub align: u64,
+}
+
+/// 节头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionHeader {
+    pub name: String,
+    pub sh_type: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub entsize: u64,
+}
+
+/// 动态符号
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub bind: u8,
+    pub sym_type: u8,
+    pub shndx: u16,
+    pub value: u64,
+}
+
+impl Symbol {
+    /// 本库定义的全局/弱函数
+    pub fn is_exported_function(&self) -> bool {
+        self.shndx != SHN_UNDEF && self.sym_type == STT_FUNC && matches!(self.bind, STB_GLOBAL | STB_WEAK)
+    }
+
+    /// 从其他库导入的符号
+    pub fn is_import(&self) -> bool {
+        self.shndx == SHN_UNDEF && !self.name.is_empty()
+    }
+}
+
+/// 解析后的ELF文件
+#[derive(Debug, Clone)]
+pub struct ElfFile {
+    pub header: ElfHeader,
+    pub program_headers: Vec<ProgramHeader>,
+    pub sections: Vec<SectionHeader>,
+    /// 动态段条目（标签, 值）
+    pub dynamic: Vec<(u64, u64)>,
+    /// 动态字符串表中的字符串：依赖库、SONAME、RPATH、RUNPATH
+    pub needed: Vec<String>,
+    pub soname: Option<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    pub dynamic_symbols: Vec<Symbol>,
+}
+
+/// 按字节序读取整数
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+    is_64: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&self, offset: u64) -> Result<[u8; N], ApkParserError> {
+        let start = usize::try_from(offset).map_err(|_| invalid("偏移超出范围"))?;
+        self.data.get(start..start.saturating_add(N))
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| invalid(format!("偏移0x{:x}处的数据被截断", offset)))
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, ApkParserError> {
+        let b = self.bytes::<2>(offset)?;
+        Ok(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, ApkParserError> {
+        let b = self.bytes::<4>(offset)?;
+        Ok(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, ApkParserError> {
+        let b = self.bytes::<8>(offset)?;
+        Ok(if self.little_endian { u64::from_le_bytes(b) } else { u64::from_be_bytes(b) })
+    }
+
+    /// 地址宽度的整数（32位为u32，64位为u64）
+    fn word(&self, offset: u64) -> Result<u64, ApkParserError> {
+        if self.is_64 { self.u64(offset) } else { self.u32(offset).map(u64::from) }
+    }
+
+    /// 以0结尾的字符串
+    fn c_str(&self, offset: u64) -> Option<String> {
+        let start = usize::try_from(offset).ok()?;
+        let bytes = self.data.get(start..)?;
+        let end = bytes.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+}
+
+impl ElfFile {
+    /// 解析ELF文件内容
+    pub fn parse(data: &[u8]) -> Result<Self, ApkParserError> {
+        if data.len() < 0x34 || &data[..4] != ELF_MAGIC {
+            return Err(invalid("缺少ELF文件头"));
+        }
+        let is_64 = match data[4] {
+            1 => false,
+            2 => true,
+            class => return Err(invalid(format!("未知的ELF类别: {}", class))),
+        };
+        let little_endian = match data[5] {
+            1 => true,
+            2 => false,
+            encoding => return Err(invalid(format!("未知的字节序: {}", encoding))),
+        };
+        let r = Reader { data, little_endian, is_64 };
+
+        // 32位与64位文件头中各字段的偏移不同
+        let (entry, phoff, shoff, flags_end) = if is_64 {
+            (r.u64(0x18)?, r.u64(0x20)?, r.u64(0x28)?, 0x34)
+        } else {
+            (r.u32(0x18)? as u64, r.u32(0x1C)? as u64, r.u32(0x20)? as u64, 0x28)
+        };
+        let header = ElfHeader {
+            is_64,
+            little_endian,
+            file_type: r.u16(0x10)?,
+            machine: r.u16(0x12)?,
+            entry,
+            phoff,
+            shoff,
+            phnum: r.u16(flags_end + 4)?,
+            shnum: r.u16(flags_end + 8)?,
+            shstrndx: r.u16(flags_end + 10)?,
+        };
+        let phentsize = r.u16(flags_end + 2)? as u64;
+        let shentsize = r.u16(flags_end + 6)? as u64;
+
+        // 表项偏移来自文件，计算溢出的表项直接跳过
+        let (program_header_size, section_header_size) = if is_64 { (56, 64) } else { (32, 40) };
+        let program_headers = (0..header.phnum as u64)
+            .filter_map(|i| table_offset(header.phoff, i, phentsize, program_header_size))
+            .map(|offset| parse_program_header(&r, offset))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 节头可能被加固工具去掉，解析失败时按无节头处理
+        let raw_sections = (0..header.shnum as u64)
+            .filter_map(|i| table_offset(header.shoff, i, shentsize, section_header_size))
+            .map(|offset| parse_section_header(&r, offset))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_default();
+        let shstrtab = raw_sections.get(header.shstrndx as usize).map(|(_, s)| s.offset);
+        let sections = raw_sections.into_iter()
+            .map(|(name_offset, mut section)| {
+                section.name = shstrtab
+                    .and_then(|base| base.checked_add(name_offset as u64))
+                    .and_then(|offset| r.c_str(offset))
+                    .unwrap_or_default();
+                section
+            })
+            .collect();
+
+        let mut elf = ElfFile {
+            header,
+            program_headers,
+            sections,
+            dynamic: Vec::new(),
+            needed: Vec::new(),
+            soname: None,
+            rpath: None,
+            runpath: None,
+            dynamic_symbols: Vec::new(),
+        };
+        elf.parse_dynamic(&r)?;
+        elf.parse_dynamic_symbols(&r);
+        Ok(elf)
+    }
+
+    /// 把虚拟地址换算为文件偏移
+    pub fn vaddr_to_offset(&self, vaddr: u64) -> Option<u64> {
+        self.program_headers.iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .find(|ph| vaddr >= ph.vaddr && vaddr - ph.vaddr < ph.filesz)
+            .and_then(|ph| (vaddr - ph.vaddr).checked_add(ph.offset))
+    }
+
+    fn parse_dynamic(&mut self, r: &Reader) -> Result<(), ApkParserError> {
+        let Some(dynamic) = self.program_headers.iter().find(|ph| ph.p_type == PT_DYNAMIC).cloned() else {
+            return Ok(());
+        };
+        let entry_size = if r.is_64 { 16 } else { 8 };
+        for i in 0..dynamic.filesz / entry_size {
+            let Some(offset) = table_offset(dynamic.offset, i, entry_size, entry_size) else {
+                break;
+            };
+            let tag = r.word(offset)?;
+            if tag == DT_NULL {
+                break;
+            }
+            let value = r.word(offset + entry_size / 2)?;
+            self.dynamic.push((tag, value));
+        }
+
+        let Some(strtab) = self.dynamic_value(DT_STRTAB).and_then(|vaddr| self.vaddr_to_offset(vaddr)) else {
+            return Ok(());
+        };
+        let string = |value: u64| strtab.checked_add(value).and_then(|offset| r.c_str(offset));
+        for (tag, value) in self.dynamic.clone() {
+            match tag {
+                DT_NEEDED => self.needed.extend(string(value)),
+                DT_SONAME => self.soname = string(value),
+                DT_RPATH => self.rpath = string(value),
+                DT_RUNPATH => self.runpath = string(value),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_dynamic_symbols(&mut self, r: &Reader) {
+        let Some(dynsym) = self.sections.iter().find(|s| s.sh_type == SHT_DYNSYM).cloned() else {
+            return;
+        };
+        let Some(strtab) = self.sections.get(dynsym.link as usize).map(|s| s.offset) else {
+            return;
+        };
+        // 表项至少要容纳一个Elf32_Sym/Elf64_Sym，否则字段会读到相邻表项
+        let min_entry_size = if r.is_64 { 24 } else { 16 };
+        let entry_size = match dynsym.entsize {
+            0 => min_entry_size,
+            size if size >= min_entry_size => size,
+            _ => return,
+        };
+        let count = (dynsym.size / entry_size).min(MAX_DYNAMIC_SYMBOLS);
+        for i in 1..count {
+            let Some(offset) = table_offset(dynsym.offset, i, entry_size, min_entry_size) else {
+                break;
+            };
+            let symbol = if r.is_64 {
+                (r.u32(offset), r.bytes::<1>(offset + 4), r.u16(offset + 6), r.u64(offset + 8))
+            } else {
+                (r.u32(offset), r.bytes::<1>(offset + 12), r.u16(offset + 14), r.u32(offset + 4).map(u64::from))
+            };
+            let (Ok(name), Ok([info]), Ok(shndx), Ok(value)) = symbol else {
+                break;
+            };
+            self.dynamic_symbols.push(Symbol {
+                name: strtab.checked_add(name as u64).and_then(|offset| r.c_str(offset)).unwrap_or_default(),
+                bind: info >> 4,
+                sym_type: info & 0xF,
+                shndx,
+                value,
+            });
+        }
+    }
+
+    /// 第一个指定标签的动态段值
+    pub fn dynamic_value(&self, tag: u64) -> Option<u64> {
+        self.dynamic.iter().find(|(t, _)| *t == tag).map(|(_, v)| *v)
+    }
+
+    /// 机器类型名称
+    pub fn architecture(&self) -> &'static str {
+        machine_name(self.header.machine)
+    }
+
+    /// 没有.symtab节即视为已去除符号
+    pub fn is_stripped(&self) -> bool {
+        !self.sections.iter().any(|s| s.sh_type == SHT_SYMTAB)
+    }
+
+    /// 导出的JNI函数（`Java_*`、`JNI_OnLoad`、`JNI_OnUnload`）
+    pub fn jni_functions(&self) -> Vec<String> {
+        self.dynamic_symbols.iter()
+            .filter(|s| s.is_exported_function())
+            .filter(|s| s.name.starts_with("Java_") || s.name == "JNI_OnLoad" || s.name == "JNI_OnUnload")
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    /// checksec风格的加固检查
+    pub fn hardening(&self) -> Hardening {
+        let has_segment = |p_type: u32| self.program_headers.iter().any(|ph| ph.p_type == p_type);
+        let flags = self.dynamic_value(DT_FLAGS).unwrap_or(0);
+        let flags_1 = self.dynamic_value(DT_FLAGS_1).unwrap_or(0);
+
+        let nx = self.program_headers.iter()
+            .find(|ph| ph.p_type == PT_GNU_STACK)
+            .is_some_and(|ph| ph.flags & PF_X == 0);
+        let pie = match self.header.file_type {
+            ET_EXEC => PieStatus::Disabled,
+            ET_DYN if has_segment(PT_INTERP) || flags_1 & DF_1_PIE != 0 => PieStatus::Enabled,
+            ET_DYN => PieStatus::SharedObject,
+            _ => PieStatus::Disabled,
+        };
+        let bind_now = self.dynamic_value(DT_BIND_NOW).is_some() || flags & DF_BIND_NOW != 0 || flags_1 & DF_1_NOW != 0;
+        let relro = match (has_segment(PT_GNU_RELRO), bind_now) {
+            (true, true) => RelroStatus::Full,
+            (true, false) => RelroStatus::Partial,
+            (false, _) => RelroStatus::None,
+        };
+
+        let imports: Vec<&str> = self.dynamic_symbols.iter().filter(|s| s.is_import()).map(|s| s.name.as_str()).collect();
+        let stack_canary = imports.iter().any(|name| *name == "__stack_chk_fail" || *name == "__stack_chk_guard");
+        let fortified_functions: Vec<String> = imports.iter()
+            .filter(|name| name.starts_with("__") && name.ends_with("_chk") && !name.starts_with("__stack_chk"))
+            .map(|name| name.to_string())
+            .collect();
+
+        Hardening {
+            nx,
+            pie,
+            relro,
+            stack_canary,
+            fortify: !fortified_functions.is_empty(),
+            fortified_functions,
+            rpath: self.rpath.clone(),
+            runpath: self.runpath.clone(),
+            text_relocations: self.dynamic_value(DT_TEXTREL).is_some() || flags & DF_TEXTREL != 0,
+        }
+    }
+}
+
+fn parse_program_header(r: &Reader, offset: u64) -> Result<ProgramHeader, ApkParserError> {
+    if r.is_64 {
+        Ok(ProgramHeader {
+            p_type: r.u32(offset)?,
+            flags: r.u32(offset + 4)?,
+            offset: r.u64(offset + 8)?,
+            vaddr: r.u64(offset + 16)?,
+            filesz: r.u64(offset + 32)?,
+            memsz: r.u64(offset + 40)?,
+            align: r.u64(offset + 48)?,
+        })
+    } else {
+        Ok(ProgramHeader {
+            p_type: r.u32(offset)?,
+            offset: r.u32(offset + 4)? as u64,
+            vaddr: r.u32(offset + 8)? as u64,
+            filesz: r.u32(offset + 16)? as u64,
+            memsz: r.u32(offset + 20)? as u64,
+            flags: r.u32(offset + 24)?,
+            align: r.u32(offset + 28)? as u64,
+        })
+    }
+}
+
+/// 表中第`index`项的偏移，该项的前`span`字节的偏移计算溢出时返回None
+fn table_offset(base: u64, index: u64, entry_size: u64, span: u64) -> Option<u64> {
+    let offset = base.checked_add(index.checked_mul(entry_size)?)?;
+    offset.checked_add(span)?;
+    Some(offset)
+}
+
+/// 返回节名在shstrtab中的偏移和节头
+fn parse_section_header(r: &Reader, offset: u64) -> Result<(u32, SectionHeader), ApkParserError> {
+    let name_offset = r.u32(offset)?;
+    let sh_type = r.u32(offset + 4)?;
+    let (sh_offset, size, link, entsize) = if r.is_64 {
+        (r.u64(offset + 24)?, r.u64(offset + 32)?, r.u32(offset + 40)?, r.u64(offset + 56)?)
+    } else {
+        (r.u32(offset + 16)? as u64, r.u32(offset + 20)? as u64, r.u32(offset + 24)?, r.u32(offset + 36)? as u64)
+    };
+    Ok((name_offset, SectionHeader { name: String::new(), sh_type, offset: sh_offset, size, link, entsize }))
+}
+
+/// ELF机器类型名称
+pub fn machine_name(machine: u16) -> &'static str {
+    match machine {
+        3 => "x86",
+        8 => "MIPS",
+        40 => "ARM",
+        62 => "x86_64",
+        183 => "AArch64",
+        243 => "RISC-V",
+        _ => "unknown",
+    }
+}
+
+/// ABI目录对应的ELF机器类型与位数
+fn expected_machine(abi: &str) -> Option<(u16, bool)> {
+    match abi {
+        "armeabi" | "armeabi-v7a" => Some((40, false)),
+        "arm64-v8a" => Some((183, true)),
+        "x86" => Some((3, false)),
+        "x86_64" => Some((62, true)),
+        "mips" => Some((8, false)),
+        "mips64" => Some((8, true)),
+        "riscv64" => Some((243, true)),
+        _ => None,
+    }
+}
+
+/// PIE状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PieStatus {
+    Enabled,
+    /// 共享库（DSO），本身即位置无关
+    SharedObject,
+    Disabled,
+}
+
+/// RELRO状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelroStatus {
+    Full,
+    Partial,
+    None,
+}
+
+/// checksec风格的加固检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hardening {
+    /// 栈不可执行（PT_GNU_STACK无X标志）
+    pub nx: bool,
+    pub pie: PieStatus,
+    pub relro: RelroStatus,
+    /// 引用了`__stack_chk_fail`
+    pub stack_canary: bool,
+    /// 引用了`__*_chk`加固函数
+    pub fortify: bool,
+    pub fortified_functions: Vec<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    /// 含有文本重定位（Android 6.0起拒绝加载）
+    pub text_relocations: bool,
+}
+
+/// 单个原生库的分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NativeLibrary {
+    /// APK中的路径，如`lib/arm64-v8a/libfoo.so`
+    pub path: String,
+    /// 所在ABI目录
+    pub abi: Option<String>,
+    pub architecture: String,
+    pub is_64: bool,
+    pub size: u64,
+    pub stripped: bool,
+    pub soname: Option<String>,
+    pub needed: Vec<String>,
+    pub jni_functions: Vec<String>,
+    pub has_jni_onload: bool,
+    pub hardening: Hardening,
+    /// ABI目录与ELF机器类型不符时的说明
+    pub abi_mismatch: Option<String>,
+    /// 解析后的ELF，供后续检查使用
+    #[serde(skip)]
+    pub elf: Option<ElfFile>,
+}
+
+/// APK中全部原生库的分析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NativeLibraryAnalysis {
+    pub libraries: Vec<NativeLibrary>,
+    /// ABI目录与ELF机器类型不符的库数量
+    pub abi_mismatches: usize,
+    /// 无法解析的库及原因
+    pub errors: Vec<String>,
+}
+
+impl NativeLibraryAnalysis {
+    /// 解析APK中的全部`lib/<abi>/*.so`
+    pub fn from_archive(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Self {
+        let names: Vec<String> = archive.file_names()
+            .filter(|name| name.starts_with("lib/") && name.ends_with(".so"))
+            .map(String::from)
+            .collect();
+
+        let mut analysis = NativeLibraryAnalysis::default();
+        for name in names {
+            let result = archive.by_name(&name)
+                .map_err(ApkParserError::from)
+                .and_then(|entry| {
+                    if entry.size() > MAX_LIBRARY_SIZE {
+                        return Err(invalid(format!("文件过大（{} 字节）", entry.size())));
+                    }
+                    budget.read(entry)
+                })
+                .and_then(|data| ElfFile::parse(&data).map(|elf| (data.len() as u64, elf)));
+            match result {
+                Ok((size, elf)) => analysis.add(name, size, elf),
+                Err(e) => analysis.errors.push(format!("{}: {}", name, e)),
+            }
+        }
+        analysis.abi_mismatches = analysis.libraries.iter().filter(|lib| lib.abi_mismatch.is_some()).count();
+        analysis
+    }
+
+    fn add(&mut self, path: String, size: u64, elf: ElfFile) {
+        let abi = path.split('/').nth(1).filter(|_| path.matches('/').count() == 2).map(String::from);
+        let abi_mismatch = abi.as_deref().and_then(|abi| {
+            let (machine, is_64) = expected_machine(abi)?;
+            (machine != elf.header.machine || is_64 != elf.header.is_64).then(|| {
+                format!(
+                    "位于{}目录，但ELF为{}位{}",
+                    abi,
+                    if elf.header.is_64 { 64 } else { 32 },
+                    elf.architecture()
+                )
+            })
+        });
+        let jni_functions = elf.jni_functions();
+        self.libraries.push(NativeLibrary {
+            abi,
+            architecture: elf.architecture().to_string(),
+            is_64: elf.header.is_64,
+            size,
+            stripped: elf.is_stripped(),
+            soname: elf.soname.clone(),
+            needed: elf.needed.clone(),
+            has_jni_onload: jni_functions.iter().any(|f| f == "JNI_OnLoad"),
+            jni_functions,
+            hardening: elf.hardening(),
+            abi_mismatch,
+            path,
+            elf: Some(elf),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EHDR_SIZE: usize = 64;
+    const PHDR_SIZE: usize = 56;
+    const SHDR_SIZE: usize = 64;
+
+    fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u64(data: &mut [u8], offset: usize, value: u64) {
+        data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// 64位小端AArch64共享库的文件头
+    fn elf64_header(phoff: u64, phnum: u16, shoff: u64, shnum: u16) -> Vec<u8> {
+        let mut data = vec![0u8; EHDR_SIZE];
+        data[..4].copy_from_slice(ELF_MAGIC);
+        data[4] = 2;
+        data[5] = 1;
+        put_u16(&mut data, 0x10, ET_DYN);
+        put_u16(&mut data, 0x12, 183);
+        put_u64(&mut data, 0x20, phoff);
+        put_u64(&mut data, 0x28, shoff);
+        put_u16(&mut data, 0x36, PHDR_SIZE as u16);
+        put_u16(&mut data, 0x38, phnum);
+        put_u16(&mut data, 0x3A, SHDR_SIZE as u16);
+        put_u16(&mut data, 0x3C, shnum);
+        data
+    }
+
+    fn program_header(p_type: u32, offset: u64, vaddr: u64, filesz: u64) -> Vec<u8> {
+        let mut ph = vec![0u8; PHDR_SIZE];
+        put_u32(&mut ph, 0, p_type);
+        put_u64(&mut ph, 8, offset);
+        put_u64(&mut ph, 16, vaddr);
+        put_u64(&mut ph, 32, filesz);
+        put_u64(&mut ph, 40, filesz);
+        put_u64(&mut ph, 48, 0x4000);
+        ph
+    }
+
+    fn section_header(sh_type: u32, offset: u64, size: u64, link: u32, entsize: u64) -> Vec<u8> {
+        let mut sh = vec![0u8; SHDR_SIZE];
+        put_u32(&mut sh, 4, sh_type);
+        put_u64(&mut sh, 24, offset);
+        put_u64(&mut sh, 32, size);
+        put_u32(&mut sh, 40, link);
+        put_u64(&mut sh, 56, entsize);
+        sh
+    }
+
+    #[test]
+    fn overflowing_header_table_offsets_are_skipped() {
+        let data = elf64_header(u64::MAX - 8, 2, u64::MAX - 100, 3);
+        let elf = ElfFile::parse(&data).unwrap();
+        assert!(elf.program_headers.is_empty());
+        assert!(elf.sections.is_empty());
+    }
+
+    #[test]
+    fn overflowing_section_name_offset_is_ignored() {
+        let mut data = elf64_header(0, 0, EHDR_SIZE as u64, 2);
+        let mut names = section_header(3, (EHDR_SIZE + 2 * SHDR_SIZE) as u64, 8, 0, 0);
+        put_u32(&mut names, 0, 1);
+        let mut named = section_header(1, 0, 0, 0, 0);
+        put_u32(&mut named, 0, u32::MAX);
+        data.extend(named);
+        data.extend(names);
+        data.extend(b"\0.shstr\0");
+        put_u16(&mut data, 0x3E, 1);
+        let mut data_far = data.clone();
+        put_u64(&mut data_far, EHDR_SIZE + SHDR_SIZE + 24, u64::MAX - 4);
+
+        let elf = ElfFile::parse(&data).unwrap();
+        assert_eq!(elf.sections[1].name, ".shstr");
+        assert_eq!(elf.sections[0].name, "");
+        let elf = ElfFile::parse(&data_far).unwrap();
+        assert!(elf.sections.iter().all(|s| s.name.is_empty()));
+    }
+
+    #[test]
+    fn overflowing_dynamic_string_offsets_are_ignored() {
+        // PT_LOAD覆盖整个文件，动态段引用的字符串表紧随其后
+        let dynamic_off = EHDR_SIZE + 2 * PHDR_SIZE;
+        let strtab_off = dynamic_off + 4 * 16;
+        let mut data = elf64_header(EHDR_SIZE as u64, 2, 0, 0);
+        data.extend(program_header(PT_LOAD, 0, 0, (strtab_off + 16) as u64));
+        data.extend(program_header(PT_DYNAMIC, dynamic_off as u64, dynamic_off as u64, 4 * 16));
+        for (tag, value) in [(DT_STRTAB, strtab_off as u64), (DT_NEEDED, u64::MAX), (DT_NEEDED, 1), (DT_NULL, 0)] {
+            data.extend(tag.to_le_bytes());
+            data.extend(value.to_le_bytes());
+        }
+        data.extend(b"\0libc.so\0\0\0\0\0\0\0\0");
+
+        let elf = ElfFile::parse(&data).unwrap();
+        assert_eq!(elf.needed, vec!["libc.so".to_string()]);
+        assert_eq!(elf.vaddr_to_offset(strtab_off as u64), Some(strtab_off as u64));
+    }
+
+    #[test]
+    fn load_segment_near_address_limit_does_not_overflow() {
+        let mut data = elf64_header(EHDR_SIZE as u64, 1, 0, 0);
+        data.extend(program_header(PT_LOAD, u64::MAX, u64::MAX - 10, 100));
+        let elf = ElfFile::parse(&data).unwrap();
+        assert_eq!(elf.vaddr_to_offset(u64::MAX - 5), None);
+        assert_eq!(elf.vaddr_to_offset(0), None);
+    }
+
+    #[test]
+    fn undersized_dynsym_entries_are_rejected() {
+        let strtab_off = EHDR_SIZE + 2 * SHDR_SIZE;
+        let dynsym_off = strtab_off + 8;
+        let mut data = elf64_header(0, 0, EHDR_SIZE as u64, 2);
+        data.extend(section_header(3, strtab_off as u64, 8, 0, 0));
+        data.extend(section_header(SHT_DYNSYM, dynsym_off as u64, 24 * 4, 0, 1));
+        data.extend(b"\0JNI_On\0");
+        data.extend(vec![1u8; 24 * 4]);
+
+        let elf = ElfFile::parse(&data).unwrap();
+        assert!(elf.dynamic_symbols.is_empty());
+
+        put_u64(&mut data, EHDR_SIZE + SHDR_SIZE + 56, 24);
+        let elf = ElfFile::parse(&data).unwrap();
+        assert_eq!(elf.dynamic_symbols.len(), 3);
+    }
+
+    #[test]
+    fn huge_dynsym_size_stops_at_end_of_data() {
+        let dynsym_off = EHDR_SIZE + 2 * SHDR_SIZE;
+        let mut data = elf64_header(0, 0, EHDR_SIZE as u64, 2);
+        data.extend(section_header(3, dynsym_off as u64, 1, 0, 0));
+        data.extend(section_header(SHT_DYNSYM, dynsym_off as u64, u64::MAX, 0, 24));
+        data.extend(vec![0u8; 24 * 4]);
+
+        let elf = ElfFile::parse(&data).unwrap();
+        assert_eq!(elf.dynamic_symbols.len(), 3);
+    }
+}
@@ -0,0 +1,418 @@
+//! 跨平台开发框架识别
+//!
+//! 根据清单中的入口类、原生库、assets文件和DEX类名识别Flutter、React Native、Unity、
+//! Xamarin/.NET MAUI、Cordova/Capacitor、Kotlin Multiplatform、Qt和uni-app，
+//! 并尽量提取各框架的细节：Flutter引擎哈希、RN使用Hermes还是JSC、Unity版本、uni-app的appid等。
+
+use std::collections::BTreeMap;
+use std::fs::File;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::dex::DexAnalysis;
+use crate::limits::ReadBudget;
+use crate::manifest::AndroidManifest;
+
+/// 读取用于提取细节的文件的大小上限
+const MAX_DETAIL_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Hermes字节码文件的魔数
+const HERMES_MAGIC: [u8; 8] = [0xC6, 0x1F, 0xBC, 0x03, 0xC1, 0x03, 0x19, 0x1F];
+
+/// 特征的匹配对象
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// 清单中的Application或组件类名（前缀）
+    ManifestClass(&'static str),
+    /// DEX中定义了以此为前缀的类
+    ClassPrefix(&'static str),
+    /// DEX中定义了以此结尾的类
+    ClassSuffix(&'static str),
+    /// `lib/<abi>/`下的原生库文件名（正则）
+    NativeLibrary(&'static str),
+    /// 同上，但文件名过于通用，只在已有其他证据时作为补充证据
+    SupportingLibrary(&'static str),
+    /// APK内的文件路径（正则）
+    Asset(&'static str),
+}
+
+struct FrameworkSignature {
+    id: &'static str,
+    name: &'static str,
+    targets: &'static [Target],
+}
+
+use Target::{Asset, ClassPrefix, ClassSuffix, ManifestClass, NativeLibrary, SupportingLibrary};
+
+const SIGNATURES: &[FrameworkSignature] = &[
+    FrameworkSignature {
+        id: "flutter",
+        name: "Flutter",
+        targets: &[
+            ManifestClass("io.flutter."),
+            ClassPrefix("io.flutter."),
+            NativeLibrary(r"^libflutter\.so$"),
+            // AOT快照libapp.so的文件名也常见于其他原生库
+            SupportingLibrary(r"^libapp\.so$"),
+            Asset(r"^assets/flutter_assets/"),
+        ],
+    },
+    FrameworkSignature {
+        id: "react_native",
+        name: "React Native",
+        targets: &[
+            ClassPrefix("com.facebook.react."),
+            NativeLibrary(r"^lib(?:reactnativejni|hermes|jsc|reactnative)\.so$"),
+            Asset(r"^assets/index\.android\.bundle$"),
+        ],
+    },
+    FrameworkSignature {
+        id: "unity",
+        name: "Unity",
+        targets: &[
+            ManifestClass("com.unity3d.player."),
+            ClassPrefix("com.unity3d.player."),
+            NativeLibrary(r"^lib(?:unity|il2cpp)\.so$"),
+            Asset(r"^assets/bin/Data/"),
+        ],
+    },
+    FrameworkSignature {
+        id: "xamarin",
+        name: "Xamarin / .NET MAUI",
+        targets: &[
+            ClassPrefix("mono.android."),
+            ClassPrefix("crc64"),
+            ClassPrefix("com.microsoft.maui."),
+            NativeLibrary(r"^lib(?:monodroid|monosgen-2\.0|xamarin-app|mono-native)\.so$"),
+            Asset(r"^assemblies/"),
+        ],
+    },
+    FrameworkSignature {
+        id: "cordova",
+        name: "Cordova",
+        targets: &[
+            ClassPrefix("org.apache.cordova."),
+            Asset(r"^assets/www/cordova\.js$"),
+        ],
+    },
+    FrameworkSignature {
+        id: "capacitor",
+        name: "Capacitor",
+        targets: &[
+            ClassPrefix("com.getcapacitor."),
+            Asset(r"^assets/capacitor\.(?:config|plugins)\.json$"),
+        ],
+    },
+    FrameworkSignature {
+        id: "kotlin_multiplatform",
+        name: "Kotlin Multiplatform",
+        targets: &[
+            ClassPrefix("org.jetbrains.compose.resources."),
+            // expect/actual的Android实现文件（如Platform.android.kt）编译为`Platform_androidKt`
+            ClassSuffix("_androidKt"),
+            Asset(r"^assets/composeResources/"),
+        ],
+    },
+    FrameworkSignature {
+        id: "qt",
+        name: "Qt",
+        targets: &[
+            ManifestClass("org.qtproject.qt"),
+            ClassPrefix("org.qtproject.qt"),
+            NativeLibrary(r"^libQt[56]Core(?:_\w+)?\.so$"),
+            NativeLibrary(r"^libplugins_platforms_(?:qtforandroid|android)"),
+        ],
+    },
+    FrameworkSignature {
+        id: "uni_app",
+        name: "uni-app (DCloud)",
+        targets: &[
+            ManifestClass("io.dcloud."),
+            ClassPrefix("io.dcloud."),
+            Asset(r"^assets/apps/__UNI__[0-9A-Za-z]+/"),
+            Asset(r"^assets/data/dcloud_control\.xml$"),
+        ],
+    },
+];
+
+/// 一条命中证据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkEvidence {
+    /// 证据类型：manifest_class、class、native_library、asset
+    pub kind: String,
+    pub matched: String,
+}
+
+/// 识别出的框架
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedFramework {
+    pub id: String,
+    pub name: String,
+    pub evidence: Vec<FrameworkEvidence>,
+    /// 框架相关细节，如`engine_hash`、`js_engine`、`unity_version`、`appid`
+    pub details: BTreeMap<String, String>,
+}
+
+/// 识别跨平台框架
+pub fn detect(
+    archive: &mut ZipArchive<File>,
+    dex: Option<&DexAnalysis>,
+    manifest: Option<&AndroidManifest>,
+    budget: &ReadBudget,
+) -> Vec<DetectedFramework> {
+    let manifest_classes: Vec<&str> = manifest
+        .and_then(|m| m.application.as_ref())
+        .map(|app| app.name.iter().map(String::as_str).chain(app.components().map(|c| c.name.as_str())).collect())
+        .unwrap_or_default();
+    let class_names: Vec<String> = dex.map(|dex| dex.class_names().collect()).unwrap_or_default();
+    let file_names: Vec<String> = archive.file_names().map(String::from).collect();
+
+    let mut frameworks = Vec::new();
+    for signature in SIGNATURES {
+        let mut evidence = Vec::new();
+        let mut supporting = Vec::new();
+        for target in signature.targets {
+            match *target {
+                ManifestClass(prefix) => {
+                    if let Some(class) = manifest_classes.iter().find(|name| name.starts_with(prefix)) {
+                        evidence.push(FrameworkEvidence { kind: "manifest_class".to_string(), matched: class.to_string() });
+                    }
+                }
+                ClassPrefix(prefix) => {
+                    if let Some(class) = class_names.iter().find(|name| name.starts_with(prefix)) {
+                        evidence.push(FrameworkEvidence { kind: "class".to_string(), matched: class.clone() });
+                    }
+                }
+                ClassSuffix(suffix) => {
+                    if let Some(class) = class_names.iter().find(|name| name.ends_with(suffix)) {
+                        evidence.push(FrameworkEvidence { kind: "class".to_string(), matched: class.clone() });
+                    }
+                }
+                NativeLibrary(pattern) => evidence.extend(native_libraries(&file_names, pattern)),
+                SupportingLibrary(pattern) => supporting.extend(native_libraries(&file_names, pattern)),
+                Asset(pattern) => {
+                    let regex = Regex::new(pattern).expect("框架特征正则无效");
+                    if let Some(path) = file_names.iter().find(|path| regex.is_match(path)) {
+                        evidence.push(FrameworkEvidence { kind: "asset".to_string(), matched: path.clone() });
+                    }
+                }
+            }
+        }
+        if evidence.is_empty() {
+            continue;
+        }
+        evidence.extend(supporting);
+        let details = match signature.id {
+            "flutter" => flutter_details(archive, &file_names, budget),
+            "react_native" => react_native_details(archive, &file_names, budget),
+            "unity" => unity_details(archive, &file_names, budget),
+            "xamarin" => xamarin_details(&file_names, &class_names),
+            "cordova" => cordova_details(archive, budget),
+            "capacitor" => capacitor_details(archive, budget),
+            "qt" => qt_details(&file_names),
+            "uni_app" => uni_app_details(archive, &file_names, budget),
+            _ => BTreeMap::new(),
+        };
+        frameworks.push(DetectedFramework {
+            id: signature.id.to_string(),
+            name: signature.name.to_string(),
+            evidence,
+            details,
+        });
+    }
+    frameworks
+}
+
+/// `lib/<abi>/`下文件名匹配`pattern`的原生库
+fn native_libraries(file_names: &[String], pattern: &str) -> Vec<FrameworkEvidence> {
+    let regex = Regex::new(pattern).expect("框架特征正则无效");
+    file_names.iter()
+        .filter(|path| path.starts_with("lib/"))
+        .filter(|path| regex.is_match(path.rsplit('/').next().unwrap_or(path)))
+        .map(|path| FrameworkEvidence { kind: "native_library".to_string(), matched: path.clone() })
+        .collect()
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, budget: &ReadBudget, name: &str) -> Option<Vec<u8>> {
+    let entry = archive.by_name(name).ok()?;
+    if entry.size() > MAX_DETAIL_FILE_SIZE {
+        return None;
+    }
+    budget.read(entry).ok()
+}
+
+/// 在原生库中找第一个匹配（第一个捕获组）
+fn find_in(data: &[u8], pattern: &str) -> Option<String> {
+    let regex = regex::bytes::Regex::new(pattern).ok()?;
+    regex.captures(data)
+        .and_then(|captures| captures.get(1))
+        .map(|m| String::from_utf8_lossy(m.as_bytes()).into_owned())
+}
+
+/// 优先取arm64-v8a下的库
+fn preferred_library<'a>(file_names: &'a [String], library: &str) -> Option<&'a String> {
+    let suffix = format!("/{}", library);
+    let mut candidates: Vec<&String> = file_names.iter()
+        .filter(|path| path.starts_with("lib/") && path.ends_with(&suffix))
+        .collect();
+    candidates.sort_by_key(|path| !path.contains("arm64-v8a"));
+    candidates.into_iter().next()
+}
+
+/// Flutter：引擎修订哈希、Dart版本、AOT快照哈希与构建模式
+fn flutter_details(archive: &mut ZipArchive<File>, file_names: &[String], budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    if let Some(data) = preferred_library(file_names, "libflutter.so").cloned().and_then(|path| read_entry(archive, budget, &path)) {
+        if let Some(hash) = find_in(&data, r"(?-u)\x00([0-9a-f]{40})\x00") {
+            details.insert("engine_hash".to_string(), hash);
+        }
+        if let Some(version) = find_in(&data, r"(?-u)(\d+\.\d+\.\d+(?:-[\w.]+)? \((?:stable|beta|dev|main)\))") {
+            details.insert("dart_version".to_string(), version);
+        }
+    }
+    if let Some(data) = preferred_library(file_names, "libapp.so").cloned().and_then(|path| read_entry(archive, budget, &path)) {
+        if let Some(hash) = find_in(&data, r"(?-u)([0-9a-f]{32})(?:product|release|debug|profile) ") {
+            details.insert("snapshot_hash".to_string(), hash);
+        }
+        if let Some(mode) = find_in(&data, r"(?-u)[0-9a-f]{32}(product|release|debug|profile) ") {
+            details.insert("build_mode".to_string(), mode);
+        }
+    }
+    details
+}
+
+/// React Native：JS引擎（Hermes字节码或JSC源码包）及Hermes字节码版本
+fn react_native_details(archive: &mut ZipArchive<File>, file_names: &[String], budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    let header = archive.by_name("assets/index.android.bundle").ok()
+        .and_then(|entry| budget.read_head(entry, 12).ok())
+        .unwrap_or_default();
+    if header.len() >= 12 && header[..8] == HERMES_MAGIC {
+        let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        details.insert("js_engine".to_string(), "Hermes".to_string());
+        details.insert("hermes_bytecode_version".to_string(), version.to_string());
+    } else if !header.is_empty() {
+        let engine = if preferred_library(file_names, "libhermes.so").is_some() { "Hermes（JS源码包）" } else { "JSC" };
+        details.insert("js_engine".to_string(), engine.to_string());
+    } else if preferred_library(file_names, "libhermes.so").is_some() {
+        details.insert("js_engine".to_string(), "Hermes".to_string());
+    } else if preferred_library(file_names, "libjsc.so").is_some() {
+        details.insert("js_engine".to_string(), "JSC".to_string());
+    }
+    details
+}
+
+/// Unity：引擎版本与脚本后端
+fn unity_details(archive: &mut ZipArchive<File>, file_names: &[String], budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    let backend = if preferred_library(file_names, "libil2cpp.so").is_some() { "IL2CPP" } else { "Mono" };
+    details.insert("scripting_backend".to_string(), backend.to_string());
+
+    const VERSION_PATTERN: &str = r"(?-u)(\d{1,4}\.\d+\.\d+[abfp]\d+)";
+    for name in ["assets/bin/Data/globalgamemanagers", "assets/bin/Data/data.unity3d", "assets/bin/Data/unity default resources"] {
+        let header = archive.by_name(name).ok()
+            .and_then(|entry| budget.read_head(entry, 4096).ok())
+            .unwrap_or_default();
+        if let Some(version) = find_in(&header, VERSION_PATTERN) {
+            details.insert("unity_version".to_string(), version);
+            return details;
+        }
+    }
+    if let Some(version) = preferred_library(file_names, "libunity.so").cloned()
+        .and_then(|path| read_entry(archive, budget, &path))
+        .and_then(|data| find_in(&data, VERSION_PATTERN))
+    {
+        details.insert("unity_version".to_string(), version);
+    }
+    details
+}
+
+/// Xamarin/MAUI：区分Xamarin.Android与.NET MAUI，以及程序集的打包方式
+fn xamarin_details(file_names: &[String], class_names: &[String]) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    let maui = class_names.iter().any(|name| name.starts_with("com.microsoft.maui."))
+        || file_names.iter().any(|name| name.ends_with("/Microsoft.Maui.dll"));
+    details.insert("flavor".to_string(), if maui { ".NET MAUI" } else { "Xamarin.Android" }.to_string());
+    let assemblies = if file_names.iter().any(|name| name == "assemblies/assemblies.blob") {
+        "assemblies.blob".to_string()
+    } else {
+        format!("{} 个DLL", file_names.iter().filter(|name| name.starts_with("assemblies/") && name.ends_with(".dll")).count())
+    };
+    details.insert("assemblies".to_string(), assemblies);
+    details
+}
+
+/// Cordova：cordova-android版本
+fn cordova_details(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    if let Some(version) = read_entry(archive, budget, "assets/www/cordova.js")
+        .and_then(|data| find_in(&data, r#"PLATFORM_VERSION_BUILD_LABEL\s*=\s*['"]([^'"]+)['"]"#))
+    {
+        details.insert("cordova_android_version".to_string(), version);
+    }
+    details
+}
+
+/// Capacitor：配置中的appId与appName
+fn capacitor_details(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    let config = read_entry(archive, budget, "assets/capacitor.config.json")
+        .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok());
+    if let Some(config) = config {
+        for (key, detail) in [("appId", "app_id"), ("appName", "app_name")] {
+            if let Some(value) = config.get(key).and_then(|v| v.as_str()) {
+                details.insert(detail.to_string(), value.to_string());
+            }
+        }
+    }
+    details
+}
+
+/// Qt：主版本
+fn qt_details(file_names: &[String]) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    if let Some(major) = file_names.iter()
+        .filter_map(|name| name.rsplit('/').next())
+        .find_map(|name| name.strip_prefix("libQt").and_then(|rest| rest.chars().next()).filter(char::is_ascii_digit))
+    {
+        details.insert("qt_major_version".to_string(), major.to_string());
+    }
+    details
+}
+
+/// uni-app：appid、应用名、版本以及是否为uni-app（而非5+ App）
+fn uni_app_details(archive: &mut ZipArchive<File>, file_names: &[String], budget: &ReadBudget) -> BTreeMap<String, String> {
+    let mut details = BTreeMap::new();
+    let app_dir = file_names.iter()
+        .filter_map(|name| name.strip_prefix("assets/apps/"))
+        .filter_map(|rest| rest.split('/').next())
+        .find(|id| id.starts_with("__UNI__"))
+        .map(String::from);
+    let Some(appid) = app_dir else {
+        return details;
+    };
+    details.insert("appid".to_string(), appid.clone());
+
+    let www = format!("assets/apps/{}/www/", appid);
+    let is_uni_app = file_names.iter().any(|name| {
+        name.strip_prefix(&www).is_some_and(|rest| rest == "app-service.js" || rest == "app-config-service.js" || rest == "__uniappview.html")
+    });
+    details.insert("app_type".to_string(), if is_uni_app { "uni-app" } else { "5+ App" }.to_string());
+
+    let manifest = read_entry(archive, budget, &format!("{}manifest.json", www))
+        .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok());
+    if let Some(manifest) = manifest {
+        if let Some(name) = manifest.get("name").and_then(|v| v.as_str()) {
+            details.insert("app_name".to_string(), name.to_string());
+        }
+        if let Some(version) = manifest.pointer("/version/name").and_then(|v| v.as_str()) {
+            details.insert("app_version".to_string(), version.to_string());
+        }
+        if let Some(compiler) = manifest.pointer("/plus/uni-app/compilerVersion").and_then(|v| v.as_str()) {
+            details.insert("compiler_version".to_string(), compiler.to_string());
+        }
+    }
+    details
+}
@@ -0,0 +1,372 @@
+//! APK签名方案v4（`.apk.idsig`）解析与校验
+//!
+//! 增量安装时，v4签名保存在APK旁的`.idsig`文件中：
+//!
+//! ```text
+//! u32 版本（2或3）
+//! 长度前缀的hashing_info { u32 摘要算法, u8 log2块大小, 长度前缀的盐, 长度前缀的Merkle树根哈希 }
+//! 长度前缀的signing_info { 长度前缀的apk_digest, 证书, additional_data, 公钥, u32 签名算法, 签名 }
+//!                        （版本3之后可跟随若干“u32大小, u32 ID, 数据”的附加签名信息块）
+//! 长度前缀的Merkle树
+//! ```
+//!
+//! Merkle树以4 KiB为块，对整个APK文件逐层计算SHA-256（fs-verity格式）。
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::apk_parser::{ApkParser, ApkParserError, CertificateInfo};
+use crate::limits::ReadBudget;
+use crate::pkcs7::hex;
+use crate::signing_block::{
+    describe_public_key, read_length_prefixed, read_u32, signature_algorithm_name, SchemePublicKey, SigningSchemes,
+};
+use crate::verifier::{verify_scheme_signature, SchemeVerification};
+
+/// hashing_info中SHA-256的算法编号
+const HASH_ALGORITHM_SHA256: u32 = 1;
+/// Merkle树块大小的以2为底的对数（4 KiB）
+const LOG2_BLOCK_SIZE: u8 = 12;
+const SUPPORTED_VERSIONS: [u32; 2] = [2, 3];
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidSignature(msg.into())
+}
+
+/// Merkle树的哈希参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashingInfo {
+    pub hash_algorithm: String,
+    pub log2_block_size: u8,
+    /// 盐（十六进制）
+    pub salt: String,
+    /// Merkle树根哈希（十六进制）
+    pub root_hash: String,
+    #[serde(skip)]
+    raw_hash_algorithm: u32,
+    #[serde(skip)]
+    raw_salt: Vec<u8>,
+    #[serde(skip)]
+    raw_root_hash: Vec<u8>,
+}
+
+/// v4签名者信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V4SigningInfo {
+    /// 附加签名信息块的ID（主签名信息为None）
+    pub block_id: Option<String>,
+    /// 对应v2/v3签名中的内容摘要（十六进制）
+    pub apk_digest: String,
+    pub certificate: CertificateInfo,
+    pub public_key: SchemePublicKey,
+    pub signature_algorithm_id: u32,
+    pub signature_algorithm: String,
+    /// 附加数据（十六进制）
+    pub additional_data: String,
+    #[serde(skip)]
+    raw_apk_digest: Vec<u8>,
+    #[serde(skip)]
+    certificate_der: Vec<u8>,
+    #[serde(skip)]
+    raw_additional_data: Vec<u8>,
+    #[serde(skip)]
+    signature: Vec<u8>,
+}
+
+/// 解析后的`.idsig`文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V4Signature {
+    pub version: u32,
+    pub hashing_info: HashingInfo,
+    pub signing_info: V4SigningInfo,
+    /// 版本3中的附加签名信息（如v3.1轮换后的签名者）
+    pub additional_signing_infos: Vec<V4SigningInfo>,
+    /// 文件中附带的Merkle树大小
+    pub merkle_tree_size: usize,
+}
+
+/// v4签名分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdsigReport {
+    pub apk_path: String,
+    pub idsig_path: String,
+    pub signature: V4Signature,
+    /// 根据APK内容计算出的Merkle树根哈希
+    pub computed_root_hash: Option<String>,
+    pub verification: SchemeVerification,
+}
+
+impl V4Signature {
+    /// 解析`.idsig`文件内容
+    pub fn parse(data: &[u8]) -> Result<Self, ApkParserError> {
+        let mut pos = 0;
+        let version = read_u32(data, &mut pos)?;
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            return Err(invalid(format!("不支持的v4签名版本: {}", version)));
+        }
+        let hashing_info = Self::parse_hashing_info(read_length_prefixed(data, &mut pos)?)?;
+
+        let signing_infos = read_length_prefixed(data, &mut pos)?;
+        let mut infos_pos = 0;
+        let signing_info = Self::parse_signing_info(signing_infos, &mut infos_pos, None)?;
+        let mut additional_signing_infos = Vec::new();
+        while infos_pos < signing_infos.len() {
+            let size = read_u32(signing_infos, &mut infos_pos)? as usize;
+            let id = read_u32(signing_infos, &mut infos_pos)?;
+            let block = infos_pos
+                .checked_add(size)
+                .and_then(|end| signing_infos.get(infos_pos..end))
+                .ok_or_else(|| invalid("附加签名信息块超出范围"))?;
+            infos_pos += size;
+            let mut block_pos = 0;
+            additional_signing_infos.push(Self::parse_signing_info(block, &mut block_pos, Some(id))?);
+        }
+
+        let merkle_tree_size = if pos < data.len() {
+            read_length_prefixed(data, &mut pos)?.len()
+        } else {
+            0
+        };
+
+        Ok(V4Signature {
+            version,
+            hashing_info,
+            signing_info,
+            additional_signing_infos,
+            merkle_tree_size,
+        })
+    }
+
+    fn parse_hashing_info(data: &[u8]) -> Result<HashingInfo, ApkParserError> {
+        let mut pos = 0;
+        let raw_hash_algorithm = read_u32(data, &mut pos)?;
+        let log2_block_size = *data.get(pos).ok_or_else(|| invalid("hashing_info被截断"))?;
+        pos += 1;
+        let raw_salt = read_length_prefixed(data, &mut pos)?.to_vec();
+        let raw_root_hash = read_length_prefixed(data, &mut pos)?.to_vec();
+
+        Ok(HashingInfo {
+            hash_algorithm: match raw_hash_algorithm {
+                HASH_ALGORITHM_SHA256 => "SHA-256".to_string(),
+                other => format!("未知算法 {}", other),
+            },
+            log2_block_size,
+            salt: hex(&raw_salt),
+            root_hash: hex(&raw_root_hash),
+            raw_hash_algorithm,
+            raw_salt,
+            raw_root_hash,
+        })
+    }
+
+    fn parse_signing_info(data: &[u8], pos: &mut usize, block_id: Option<u32>) -> Result<V4SigningInfo, ApkParserError> {
+        let raw_apk_digest = read_length_prefixed(data, pos)?.to_vec();
+        let certificate_der = read_length_prefixed(data, pos)?.to_vec();
+        let raw_additional_data = read_length_prefixed(data, pos)?.to_vec();
+        let public_key = describe_public_key(read_length_prefixed(data, pos)?);
+        let signature_algorithm_id = read_u32(data, pos)?;
+        let signature = read_length_prefixed(data, pos)?.to_vec();
+
+        Ok(V4SigningInfo {
+            block_id: block_id.map(|id| format!("0x{:08x}", id)),
+            apk_digest: hex(&raw_apk_digest),
+            certificate: ApkParser::certificate_info(&certificate_der)?,
+            public_key,
+            signature_algorithm_id,
+            signature_algorithm: signature_algorithm_name(signature_algorithm_id),
+            additional_data: hex(&raw_additional_data),
+            raw_apk_digest,
+            certificate_der,
+            raw_additional_data,
+            signature,
+        })
+    }
+
+    /// v4签名所覆盖的数据：文件大小、hashing_info与signing_info中的各字段
+    fn signed_data(&self, info: &V4SigningInfo, file_size: u64) -> Vec<u8> {
+        let hashing = &self.hashing_info;
+        let fields: [&[u8]; 5] = [
+            &hashing.raw_salt,
+            &hashing.raw_root_hash,
+            &info.raw_apk_digest,
+            &info.certificate_der,
+            &info.raw_additional_data,
+        ];
+        let size = 4 + 8 + 4 + 1 + fields.iter().map(|field| 4 + field.len()).sum::<usize>();
+
+        let mut data = Vec::with_capacity(size);
+        data.extend_from_slice(&(size as u32).to_le_bytes());
+        data.extend_from_slice(&file_size.to_le_bytes());
+        data.extend_from_slice(&hashing.raw_hash_algorithm.to_le_bytes());
+        data.push(hashing.log2_block_size);
+        for field in fields {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data
+    }
+}
+
+/// 计算fs-verity格式Merkle树的根哈希
+pub fn merkle_root_hash<R: Read>(reader: &mut R, log2_block_size: u8, salt: &[u8]) -> std::io::Result<Vec<u8>> {
+    let block_size = 1usize << log2_block_size;
+    let hash_block = |block: &[u8]| -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(block);
+        hasher.finalize().to_vec()
+    };
+
+    // 第一层：对文件的每个块计算哈希，末尾不足一块时补零
+    let mut level = Vec::new();
+    let mut buffer = vec![0u8; block_size];
+    loop {
+        let mut filled = 0;
+        while filled < block_size {
+            let read = reader.read(&mut buffer[filled..])?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        if filled == 0 {
+            break;
+        }
+        buffer[filled..].fill(0);
+        level.extend(hash_block(&buffer));
+        if filled < block_size {
+            break;
+        }
+    }
+
+    // 逐层向上，直到某一层只占一个块
+    loop {
+        let padded = ((level.len() + block_size - 1) / block_size).max(1) * block_size;
+        level.resize(padded, 0);
+        if level.len() == block_size {
+            return Ok(hash_block(&level));
+        }
+        level = level.chunks(block_size).flat_map(hash_block).collect();
+    }
+}
+
+/// `.idsig`文件的默认位置：APK路径后追加`.idsig`
+pub fn default_idsig_path(apk_path: &Path) -> PathBuf {
+    let mut path = apk_path.as_os_str().to_owned();
+    path.push(".idsig");
+    PathBuf::from(path)
+}
+
+/// 解析`.idsig`并针对APK校验：根哈希、签名、以及与v2/v3签名者的一致性
+pub fn analyze_idsig(apk_path: &Path, idsig_path: Option<&Path>) -> Result<IdsigReport, ApkParserError> {
+    let idsig_path = idsig_path.map(Path::to_path_buf).unwrap_or_else(|| default_idsig_path(apk_path));
+    if !idsig_path.exists() {
+        return Err(ApkParserError::InvalidApk(format!("找不到v4签名文件: {}", idsig_path.display())));
+    }
+    let signature = V4Signature::parse(&ReadBudget::default().read_file(&idsig_path)?)?;
+    let file_size = std::fs::metadata(apk_path)?.len();
+    let mut verification = SchemeVerification::new("v4");
+
+    // Merkle树根哈希
+    let hashing = &signature.hashing_info;
+    let computed_root_hash = if hashing.raw_hash_algorithm != HASH_ALGORITHM_SHA256 {
+        verification.errors.push(format!("不支持的哈希算法: {}", hashing.hash_algorithm));
+        None
+    } else if hashing.log2_block_size != LOG2_BLOCK_SIZE {
+        verification.errors.push(format!("不支持的块大小: 2^{}", hashing.log2_block_size));
+        None
+    } else {
+        let root = merkle_root_hash(&mut File::open(apk_path)?, hashing.log2_block_size, &hashing.raw_salt)?;
+        if root != hashing.raw_root_hash {
+            verification.errors.push("Merkle树根哈希与APK内容不匹配".to_string());
+        }
+        Some(hex(&root))
+    };
+
+    // v2/v3签名者：v4签名者的证书须与之一致，apk_digest须为其内容摘要之一
+    let schemes = SigningSchemes::from_path(apk_path, false)?;
+    let scheme_signers: Vec<_> = [&schemes.v3_1_signers, &schemes.v3_signers, &schemes.v2_signers]
+        .into_iter()
+        .flatten()
+        .collect();
+    if scheme_signers.is_empty() {
+        verification.errors.push("APK没有v2/v3签名，v4签名无法独立生效".to_string());
+    }
+
+    for info in std::iter::once(&signature.signing_info).chain(signature.additional_signing_infos.iter()) {
+        let label = info.block_id.as_deref().map(|id| format!("签名信息块{}", id)).unwrap_or_else(|| "签名信息".to_string());
+
+        let certificate_spki = x509_parser::parse_x509_certificate(&info.certificate_der)
+            .map(|(_, cert)| cert.tbs_certificate.subject_pki.raw.to_vec())
+            .unwrap_or_default();
+        if certificate_spki != info.public_key.der {
+            verification.errors.push(format!("{}: 公钥与证书不一致", label));
+        }
+        if let Err(e) = verify_scheme_signature(
+            &info.public_key.der,
+            info.signature_algorithm_id,
+            &signature.signed_data(info, file_size),
+            &info.signature,
+        ) {
+            verification.errors.push(format!("{}: 签名校验失败: {}", label, e));
+        }
+
+        if scheme_signers.is_empty() {
+            continue;
+        }
+        let matching_signer = scheme_signers
+            .iter()
+            .find(|signer| signer.certificates_der.first() == Some(&info.certificate_der));
+        match matching_signer {
+            Some(signer) => {
+                if !signer.digests.iter().any(|digest| digest.digest == info.apk_digest) {
+                    verification.errors.push(format!("{}: apk_digest与v2/v3签名中的内容摘要不一致", label));
+                }
+            }
+            None => verification.errors.push(format!("{}: v4签名者证书与v2/v3签名者不一致", label)),
+        }
+    }
+
+    Ok(IdsigReport {
+        apk_path: apk_path.display().to_string(),
+        idsig_path: idsig_path.display().to_string(),
+        signature,
+        computed_root_hash,
+        verification: verification.finish(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 期望值按AOSP apksig中VerityTreeBuilder的构造方式计算：
+    // 每块先拼接盐再做SHA-256，每层补零到整块，直到只剩一块
+    fn root_hex(data: &[u8], salt: &[u8]) -> String {
+        hex(&merkle_root_hash(&mut &data[..], LOG2_BLOCK_SIZE, salt).unwrap())
+    }
+
+    #[test]
+    fn single_block_root_hash() {
+        let expected = "ec8e469cd349676fea41eeeb5b70e45a30f9a058d862edc5823b95ddf135c801";
+        assert_eq!(root_hex(&[0; 4096], &[]), expected);
+        // 不足一块的数据补零后与整块相同
+        assert_eq!(root_hex(&[0; 100], &[]), expected);
+    }
+
+    #[test]
+    fn two_level_root_hash() {
+        // 129个块的哈希超过一块，需要再向上一层
+        let data: Vec<u8> = (0..129 * 4096).map(|i| (i % 251) as u8).collect();
+        assert_eq!(root_hex(&data, &[]), "6febabcdf0b08f8ebb0583e06d1747ec3cd63a99efb0437efef8fe54223c7760");
+    }
+
+    #[test]
+    fn salted_root_hash() {
+        assert_eq!(root_hex(&[0; 4096], b"salt"), "1f4f245dd8be191f0fb64eab5aa297d8fbcfb261ead8706e0bcec08d3fcffa49");
+    }
+}
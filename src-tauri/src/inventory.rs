@@ -0,0 +1,261 @@
+//! APK内容清单
+//!
+//! 遍历全部条目，按文件头魔数识别内容类型（DEX、ELF、各类图片、OGG、JSON、HTML、JS、
+//! 二进制XML、资源表、ZIP/JAR、SQLite、密钥库等），按类型与扩展名汇总数量和体积，
+//! 并解码条目的DOS时间戳，得到最早与最晚的内容修改时间。
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::limits::ReadBudget;
+
+/// 识别类型时读取的文件头长度
+const HEADER_LEN: u64 = 512;
+
+/// 按魔数识别的内容类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    Dex,
+    Elf,
+    JavaClass,
+    Png,
+    Webp,
+    Jpeg,
+    Gif,
+    Ogg,
+    Json,
+    Html,
+    Javascript,
+    /// Android二进制XML（AXML）
+    BinaryXml,
+    /// 文本XML
+    Xml,
+    /// 编译后的资源表（resources.arsc）
+    ResourceTable,
+    /// ZIP、JAR、AAR等ZIP格式文件
+    Zip,
+    Sqlite,
+    /// JKS、JCEKS、BKS与PKCS#12密钥库
+    Keystore,
+    Unknown,
+}
+
+impl ContentType {
+    /// 根据文件头与扩展名识别内容类型
+    pub fn detect(header: &[u8], extension: &str) -> Self {
+        if header.starts_with(b"dex\n") {
+            return ContentType::Dex;
+        }
+        if header.starts_with(b"\x7fELF") {
+            return ContentType::Elf;
+        }
+        if header.starts_with(&[0xCA, 0xFE, 0xBA, 0xBE]) && extension == "class" {
+            return ContentType::JavaClass;
+        }
+        if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return ContentType::Png;
+        }
+        if header.len() >= 12 && header.starts_with(b"RIFF") && &header[8..12] == b"WEBP" {
+            return ContentType::Webp;
+        }
+        if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return ContentType::Jpeg;
+        }
+        if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+            return ContentType::Gif;
+        }
+        if header.starts_with(b"OggS") {
+            return ContentType::Ogg;
+        }
+        if header.starts_with(&[0x03, 0x00, 0x08, 0x00]) {
+            return ContentType::BinaryXml;
+        }
+        if header.starts_with(&[0x02, 0x00, 0x0C, 0x00]) {
+            return ContentType::ResourceTable;
+        }
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            return ContentType::Zip;
+        }
+        if header.starts_with(b"SQLite format 3\0") {
+            return ContentType::Sqlite;
+        }
+        if header.starts_with(&[0xFE, 0xED, 0xFE, 0xED]) || header.starts_with(&[0xCE, 0xCE, 0xCE, 0xCE]) {
+            return ContentType::Keystore;
+        }
+        // BKS与PKCS#12没有独立的魔数，结合扩展名判断
+        if matches!(extension, "bks" | "p12" | "pfx" | "keystore" | "jks")
+            && (header.starts_with(&[0x00, 0x00, 0x00, 0x01]) || header.starts_with(&[0x00, 0x00, 0x00, 0x02]) || header.first() == Some(&0x30))
+        {
+            return ContentType::Keystore;
+        }
+        Self::detect_text(header, extension)
+    }
+
+    /// 识别文本格式：去掉BOM与前导空白后按开头字符判断
+    fn detect_text(header: &[u8], extension: &str) -> Self {
+        let text = header.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(header);
+        let start = text.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(text.len());
+        let text = &text[start..];
+        let lower: Vec<u8> = text.iter().take(64).map(u8::to_ascii_lowercase).collect();
+
+        if lower.starts_with(b"<!doctype html") || lower.starts_with(b"<html") {
+            return ContentType::Html;
+        }
+        if lower.starts_with(b"<?xml") {
+            return ContentType::Xml;
+        }
+        if extension == "json" && (text.starts_with(b"{") || text.starts_with(b"[")) {
+            return ContentType::Json;
+        }
+        if matches!(extension, "js" | "mjs" | "bundle") && !text.is_empty() && std::str::from_utf8(text).is_ok() {
+            return ContentType::Javascript;
+        }
+        match extension {
+            "html" | "htm" if text.starts_with(b"<") => ContentType::Html,
+            _ => ContentType::Unknown,
+        }
+    }
+}
+
+/// 单个条目的内容信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentEntry {
+    pub name: String,
+    pub content_type: ContentType,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    /// 是否以不压缩方式存储
+    pub stored: bool,
+    /// DOS时间戳，格式为`YYYY-MM-DD HH:MM:SS`
+    pub modified: String,
+}
+
+/// 按类型或扩展名汇总的数量与体积
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentGroup {
+    /// 类型名或扩展名，无扩展名时为空字符串
+    pub key: String,
+    pub count: usize,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+/// 内容清单报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentInventory {
+    /// 文件条目数（不含目录）
+    pub file_count: usize,
+    pub total_compressed_size: u64,
+    pub total_uncompressed_size: u64,
+    /// 最早的内容修改时间
+    pub earliest_modification: Option<String>,
+    /// 最晚的内容修改时间
+    pub latest_modification: Option<String>,
+    /// 按内容类型汇总，按数量从多到少排列
+    pub by_type: Vec<ContentGroup>,
+    /// 按扩展名汇总，按数量从多到少排列
+    pub by_extension: Vec<ContentGroup>,
+    pub entries: Vec<ContentEntry>,
+}
+
+impl ContentInventory {
+    /// 遍历APK中的全部条目
+    pub fn from_archive(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Self {
+        let mut inventory = ContentInventory::default();
+        let mut by_type: HashMap<ContentType, ContentGroup> = HashMap::new();
+        let mut by_extension: HashMap<String, ContentGroup> = HashMap::new();
+        let mut earliest: Option<(u16, u8, u8, u8, u8, u8)> = None;
+        let mut latest: Option<(u16, u8, u8, u8, u8, u8)> = None;
+
+        for i in 0..archive.len() {
+            let Ok(entry) = archive.by_index(i) else {
+                continue;
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            let extension = extension(&name);
+            let compressed_size = entry.compressed_size();
+            let uncompressed_size = entry.size();
+            let stored = entry.compression() == CompressionMethod::Stored;
+            let time = entry.last_modified();
+            let timestamp = (time.year(), time.month(), time.day(), time.hour(), time.minute(), time.second());
+
+            let content_type = match budget.read_head(entry, HEADER_LEN) {
+                Ok(header) => ContentType::detect(&header, &extension),
+                Err(_) => ContentType::Unknown,
+            };
+
+            by_type.entry(content_type)
+                .or_insert_with(|| ContentGroup::new(type_key(content_type)))
+                .add(compressed_size, uncompressed_size);
+            by_extension.entry(extension.clone())
+                .or_insert_with(|| ContentGroup::new(extension))
+                .add(compressed_size, uncompressed_size);
+
+            earliest = Some(earliest.map_or(timestamp, |earliest| earliest.min(timestamp)));
+            latest = Some(latest.map_or(timestamp, |latest| latest.max(timestamp)));
+            inventory.file_count += 1;
+            inventory.total_compressed_size += compressed_size;
+            inventory.total_uncompressed_size += uncompressed_size;
+            inventory.entries.push(ContentEntry {
+                name,
+                content_type,
+                compressed_size,
+                uncompressed_size,
+                stored,
+                modified: format_timestamp(timestamp),
+            });
+        }
+
+        inventory.earliest_modification = earliest.map(format_timestamp);
+        inventory.latest_modification = latest.map(format_timestamp);
+        inventory.by_type = sorted_groups(by_type.into_values());
+        inventory.by_extension = sorted_groups(by_extension.into_values());
+        inventory
+    }
+}
+
+impl ContentGroup {
+    fn new(key: String) -> Self {
+        ContentGroup { key, count: 0, compressed_size: 0, uncompressed_size: 0 }
+    }
+
+    fn add(&mut self, compressed_size: u64, uncompressed_size: u64) {
+        self.count += 1;
+        self.compressed_size += compressed_size;
+        self.uncompressed_size += uncompressed_size;
+    }
+}
+
+/// 类型的序列化名称，与`ContentType`的serde表示一致
+fn type_key(content_type: ContentType) -> String {
+    serde_json::to_value(content_type)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// 条目文件名的小写扩展名，没有扩展名时返回空字符串
+fn extension(name: &str) -> String {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
+
+fn format_timestamp((year, month, day, hour, minute, second): (u16, u8, u8, u8, u8, u8)) -> String {
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second)
+}
+
+fn sorted_groups(groups: impl Iterator<Item = ContentGroup>) -> Vec<ContentGroup> {
+    let mut groups: Vec<ContentGroup> = groups.collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
@@ -2,7 +2,31 @@
 use tauri_plugin_dialog; 
 
 
+pub mod abi;
+pub mod anti_analysis;
 pub mod apk_parser;
+pub mod arsc;
+pub mod axml;
+pub mod cert_audit;
+pub mod compare;
+pub mod dex;
+pub mod elf;
+pub mod framework;
+pub mod idsig;
+pub mod inventory;
+pub mod lineage;
+pub mod limits;
+pub mod manifest;
+pub mod obfuscation;
+pub mod packer;
+pub mod page_size;
+pub mod pinning;
+pub mod pkcs7;
+pub mod sdk_detect;
+pub mod signing_block;
+pub mod size_breakdown;
+pub mod verifier;
+pub mod zip_audit;
 pub mod commands;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -11,6 +35,11 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             commands::parse_apk,
+            commands::parse_manifest,
+            commands::parse_idsig,
+            commands::inspect_zip,
+            commands::analyze_apk_size,
+            commands::compare_apks,
             // commands::parse_apk_data,
             commands::select_apk_file,
         ])
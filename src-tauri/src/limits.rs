@@ -0,0 +1,292 @@
+//! 解析资源限制
+//!
+//! 构造的APK可以用压缩炸弹、超大条目、海量条目、深层嵌套的XML或巨大的字符串池
+//! 耗尽桌面应用的内存或让解析长时间挂起。`ParseLimits`定义各项上限，
+//! `ReadBudget`按解析阶段累计读取量与耗时，超出时返回类型化的
+//! `ApkParserError::LimitExceeded`，而不是让Tauri进程崩溃或挂起。
+//! 每个阶段有独立的额度，多个阶段重复读取同一批条目不会叠加；
+//! 次要阶段超限只记为警告，由调用方决定哪些阶段超限时终止解析。
+
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+use crate::apk_parser::ApkParserError;
+
+/// 压缩比只对解压后超过该大小的条目检查，小文件的高压缩比并无危害
+const RATIO_CHECK_MIN_SIZE: u64 = 1024 * 1024;
+
+/// 超出的限制类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    EntrySize,
+    TotalBytes,
+    CompressionRatio,
+    EntryCount,
+    XmlDepth,
+    StringPoolSize,
+    PhaseTime,
+}
+
+impl LimitKind {
+    fn description(self) -> &'static str {
+        match self {
+            LimitKind::EntrySize => "单个条目解压大小",
+            LimitKind::TotalBytes => "累计读取字节数",
+            LimitKind::CompressionRatio => "压缩比",
+            LimitKind::EntryCount => "条目数量",
+            LimitKind::XmlDepth => "XML嵌套深度",
+            LimitKind::StringPoolSize => "字符串池大小",
+            LimitKind::PhaseTime => "阶段耗时（毫秒）",
+        }
+    }
+}
+
+/// 超出限制的详情
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitExceeded {
+    pub kind: LimitKind,
+    /// 触发限制的条目或解析阶段
+    pub context: String,
+    pub actual: u64,
+    pub limit: u64,
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}超出限制（{}）: {} > {}", self.kind.description(), self.context, self.actual, self.limit)
+    }
+}
+
+/// 解析限制配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseLimits {
+    /// 单个条目解压后的最大字节数
+    pub max_entry_size: u64,
+    /// 每个解析阶段累计读取的最大字节数
+    pub max_total_bytes: u64,
+    /// 单个条目的最大压缩比（解压大小 / 压缩大小）
+    pub max_compression_ratio: u64,
+    /// ZIP中的最大条目数
+    pub max_entry_count: usize,
+    /// XML的最大嵌套深度
+    pub max_xml_depth: usize,
+    /// 单个字符串池的最大字符串数
+    pub max_string_pool_size: usize,
+    /// 每个解析阶段的时间预算
+    ///
+    /// 在读取条目与阶段结束时检查，单个条目的解码过程中不会中断；
+    /// 单个条目的解码量已由条目大小与读取量限制约束。
+    pub phase_time_budget: Duration,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_entry_size: 512 * 1024 * 1024,
+            max_total_bytes: 4 * 1024 * 1024 * 1024,
+            max_compression_ratio: 500,
+            max_entry_count: 100_000,
+            max_xml_depth: 256,
+            max_string_pool_size: 1_000_000,
+            phase_time_budget: Duration::from_secs(120),
+        }
+    }
+}
+
+impl ParseLimits {
+    /// 检查XML嵌套深度
+    pub fn check_xml_depth(&self, depth: usize) -> Result<(), ApkParserError> {
+        check(LimitKind::XmlDepth, "XML", depth as u64, self.max_xml_depth as u64)
+    }
+
+    /// 检查字符串池中的字符串数
+    pub fn check_string_pool(&self, count: usize) -> Result<(), ApkParserError> {
+        check(LimitKind::StringPoolSize, "字符串池", count as u64, self.max_string_pool_size as u64)
+    }
+}
+
+fn check(kind: LimitKind, context: &str, actual: u64, limit: u64) -> Result<(), ApkParserError> {
+    if actual > limit {
+        return Err(ApkParserError::LimitExceeded(LimitExceeded { kind, context: context.to_string(), actual, limit }));
+    }
+    Ok(())
+}
+
+/// 一次解析的读取预算
+///
+/// 记录当前阶段累计读取的字节数与开始时间。阶段内首个超出的限制会被保留，
+/// 吞掉错误的子分析之后仍可通过`check`取回；开始下一阶段时转为警告。
+#[derive(Debug)]
+pub struct ReadBudget {
+    limits: ParseLimits,
+    total_read: Cell<u64>,
+    phase: RefCell<(String, Instant)>,
+    exceeded: RefCell<Option<LimitExceeded>>,
+    warnings: RefCell<Vec<String>>,
+}
+
+impl Default for ReadBudget {
+    fn default() -> Self {
+        Self::new(ParseLimits::default())
+    }
+}
+
+impl ReadBudget {
+    pub fn new(limits: ParseLimits) -> Self {
+        Self {
+            limits,
+            total_read: Cell::new(0),
+            phase: RefCell::new(("解析".to_string(), Instant::now())),
+            exceeded: RefCell::new(None),
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    /// 当前阶段已累计读取的字节数
+    pub fn total_read(&self) -> u64 {
+        self.total_read.get()
+    }
+
+    /// 开始新的解析阶段，重新计时并重置读取量
+    ///
+    /// 上一阶段超出的限制记为警告，该阶段的结果可能不完整。
+    pub fn start_phase(&self, name: &str) {
+        self.record_warning();
+        *self.phase.borrow_mut() = (name.to_string(), Instant::now());
+        self.total_read.set(0);
+    }
+
+    /// 超出限制、结果可能不完整的阶段，包括当前阶段
+    pub fn warnings(&self) -> Vec<String> {
+        self.record_warning();
+        self.warnings.borrow().clone()
+    }
+
+    fn record_warning(&self) {
+        // 阶段超时也由check记入exceeded
+        let _ = self.check();
+        let exceeded = self.exceeded.borrow_mut().take();
+        if let Some(exceeded) = exceeded {
+            let warning = format!("{}: {}", self.phase.borrow().0, exceeded);
+            println!("WARN: 阶段超出解析限制，结果可能不完整: {}", warning);
+            self.warnings.borrow_mut().push(warning);
+        }
+    }
+
+    /// 返回此前记录的超限错误，或当前阶段是否已超时
+    pub fn check(&self) -> Result<(), ApkParserError> {
+        if let Some(exceeded) = self.exceeded.borrow().clone() {
+            return Err(ApkParserError::LimitExceeded(exceeded));
+        }
+        let (name, started) = self.phase.borrow().clone();
+        let elapsed = started.elapsed();
+        if elapsed > self.limits.phase_time_budget {
+            return Err(self.exceed(LimitKind::PhaseTime, &name, elapsed.as_millis() as u64, self.limits.phase_time_budget.as_millis() as u64));
+        }
+        Ok(())
+    }
+
+    /// 检查ZIP条目数量
+    pub fn check_entry_count(&self, count: usize) -> Result<(), ApkParserError> {
+        if count > self.limits.max_entry_count {
+            return Err(self.exceed(LimitKind::EntryCount, "ZIP", count as u64, self.limits.max_entry_count as u64));
+        }
+        Ok(())
+    }
+
+    /// 记录超出的限制并返回对应错误
+    pub fn exceed(&self, kind: LimitKind, context: &str, actual: u64, limit: u64) -> ApkParserError {
+        let exceeded = LimitExceeded { kind, context: context.to_string(), actual, limit };
+        self.exceeded.borrow_mut().get_or_insert_with(|| exceeded.clone());
+        ApkParserError::LimitExceeded(exceeded)
+    }
+
+    /// 把解码得到的数据计入累计读取量，用于条目内容被展开或重复解码的情况
+    pub fn charge(&self, context: &str, bytes: u64) -> Result<(), ApkParserError> {
+        let total = self.total_read.get().saturating_add(bytes);
+        if total > self.limits.max_total_bytes {
+            return Err(self.exceed(LimitKind::TotalBytes, context, total, self.limits.max_total_bytes));
+        }
+        self.total_read.set(total);
+        Ok(())
+    }
+
+    /// 读取整个文件，文件大小计入当前阶段的读取量
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, ApkParserError> {
+        self.check()?;
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        self.charge(&path.display().to_string(), size)?;
+        let mut data = Vec::new();
+        file.take(size).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// 按名称读取条目的全部内容，条目不存在时返回ZIP错误
+    pub fn read_entry(&self, archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, ApkParserError> {
+        let entry = archive.by_name(name)?;
+        self.read(entry)
+    }
+
+    /// 读取已打开条目的全部内容
+    ///
+    /// 读取前依据中央目录中的大小检查条目大小与压缩比，读取时再以实际字节数复核，
+    /// 防止中央目录记录的大小被伪造。
+    pub fn read(&self, entry: ZipFile) -> Result<Vec<u8>, ApkParserError> {
+        self.check()?;
+        let name = entry.name().to_string();
+        let declared = entry.size();
+        let compressed = entry.compressed_size();
+        self.check_entry(&name, declared, compressed)?;
+
+        let remaining = self.limits.max_total_bytes.saturating_sub(self.total_read.get());
+        let cap = self.limits.max_entry_size.min(remaining);
+        let mut data = Vec::new();
+        entry.take(cap.saturating_add(1)).read_to_end(&mut data)?;
+        let actual = data.len() as u64;
+        self.check_entry(&name, actual, compressed)?;
+
+        let total = self.total_read.get() + actual;
+        if total > self.limits.max_total_bytes {
+            return Err(self.exceed(LimitKind::TotalBytes, &name, total, self.limits.max_total_bytes));
+        }
+        self.total_read.set(total);
+        Ok(data)
+    }
+
+    /// 读取条目开头至多`len`字节，用于采样或识别文件头，实际读取量计入当前阶段
+    pub fn read_head(&self, entry: ZipFile, len: u64) -> Result<Vec<u8>, ApkParserError> {
+        self.check()?;
+        let name = entry.name().to_string();
+        let remaining = self.limits.max_total_bytes.saturating_sub(self.total_read.get());
+        let mut data = Vec::new();
+        entry.take(len.min(remaining.saturating_add(1))).read_to_end(&mut data)?;
+        self.charge(&name, data.len() as u64)?;
+        Ok(data)
+    }
+
+    fn check_entry(&self, name: &str, size: u64, compressed: u64) -> Result<(), ApkParserError> {
+        if size > self.limits.max_entry_size {
+            return Err(self.exceed(LimitKind::EntrySize, name, size, self.limits.max_entry_size));
+        }
+        if size > RATIO_CHECK_MIN_SIZE {
+            let ratio = size / compressed.max(1);
+            if ratio > self.limits.max_compression_ratio {
+                return Err(self.exceed(LimitKind::CompressionRatio, name, ratio, self.limits.max_compression_ratio));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! v3签名的密钥轮换证明（proof-of-rotation）解析
+//!
+//! 轮换证明记录了从最初的签名证书到当前签名证书的有序谱系。
+//! 每个节点由上一个节点的证书签名，并携带授予旧证书的能力标志：
+//!
+//! ```text
+//! u32 版本
+//! 重复 { 长度前缀的节点:
+//!     长度前缀的签名数据 { 长度前缀的证书DER, u32 上一节点签名所用算法 }
+//!     u32 能力标志
+//!     u32 本节点证书签名下一节点所用算法
+//!     长度前缀的签名（由上一节点证书生成，第一个节点为空）
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::{ApkParser, ApkParserError, CertificateInfo};
+use crate::signing_block::{read_length_prefixed, read_u32, signature_algorithm_name};
+use crate::verifier::verify_scheme_signature;
+
+/// 当前支持的谱系格式版本
+const LINEAGE_VERSION: u32 = 1;
+
+// 能力标志
+const PAST_CERT_INSTALLED_DATA: u32 = 1;
+const PAST_CERT_SHARED_USER_ID: u32 = 2;
+const PAST_CERT_PERMISSION: u32 = 4;
+const PAST_CERT_ROLLBACK: u32 = 8;
+const PAST_CERT_AUTH: u32 = 16;
+
+/// 谱系节点授予旧证书的能力
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LineageCapabilities {
+    /// 已安装的应用数据可继续被该证书签名的更新访问
+    pub installed_data: bool,
+    /// 可与该证书签名的应用共享UID
+    pub shared_uid: bool,
+    /// 可获得该证书签名的应用声明的签名级权限
+    pub permission: bool,
+    /// 允许回退到该证书签名的版本
+    pub rollback: bool,
+    /// 可用于该证书相关的身份认证（如账户）
+    pub auth: bool,
+}
+
+impl LineageCapabilities {
+    fn from_flags(flags: u32) -> Self {
+        Self {
+            installed_data: flags & PAST_CERT_INSTALLED_DATA != 0,
+            shared_uid: flags & PAST_CERT_SHARED_USER_ID != 0,
+            permission: flags & PAST_CERT_PERMISSION != 0,
+            rollback: flags & PAST_CERT_ROLLBACK != 0,
+            auth: flags & PAST_CERT_AUTH != 0,
+        }
+    }
+}
+
+/// 谱系中的一个证书节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageNode {
+    pub certificate: CertificateInfo,
+    /// 原始能力标志
+    pub flags: u32,
+    pub capabilities: LineageCapabilities,
+    /// 上一节点签名本节点所用的算法（第一个节点为None）
+    pub signed_with: Option<String>,
+    /// 本节点证书签名下一节点所用的算法
+    pub signs_next_with: Option<String>,
+    /// 证书的DER编码
+    #[serde(skip)]
+    pub certificate_der: Vec<u8>,
+}
+
+/// 签名证书谱系，从最旧到最新排列
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningCertificateLineage {
+    pub version: u32,
+    pub nodes: Vec<LineageNode>,
+    /// 节点间的签名是否全部校验通过
+    pub verified: bool,
+    /// 校验谱系时发现的问题
+    pub errors: Vec<String>,
+}
+
+impl SigningCertificateLineage {
+    /// 解析并校验轮换证明属性的值
+    pub fn parse(data: &[u8]) -> Result<Self, ApkParserError> {
+        let mut pos = 0;
+        let version = read_u32(data, &mut pos)?;
+        if version != LINEAGE_VERSION {
+            return Err(invalid(format!("不支持的轮换证明版本: {}", version)));
+        }
+
+        let mut lineage = SigningCertificateLineage {
+            version,
+            ..Default::default()
+        };
+        // (上一节点的证书DER, 上一节点签名下一节点所用的算法)
+        let mut previous: Option<(Vec<u8>, u32)> = None;
+
+        while pos < data.len() {
+            let index = lineage.nodes.len() + 1;
+            let node = read_length_prefixed(data, &mut pos)?;
+            let mut node_pos = 0;
+            let signed_data = read_length_prefixed(node, &mut node_pos)?;
+            let flags = read_u32(node, &mut node_pos)?;
+            let signs_next_with = read_u32(node, &mut node_pos)?;
+            let signature = read_length_prefixed(node, &mut node_pos)?;
+
+            let mut signed_pos = 0;
+            let certificate_der = read_length_prefixed(signed_data, &mut signed_pos)?.to_vec();
+            let signed_with = read_u32(signed_data, &mut signed_pos)?;
+
+            match &previous {
+                Some((previous_der, previous_algorithm)) => {
+                    if *previous_algorithm != signed_with {
+                        lineage.errors.push(format!("节点#{}: 签名算法与上一节点声明的不一致", index));
+                    }
+                    let verification = x509_parser::parse_x509_certificate(previous_der)
+                        .map_err(|e| format!("无法解析上一节点证书: {}", e))
+                        .and_then(|(_, cert)| {
+                            verify_scheme_signature(
+                                cert.tbs_certificate.subject_pki.raw,
+                                *previous_algorithm,
+                                signed_data,
+                                signature,
+                            )
+                        });
+                    if let Err(e) = verification {
+                        lineage.errors.push(format!("节点#{}: 上一节点的签名校验失败: {}", index, e));
+                    }
+                }
+                None if !signature.is_empty() || signed_with != 0 => {
+                    lineage.errors.push("节点#1: 第一个节点不应带有签名".to_string());
+                }
+                None => {}
+            }
+
+            if lineage.nodes.iter().any(|n: &LineageNode| n.certificate_der == certificate_der) {
+                lineage.errors.push(format!("节点#{}: 证书在谱系中重复出现", index));
+            }
+
+            lineage.nodes.push(LineageNode {
+                certificate: ApkParser::certificate_info(&certificate_der)?,
+                flags,
+                capabilities: LineageCapabilities::from_flags(flags),
+                signed_with: previous.as_ref().map(|_| signature_algorithm_name(signed_with)),
+                signs_next_with: (signs_next_with != 0).then(|| signature_algorithm_name(signs_next_with)),
+                certificate_der: certificate_der.clone(),
+            });
+            previous = Some((certificate_der, signs_next_with));
+        }
+
+        if lineage.nodes.is_empty() {
+            return Err(invalid("轮换证明中没有证书"));
+        }
+        lineage.verified = lineage.errors.is_empty();
+        Ok(lineage)
+    }
+
+    /// 最新（当前）的签名证书
+    pub fn current(&self) -> Option<&LineageNode> {
+        self.nodes.last()
+    }
+
+    /// 谱系中是否包含指定证书
+    pub fn contains(&self, certificate_der: &[u8]) -> bool {
+        self.nodes.iter().any(|node| node.certificate_der == certificate_der)
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidSignature(msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 两个RSA证书的谱系：第一个节点授予installed_data、shared_uid、permission与auth，
+    /// 并以RSASSA-PKCS1-v1_5 with SHA2-256签名第二个节点
+    const LINEAGE: &[u8] = include_bytes!("../tests/fixtures/lineage-rsa.bin");
+
+    /// 拆出的节点字段：(签名数据, 能力标志, 签名下一节点所用算法, 签名)
+    type NodeParts<'a> = (&'a [u8], u32, u32, &'a [u8]);
+
+    fn lp(data: &[u8]) -> Vec<u8> {
+        [&(data.len() as u32).to_le_bytes()[..], data].concat()
+    }
+
+    fn split(data: &[u8]) -> Vec<NodeParts<'_>> {
+        let mut pos = 4;
+        let mut nodes = Vec::new();
+        while pos < data.len() {
+            let node = read_length_prefixed(data, &mut pos).unwrap();
+            let mut node_pos = 0;
+            let signed_data = read_length_prefixed(node, &mut node_pos).unwrap();
+            let flags = read_u32(node, &mut node_pos).unwrap();
+            let signs_next_with = read_u32(node, &mut node_pos).unwrap();
+            let signature = read_length_prefixed(node, &mut node_pos).unwrap();
+            nodes.push((signed_data, flags, signs_next_with, signature));
+        }
+        nodes
+    }
+
+    fn join(version: u32, nodes: &[NodeParts]) -> Vec<u8> {
+        let mut data = version.to_le_bytes().to_vec();
+        for (signed_data, flags, signs_next_with, signature) in nodes {
+            let node = [lp(signed_data), flags.to_le_bytes().to_vec(), signs_next_with.to_le_bytes().to_vec(), lp(signature)].concat();
+            data.extend(lp(&node));
+        }
+        data
+    }
+
+    #[test]
+    fn parses_two_node_lineage() {
+        let lineage = SigningCertificateLineage::parse(LINEAGE).unwrap();
+        assert!(lineage.verified, "{:?}", lineage.errors);
+        assert_eq!(lineage.version, 1);
+        assert_eq!(lineage.nodes.len(), 2);
+
+        let first = &lineage.nodes[0];
+        assert_eq!(first.flags, 1 | 2 | 4 | 16);
+        let capabilities = &first.capabilities;
+        assert!(capabilities.installed_data && capabilities.shared_uid && capabilities.permission && capabilities.auth);
+        assert!(!capabilities.rollback);
+        assert_eq!(first.signed_with, None);
+        assert_eq!(first.signs_next_with.as_deref(), Some("RSASSA-PKCS1-v1_5 with SHA2-256"));
+
+        let current = lineage.current().unwrap();
+        assert_eq!(current.flags, 0);
+        assert!(!current.capabilities.installed_data && !current.capabilities.auth);
+        assert_eq!(current.signed_with.as_deref(), Some("RSASSA-PKCS1-v1_5 with SHA2-256"));
+        assert!(lineage.contains(&first.certificate_der));
+    }
+
+    #[test]
+    fn reports_algorithm_mismatch_with_previous_node() {
+        let mut nodes = split(LINEAGE);
+        // 第一个节点声明以SHA2-512签名下一节点，与第二个节点记录的算法不一致
+        nodes[0].2 = 0x0104;
+        let lineage = SigningCertificateLineage::parse(&join(1, &nodes)).unwrap();
+        assert!(!lineage.verified);
+        assert!(lineage.errors.iter().any(|e| e == "节点#2: 签名算法与上一节点声明的不一致"), "{:?}", lineage.errors);
+    }
+
+    #[test]
+    fn reports_signature_on_first_node() {
+        let mut nodes = split(LINEAGE);
+        nodes[0].3 = b"unexpected";
+        let lineage = SigningCertificateLineage::parse(&join(1, &nodes)).unwrap();
+        assert!(!lineage.verified);
+        assert_eq!(lineage.errors, ["节点#1: 第一个节点不应带有签名"]);
+        assert_eq!(lineage.nodes.len(), 2);
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let data = join(2, &split(LINEAGE));
+        assert!(matches!(SigningCertificateLineage::parse(&data), Err(ApkParserError::InvalidSignature(_))));
+        assert!(SigningCertificateLineage::parse(&join(1, &[])).is_err());
+    }
+}
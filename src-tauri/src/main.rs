@@ -1,7 +1,31 @@
 // 在Windows上的发布版本中防止额外的控制台窗口，请勿删除！
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod abi;
+mod anti_analysis;
 mod apk_parser;
+mod arsc;
+mod axml;
+mod cert_audit;
+mod compare;
+mod dex;
+mod elf;
+mod framework;
+mod idsig;
+mod inventory;
+mod lineage;
+mod limits;
+mod manifest;
+mod obfuscation;
+mod packer;
+mod page_size;
+mod pinning;
+mod pkcs7;
+mod sdk_detect;
+mod signing_block;
+mod size_breakdown;
+mod verifier;
+mod zip_audit;
 mod commands;
 
 use std::fs;
@@ -47,6 +71,11 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             commands::parse_apk,
+            commands::parse_manifest,
+            commands::parse_idsig,
+            commands::inspect_zip,
+            commands::analyze_apk_size,
+            commands::compare_apks,
             commands::get_app_info,
             // commands::parse_apk_data,
             commands::select_apk_file,
@@ -0,0 +1,857 @@
+//! AndroidManifest.xml 类型化模型
+//!
+//! 基于真正的XML元素树（二进制或纯文本清单）构建，取代原先基于正则表达式的字段提取。
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::ApkParserError;
+use crate::axml::{self, AttributeValue, XmlElement};
+use crate::limits::ParseLimits;
+
+/// uses-sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsesSdk {
+    /// 最低SDK版本
+    pub min_sdk_version: Option<u32>,
+    /// 目标SDK版本
+    pub target_sdk_version: Option<u32>,
+    /// 最高SDK版本
+    pub max_sdk_version: Option<u32>,
+    /// 预览版代号（如`VanillaIceCream`），仅在版本号不是数字时存在
+    pub codename: Option<String>,
+}
+
+/// uses-permission / uses-permission-sdk-23
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsesPermission {
+    /// 权限名
+    pub name: String,
+    /// 仅在该SDK及以下版本申请
+    pub max_sdk_version: Option<u32>,
+    /// 是否仅在Android 6.0+申请（uses-permission-sdk-23）
+    pub sdk23_only: bool,
+}
+
+/// 应用自定义的权限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionDeclaration {
+    /// 权限名
+    pub name: String,
+    /// 保护级别
+    pub protection_level: Option<String>,
+    /// 权限组
+    pub permission_group: Option<String>,
+}
+
+/// uses-feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsesFeature {
+    /// 特性名（OpenGL ES要求时为空）
+    pub name: Option<String>,
+    /// 是否必需
+    pub required: bool,
+    /// 要求的OpenGL ES版本，如`0x00020000`
+    pub gl_es_version: Option<String>,
+}
+
+/// uses-library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsesLibrary {
+    /// 库名
+    pub name: String,
+    /// 是否必需
+    pub required: bool,
+}
+
+/// meta-data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetaData {
+    /// 名称
+    pub name: String,
+    /// android:value
+    pub value: Option<String>,
+    /// android:resource
+    pub resource: Option<String>,
+}
+
+/// intent-filter中的data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntentData {
+    pub scheme: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<String>,
+    pub path: Option<String>,
+    pub path_prefix: Option<String>,
+    pub path_pattern: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+/// intent-filter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntentFilter {
+    /// action列表
+    pub actions: Vec<String>,
+    /// category列表
+    pub categories: Vec<String>,
+    /// data列表
+    pub data: Vec<IntentData>,
+    /// 优先级
+    pub priority: Option<i32>,
+    /// 是否声明了自动验证（App Links）
+    pub auto_verify: bool,
+}
+
+impl IntentFilter {
+    /// 是否为启动器入口（MAIN + LAUNCHER）
+    pub fn is_launcher(&self) -> bool {
+        self.actions.iter().any(|a| a == "android.intent.action.MAIN")
+            && self.categories.iter().any(|c| c == "android.intent.category.LAUNCHER")
+    }
+}
+
+/// 组件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Activity,
+    ActivityAlias,
+    Service,
+    Receiver,
+    Provider,
+}
+
+/// 四大组件（activity、activity-alias、service、receiver、provider）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Component {
+    /// 组件类型
+    pub kind: ComponentKind,
+    /// 完整类名
+    pub name: String,
+    /// 是否导出；未声明时为None（取决于是否有intent-filter和targetSdk）
+    pub exported: Option<bool>,
+    /// 是否启用
+    pub enabled: Option<bool>,
+    /// 访问所需权限
+    pub permission: Option<String>,
+    /// 运行进程
+    pub process: Option<String>,
+    /// activity-alias指向的目标
+    pub target_activity: Option<String>,
+    /// provider的authorities
+    pub authorities: Option<String>,
+    /// provider是否允许临时授权
+    pub grant_uri_permissions: Option<bool>,
+    /// service的前台服务类型
+    pub foreground_service_type: Option<String>,
+    /// intent-filter列表
+    pub intent_filters: Vec<IntentFilter>,
+    /// meta-data列表
+    pub meta_data: Vec<MetaData>,
+}
+
+/// application元素
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Application {
+    /// Application类名
+    pub name: Option<String>,
+    /// 应用名称（字面值或资源引用）
+    pub label: Option<String>,
+    /// 图标引用
+    pub icon: Option<String>,
+    /// 圆形图标引用
+    pub round_icon: Option<String>,
+    /// 主题引用
+    pub theme: Option<String>,
+    pub debuggable: Option<bool>,
+    pub allow_backup: Option<bool>,
+    pub full_backup_content: Option<String>,
+    pub data_extraction_rules: Option<String>,
+    pub uses_cleartext_traffic: Option<bool>,
+    pub network_security_config: Option<String>,
+    pub extract_native_libs: Option<bool>,
+    pub test_only: Option<bool>,
+    pub large_heap: Option<bool>,
+    pub has_code: Option<bool>,
+    pub app_component_factory: Option<String>,
+    pub request_legacy_external_storage: Option<bool>,
+    /// 组件
+    pub activities: Vec<Component>,
+    pub activity_aliases: Vec<Component>,
+    pub services: Vec<Component>,
+    pub receivers: Vec<Component>,
+    pub providers: Vec<Component>,
+    /// application级别的meta-data
+    pub meta_data: Vec<MetaData>,
+    /// application内声明的uses-library
+    pub uses_libraries: Vec<UsesLibrary>,
+}
+
+impl Application {
+    /// 按顺序遍历所有组件
+    pub fn components(&self) -> impl Iterator<Item = &Component> {
+        self.activities
+            .iter()
+            .chain(self.activity_aliases.iter())
+            .chain(self.services.iter())
+            .chain(self.receivers.iter())
+            .chain(self.providers.iter())
+    }
+}
+
+/// queries（Android 11包可见性）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Queries {
+    /// 查询的包名
+    pub packages: Vec<String>,
+    /// 查询的intent
+    pub intents: Vec<IntentFilter>,
+    /// 查询的provider authorities
+    pub providers: Vec<String>,
+}
+
+/// instrumentation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Instrumentation {
+    /// 类名
+    pub name: String,
+    /// 被测目标包名
+    pub target_package: Option<String>,
+    pub handle_profiling: Option<bool>,
+    pub functional_test: Option<bool>,
+}
+
+/// 类型化的AndroidManifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AndroidManifest {
+    /// 包名
+    pub package: String,
+    /// 完整版本号：(versionCodeMajor << 32) | versionCode
+    pub version_code: Option<u64>,
+    /// versionCodeMajor
+    pub version_code_major: Option<u32>,
+    /// 版本名称
+    pub version_name: Option<String>,
+    pub shared_user_id: Option<String>,
+    pub install_location: Option<String>,
+    pub compile_sdk_version: Option<u32>,
+    pub compile_sdk_version_codename: Option<String>,
+    /// uses-sdk
+    pub uses_sdk: Option<UsesSdk>,
+    pub uses_permissions: Vec<UsesPermission>,
+    pub permissions: Vec<PermissionDeclaration>,
+    pub uses_features: Vec<UsesFeature>,
+    pub uses_libraries: Vec<UsesLibrary>,
+    pub queries: Option<Queries>,
+    pub instrumentations: Vec<Instrumentation>,
+    pub application: Option<Application>,
+}
+
+impl AndroidManifest {
+    /// 从AndroidManifest.xml的原始内容（二进制或纯文本）解析
+    ///
+    /// # 参数
+    /// * `data` - 清单文件内容
+    /// * `limits` - 解析限制
+    ///
+    /// # 返回
+    /// 类型化的清单或错误
+    pub fn parse(data: &[u8], limits: &ParseLimits) -> Result<Self, ApkParserError> {
+        let document = axml::parse_any(data, limits)?;
+        Self::from_element(&document.root)
+    }
+
+    /// 从元素树构建
+    pub fn from_element(root: &XmlElement) -> Result<Self, ApkParserError> {
+        if root.name != "manifest" {
+            return Err(ApkParserError::InvalidXml(format!("根元素不是manifest: {}", root.name)));
+        }
+
+        let package = root.attr("package").map(|a| a.value.to_string()).unwrap_or_default();
+        let version_code_major = attr_u32(root, "versionCodeMajor");
+        let version_code = attr_u32(root, "versionCode")
+            .map(|minor| ((version_code_major.unwrap_or(0) as u64) << 32) | minor as u64);
+
+        let mut manifest = AndroidManifest {
+            version_code,
+            version_code_major,
+            version_name: attr_string(root, "versionName"),
+            shared_user_id: attr_string(root, "sharedUserId"),
+            install_location: attr_install_location(root),
+            compile_sdk_version: attr_u32(root, "compileSdkVersion"),
+            compile_sdk_version_codename: attr_string(root, "compileSdkVersionCodename"),
+            ..Default::default()
+        };
+
+        for child in &root.children {
+            match child.name.as_str() {
+                "uses-sdk" => {
+                    let mut sdk = UsesSdk {
+                        min_sdk_version: attr_u32(child, "minSdkVersion"),
+                        target_sdk_version: attr_u32(child, "targetSdkVersion"),
+                        max_sdk_version: attr_u32(child, "maxSdkVersion"),
+                        codename: None,
+                    };
+                    // 预览版SDK以代号字符串表示
+                    sdk.codename = ["targetSdkVersion", "minSdkVersion"]
+                        .iter()
+                        .filter_map(|name| attr_string(child, name))
+                        .find(|value| value.parse::<u32>().is_err());
+                    manifest.uses_sdk = Some(sdk);
+                }
+                "uses-permission" | "uses-permission-sdk-23" | "uses-permission-sdk-m" => {
+                    if let Some(name) = attr_string(child, "name") {
+                        manifest.uses_permissions.push(UsesPermission {
+                            name,
+                            max_sdk_version: attr_u32(child, "maxSdkVersion"),
+                            sdk23_only: child.name != "uses-permission",
+                        });
+                    }
+                }
+                "permission" => {
+                    if let Some(name) = attr_string(child, "name") {
+                        manifest.permissions.push(PermissionDeclaration {
+                            name,
+                            protection_level: attr_protection_level(child),
+                            permission_group: attr_string(child, "permissionGroup"),
+                        });
+                    }
+                }
+                "uses-feature" => {
+                    manifest.uses_features.push(UsesFeature {
+                        name: attr_string(child, "name"),
+                        required: attr_bool(child, "required").unwrap_or(true),
+                        gl_es_version: attr_u32(child, "glEsVersion").map(|v| format!("0x{:08x}", v)),
+                    });
+                }
+                "uses-library" => {
+                    if let Some(library) = parse_uses_library(child) {
+                        manifest.uses_libraries.push(library);
+                    }
+                }
+                "queries" => {
+                    manifest.queries = Some(parse_queries(child));
+                }
+                "instrumentation" => {
+                    if let Some(name) = attr_string(child, "name") {
+                        manifest.instrumentations.push(Instrumentation {
+                            name: qualify_class_name(&package, &name),
+                            target_package: attr_string(child, "targetPackage"),
+                            handle_profiling: attr_bool(child, "handleProfiling"),
+                            functional_test: attr_bool(child, "functionalTest"),
+                        });
+                    }
+                }
+                "application" => {
+                    manifest.application = Some(parse_application(child, &package));
+                }
+                _ => {}
+            }
+        }
+
+        manifest.package = package;
+        Ok(manifest)
+    }
+
+    /// 从`aapt2 dump badging`的文本输出构建（内置解析器无法解析清单时的回退）
+    ///
+    /// 该输出只包含清单的一部分信息，组件仅保留启动Activity，未出现的字段保持为空
+    ///
+    /// # 返回
+    /// 输出中没有`package:`行时为None
+    pub fn from_aapt_badging(output: &str) -> Option<Self> {
+        let mut manifest = AndroidManifest::default();
+        let mut uses_sdk = UsesSdk::default();
+        let mut application = Application::default();
+        let mut has_package = false;
+
+        for line in output.lines() {
+            let (tag, rest) = line.trim().split_once(':').unwrap_or((line.trim(), ""));
+            let attrs = badging_attrs(rest);
+            let value = |name: &str| attrs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string());
+            let number = |name: &str| value(name).and_then(|value| value.parse::<u32>().ok());
+            // `sdkVersion:'21'`这类行的值直接跟在冒号后
+            let bare = rest.trim().strip_prefix('\'').and_then(|v| v.strip_suffix('\'')).map(str::to_string);
+
+            match tag {
+                "package" => {
+                    has_package = true;
+                    manifest.package = value("name").unwrap_or_default();
+                    manifest.version_code_major = number("versionCodeMajor");
+                    manifest.version_code = number("versionCode")
+                        .map(|minor| ((manifest.version_code_major.unwrap_or(0) as u64) << 32) | minor as u64);
+                    manifest.version_name = value("versionName").filter(|v| !v.is_empty());
+                    manifest.compile_sdk_version = number("compileSdkVersion");
+                    manifest.compile_sdk_version_codename = value("compileSdkVersionCodename").filter(|v| !v.is_empty());
+                }
+                "sdkVersion" => uses_sdk.min_sdk_version = bare.and_then(|v| v.parse().ok()),
+                "targetSdkVersion" => uses_sdk.target_sdk_version = bare.and_then(|v| v.parse().ok()),
+                "maxSdkVersion" => uses_sdk.max_sdk_version = bare.and_then(|v| v.parse().ok()),
+                "uses-permission" | "uses-permission-sdk-23" => {
+                    if let Some(name) = value("name") {
+                        manifest.uses_permissions.push(UsesPermission {
+                            name,
+                            max_sdk_version: number("maxSdkVersion"),
+                            sdk23_only: tag != "uses-permission",
+                        });
+                    }
+                }
+                "uses-feature" | "uses-feature-not-required" => {
+                    manifest.uses_features.push(UsesFeature {
+                        name: value("name"),
+                        required: tag == "uses-feature",
+                        gl_es_version: None,
+                    });
+                }
+                "uses-library" | "uses-library-not-required" => {
+                    if let Some(name) = bare {
+                        manifest.uses_libraries.push(UsesLibrary { name, required: tag == "uses-library" });
+                    }
+                }
+                "application" => {
+                    application.label = value("label").filter(|v| !v.is_empty());
+                    application.icon = value("icon").filter(|v| !v.is_empty());
+                }
+                "application-debuggable" => application.debuggable = Some(true),
+                "launchable-activity" => {
+                    if let Some(name) = value("name") {
+                        application.activities.push(Component {
+                            kind: ComponentKind::Activity,
+                            name,
+                            exported: None,
+                            enabled: None,
+                            permission: None,
+                            process: None,
+                            target_activity: None,
+                            authorities: None,
+                            grant_uri_permissions: None,
+                            foreground_service_type: None,
+                            intent_filters: vec![IntentFilter {
+                                actions: vec!["android.intent.action.MAIN".to_string()],
+                                categories: vec!["android.intent.category.LAUNCHER".to_string()],
+                                ..Default::default()
+                            }],
+                            meta_data: Vec::new(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !has_package {
+            return None;
+        }
+        if uses_sdk.min_sdk_version.is_some() || uses_sdk.target_sdk_version.is_some() || uses_sdk.max_sdk_version.is_some() {
+            manifest.uses_sdk = Some(uses_sdk);
+        }
+        manifest.application = Some(application);
+        Some(manifest)
+    }
+
+    /// 最低SDK版本（未声明时为None）
+    pub fn min_sdk_version(&self) -> Option<u32> {
+        self.uses_sdk.as_ref().and_then(|s| s.min_sdk_version)
+    }
+
+    /// 目标SDK版本，未声明时按Android规则回退到minSdkVersion
+    pub fn target_sdk_version(&self) -> Option<u32> {
+        self.uses_sdk
+            .as_ref()
+            .and_then(|s| s.target_sdk_version)
+            .or_else(|| self.min_sdk_version())
+    }
+
+    /// 启动Activity（带MAIN/LAUNCHER的activity或activity-alias）
+    pub fn main_activity(&self) -> Option<String> {
+        let application = self.application.as_ref()?;
+        application
+            .activities
+            .iter()
+            .chain(application.activity_aliases.iter())
+            .find(|c| c.enabled != Some(false) && c.intent_filters.iter().any(IntentFilter::is_launcher))
+            .map(|c| c.name.clone())
+    }
+
+    /// 所有申请的权限名
+    pub fn permission_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for permission in &self.uses_permissions {
+            if !names.contains(&permission.name) {
+                names.push(permission.name.clone());
+            }
+        }
+        names
+    }
+}
+
+fn parse_application(element: &XmlElement, package: &str) -> Application {
+    let mut application = Application {
+        name: attr_string(element, "name").map(|n| qualify_class_name(package, &n)),
+        label: attr_string(element, "label"),
+        icon: attr_string(element, "icon"),
+        round_icon: attr_string(element, "roundIcon"),
+        theme: attr_string(element, "theme"),
+        debuggable: attr_bool(element, "debuggable"),
+        allow_backup: attr_bool(element, "allowBackup"),
+        full_backup_content: attr_string(element, "fullBackupContent"),
+        data_extraction_rules: attr_string(element, "dataExtractionRules"),
+        uses_cleartext_traffic: attr_bool(element, "usesCleartextTraffic"),
+        network_security_config: attr_string(element, "networkSecurityConfig"),
+        extract_native_libs: attr_bool(element, "extractNativeLibs"),
+        test_only: attr_bool(element, "testOnly"),
+        large_heap: attr_bool(element, "largeHeap"),
+        has_code: attr_bool(element, "hasCode"),
+        app_component_factory: attr_string(element, "appComponentFactory").map(|n| qualify_class_name(package, &n)),
+        request_legacy_external_storage: attr_bool(element, "requestLegacyExternalStorage"),
+        ..Default::default()
+    };
+
+    for child in &element.children {
+        match child.name.as_str() {
+            "activity" => push_component(&mut application.activities, child, ComponentKind::Activity, package),
+            "activity-alias" => push_component(&mut application.activity_aliases, child, ComponentKind::ActivityAlias, package),
+            "service" => push_component(&mut application.services, child, ComponentKind::Service, package),
+            "receiver" => push_component(&mut application.receivers, child, ComponentKind::Receiver, package),
+            "provider" => push_component(&mut application.providers, child, ComponentKind::Provider, package),
+            "meta-data" => application.meta_data.extend(parse_meta_data(child)),
+            "uses-library" => application.uses_libraries.extend(parse_uses_library(child)),
+            _ => {}
+        }
+    }
+
+    application
+}
+
+fn push_component(list: &mut Vec<Component>, element: &XmlElement, kind: ComponentKind, package: &str) {
+    let Some(name) = attr_string(element, "name") else {
+        return;
+    };
+
+    list.push(Component {
+        kind,
+        name: qualify_class_name(package, &name),
+        exported: attr_bool(element, "exported"),
+        enabled: attr_bool(element, "enabled"),
+        permission: attr_string(element, "permission"),
+        process: attr_string(element, "process"),
+        target_activity: attr_string(element, "targetActivity").map(|n| qualify_class_name(package, &n)),
+        authorities: attr_string(element, "authorities"),
+        grant_uri_permissions: attr_bool(element, "grantUriPermissions"),
+        foreground_service_type: attr_string(element, "foregroundServiceType"),
+        intent_filters: element.children_named("intent-filter").map(parse_intent_filter).collect(),
+        meta_data: element.children_named("meta-data").filter_map(parse_meta_data).collect(),
+    });
+}
+
+fn parse_intent_filter(element: &XmlElement) -> IntentFilter {
+    let mut filter = IntentFilter {
+        priority: attr_u32(element, "priority").map(|p| p as i32),
+        auto_verify: attr_bool(element, "autoVerify").unwrap_or(false),
+        ..Default::default()
+    };
+    for child in &element.children {
+        match child.name.as_str() {
+            "action" => filter.actions.extend(attr_string(child, "name")),
+            "category" => filter.categories.extend(attr_string(child, "name")),
+            "data" => filter.data.push(IntentData {
+                scheme: attr_string(child, "scheme"),
+                host: attr_string(child, "host"),
+                port: attr_string(child, "port"),
+                path: attr_string(child, "path"),
+                path_prefix: attr_string(child, "pathPrefix"),
+                path_pattern: attr_string(child, "pathPattern"),
+                mime_type: attr_string(child, "mimeType"),
+            }),
+            _ => {}
+        }
+    }
+    filter
+}
+
+fn parse_meta_data(element: &XmlElement) -> Option<MetaData> {
+    Some(MetaData {
+        name: attr_string(element, "name")?,
+        value: attr_string(element, "value"),
+        resource: attr_string(element, "resource"),
+    })
+}
+
+fn parse_uses_library(element: &XmlElement) -> Option<UsesLibrary> {
+    Some(UsesLibrary {
+        name: attr_string(element, "name")?,
+        required: attr_bool(element, "required").unwrap_or(true),
+    })
+}
+
+fn parse_queries(element: &XmlElement) -> Queries {
+    let mut queries = Queries::default();
+    for child in &element.children {
+        match child.name.as_str() {
+            "package" => queries.packages.extend(attr_string(child, "name")),
+            "intent" => queries.intents.push(parse_intent_filter(child)),
+            "provider" => queries.providers.extend(attr_string(child, "authorities")),
+            _ => {}
+        }
+    }
+    queries
+}
+
+/// 将`.MainActivity`或`MainActivity`补全为完整类名
+pub fn qualify_class_name(package: &str, name: &str) -> String {
+    if let Some(stripped) = name.strip_prefix('.') {
+        format!("{}.{}", package, stripped)
+    } else if !name.contains('.') && !package.is_empty() {
+        format!("{}.{}", package, name)
+    } else {
+        name.to_string()
+    }
+}
+
+// 拆分aapt2 badging行中的`key='value'`对，值中不含单引号
+fn badging_attrs(rest: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = rest;
+    while let Some((key, tail)) = rest.split_once("='") {
+        let Some((value, tail)) = tail.split_once('\'') else {
+            break;
+        };
+        attrs.push((key.trim(), value));
+        rest = tail;
+    }
+    attrs
+}
+
+// 属性值转字符串，空值视为未声明
+fn attr_string(element: &XmlElement, name: &str) -> Option<String> {
+    let attr = element.android_attr(name)?;
+    match &attr.value {
+        AttributeValue::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+fn attr_bool(element: &XmlElement, name: &str) -> Option<bool> {
+    match &element.android_attr(name)?.value {
+        AttributeValue::Bool(b) => Some(*b),
+        AttributeValue::Int(v) => Some(*v != 0),
+        AttributeValue::String(s) => match s.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn attr_u32(element: &XmlElement, name: &str) -> Option<u32> {
+    match &element.android_attr(name)?.value {
+        AttributeValue::Int(v) => Some(*v as u32),
+        AttributeValue::Hex(v) => Some(*v),
+        AttributeValue::String(s) => {
+            let s = s.trim();
+            match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => s.parse::<u32>().ok(),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn attr_install_location(element: &XmlElement) -> Option<String> {
+    match &element.android_attr("installLocation")?.value {
+        AttributeValue::Int(0) => Some("auto".to_string()),
+        AttributeValue::Int(1) => Some("internalOnly".to_string()),
+        AttributeValue::Int(2) => Some("preferExternal".to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn attr_protection_level(element: &XmlElement) -> Option<String> {
+    let value = &element.android_attr("protectionLevel")?.value;
+    let level = match value {
+        AttributeValue::Int(v) => *v as u32,
+        AttributeValue::Hex(v) => *v,
+        other => return Some(other.to_string()),
+    };
+
+    let base = match level & 0xf {
+        0 => "normal",
+        1 => "dangerous",
+        2 => "signature",
+        3 => "signatureOrSystem",
+        4 => "internal",
+        _ => "unknown",
+    };
+    let mut parts = vec![base.to_string()];
+    let flags = [
+        (0x10, "privileged"),
+        (0x20, "development"),
+        (0x40, "appop"),
+        (0x80, "pre23"),
+        (0x100, "installer"),
+        (0x200, "verifier"),
+        (0x400, "preinstalled"),
+        (0x800, "setup"),
+        (0x1000, "instant"),
+        (0x2000, "runtime"),
+    ];
+    for (bit, name) in flags {
+        if level & bit != 0 {
+            parts.push(name.to_string());
+        }
+    }
+    Some(parts.join("|"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::axml::testing::{AxmlBuilder, Value};
+    use crate::axml::{ANDROID_NS, TYPE_INT_BOOLEAN, TYPE_INT_DEC};
+
+    const ANDROID_ATTRS: &[&str] = &["name", "versionCode", "versionName", "minSdkVersion", "targetSdkVersion", "versionCodeMajor", "debuggable"];
+
+    fn binary_manifest(version_code_major: Option<u32>, uses_sdk: &[(&str, u32)]) -> Vec<u8> {
+        let android = Some(ANDROID_NS);
+        let mut builder = AxmlBuilder::new(false, ANDROID_ATTRS);
+        let mut root = vec![
+            (None, "package", Value::Str("com.example.app")),
+            (android, "versionCode", Value::Typed(TYPE_INT_DEC, 7)),
+            (android, "versionName", Value::Str("1.2.3")),
+        ];
+        if let Some(major) = version_code_major {
+            root.push((android, "versionCodeMajor", Value::Typed(TYPE_INT_DEC, major)));
+        }
+        let sdk: Vec<_> = uses_sdk.iter().map(|(name, value)| (android, *name, Value::Typed(TYPE_INT_DEC, *value))).collect();
+
+        builder.start_namespace("android", ANDROID_NS).start("manifest", &root);
+        if !sdk.is_empty() {
+            builder.start("uses-sdk", &sdk).end("uses-sdk");
+        }
+        builder
+            .start("uses-permission", &[(android, "name", Value::Str("android.permission.CAMERA"))])
+            .end("uses-permission")
+            .start("uses-permission", &[(android, "name", Value::Str("android.permission.CAMERA"))])
+            .end("uses-permission")
+            .start("application", &[(android, "debuggable", Value::Typed(TYPE_INT_BOOLEAN, u32::MAX))])
+            .start("activity", &[(android, "name", Value::Str(".MainActivity"))])
+            .start("intent-filter", &[])
+            .start("action", &[(android, "name", Value::Str("android.intent.action.MAIN"))])
+            .end("action")
+            .start("category", &[(android, "name", Value::Str("android.intent.category.LAUNCHER"))])
+            .end("category")
+            .end("intent-filter")
+            .end("activity")
+            .end("application")
+            .end("manifest")
+            .end_namespace("android", ANDROID_NS);
+        builder.build()
+    }
+
+    #[test]
+    fn builds_model_from_binary_xml() {
+        let data = binary_manifest(None, &[("minSdkVersion", 21), ("targetSdkVersion", 34)]);
+        let manifest = AndroidManifest::parse(&data, &ParseLimits::default()).unwrap();
+
+        assert_eq!(manifest.package, "com.example.app");
+        assert_eq!(manifest.version_code, Some(7));
+        assert_eq!(manifest.version_code_major, None);
+        assert_eq!(manifest.version_name.as_deref(), Some("1.2.3"));
+        assert_eq!(manifest.min_sdk_version(), Some(21));
+        assert_eq!(manifest.target_sdk_version(), Some(34));
+        assert_eq!(manifest.permission_names(), vec!["android.permission.CAMERA".to_string()]);
+        assert_eq!(manifest.main_activity().as_deref(), Some("com.example.app.MainActivity"));
+        assert_eq!(manifest.application.as_ref().and_then(|a| a.debuggable), Some(true));
+    }
+
+    #[test]
+    fn version_code_major_forms_the_high_word() {
+        let data = binary_manifest(Some(1), &[]);
+        let manifest = AndroidManifest::parse(&data, &ParseLimits::default()).unwrap();
+
+        assert_eq!(manifest.version_code_major, Some(1));
+        assert_eq!(manifest.version_code, Some((1 << 32) | 7));
+    }
+
+    #[test]
+    fn sdk_versions_fall_back() {
+        // 未声明targetSdkVersion时回退到minSdkVersion
+        let data = binary_manifest(None, &[("minSdkVersion", 19)]);
+        let manifest = AndroidManifest::parse(&data, &ParseLimits::default()).unwrap();
+        assert_eq!(manifest.min_sdk_version(), Some(19));
+        assert_eq!(manifest.target_sdk_version(), Some(19));
+
+        // 没有uses-sdk时两者都未知
+        let data = binary_manifest(None, &[]);
+        let manifest = AndroidManifest::parse(&data, &ParseLimits::default()).unwrap();
+        assert_eq!(manifest.min_sdk_version(), None);
+        assert_eq!(manifest.target_sdk_version(), None);
+    }
+
+    #[test]
+    fn rejects_non_manifest_root() {
+        let mut builder = AxmlBuilder::new(true, &[]);
+        builder.start("resources", &[]).end("resources");
+        assert!(AndroidManifest::parse(&builder.build(), &ParseLimits::default()).is_err());
+    }
+
+    const BADGING: &str = "\
+package: name='com.example.app' versionCode='7' versionName='1.2.3' versionCodeMajor='2' platformBuildVersionName='14' platformBuildVersionCode='34' compileSdkVersion='34' compileSdkVersionCodename='14'
+sdkVersion:'21'
+targetSdkVersion:'34'
+uses-permission: name='android.permission.INTERNET'
+uses-permission: name='android.permission.READ_EXTERNAL_STORAGE' maxSdkVersion='32'
+uses-permission-sdk-23: name='android.permission.CAMERA'
+application-label:'Example'
+application: label='Example' icon='res/mipmap-anydpi-v26/ic_launcher.xml'
+application-debuggable
+launchable-activity: name='com.example.app.MainActivity'  label='' icon=''
+uses-library-not-required:'org.apache.http.legacy'
+uses-feature-not-required: name='android.hardware.camera'
+native-code: 'arm64-v8a' 'armeabi-v7a'
+";
+
+    #[test]
+    fn builds_model_from_aapt_badging() {
+        let manifest = AndroidManifest::from_aapt_badging(BADGING).unwrap();
+
+        assert_eq!(manifest.package, "com.example.app");
+        assert_eq!(manifest.version_code_major, Some(2));
+        assert_eq!(manifest.version_code, Some((2 << 32) | 7));
+        assert_eq!(manifest.version_name.as_deref(), Some("1.2.3"));
+        assert_eq!(manifest.compile_sdk_version, Some(34));
+        assert_eq!(manifest.min_sdk_version(), Some(21));
+        assert_eq!(manifest.target_sdk_version(), Some(34));
+        assert_eq!(manifest.permission_names(), vec![
+            "android.permission.INTERNET".to_string(),
+            "android.permission.READ_EXTERNAL_STORAGE".to_string(),
+            "android.permission.CAMERA".to_string(),
+        ]);
+        assert_eq!(manifest.uses_permissions[1].max_sdk_version, Some(32));
+        assert!(manifest.uses_permissions[2].sdk23_only);
+        assert_eq!(manifest.main_activity().as_deref(), Some("com.example.app.MainActivity"));
+
+        let application = manifest.application.as_ref().unwrap();
+        assert_eq!(application.label.as_deref(), Some("Example"));
+        assert_eq!(application.debuggable, Some(true));
+        assert!(!manifest.uses_libraries[0].required);
+        assert!(!manifest.uses_features[0].required);
+    }
+
+    #[test]
+    fn aapt_badging_sdk_fallbacks() {
+        let manifest = AndroidManifest::from_aapt_badging("package: name='a.b' versionCode='3' versionName=''\nsdkVersion:'16'\n").unwrap();
+        assert_eq!(manifest.version_code, Some(3));
+        assert_eq!(manifest.version_name, None);
+        assert_eq!(manifest.target_sdk_version(), Some(16));
+
+        let manifest = AndroidManifest::from_aapt_badging("package: name='a.b'\n").unwrap();
+        assert_eq!(manifest.version_code, None);
+        assert_eq!(manifest.min_sdk_version(), None);
+        assert_eq!(manifest.target_sdk_version(), None);
+        assert_eq!(manifest.main_activity(), None);
+
+        assert!(AndroidManifest::from_aapt_badging("ERROR: dump failed\n").is_none());
+    }
+}
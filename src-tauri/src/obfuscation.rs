@@ -0,0 +1,410 @@
+//! 混淆与R8/ProGuard识别
+//!
+//! 从DEX表和资源路径中计算多项混淆指标（短类名/方法名比例、重命名的包、字符串加密、
+//! 反射密度、资源名压缩以及R8编译标记），按权重合成0-100的混淆评分并给出说明，
+//! 用于确认发布版本确实经过了压缩混淆。
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::dex::{DexAnalysis, DexFile};
+
+/// 评分达到该值视为已混淆
+const OBFUSCATED_SCORE: u32 = 50;
+/// 评分达到该值视为可能混淆
+const POTENTIALLY_OBFUSCATED_SCORE: u32 = 25;
+
+/// 高熵字符串的最小长度与熵阈值（比特/字符）
+const HIGH_ENTROPY_MIN_LENGTH: usize = 20;
+const HIGH_ENTROPY_THRESHOLD: f64 = 4.2;
+
+/// 每项指标最多列出的示例数
+const MAX_EXAMPLES: usize = 5;
+
+/// 反射相关的方法引用
+const REFLECTION_METHODS: &[&str] = &[
+    "java.lang.Class.forName",
+    "java.lang.Class.getMethod",
+    "java.lang.Class.getDeclaredMethod",
+    "java.lang.Class.getField",
+    "java.lang.Class.getDeclaredField",
+    "java.lang.Class.getConstructor",
+    "java.lang.Class.getDeclaredConstructor",
+    "java.lang.Class.newInstance",
+    "java.lang.reflect.Method.invoke",
+    "java.lang.reflect.Field.get",
+    "java.lang.reflect.Field.set",
+    "java.lang.reflect.Constructor.newInstance",
+];
+
+/// 解密函数常见的命名
+const DECRYPT_NAME_HINTS: &[&str] = &["decrypt", "decode", "deobf", "unscramble"];
+
+/// 混淆程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObfuscationLevel {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+/// 单项混淆指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObfuscationIndicator {
+    /// 指标标识，如`short_class_names`
+    pub id: String,
+    pub title: String,
+    /// 指标原始值（比例或数量）
+    pub value: f64,
+    /// 指标强度（0-1）
+    pub strength: f64,
+    /// 指标权重，全部指标权重之和为100
+    pub weight: u32,
+    /// 对总分的贡献
+    pub contribution: f64,
+    pub detail: String,
+    /// 命中示例
+    pub examples: Vec<String>,
+}
+
+/// 混淆识别结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObfuscationReport {
+    /// 混淆评分（0-100）
+    pub score: u32,
+    pub level: ObfuscationLevel,
+    pub is_obfuscated: bool,
+    pub potentially_obfuscated: bool,
+    /// 推测使用的工具，如`R8`、`ProGuard/R8`
+    pub tool: Option<String>,
+    /// DEX中的编译器标记（`~~R8{…}`、`~~D8{…}`）
+    pub compiler_markers: Vec<String>,
+    pub indicators: Vec<ObfuscationIndicator>,
+    /// 评分说明
+    pub explanation: Vec<String>,
+}
+
+/// 从DEX与资源计算混淆指标
+pub fn analyze(archive: &ZipArchive<File>, dex: &DexAnalysis) -> ObfuscationReport {
+    let mut stats = DexStats::default();
+    for (_, file) in &dex.files {
+        stats.collect(file);
+    }
+    let resources = resource_stats(archive);
+
+    let compiler_markers: Vec<String> = dex.strings()
+        .filter(|s| s.starts_with("~~R8{") || s.starts_with("~~D8{") || s.starts_with("~~L8{"))
+        .map(String::from)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let r8_marker = compiler_markers.iter().any(|m| m.starts_with("~~R8{"));
+
+    let short_class_names = indicator(
+        "short_class_names", "短类名比例", 25,
+        ratio(stats.short_classes.count, stats.classes), 0.5,
+        format!("{} / {} 个类的简单名称不超过2个字母", stats.short_classes.count, stats.classes),
+        &stats.short_classes.examples,
+    );
+    let short_method_names = indicator(
+        "short_method_names", "短方法名比例", 20,
+        ratio(stats.short_methods.count, stats.methods), 0.4,
+        format!("{} / {} 个方法的名称不超过2个字母", stats.short_methods.count, stats.methods),
+        &stats.short_methods.examples,
+    );
+    let naming = short_class_names.strength.max(short_method_names.strength);
+
+    let mut indicators = vec![
+        short_class_names,
+        short_method_names,
+        indicator(
+            "renamed_packages", "重命名的包", 15,
+            ratio(stats.renamed_package_classes, stats.classes), 0.5,
+            format!("{} 个类位于全部由短名称组成的包中", stats.renamed_package_classes),
+            &stats.renamed_packages.iter().cloned().collect::<Vec<_>>(),
+        ),
+        indicator(
+            "resource_shrinking", "资源名压缩", 10,
+            ratio(resources.shortened.count, resources.total), 0.5,
+            format!("{} / {} 个资源文件被重命名到res/根目录下的短名称", resources.shortened.count, resources.total),
+            &resources.shortened.examples,
+        ),
+    ];
+
+    let entropy_ratio = ratio(stats.high_entropy_strings.count, stats.strings);
+    let string_encryption = match (stats.decrypt_methods.count == 0, entropy_ratio >= 0.05) {
+        (false, true) => 1.0,
+        (false, false) | (true, true) => 0.5,
+        (true, false) => 0.0,
+    };
+    let mut encryption_examples = stats.decrypt_methods.examples.clone();
+    encryption_examples.extend(stats.high_entropy_strings.examples.iter().cloned());
+    indicators.push(indicator(
+        "string_encryption", "字符串加密", 10,
+        string_encryption, 1.0,
+        format!(
+            "{:.1}% 的字符串为高熵字符串，{} 个疑似解密方法（静态、返回String）",
+            entropy_ratio * 100.0,
+            stats.decrypt_methods.count
+        ),
+        &encryption_examples,
+    ));
+
+    let reflection_density = ratio(stats.reflection_calls, stats.invocations);
+    indicators.push(indicator(
+        "reflection_density", "反射密度", 5,
+        reflection_density, 0.02,
+        format!("{} / {} 次方法调用为反射调用", stats.reflection_calls, stats.invocations),
+        &[],
+    ));
+    indicators.push(indicator(
+        "r8_marker", "R8编译标记", 15,
+        if r8_marker { 1.0 } else { 0.0 }, 1.0,
+        if r8_marker {
+            "DEX包含R8编译标记，构建时启用了代码压缩".to_string()
+        } else if compiler_markers.is_empty() {
+            "未发现编译器标记".to_string()
+        } else {
+            "仅发现D8/L8编译标记，构建时未启用R8".to_string()
+        },
+        &compiler_markers,
+    ));
+
+    let score = indicators.iter().map(|i| i.contribution).sum::<f64>().round().min(100.0) as u32;
+    let level = match score {
+        0..=9 => ObfuscationLevel::None,
+        10..=24 => ObfuscationLevel::Low,
+        25..=49 => ObfuscationLevel::Medium,
+        _ => ObfuscationLevel::High,
+    };
+
+    let tool = if string_encryption >= 1.0 && naming > 0.5 {
+        Some("商业混淆器（疑似DexGuard等，含字符串加密）".to_string())
+    } else if r8_marker {
+        Some("R8".to_string())
+    } else if score >= OBFUSCATED_SCORE {
+        Some("ProGuard/R8".to_string())
+    } else {
+        None
+    };
+
+    let mut explanation: Vec<String> = indicators.iter()
+        .filter(|i| i.contribution >= 1.0)
+        .map(|i| format!("{}（+{:.0}）：{}", i.title, i.contribution, i.detail))
+        .collect();
+    if explanation.is_empty() {
+        explanation.push("未发现混淆迹象，类名与方法名基本保留原始名称".to_string());
+    }
+    if score < OBFUSCATED_SCORE && !r8_marker {
+        explanation.push("发布版本通常应启用minifyEnabled（R8）以压缩和混淆代码".to_string());
+    }
+
+    ObfuscationReport {
+        score,
+        level,
+        is_obfuscated: score >= OBFUSCATED_SCORE,
+        potentially_obfuscated: score >= POTENTIALLY_OBFUSCATED_SCORE,
+        tool,
+        compiler_markers,
+        indicators,
+        explanation,
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+/// `saturation`为强度达到1时的指标值
+fn indicator(
+    id: &str,
+    title: &str,
+    weight: u32,
+    value: f64,
+    saturation: f64,
+    detail: String,
+    examples: &[String],
+) -> ObfuscationIndicator {
+    let strength = (value / saturation).clamp(0.0, 1.0);
+    ObfuscationIndicator {
+        id: id.to_string(),
+        title: title.to_string(),
+        value,
+        strength,
+        weight,
+        contribution: strength * weight as f64,
+        detail,
+        examples: examples.iter().take(MAX_EXAMPLES).cloned().collect(),
+    }
+}
+
+/// 命中计数，只保留前几个示例
+#[derive(Default)]
+struct Matches {
+    count: usize,
+    examples: Vec<String>,
+}
+
+impl Matches {
+    fn add(&mut self, example: impl FnOnce() -> String) {
+        self.count += 1;
+        if self.examples.len() < MAX_EXAMPLES {
+            self.examples.push(example());
+        }
+    }
+}
+
+/// 汇总的DEX统计
+#[derive(Default)]
+struct DexStats {
+    classes: usize,
+    short_classes: Matches,
+    renamed_package_classes: usize,
+    renamed_packages: BTreeSet<String>,
+    methods: usize,
+    short_methods: Matches,
+    strings: usize,
+    high_entropy_strings: Matches,
+    decrypt_methods: Matches,
+    invocations: usize,
+    reflection_calls: usize,
+}
+
+impl DexStats {
+    fn collect(&mut self, dex: &DexFile) {
+        let reflection: HashSet<u32> = (0..dex.method_ids.len() as u32)
+            .filter(|idx| dex.method_name_at(*idx).is_some_and(|name| REFLECTION_METHODS.contains(&name.as_str())))
+            .collect();
+
+        for class in &dex.class_defs {
+            let Some(class_name) = dex.type_name(class.class_idx) else {
+                continue;
+            };
+            self.classes += 1;
+            let (package, simple_name) = split_class_name(&class_name);
+            if is_short_name(simple_name) {
+                self.short_classes.add(|| class_name.clone());
+            }
+            if package.is_empty() || package.split('.').all(is_short_name) {
+                self.renamed_package_classes += 1;
+                if !package.is_empty() && self.renamed_packages.len() < MAX_EXAMPLES {
+                    self.renamed_packages.insert(package.to_string());
+                }
+            }
+
+            for method in &class.methods {
+                let Some(id) = dex.method_ids.get(method.method_idx as usize) else {
+                    continue;
+                };
+                let Some(name) = dex.strings.get(id.name_idx as usize) else {
+                    continue;
+                };
+                if name.starts_with('<') {
+                    continue;
+                }
+                self.methods += 1;
+                if is_short_name(name) {
+                    self.short_methods.add(|| format!("{}.{}", class_name, name));
+                }
+                if is_decrypt_like(dex, id.proto_idx, method.access_flags, name) {
+                    self.decrypt_methods.add(|| format!("{}.{}", class_name, name));
+                }
+                if let Some(code) = &method.code {
+                    for insn in code.instructions() {
+                        if let Some(idx) = insn.method_index() {
+                            self.invocations += 1;
+                            if reflection.contains(&idx) {
+                                self.reflection_calls += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.strings += dex.strings.len();
+        for string in dex.strings.iter().filter(|s| is_high_entropy(s)) {
+            self.high_entropy_strings.add(|| string.clone());
+        }
+    }
+}
+
+/// 拆分为包名与简单类名，内部类取`$`之后的部分
+fn split_class_name(class_name: &str) -> (&str, &str) {
+    let (package, name) = class_name.rsplit_once('.').unwrap_or(("", class_name));
+    (package, name.rsplit('$').next().unwrap_or(name))
+}
+
+/// 1-2个ASCII字母的名称，如`a`、`bC`
+fn is_short_name(name: &str) -> bool {
+    (1..=2).contains(&name.len()) && name.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// 静态、返回String、参数仅为String/字节数组/字符数组/int，且名称很短或带有解密含义的方法
+fn is_decrypt_like(dex: &DexFile, proto_idx: u16, access_flags: u32, name: &str) -> bool {
+    const ACC_STATIC: u32 = 0x0008;
+    let Some(proto) = dex.proto_ids.get(proto_idx as usize) else {
+        return false;
+    };
+    if access_flags & ACC_STATIC == 0 || dex.type_descriptor(proto.return_type_idx) != Some("Ljava/lang/String;") {
+        return false;
+    }
+    let params_ok = !proto.parameters.is_empty() && proto.parameters.iter().all(|idx| {
+        matches!(dex.type_descriptor(*idx), Some("Ljava/lang/String;" | "[B" | "[C" | "I"))
+    });
+    let lower = name.to_lowercase();
+    params_ok && (is_short_name(name) || DECRYPT_NAME_HINTS.iter().any(|hint| lower.contains(hint)))
+}
+
+/// 无空白、长度足够且香农熵较高的字符串（排除类型描述符和URL）
+fn is_high_entropy(s: &str) -> bool {
+    if s.chars().count() < HIGH_ENTROPY_MIN_LENGTH
+        || s.contains(char::is_whitespace)
+        || s.contains("://")
+        || s.starts_with("~~")
+        || (s.starts_with('L') && s.ends_with(';'))
+    {
+        return false;
+    }
+    let mut counts = HashMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = s.chars().count() as f64;
+    let entropy: f64 = counts.values()
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy >= HIGH_ENTROPY_THRESHOLD
+}
+
+/// 资源文件统计
+struct ResourceStats {
+    total: usize,
+    shortened: Matches,
+}
+
+/// R8资源名压缩会把`res/layout/main.xml`改写为`res/a0.xml`这样的短路径
+fn resource_stats(archive: &ZipArchive<File>) -> ResourceStats {
+    let mut stats = ResourceStats { total: 0, shortened: Matches::default() };
+    let mut names: Vec<&str> = archive.file_names()
+        .filter(|name| name.starts_with("res/") && !name.ends_with('/'))
+        .collect();
+    names.sort_unstable();
+    for name in names {
+        stats.total += 1;
+        let rest = &name["res/".len()..];
+        let stem = rest.split('.').next().unwrap_or(rest);
+        if !rest.contains('/') && (1..=3).contains(&stem.len()) {
+            stats.shortened.add(|| name.to_string());
+        }
+    }
+    stats
+}
@@ -0,0 +1,323 @@
+//! 加固（壳）识别
+//!
+//! 根据清单中的壳入口类、特征原生库、assets文件以及DEX中定义的壳类识别常见加固厂商，
+//! 并检查“很小的DEX + assets中的加密数据”这种典型的加固布局。
+//! 识别到加固时，APK中的DEX只是壳代码，真正的应用代码在运行时才被解密加载。
+
+use std::fs::File;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::dex::DexAnalysis;
+use crate::limits::ReadBudget;
+use crate::manifest::AndroidManifest;
+
+/// 壳DEX通常只有少量类
+const SHELL_DEX_MAX_CLASSES: usize = 100;
+/// 视为加密数据的assets文件的最小大小
+const ENCRYPTED_BLOB_MIN_SIZE: u64 = 64 * 1024;
+/// 加密数据的字节熵阈值（比特/字节）
+const ENCRYPTED_BLOB_MIN_ENTROPY: f64 = 7.5;
+/// 计算熵时读取的字节数
+const ENTROPY_SAMPLE_SIZE: u64 = 64 * 1024;
+/// 本身就是压缩格式、熵天然很高的媒体文件
+const MEDIA_EXTENSIONS: &[&str] = &[".png", ".jpg", ".jpeg", ".webp", ".gif", ".mp3", ".ogg", ".mp4", ".m4a", ".aac"];
+
+/// 特征的匹配对象
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// 清单中的Application类或appComponentFactory
+    StubApplication(&'static str),
+    /// DEX中定义了以此为前缀的类
+    ClassPrefix(&'static str),
+    /// `lib/<abi>/`或`assets/`下的原生库文件名（正则，第一个捕获组为版本号）
+    NativeLibrary(&'static str),
+    /// assets下的文件路径（正则）
+    Asset(&'static str),
+}
+
+struct Fingerprint {
+    id: &'static str,
+    name: &'static str,
+    vendor: &'static str,
+    targets: &'static [Target],
+}
+
+use Target::{Asset, ClassPrefix, NativeLibrary, StubApplication};
+
+const FINGERPRINTS: &[Fingerprint] = &[
+    Fingerprint {
+        id: "jiagu360",
+        name: "360加固",
+        vendor: "奇虎360",
+        targets: &[
+            StubApplication("com.stub.StubApp"),
+            ClassPrefix("com.stub.StubApp"),
+            ClassPrefix("com.qihoo.util"),
+            NativeLibrary(r"^libjiagu(?:_a64|_x86|_x64)?\.so$"),
+            NativeLibrary(r"^libjgdtc(?:_a64|_x86|_x64)?\.so$"),
+            Asset(r"^assets/(?:libjiagu.*\.so|\.appkey|jiagu_.*)$"),
+        ],
+    },
+    Fingerprint {
+        id: "legu",
+        name: "腾讯乐固",
+        vendor: "腾讯",
+        targets: &[
+            StubApplication("com.tencent.StubShell.TxAppEntry"),
+            StubApplication("com.wrapper.proxyapplication.WrapperProxyApplication"),
+            ClassPrefix("com.tencent.StubShell"),
+            ClassPrefix("com.wrapper.proxyapplication"),
+            NativeLibrary(r"^libshell[ax]?-([0-9][0-9.]*)\.so$"),
+            NativeLibrary(r"^libshell-super\.([0-9]+)\.so$"),
+            NativeLibrary(r"^lib(?:tup|legudb|mix)\.so$"),
+            Asset(r"^assets/(?:tosversion|t86|0OO00l111l1l|o0oooOO0ooOo\.dat|mix\.dex)$"),
+        ],
+    },
+    Fingerprint {
+        id: "bangcle",
+        name: "梆梆加固",
+        vendor: "梆梆安全（SecNeo）",
+        targets: &[
+            StubApplication("com.secneo.apkwrapper.ApplicationWrapper"),
+            StubApplication("com.SecShell.SecShell.ApplicationWrapper"),
+            StubApplication("com.secneo.apkwrapper.AppWrapper"),
+            ClassPrefix("com.secneo.apkwrapper"),
+            ClassPrefix("com.SecShell.SecShell"),
+            NativeLibrary(r"^lib(?:secexe|secmain|SecShell|secpreload)(?:\.x86)?\.so$"),
+            NativeLibrary(r"^libDexHelper(?:-x86)?\.so$"),
+            Asset(r"^assets/(?:secData0\.jar|bangcle_classes\.jar|meta-data/.*|classes0\.jar)$"),
+        ],
+    },
+    Fingerprint {
+        id: "ijiami",
+        name: "爱加密",
+        vendor: "爱加密",
+        targets: &[
+            StubApplication("s.h.e.l.l.S"),
+            StubApplication("com.shell.SuperApplication"),
+            ClassPrefix("s.h.e.l.l"),
+            ClassPrefix("com.shell.SuperApplication"),
+            NativeLibrary(r"^lib(?:exec|execmain|ijiami|ijmDataEncryption)\.so$"),
+            Asset(r"^assets/(?:ijiami\.dat|ijiami\.ajm|ijm_lib/.*|af\.bin|signed\.bin)$"),
+        ],
+    },
+    Fingerprint {
+        id: "baidu",
+        name: "百度加固",
+        vendor: "百度",
+        targets: &[
+            StubApplication("com.baidu.protect.StubApplication"),
+            ClassPrefix("com.baidu.protect"),
+            NativeLibrary(r"^libbaiduprotect(?:_x86)?\.so$"),
+            Asset(r"^assets/baiduprotect[0-9]*\.jar$"),
+        ],
+    },
+    Fingerprint {
+        id: "alibaba",
+        name: "阿里聚安全",
+        vendor: "阿里巴巴",
+        targets: &[
+            StubApplication("com.ali.mobisecenhance.StubApplication"),
+            ClassPrefix("com.ali.mobisecenhance"),
+            NativeLibrary(r"^libmobisec[yz]?[0-9]*\.so$"),
+            NativeLibrary(r"^libdemolish\.so$"),
+            Asset(r"^assets/(?:aliprotect\.dat|libpreverify1\.so)$"),
+        ],
+    },
+    Fingerprint {
+        id: "dexprotector",
+        name: "DexProtector",
+        vendor: "Licel",
+        targets: &[
+            NativeLibrary(r"^libdexprotector(?:\.[0-9a-f]+)?\.so$"),
+            NativeLibrary(r"^libdpboot\.so$"),
+            Asset(r"^assets/(?:dp\.arm[-\w]*\.so\.dat|dp\.x86[-\w]*\.so\.dat|dp\.mp3|classes\.dex\.dat)$"),
+        ],
+    },
+    Fingerprint {
+        id: "naga",
+        name: "娜迦加固",
+        vendor: "娜迦信息",
+        targets: &[
+            NativeLibrary(r"^lib(?:chaosvmp|ddog|fdog|edog)\.so$"),
+        ],
+    },
+    Fingerprint {
+        id: "yidun",
+        name: "网易易盾",
+        vendor: "网易",
+        targets: &[
+            StubApplication("com.netease.nis.wrapper.MyApplication"),
+            ClassPrefix("com.netease.nis.wrapper"),
+            NativeLibrary(r"^libnesec(?:-x86)?\.so$"),
+        ],
+    },
+];
+
+/// 一条命中证据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackerEvidence {
+    /// 证据类型：stub_application、class、native_library、asset
+    pub kind: String,
+    /// 命中的类名或文件路径
+    pub matched: String,
+}
+
+/// 识别出的加固
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedPacker {
+    pub id: String,
+    pub name: String,
+    pub vendor: String,
+    /// 从库文件名等处解析出的版本
+    pub version: Option<String>,
+    pub evidence: Vec<PackerEvidence>,
+}
+
+/// 疑似加固的布局：很小的DEX与assets中的高熵数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellLayout {
+    /// 所有DEX定义的类总数
+    pub dex_classes: usize,
+    /// assets中的高熵文件及其熵（比特/字节）
+    pub encrypted_blobs: Vec<(String, f64)>,
+}
+
+/// 加固识别结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackerReport {
+    pub packed: bool,
+    pub packers: Vec<DetectedPacker>,
+    pub shell_layout: Option<ShellLayout>,
+    /// 识别到加固时的提示
+    pub note: Option<String>,
+}
+
+/// 识别加固
+pub fn detect(
+    archive: &mut ZipArchive<File>,
+    dex: Option<&DexAnalysis>,
+    manifest: Option<&AndroidManifest>,
+    budget: &ReadBudget,
+) -> PackerReport {
+    let application = manifest.and_then(|m| m.application.as_ref());
+    let entry_classes: Vec<&str> = application
+        .map(|app| app.name.iter().chain(app.app_component_factory.iter()).map(String::as_str).collect())
+        .unwrap_or_default();
+    let class_names: Vec<String> = dex.map(|dex| dex.class_names().collect()).unwrap_or_default();
+    let file_names: Vec<String> = archive.file_names().map(String::from).collect();
+
+    let mut packers = Vec::new();
+    for fingerprint in FINGERPRINTS {
+        let mut evidence = Vec::new();
+        let mut version = None;
+        for target in fingerprint.targets {
+            match *target {
+                StubApplication(class) => {
+                    evidence.extend(entry_classes.iter()
+                        .filter(|name| **name == class)
+                        .map(|name| PackerEvidence { kind: "stub_application".to_string(), matched: name.to_string() }));
+                }
+                ClassPrefix(prefix) => {
+                    if let Some(class) = class_names.iter().find(|name| name.starts_with(prefix)) {
+                        evidence.push(PackerEvidence { kind: "class".to_string(), matched: class.clone() });
+                    }
+                }
+                NativeLibrary(pattern) => {
+                    let regex = Regex::new(pattern).expect("加固特征正则无效");
+                    for path in file_names.iter().filter(|path| path.starts_with("lib/") || path.starts_with("assets/")) {
+                        let file_name = path.rsplit('/').next().unwrap_or(path);
+                        if let Some(captures) = regex.captures(file_name) {
+                            if version.is_none() {
+                                version = captures.get(1).map(|m| m.as_str().to_string());
+                            }
+                            evidence.push(PackerEvidence { kind: "native_library".to_string(), matched: path.clone() });
+                        }
+                    }
+                }
+                Asset(pattern) => {
+                    let regex = Regex::new(pattern).expect("加固特征正则无效");
+                    evidence.extend(file_names.iter()
+                        .filter(|path| regex.is_match(path))
+                        .map(|path| PackerEvidence { kind: "asset".to_string(), matched: path.clone() }));
+                }
+            }
+        }
+        evidence.dedup_by(|a, b| a.matched == b.matched);
+        if !evidence.is_empty() {
+            packers.push(DetectedPacker {
+                id: fingerprint.id.to_string(),
+                name: fingerprint.name.to_string(),
+                vendor: fingerprint.vendor.to_string(),
+                version,
+                evidence,
+            });
+        }
+    }
+
+    let shell_layout = dex.and_then(|dex| shell_layout(archive, dex, budget));
+    let packed = !packers.is_empty() || shell_layout.is_some();
+    let note = packed.then(|| {
+        let vendor = packers.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join("、");
+        format!(
+            "APK经过{}加固，DEX分析结果反映的是壳代码而非应用本身，\
+             应用代码在运行时解密加载，SDK、混淆与安全检测结果可能不完整",
+            if vendor.is_empty() { "未知厂商".to_string() } else { vendor }
+        )
+    });
+
+    PackerReport { packed, packers, shell_layout, note }
+}
+
+/// 检查“DEX很小而assets中有大块高熵数据”的布局
+fn shell_layout(archive: &mut ZipArchive<File>, dex: &DexAnalysis, budget: &ReadBudget) -> Option<ShellLayout> {
+    if dex.files.is_empty() || dex.total_classes > SHELL_DEX_MAX_CLASSES {
+        return None;
+    }
+    let candidates: Vec<String> = archive.file_names()
+        .filter(|name| name.starts_with("assets/") && !name.ends_with('/'))
+        .filter(|name| !MEDIA_EXTENSIONS.iter().any(|ext| name.to_lowercase().ends_with(ext)))
+        .map(String::from)
+        .collect();
+
+    let mut encrypted_blobs = Vec::new();
+    for name in candidates {
+        let Ok(entry) = archive.by_name(&name) else {
+            continue;
+        };
+        if entry.size() < ENCRYPTED_BLOB_MIN_SIZE {
+            continue;
+        }
+        let Ok(sample) = budget.read_head(entry, ENTROPY_SAMPLE_SIZE) else {
+            continue;
+        };
+        let entropy = byte_entropy(&sample);
+        if entropy >= ENCRYPTED_BLOB_MIN_ENTROPY {
+            encrypted_blobs.push((name, (entropy * 100.0).round() / 100.0));
+        }
+    }
+
+    (!encrypted_blobs.is_empty()).then_some(ShellLayout { dex_classes: dex.total_classes, encrypted_blobs })
+}
+
+/// 字节的香农熵（比特/字节）
+fn byte_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
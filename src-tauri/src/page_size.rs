@@ -0,0 +1,171 @@
+//! 16KB页面大小兼容性检查
+//!
+//! Android 15起部分设备使用16KB内存页。`lib/arm64-v8a`与`lib/x86_64`下的原生库需满足：
+//! 所有PT_LOAD段的`p_align`不小于16384；当`extractNativeLibs=false`时，库文件须以
+//! 不压缩方式存储，且数据在ZIP中的偏移按16KB对齐，才能直接从APK映射加载。
+//! ELF无法解析的库记为未知，只要存在未知的库就不报告为兼容。
+
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::elf::{NativeLibraryAnalysis, PT_LOAD};
+use crate::manifest::AndroidManifest;
+
+/// 16KB页面大小
+pub const PAGE_SIZE_16K: u64 = 16 * 1024;
+
+/// 需要检查的ABI（仅64位ABI会运行在16KB页面设备上）
+const CHECKED_ABIS: &[&str] = &["arm64-v8a", "x86_64"];
+
+/// 对齐不足的PT_LOAD段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MisalignedSegment {
+    /// 程序头序号
+    pub index: usize,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub align: u64,
+}
+
+/// 单个原生库的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryPageSizeCheck {
+    pub path: String,
+    pub abi: String,
+    pub passed: bool,
+    /// ELF无法解析，段对齐情况未知
+    pub unknown: bool,
+    /// 所有PT_LOAD段中最小的对齐值
+    pub min_load_align: Option<u64>,
+    /// `p_align`小于16KB的PT_LOAD段
+    pub misaligned_segments: Vec<MisalignedSegment>,
+    /// 是否以不压缩方式存储
+    pub stored: bool,
+    /// 库数据在APK中的偏移
+    pub data_offset: u64,
+    /// ZIP层面是否按16KB对齐，仅在`extractNativeLibs=false`时检查
+    pub zip_aligned: Option<bool>,
+    /// 未通过的原因
+    pub issues: Vec<String>,
+}
+
+/// 16KB页面大小兼容性报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageSizeReport {
+    /// 所有被检查的库均通过，存在无法判断的库时为false
+    pub compatible: bool,
+    /// 无法判断是否兼容的库数量
+    pub unknown_count: usize,
+    /// 清单中的`extractNativeLibs`值
+    pub extract_native_libs: Option<bool>,
+    pub libraries: Vec<LibraryPageSizeCheck>,
+}
+
+impl PageSizeReport {
+    /// 未通过检查的库
+    pub fn failures(&self) -> impl Iterator<Item = &LibraryPageSizeCheck> {
+        self.libraries.iter().filter(|lib| !lib.passed && !lib.unknown)
+    }
+
+    /// ELF无法解析、无法判断是否兼容的库
+    pub fn unknowns(&self) -> impl Iterator<Item = &LibraryPageSizeCheck> {
+        self.libraries.iter().filter(|lib| lib.unknown)
+    }
+}
+
+/// 检查64位ABI原生库的段对齐与ZIP对齐
+pub fn check(
+    archive: &mut ZipArchive<File>,
+    native_libraries: &NativeLibraryAnalysis,
+    manifest: Option<&AndroidManifest>,
+) -> PageSizeReport {
+    let extract_native_libs = manifest
+        .and_then(|manifest| manifest.application.as_ref())
+        .and_then(|application| application.extract_native_libs);
+    // 未声明时平台默认解压原生库，此时无需ZIP对齐
+    let check_zip_alignment = extract_native_libs == Some(false);
+
+    // 以存档中的条目为准，解析失败的库不在native_libraries.libraries中
+    let mut paths: Vec<(String, String)> = archive.file_names()
+        .filter_map(|name| {
+            let mut parts = name.split('/');
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some("lib"), Some(abi), Some(file), None) if CHECKED_ABIS.contains(&abi) && file.ends_with(".so") => {
+                    Some((name.to_string(), abi.to_string()))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    paths.sort();
+
+    let mut libraries = Vec::new();
+    for (path, abi) in paths {
+        let elf = native_libraries.libraries.iter()
+            .find(|library| library.path == path)
+            .and_then(|library| library.elf.as_ref());
+
+        let (stored, data_offset) = match archive.by_name(&path) {
+            Ok(entry) => (entry.compression() == CompressionMethod::Stored, entry.data_start()),
+            Err(_) => continue,
+        };
+
+        let mut issues = Vec::new();
+        let (min_load_align, misaligned_segments) = match elf {
+            Some(elf) => {
+                let loads: Vec<_> = elf.program_headers.iter().enumerate()
+                    .filter(|(_, ph)| ph.p_type == PT_LOAD)
+                    .collect();
+                let misaligned_segments: Vec<MisalignedSegment> = loads.iter()
+                    .filter(|(_, ph)| ph.align < PAGE_SIZE_16K)
+                    .map(|(index, ph)| MisalignedSegment { index: *index, offset: ph.offset, vaddr: ph.vaddr, align: ph.align })
+                    .collect();
+                (loads.iter().map(|(_, ph)| ph.align).min(), misaligned_segments)
+            }
+            None => {
+                let prefix = format!("{}: ", path);
+                let reason = native_libraries.errors.iter()
+                    .find_map(|error| error.strip_prefix(&prefix))
+                    .unwrap_or("未解析");
+                issues.push(format!("ELF无法解析（{}），无法判断段对齐", reason));
+                (None, Vec::new())
+            }
+        };
+        for segment in &misaligned_segments {
+            issues.push(format!("LOAD段#{}的p_align为0x{:x}，小于0x{:x}", segment.index, segment.align, PAGE_SIZE_16K));
+        }
+        let zip_aligned = check_zip_alignment.then(|| {
+            if !stored {
+                issues.push("extractNativeLibs=false时库文件被压缩存储".to_string());
+                false
+            } else if data_offset % PAGE_SIZE_16K != 0 {
+                issues.push(format!("ZIP数据偏移0x{:x}未按16KB对齐", data_offset));
+                false
+            } else {
+                true
+            }
+        });
+
+        libraries.push(LibraryPageSizeCheck {
+            path,
+            abi,
+            passed: issues.is_empty(),
+            unknown: elf.is_none(),
+            min_load_align,
+            misaligned_segments,
+            stored,
+            data_offset,
+            zip_aligned,
+            issues,
+        });
+    }
+
+    PageSizeReport {
+        compatible: libraries.iter().all(|lib| lib.passed),
+        unknown_count: libraries.iter().filter(|lib| lib.unknown).count(),
+        extract_native_libs,
+        libraries,
+    }
+}
@@ -0,0 +1,408 @@
+//! 证书固定（Certificate Pinning）识别
+//!
+//! 从三个来源识别证书固定：网络安全配置中的`<pin-set>`、DEX中OkHttp `CertificatePinner`
+//! 的调用及`sha256/`固定值，以及自定义的`X509TrustManager`/`HostnameVerifier`实现。
+//! 后者若不做任何校验（信任所有证书或主机名），会单独标记为宽松实现。
+
+use std::fs::File;
+
+use chrono::{NaiveDate, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::axml::{self, XmlElement};
+use crate::dex::{ClassDef, CodeItem, DexAnalysis, DexFile};
+use crate::limits::ReadBudget;
+
+/// 未在清单中声明时按惯例查找的网络安全配置
+const DEFAULT_CONFIG_PATH: &str = "res/xml/network_security_config.xml";
+
+/// OkHttp 3/4与OkHttp 2中添加固定值的方法
+const PINNER_ADD_METHODS: &[&str] = &[
+    "okhttp3.CertificatePinner$Builder.add",
+    "com.squareup.okhttp.CertificatePinner$Builder.add",
+];
+/// 表明使用了CertificatePinner的方法引用
+const PINNER_USAGE_METHODS: &[&str] = &[
+    "okhttp3.OkHttpClient$Builder.certificatePinner",
+    "com.squareup.okhttp.OkHttpClient.setCertificatePinner",
+];
+
+const TRUST_MANAGER_INTERFACES: &[&str] = &["javax.net.ssl.X509TrustManager", "javax.net.ssl.TrustManager"];
+const TRUST_MANAGER_SUPERCLASS: &str = "javax.net.ssl.X509ExtendedTrustManager";
+const HOSTNAME_VERIFIER_INTERFACE: &str = "javax.net.ssl.HostnameVerifier";
+
+/// 框架自带的不校验主机名的实现
+const ALLOW_ALL_VERIFIERS: &[&str] = &[
+    "org.apache.http.conn.ssl.AllowAllHostnameVerifier",
+    "org.apache.http.conn.ssl.SSLSocketFactory.ALLOW_ALL_HOSTNAME_VERIFIER",
+];
+
+/// 每个DEX中最多记录的未归属固定值
+const MAX_UNATTRIBUTED_PINS: usize = 50;
+
+/// 一个固定值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pin {
+    /// 摘要算法，如`SHA-256`
+    pub digest: String,
+    /// Base64编码的SubjectPublicKeyInfo摘要
+    pub value: String,
+}
+
+/// 网络安全配置中的域名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDomain {
+    pub name: String,
+    pub include_subdomains: bool,
+}
+
+/// 信任锚
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustAnchor {
+    /// `system`、`user`或证书资源引用
+    pub source: String,
+    /// 该信任锚签发的证书是否绕过固定
+    pub override_pins: bool,
+}
+
+/// 带有`<pin-set>`的`<domain-config>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainPinSet {
+    pub domains: Vec<ConfigDomain>,
+    pub pins: Vec<Pin>,
+    /// 固定失效日期（`yyyy-MM-dd`），过期后不再校验固定值
+    pub expiration: Option<String>,
+    pub expired: bool,
+    pub trust_anchors: Vec<TrustAnchor>,
+}
+
+/// 网络安全配置中的固定设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSecurityConfigPins {
+    /// 配置文件在APK中的路径
+    pub path: String,
+    pub pin_sets: Vec<DomainPinSet>,
+    /// `<debug-overrides>`中的信任锚（仅在debuggable时生效）
+    pub debug_overrides: Vec<TrustAnchor>,
+}
+
+/// DEX中发现的固定值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexPin {
+    /// 所在DEX与方法，如`classes.dex: com.example.Api.client`
+    pub location: String,
+    /// 调用`CertificatePinner.Builder.add`时传入的主机名；仅出现在字符串池中时为空
+    pub host: Option<String>,
+    /// 固定值，如`sha256/AAAA…=`
+    pub pin: String,
+}
+
+/// 自定义证书/主机名校验实现
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomTrustComponent {
+    /// `trust_manager`或`hostname_verifier`
+    pub kind: String,
+    pub class_name: String,
+    /// 所在DEX
+    pub location: String,
+    /// 是否不做任何校验
+    pub permissive: bool,
+    pub reason: Option<String>,
+}
+
+/// 证书固定识别结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CertificatePinningReport {
+    pub uses_certificate_pinning: bool,
+    pub network_security_config: Option<NetworkSecurityConfigPins>,
+    /// 是否调用了OkHttp CertificatePinner
+    pub okhttp_certificate_pinner: bool,
+    pub dex_pins: Vec<DexPin>,
+    pub custom_trust_components: Vec<CustomTrustComponent>,
+    /// 所有来源汇总的固定主机
+    pub pinned_hosts: Vec<String>,
+    /// 所有来源汇总的固定值（`算法/Base64`）
+    pub pin_hashes: Vec<String>,
+    /// 是否存在信任所有证书或主机名的实现
+    pub has_permissive_trust: bool,
+}
+
+/// 识别证书固定
+///
+/// # 参数
+/// * `archive` - APK存档
+/// * `dex` - DEX解析结果
+/// * `config_path` - 清单`android:networkSecurityConfig`解析出的文件路径
+/// * `budget` - 读取预算
+pub fn analyze(
+    archive: &mut ZipArchive<File>,
+    dex: Option<&DexAnalysis>,
+    config_path: Option<&str>,
+    budget: &ReadBudget,
+) -> CertificatePinningReport {
+    let mut report = CertificatePinningReport::default();
+
+    let path = config_path
+        .filter(|path| archive.by_name(path).is_ok())
+        .or_else(|| archive.by_name(DEFAULT_CONFIG_PATH).is_ok().then_some(DEFAULT_CONFIG_PATH))
+        .map(String::from);
+    if let Some(path) = path {
+        match read_network_security_config(archive, &path, budget) {
+            Ok(config) => report.network_security_config = Some(config),
+            Err(e) => println!("WARN: 解析网络安全配置失败: {}", e),
+        }
+    }
+
+    if let Some(dex) = dex {
+        for (name, file) in &dex.files {
+            scan_dex(name, file, &mut report);
+        }
+    }
+
+    summarize(&mut report);
+    report
+}
+
+fn read_network_security_config(
+    archive: &mut ZipArchive<File>,
+    path: &str,
+    budget: &ReadBudget,
+) -> Result<NetworkSecurityConfigPins, String> {
+    let data = budget.read_entry(archive, path).map_err(|e| e.to_string())?;
+    let document = axml::parse_any(&data, budget.limits()).map_err(|e| e.to_string())?;
+
+    let root = &document.root;
+    let pin_sets = root.children_named("domain-config")
+        .flat_map(collect_domain_configs)
+        .collect();
+    let debug_overrides = root.child("debug-overrides")
+        .map(trust_anchors)
+        .unwrap_or_default();
+    Ok(NetworkSecurityConfigPins { path: path.to_string(), pin_sets, debug_overrides })
+}
+
+/// `<domain-config>`可以嵌套，内层未设置固定时继承外层
+fn collect_domain_configs(element: &XmlElement) -> Vec<DomainPinSet> {
+    let mut result = Vec::new();
+    if let Some(pin_set) = element.child("pin-set") {
+        let expiration = pin_set.attr_string("expiration");
+        let expired = expiration.as_deref()
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .is_some_and(|date| date < Utc::now().date_naive());
+        result.push(DomainPinSet {
+            domains: config_domains(element),
+            pins: pin_set.children_named("pin")
+                .map(|pin| Pin {
+                    digest: pin.attr_string("digest").unwrap_or_else(|| "SHA-256".to_string()),
+                    value: pin.text.clone().unwrap_or_default().trim().to_string(),
+                })
+                .collect(),
+            expiration,
+            expired,
+            trust_anchors: trust_anchors(element),
+        });
+    }
+    for child in element.children_named("domain-config") {
+        let mut nested = collect_domain_configs(child);
+        if nested.is_empty() && !result.is_empty() {
+            // 内层域名沿用外层的pin-set
+            let mut inherited = result[0].clone();
+            inherited.domains = config_domains(child);
+            nested.push(inherited);
+        }
+        result.extend(nested);
+    }
+    result
+}
+
+fn config_domains(element: &XmlElement) -> Vec<ConfigDomain> {
+    element.children_named("domain")
+        .map(|domain| ConfigDomain {
+            name: domain.text.clone().unwrap_or_default().trim().to_string(),
+            include_subdomains: domain.attr_string("includeSubdomains").as_deref() == Some("true"),
+        })
+        .collect()
+}
+
+fn trust_anchors(element: &XmlElement) -> Vec<TrustAnchor> {
+    element.child("trust-anchors")
+        .map(|anchors| {
+            anchors.children_named("certificates")
+                .map(|cert| TrustAnchor {
+                    source: cert.attr_string("src").unwrap_or_default(),
+                    override_pins: cert.attr_string("overridePins").as_deref() == Some("true"),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `sha256/`或`sha1/`开头的Base64固定值
+fn pin_regex() -> Regex {
+    Regex::new(r"^(sha256/[A-Za-z0-9+/]{43}=|sha1/[A-Za-z0-9+/]{27}=)$").unwrap()
+}
+
+fn scan_dex(name: &str, dex: &DexFile, report: &mut CertificatePinningReport) {
+    let pin_regex = pin_regex();
+    let method_names: Vec<Option<String>> = (0..dex.method_ids.len() as u32).map(|idx| dex.method_name_at(idx)).collect();
+    let method_is = |idx: u32, names: &[&str]| {
+        method_names.get(idx as usize)
+            .and_then(|name| name.as_deref())
+            .is_some_and(|name| names.contains(&name))
+    };
+    if method_names.iter().flatten().any(|m| PINNER_ADD_METHODS.contains(&m.as_str()) || PINNER_USAGE_METHODS.contains(&m.as_str())) {
+        report.okhttp_certificate_pinner = true;
+    }
+
+    for class in &dex.class_defs {
+        let Some(class_name) = dex.type_name(class.class_idx) else {
+            continue;
+        };
+
+        for method in &class.methods {
+            let Some(code) = &method.code else {
+                continue;
+            };
+            // 在调用CertificatePinner.Builder.add的方法中，把前面加载的主机名与固定值对应起来
+            if !code.instructions().any(|insn| insn.method_index().is_some_and(|idx| method_is(idx, PINNER_ADD_METHODS))) {
+                continue;
+            }
+            let location = format!(
+                "{}: {}",
+                name,
+                method_names.get(method.method_idx as usize).cloned().flatten().unwrap_or_else(|| class_name.clone())
+            );
+            let mut host = None;
+            let mut pins = Vec::new();
+            for insn in code.instructions() {
+                if let Some(string) = insn.string_index().and_then(|idx| dex.strings.get(idx as usize)) {
+                    if pin_regex.is_match(string) {
+                        pins.push(string.clone());
+                    } else {
+                        host = Some(string.clone());
+                    }
+                } else if insn.method_index().is_some_and(|idx| method_is(idx, PINNER_ADD_METHODS)) {
+                    for pin in pins.drain(..) {
+                        report.dex_pins.push(DexPin { location: location.clone(), host: host.clone(), pin });
+                    }
+                    host = None;
+                }
+            }
+        }
+
+        if let Some(component) = classify_trust_component(dex, class, &class_name, name) {
+            report.custom_trust_components.push(component);
+        }
+    }
+
+    // 字符串池中未能归属到调用处的固定值
+    let unattributed: Vec<&String> = dex.strings.iter()
+        .filter(|s| pin_regex.is_match(s))
+        .filter(|s| !report.dex_pins.iter().any(|pin| &pin.pin == *s))
+        .take(MAX_UNATTRIBUTED_PINS)
+        .collect();
+    for pin in unattributed {
+        report.dex_pins.push(DexPin { location: name.to_string(), host: None, pin: pin.clone() });
+    }
+
+    let allow_all = dex.class_names().into_iter()
+        .chain(dex.field_ids.iter().filter_map(|field| {
+            let class = dex.type_name(field.class_idx as u32)?;
+            Some(format!("{}.{}", class, dex.strings.get(field.name_idx as usize)?))
+        }))
+        .chain(method_names.iter().flatten().cloned())
+        .find(|name| ALLOW_ALL_VERIFIERS.iter().any(|v| name == v || name.starts_with(&format!("{}.", v))));
+    if let Some(reference) = allow_all {
+        report.custom_trust_components.push(CustomTrustComponent {
+            kind: "hostname_verifier".to_string(),
+            class_name: reference,
+            location: name.to_string(),
+            permissive: true,
+            reason: Some("引用了不校验主机名的ALLOW_ALL主机名校验器".to_string()),
+        });
+    }
+}
+
+/// 识别TrustManager与HostnameVerifier实现，并检查校验方法是否为空实现
+fn classify_trust_component(dex: &DexFile, class: &ClassDef, class_name: &str, location: &str) -> Option<CustomTrustComponent> {
+    let interfaces = dex.interface_names(class);
+    let superclass = class.superclass_idx.and_then(|idx| dex.type_name(idx));
+    let method_code = |name: &str| {
+        class.methods.iter()
+            .filter(|m| dex.method_ids.get(m.method_idx as usize)
+                .and_then(|id| dex.strings.get(id.name_idx as usize))
+                .is_some_and(|n| n == name))
+            .filter_map(|m| m.code.as_ref())
+            .collect::<Vec<_>>()
+    };
+
+    let (kind, permissive, reason) = if interfaces.iter().any(|i| TRUST_MANAGER_INTERFACES.contains(&i.as_str()))
+        || superclass.as_deref() == Some(TRUST_MANAGER_SUPERCLASS)
+    {
+        let checks = method_code("checkServerTrusted");
+        let permissive = !checks.is_empty() && checks.iter().all(|code| is_empty_method(code));
+        ("trust_manager", permissive, permissive.then(|| "checkServerTrusted为空实现，信任所有服务器证书".to_string()))
+    } else if interfaces.iter().any(|i| i == HOSTNAME_VERIFIER_INTERFACE) {
+        let verifies = method_code("verify");
+        let permissive = !verifies.is_empty() && verifies.iter().all(|code| returns_true(code));
+        ("hostname_verifier", permissive, permissive.then(|| "verify始终返回true，接受任意主机名".to_string()))
+    } else {
+        return None;
+    };
+
+    Some(CustomTrustComponent {
+        kind: kind.to_string(),
+        class_name: class_name.to_string(),
+        location: location.to_string(),
+        permissive,
+        reason,
+    })
+}
+
+/// 方法体只有`return-void`（及`nop`）
+fn is_empty_method(code: &CodeItem) -> bool {
+    code.instructions().all(|insn| insn.opcode == 0x0e || insn.opcode == 0x00)
+}
+
+/// 方法体为`const/4 vA, 1; return vA`
+fn returns_true(code: &CodeItem) -> bool {
+    let instructions: Vec<_> = code.instructions().collect();
+    matches!(instructions.as_slice(), [load, ret] if load.literal() == Some(1) && ret.opcode == 0x0f)
+}
+
+fn summarize(report: &mut CertificatePinningReport) {
+    let mut hosts: Vec<String> = Vec::new();
+    let mut hashes: Vec<String> = Vec::new();
+    let push = |list: &mut Vec<String>, value: String| {
+        if !value.is_empty() && !list.contains(&value) {
+            list.push(value);
+        }
+    };
+
+    if let Some(config) = &report.network_security_config {
+        for pin_set in &config.pin_sets {
+            for domain in &pin_set.domains {
+                push(&mut hosts, if domain.include_subdomains { format!("*.{}", domain.name) } else { domain.name.clone() });
+            }
+            for pin in &pin_set.pins {
+                let algorithm = pin.digest.to_lowercase().replace('-', "");
+                push(&mut hashes, format!("{}/{}", algorithm, pin.value));
+            }
+        }
+    }
+    for pin in &report.dex_pins {
+        if let Some(host) = &pin.host {
+            push(&mut hosts, host.clone());
+        }
+        push(&mut hashes, pin.pin.clone());
+    }
+
+    let config_pins = report.network_security_config.as_ref()
+        .is_some_and(|config| config.pin_sets.iter().any(|set| !set.pins.is_empty()));
+    report.uses_certificate_pinning = config_pins || report.okhttp_certificate_pinner || !report.dex_pins.is_empty();
+    report.has_permissive_trust = report.custom_trust_components.iter().any(|c| c.permissive);
+    report.pinned_hosts = hosts;
+    report.pin_hashes = hashes;
+}
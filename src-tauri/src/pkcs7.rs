@@ -0,0 +1,552 @@
+//! PKCS#7 / CMS SignedData 解析器
+//!
+//! v1（JAR）签名把签名块保存在`META-INF/*.RSA|*.DSA|*.EC`中，其内容是一个
+//! ContentInfo包装的SignedData（RFC 5652），而不是裸的X.509证书。
+//! 这里只实现读取所需的最小DER解码：证书集合、签名者信息、摘要/签名算法以及签名时间。
+
+use serde::{Deserialize, Serialize};
+use x509_parser::prelude::FromDer;
+
+use crate::apk_parser::ApkParserError;
+
+// 通用DER标签
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_UTC_TIME: u8 = 0x17;
+pub(crate) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+
+// 上下文标签
+const TAG_CONTEXT_0: u8 = 0xA0;
+const TAG_CONTEXT_1: u8 = 0xA1;
+const TAG_IMPLICIT_0: u8 = 0x80;
+
+// 对象标识符
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_CONTENT_TYPE: &str = "1.2.840.113549.1.9.3";
+const OID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+
+/// 不定长编码的最大嵌套深度
+const MAX_INDEFINITE_DEPTH: usize = 32;
+
+/// 常见算法OID与可读名称的对照表
+const OID_NAMES: &[(&str, &str)] = &[
+    ("1.2.840.113549.2.5", "MD5"),
+    ("1.3.14.3.2.26", "SHA-1"),
+    ("2.16.840.1.101.3.4.2.4", "SHA-224"),
+    ("2.16.840.1.101.3.4.2.1", "SHA-256"),
+    ("2.16.840.1.101.3.4.2.2", "SHA-384"),
+    ("2.16.840.1.101.3.4.2.3", "SHA-512"),
+    ("1.2.840.113549.1.1.1", "RSA"),
+    ("1.2.840.113549.1.1.4", "MD5withRSA"),
+    ("1.2.840.113549.1.1.5", "SHA1withRSA"),
+    ("1.2.840.113549.1.1.14", "SHA224withRSA"),
+    ("1.2.840.113549.1.1.11", "SHA256withRSA"),
+    ("1.2.840.113549.1.1.12", "SHA384withRSA"),
+    ("1.2.840.113549.1.1.13", "SHA512withRSA"),
+    ("1.2.840.113549.1.1.10", "RSASSA-PSS"),
+    ("1.2.840.10040.4.1", "DSA"),
+    ("1.2.840.10040.4.3", "SHA1withDSA"),
+    ("2.16.840.1.101.3.4.3.1", "SHA224withDSA"),
+    ("2.16.840.1.101.3.4.3.2", "SHA256withDSA"),
+    ("1.2.840.10045.2.1", "EC"),
+    ("1.2.840.10045.4.1", "SHA1withECDSA"),
+    ("1.2.840.10045.4.3.1", "SHA224withECDSA"),
+    ("1.2.840.10045.4.3.2", "SHA256withECDSA"),
+    ("1.2.840.10045.4.3.3", "SHA384withECDSA"),
+    ("1.2.840.10045.4.3.4", "SHA512withECDSA"),
+    ("1.2.840.10045.3.1.7", "P-256"),
+    ("1.3.132.0.34", "P-384"),
+    ("1.3.132.0.35", "P-521"),
+    ("1.3.101.112", "Ed25519"),
+    ("2.5.29.14", "subjectKeyIdentifier"),
+    ("2.5.29.15", "keyUsage"),
+    ("2.5.29.17", "subjectAltName"),
+    ("2.5.29.19", "basicConstraints"),
+    ("2.5.29.31", "cRLDistributionPoints"),
+    ("2.5.29.32", "certificatePolicies"),
+    ("2.5.29.35", "authorityKeyIdentifier"),
+    ("2.5.29.37", "extKeyUsage"),
+    ("1.3.6.1.5.5.7.1.1", "authorityInfoAccess"),
+    ("1.2.840.113549.1.7.1", "data"),
+    ("1.2.840.113549.1.7.2", "signedData"),
+];
+
+/// 返回算法OID的可读名称，未知OID原样返回
+pub fn oid_name(oid: &str) -> String {
+    OID_NAMES
+        .iter()
+        .find(|(known, _)| *known == oid)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| oid.to_string())
+}
+
+/// 一个DER编码的TLV
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    /// 内容部分
+    pub content: &'a [u8],
+    /// 包含标签与长度的完整编码
+    pub raw: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// 逐个读取内容中的子元素
+    pub fn children(&self) -> Result<Vec<Tlv<'a>>, ApkParserError> {
+        read_all(self.content)
+    }
+
+    fn expect(self, tag: u8, what: &str) -> Result<Self, ApkParserError> {
+        if self.tag == tag {
+            Ok(self)
+        } else {
+            Err(invalid(format!("{}的标签应为0x{:02x}，实际为0x{:02x}", what, tag, self.tag)))
+        }
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidSignature(msg.into())
+}
+
+/// 从`data[*pos..]`读取一个TLV并前移位置
+pub(crate) fn read_tlv<'a>(data: &'a [u8], pos: &mut usize) -> Result<Tlv<'a>, ApkParserError> {
+    read_tlv_nested(data, pos, 0)
+}
+
+fn read_tlv_nested<'a>(data: &'a [u8], pos: &mut usize, depth: usize) -> Result<Tlv<'a>, ApkParserError> {
+    let start = *pos;
+    let tag = *data.get(start).ok_or_else(|| invalid("DER数据意外结束"))?;
+    if tag & 0x1F == 0x1F {
+        return Err(invalid("不支持多字节标签"));
+    }
+    let first = *data.get(start + 1).ok_or_else(|| invalid("DER长度缺失"))?;
+    let mut cursor = start + 2;
+
+    let content_len = if first & 0x80 == 0 {
+        first as usize
+    } else if first == 0x80 {
+        // BER不定长编码：只允许构造类型，内容以00 00结束
+        if tag & 0x20 == 0 || depth >= MAX_INDEFINITE_DEPTH {
+            return Err(invalid("无效的不定长编码"));
+        }
+        let content_start = cursor;
+        while data.get(cursor..cursor + 2) != Some(&[0, 0][..]) {
+            read_tlv_nested(data, &mut cursor, depth + 1)?;
+        }
+        *pos = cursor + 2;
+        return Ok(Tlv {
+            tag,
+            content: &data[content_start..cursor],
+            raw: &data[start..*pos],
+        });
+    } else {
+        let count = (first & 0x7F) as usize;
+        if count > 4 {
+            return Err(invalid("DER长度字段过长"));
+        }
+        let bytes = data.get(cursor..cursor + count).ok_or_else(|| invalid("DER长度字段越界"))?;
+        cursor += count;
+        bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+    };
+
+    let end = cursor
+        .checked_add(content_len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| invalid("DER内容超出数据范围"))?;
+    *pos = end;
+    Ok(Tlv {
+        tag,
+        content: &data[cursor..end],
+        raw: &data[start..end],
+    })
+}
+
+/// 读取一段数据中的全部TLV
+pub(crate) fn read_all(data: &[u8]) -> Result<Vec<Tlv<'_>>, ApkParserError> {
+    let mut pos = 0;
+    let mut items = Vec::new();
+    while pos < data.len() {
+        items.push(read_tlv(data, &mut pos)?);
+    }
+    Ok(items)
+}
+
+/// 将OID内容解码为点分十进制字符串
+pub(crate) fn decode_oid(content: &[u8]) -> Result<String, ApkParserError> {
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut value: u64 = 0;
+    for (i, byte) in content.iter().enumerate() {
+        if value > (u64::MAX >> 7) {
+            return Err(invalid("OID分量过大"));
+        }
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        } else if i + 1 == content.len() {
+            return Err(invalid("OID编码不完整"));
+        }
+    }
+    if arcs.is_empty() {
+        return Err(invalid("空的OID"));
+    }
+    Ok(arcs.iter().map(|a| a.to_string()).collect::<Vec<_>>().join("."))
+}
+
+/// 读取AlgorithmIdentifier中的算法OID
+pub(crate) fn algorithm_oid(tlv: &Tlv) -> Result<String, ApkParserError> {
+    let tlv = tlv.expect(TAG_SEQUENCE, "AlgorithmIdentifier")?;
+    let children = tlv.children()?;
+    let oid = children.first().ok_or_else(|| invalid("AlgorithmIdentifier为空"))?;
+    decode_oid(oid.expect(TAG_OID, "算法OID")?.content)
+}
+
+/// 把INTEGER内容格式化为十六进制（去掉前导零）
+pub(crate) fn integer_hex(content: &[u8]) -> String {
+    let trimmed: &[u8] = match content.iter().position(|b| *b != 0) {
+        Some(index) => &content[index..],
+        None => &[0],
+    };
+    hex(trimmed)
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn small_integer(tlv: &Tlv) -> Result<u32, ApkParserError> {
+    let tlv = tlv.expect(TAG_INTEGER, "版本号")?;
+    if tlv.content.len() > 4 {
+        return Err(invalid("版本号过大"));
+    }
+    Ok(tlv.content.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32))
+}
+
+/// 解析UTCTime/GeneralizedTime并格式化为RFC 2822
+fn decode_time(tlv: &Tlv) -> Option<String> {
+    let text = std::str::from_utf8(tlv.content).ok()?;
+    let text = text.strip_suffix('Z')?;
+    let (year, rest) = match tlv.tag {
+        TAG_UTC_TIME => {
+            let yy: i32 = text.get(0..2)?.parse().ok()?;
+            (if yy >= 50 { 1900 + yy } else { 2000 + yy }, text.get(2..)?)
+        }
+        TAG_GENERALIZED_TIME => (text.get(0..4)?.parse().ok()?, text.get(4..)?),
+        _ => return None,
+    };
+    let field = |range: std::ops::Range<usize>| -> Option<u32> { rest.get(range)?.parse().ok() };
+    let seconds = if rest.len() >= 10 { field(8..10)? } else { 0 };
+    let date = chrono::NaiveDate::from_ymd_opt(year, field(0..2)?, field(2..4)?)?;
+    let time = date.and_hms_opt(field(4..6)?, field(6..8)?, seconds)?;
+    Some(time.and_utc().to_rfc2822())
+}
+
+/// SignedData中的一个签名者
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignerInfo {
+    pub version: u32,
+    /// 签名证书的颁发者（IssuerAndSerialNumber形式）
+    pub issuer: Option<String>,
+    /// 签名证书序列号（十六进制）
+    pub serial_number: Option<String>,
+    /// 签名证书的主题密钥标识符（SubjectKeyIdentifier形式）
+    pub subject_key_identifier: Option<String>,
+    /// 摘要算法
+    pub digest_algorithm: String,
+    /// 签名算法
+    pub signature_algorithm: String,
+    /// 签名时间（仅当签名属性中存在signingTime时）
+    pub signing_time: Option<String>,
+    /// 签名属性中的messageDigest（十六进制）
+    pub message_digest: Option<String>,
+    /// 签名属性中声明的内容类型
+    pub content_type: Option<String>,
+    /// 签名属性的DER编码（已还原为SET标签，用于验签）
+    #[serde(skip)]
+    pub signed_attributes: Option<Vec<u8>>,
+    /// 签名值
+    #[serde(skip)]
+    pub signature: Vec<u8>,
+    #[serde(skip)]
+    issuer_raw: Vec<u8>,
+    #[serde(skip)]
+    serial_raw: Vec<u8>,
+}
+
+impl SignerInfo {
+    fn parse(tlv: &Tlv) -> Result<Self, ApkParserError> {
+        let children = tlv.expect(TAG_SEQUENCE, "SignerInfo")?.children()?;
+        let mut iter = children.iter();
+        let mut next = |what: &str| iter.next().copied().ok_or_else(|| invalid(format!("SignerInfo缺少{}", what)));
+
+        let mut signer = SignerInfo {
+            version: small_integer(&next("版本号")?)?,
+            ..Default::default()
+        };
+
+        let sid = next("签名者标识")?;
+        match sid.tag {
+            TAG_SEQUENCE => {
+                let parts = sid.children()?;
+                let (issuer, serial) = match parts.as_slice() {
+                    [issuer, serial] => (issuer, serial.expect(TAG_INTEGER, "序列号")?),
+                    _ => return Err(invalid("无效的IssuerAndSerialNumber")),
+                };
+                signer.issuer = x509_parser::x509::X509Name::from_der(issuer.raw)
+                    .ok()
+                    .map(|(_, name)| name.to_string());
+                signer.serial_number = Some(integer_hex(serial.content));
+                signer.issuer_raw = issuer.raw.to_vec();
+                signer.serial_raw = serial.content.to_vec();
+            }
+            TAG_IMPLICIT_0 => signer.subject_key_identifier = Some(hex(sid.content)),
+            other => return Err(invalid(format!("未知的签名者标识标签0x{:02x}", other))),
+        }
+
+        signer.digest_algorithm = oid_name(&algorithm_oid(&next("摘要算法")?)?);
+
+        let mut item = next("签名算法")?;
+        if item.tag == TAG_CONTEXT_0 {
+            signer.parse_signed_attributes(&item)?;
+            item = next("签名算法")?;
+        }
+        signer.signature_algorithm = oid_name(&algorithm_oid(&item)?);
+        signer.signature = next("签名值")?.expect(TAG_OCTET_STRING, "签名值")?.content.to_vec();
+        Ok(signer)
+    }
+
+    fn parse_signed_attributes(&mut self, attrs: &Tlv) -> Result<(), ApkParserError> {
+        // 签名计算在以SET OF标签编码的属性上进行
+        let mut encoded = attrs.raw.to_vec();
+        encoded[0] = TAG_SET;
+        self.signed_attributes = Some(encoded);
+
+        for attr in attrs.children()? {
+            let parts = attr.expect(TAG_SEQUENCE, "签名属性")?.children()?;
+            let (oid, values) = match parts.as_slice() {
+                [oid, values] => (decode_oid(oid.expect(TAG_OID, "属性OID")?.content)?, values.children()?),
+                _ => continue,
+            };
+            let value = match values.first() {
+                Some(value) => value,
+                None => continue,
+            };
+            match oid.as_str() {
+                OID_SIGNING_TIME => self.signing_time = decode_time(value),
+                OID_MESSAGE_DIGEST if value.tag == TAG_OCTET_STRING => self.message_digest = Some(hex(value.content)),
+                OID_CONTENT_TYPE if value.tag == TAG_OID => {
+                    self.content_type = decode_oid(value.content).ok().map(|o| oid_name(&o));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 判断给定证书是否为该签名者的证书
+    pub fn matches_certificate(&self, cert: &x509_parser::certificate::X509Certificate) -> bool {
+        if !self.serial_raw.is_empty() {
+            let serial = cert.tbs_certificate.raw_serial();
+            let strip = |bytes: &[u8]| -> Vec<u8> { bytes.iter().skip_while(|b| **b == 0).copied().collect() };
+            return strip(serial) == strip(&self.serial_raw) && cert.tbs_certificate.issuer.as_raw() == self.issuer_raw.as_slice();
+        }
+        match (&self.subject_key_identifier, cert.tbs_certificate.extensions().iter().find_map(|ext| {
+            match ext.parsed_extension() {
+                x509_parser::extensions::ParsedExtension::SubjectKeyIdentifier(id) => Some(hex(id.0)),
+                _ => None,
+            }
+        })) {
+            (Some(expected), Some(actual)) => *expected == actual,
+            _ => false,
+        }
+    }
+}
+
+/// CMS SignedData
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignedData {
+    pub version: u32,
+    /// 摘要算法集合
+    pub digest_algorithms: Vec<String>,
+    /// 被签名内容的类型
+    pub content_type: String,
+    /// 是否为分离式签名（内容不在SignedData中，v1签名中即.SF文件）
+    pub detached: bool,
+    /// 嵌入的全部证书（DER编码，保持原始顺序）
+    #[serde(skip)]
+    pub certificates: Vec<Vec<u8>>,
+    /// 签名者
+    pub signers: Vec<SignerInfo>,
+}
+
+impl SignedData {
+    /// 解析ContentInfo包装的SignedData
+    pub fn parse(data: &[u8]) -> Result<Self, ApkParserError> {
+        let mut pos = 0;
+        let content_info = read_tlv(data, &mut pos)?.expect(TAG_SEQUENCE, "ContentInfo")?;
+        let parts = content_info.children()?;
+        let (content_type, content) = match parts.as_slice() {
+            [oid, content, ..] => (decode_oid(oid.expect(TAG_OID, "ContentInfo类型")?.content)?, content),
+            _ => return Err(invalid("ContentInfo缺少内容")),
+        };
+        if content_type != OID_SIGNED_DATA {
+            return Err(invalid(format!("不是SignedData: {}", oid_name(&content_type))));
+        }
+        let signed_data = content
+            .expect(TAG_CONTEXT_0, "ContentInfo内容")?
+            .children()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid("SignedData为空"))?;
+
+        let children = signed_data.expect(TAG_SEQUENCE, "SignedData")?.children()?;
+        let mut iter = children.iter();
+        let mut next = |what: &str| iter.next().copied().ok_or_else(|| invalid(format!("SignedData缺少{}", what)));
+
+        let version = small_integer(&next("版本号")?)?;
+        let digest_algorithms = next("摘要算法")?
+            .expect(TAG_SET, "摘要算法集合")?
+            .children()?
+            .iter()
+            .map(|alg| algorithm_oid(alg).map(|oid| oid_name(&oid)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let encap = next("封装内容")?.expect(TAG_SEQUENCE, "EncapsulatedContentInfo")?.children()?;
+        let encap_type = encap
+            .first()
+            .ok_or_else(|| invalid("EncapsulatedContentInfo为空"))
+            .and_then(|oid| decode_oid(oid.expect(TAG_OID, "内容类型")?.content))?;
+        let detached = encap.get(1).is_none();
+
+        let mut certificates = Vec::new();
+        let mut signers = Vec::new();
+        while let Ok(item) = next("签名者集合") {
+            match item.tag {
+                TAG_CONTEXT_0 => certificates = item.children()?.iter().filter(|c| c.tag == TAG_SEQUENCE).map(|c| c.raw.to_vec()).collect(),
+                TAG_CONTEXT_1 => {}
+                TAG_SET => {
+                    signers = item.children()?.iter().map(SignerInfo::parse).collect::<Result<Vec<_>, _>>()?;
+                }
+                other => return Err(invalid(format!("SignedData中未知的标签0x{:02x}", other))),
+            }
+        }
+
+        Ok(SignedData {
+            version,
+            digest_algorithms,
+            content_type: oid_name(&encap_type),
+            detached,
+            certificates,
+            signers,
+        })
+    }
+
+    /// 查找签名者对应的证书（DER编码）
+    pub fn signer_certificate(&self, signer: &SignerInfo) -> Option<&[u8]> {
+        self.certificates
+            .iter()
+            .find(|der| {
+                x509_parser::parse_x509_certificate(der)
+                    .map(|(_, cert)| signer.matches_certificate(&cert))
+                    .unwrap_or(false)
+            })
+            .map(|der| der.as_slice())
+    }
+
+    /// 按“签名者证书在前、其后依次为颁发者”的顺序排列证书链，
+    /// 未能串入链中的证书追加在末尾
+    pub fn certificate_chain(&self) -> Vec<&[u8]> {
+        let parsed: Vec<_> = self
+            .certificates
+            .iter()
+            .filter_map(|der| x509_parser::parse_x509_certificate(der).ok().map(|(_, cert)| (der.as_slice(), cert)))
+            .collect();
+
+        let mut chain: Vec<usize> = Vec::new();
+        let leaf = self
+            .signers
+            .first()
+            .and_then(|signer| parsed.iter().position(|(_, cert)| signer.matches_certificate(cert)));
+        if let Some(mut current) = leaf {
+            chain.push(current);
+            while let Some(issuer) = (0..parsed.len()).find(|index| {
+                !chain.contains(index)
+                    && parsed[*index].1.tbs_certificate.subject.as_raw() == parsed[current].1.tbs_certificate.issuer.as_raw()
+            }) {
+                chain.push(issuer);
+                current = issuer;
+            }
+        }
+        for index in 0..parsed.len() {
+            if !chain.contains(&index) {
+                chain.push(index);
+            }
+        }
+        chain.into_iter().map(|index| parsed[index].0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// openssl cms -sign -stream生成的分离式签名：不定长BER编码，证书集合中根证书在前
+    const SIGNATURE_BLOCK: &[u8] = include_bytes!("../tests/fixtures/signer-chain-ber.rsa");
+
+    fn subject(der: &[u8]) -> String {
+        x509_parser::parse_x509_certificate(der).unwrap().1.subject().to_string()
+    }
+
+    #[test]
+    fn parses_indefinite_length_signed_data() {
+        assert_eq!(&SIGNATURE_BLOCK[..2], [TAG_SEQUENCE, 0x80]);
+        let signed = SignedData::parse(SIGNATURE_BLOCK).unwrap();
+        assert_eq!(signed.version, 1);
+        assert_eq!(signed.digest_algorithms, ["SHA-256"]);
+        assert!(signed.detached);
+        assert_eq!(signed.certificates.len(), 2);
+        assert_eq!(subject(&signed.certificates[0]), "CN=Root CA");
+
+        let [signer] = signed.signers.as_slice() else {
+            panic!("应只有一个签名者");
+        };
+        assert_eq!(signer.issuer.as_deref(), Some("CN=Root CA"));
+        assert_eq!(signer.serial_number.as_deref(), Some("70fce05bf11312e0d6ab1909d55a70bfe4771401"));
+        assert_eq!(signer.digest_algorithm, "SHA-256");
+        assert!(signer.message_digest.is_some());
+        assert!(signer.signed_attributes.as_ref().is_some_and(|attrs| attrs[0] == TAG_SET));
+    }
+
+    #[test]
+    fn orders_chain_from_signer_certificate() {
+        let signed = SignedData::parse(SIGNATURE_BLOCK).unwrap();
+        let leaf = signed.signer_certificate(&signed.signers[0]).unwrap();
+        assert_eq!(subject(leaf), "CN=Leaf, O=Acme");
+        let chain: Vec<String> = signed.certificate_chain().into_iter().map(subject).collect();
+        assert_eq!(chain, ["CN=Leaf, O=Acme", "CN=Root CA"]);
+    }
+
+    #[test]
+    fn extracts_signing_time() {
+        let signed = SignedData::parse(SIGNATURE_BLOCK).unwrap();
+        assert_eq!(signed.signers[0].signing_time.as_deref(), Some("Sat, 17 Oct 2026 09:22:17 +0000"));
+    }
+
+    #[test]
+    fn rejects_truncated_indefinite_length() {
+        // 去掉末尾的内容结束标记
+        let truncated = &SIGNATURE_BLOCK[..SIGNATURE_BLOCK.len() - 2];
+        assert!(SignedData::parse(truncated).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! 第三方SDK与追踪器识别
+//!
+//! 特征库将类名前缀、清单组件、meta-data键和原生库文件名映射到SDK。内置特征库随程序打包，
+//! 用户可在`resources/sdk_signatures.user.json`中补充或覆盖（按`id`）条目。
+//! 识别结果附带命中的证据，便于核对隐私披露。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::ApkParserError;
+use crate::dex::{DexAnalysis, PackageNode};
+use crate::manifest::AndroidManifest;
+
+/// 内置特征库
+const BUNDLED_DATABASE: &str = include_str!("../resources/sdk_signatures.json");
+/// 用户特征库文件名
+const USER_DATABASE_FILE: &str = "sdk_signatures.user.json";
+
+/// SDK类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SdkCategory {
+    Ads,
+    Analytics,
+    CrashReporting,
+    Push,
+    Payments,
+    Maps,
+    Social,
+    #[serde(other)]
+    Other,
+}
+
+/// 特征库中的一条SDK特征
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkSignature {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    pub category: SdkCategory,
+    /// 类名（包名）前缀，如`com.google.firebase.analytics`
+    #[serde(default)]
+    pub class_prefixes: Vec<String>,
+    /// 清单中声明的组件类名（可为前缀）
+    #[serde(default)]
+    pub manifest_components: Vec<String>,
+    /// application或组件下的meta-data名称
+    #[serde(default)]
+    pub meta_data_keys: Vec<String>,
+    /// 原生库文件名，如`libsentry.so`
+    #[serde(default)]
+    pub native_libraries: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DatabaseFile {
+    sdks: Vec<SdkSignature>,
+}
+
+/// SDK特征库
+#[derive(Debug, Clone, Default)]
+pub struct SdkDatabase {
+    pub signatures: Vec<SdkSignature>,
+}
+
+/// 证据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    ClassPrefix,
+    ManifestComponent,
+    MetaData,
+    NativeLibrary,
+}
+
+/// 一条命中证据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SdkEvidence {
+    pub kind: EvidenceKind,
+    /// 特征库中的匹配规则
+    pub pattern: String,
+    /// 实际命中的包名、组件、meta-data键或库路径
+    pub matched: String,
+    /// 命中包下定义的类数量（仅类名前缀证据）
+    pub class_count: Option<usize>,
+}
+
+/// 识别出的SDK
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedSdk {
+    pub id: String,
+    pub name: String,
+    pub vendor: Option<String>,
+    pub category: SdkCategory,
+    pub evidence: Vec<SdkEvidence>,
+}
+
+impl SdkDatabase {
+    /// 解析JSON格式的特征库
+    pub fn from_json(json: &str) -> Result<Self, ApkParserError> {
+        let file: DatabaseFile = serde_json::from_str(json)
+            .map_err(|e| ApkParserError::InvalidApk(format!("SDK特征库格式错误: {}", e)))?;
+        Ok(Self { signatures: file.sdks })
+    }
+
+    /// 内置特征库
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_DATABASE).expect("内置SDK特征库格式错误")
+    }
+
+    /// 内置特征库，并合并用户特征库（如存在）
+    pub fn load() -> Self {
+        let mut database = Self::bundled();
+        if let Some(path) = user_database_path() {
+            match std::fs::read_to_string(&path).map_err(ApkParserError::from).and_then(|json| Self::from_json(&json)) {
+                Ok(user) => {
+                    println!("INFO: 加载用户SDK特征库: {:?}，{} 条", path, user.signatures.len());
+                    database.merge(user);
+                }
+                Err(e) => println!("WARN: 加载用户SDK特征库失败: {}", e),
+            }
+        }
+        database
+    }
+
+    /// 合并另一个特征库，`id`相同的条目被替换
+    pub fn merge(&mut self, other: SdkDatabase) {
+        for signature in other.signatures {
+            match self.signatures.iter_mut().find(|s| s.id == signature.id) {
+                Some(existing) => *existing = signature,
+                None => self.signatures.push(signature),
+            }
+        }
+    }
+
+    /// 根据DEX包树、清单和原生库识别SDK
+    pub fn detect(
+        &self,
+        dex: Option<&DexAnalysis>,
+        manifest: Option<&AndroidManifest>,
+        native_libraries: &[String],
+    ) -> Vec<DetectedSdk> {
+        let components: Vec<&str> = manifest
+            .and_then(|m| m.application.as_ref())
+            .map(|app| app.components().map(|c| c.name.as_str()).collect())
+            .unwrap_or_default();
+        let meta_data_keys: Vec<&str> = manifest
+            .and_then(|m| m.application.as_ref())
+            .map(|app| {
+                app.meta_data.iter()
+                    .chain(app.components().flat_map(|c| c.meta_data.iter()))
+                    .map(|meta| meta.name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut detected = Vec::new();
+        for signature in &self.signatures {
+            let mut evidence = Vec::new();
+
+            if let Some(dex) = dex {
+                for prefix in &signature.class_prefixes {
+                    collect_package_matches(&dex.package_tree, prefix, &mut evidence);
+                }
+            }
+            for pattern in &signature.manifest_components {
+                evidence.extend(components.iter()
+                    .filter(|name| matches_prefix(name, pattern))
+                    .map(|name| SdkEvidence {
+                        kind: EvidenceKind::ManifestComponent,
+                        pattern: pattern.clone(),
+                        matched: name.to_string(),
+                        class_count: None,
+                    }));
+            }
+            for key in &signature.meta_data_keys {
+                if meta_data_keys.contains(&key.as_str()) {
+                    evidence.push(SdkEvidence {
+                        kind: EvidenceKind::MetaData,
+                        pattern: key.clone(),
+                        matched: key.clone(),
+                        class_count: None,
+                    });
+                }
+            }
+            for library in &signature.native_libraries {
+                evidence.extend(native_libraries.iter()
+                    .filter(|path| path.rsplit('/').next() == Some(library.as_str()))
+                    .map(|path| SdkEvidence {
+                        kind: EvidenceKind::NativeLibrary,
+                        pattern: library.clone(),
+                        matched: path.clone(),
+                        class_count: None,
+                    }));
+            }
+
+            if !evidence.is_empty() {
+                detected.push(DetectedSdk {
+                    id: signature.id.clone(),
+                    name: signature.name.clone(),
+                    vendor: signature.vendor.clone(),
+                    category: signature.category,
+                    evidence,
+                });
+            }
+        }
+        detected
+    }
+}
+
+/// APK中的原生库路径（`lib/<abi>/*.so`）
+pub fn native_library_paths<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    names
+        .filter(|name| name.starts_with("lib/") && name.ends_with(".so"))
+        .map(String::from)
+        .collect()
+}
+
+/// 名称等于规则，或以“规则.”/“规则$”开头
+fn matches_prefix(name: &str, pattern: &str) -> bool {
+    name == pattern
+        || name.strip_prefix(pattern).is_some_and(|rest| rest.starts_with('.') || rest.starts_with('$'))
+}
+
+/// 在包树中查找与前缀匹配且定义了类的最上层包
+fn collect_package_matches(nodes: &[PackageNode], prefix: &str, evidence: &mut Vec<SdkEvidence>) {
+    for node in nodes {
+        if matches_prefix(&node.package, prefix) {
+            if node.classes > 0 {
+                evidence.push(SdkEvidence {
+                    kind: EvidenceKind::ClassPrefix,
+                    pattern: prefix.to_string(),
+                    matched: node.package.clone(),
+                    class_count: Some(node.classes),
+                });
+            }
+        } else if prefix.starts_with(&format!("{}.", node.package)) {
+            collect_package_matches(&node.children, prefix, evidence);
+        }
+    }
+}
+
+/// 用户特征库的位置：可执行文件旁或当前目录下的resources目录
+fn user_database_path() -> Option<PathBuf> {
+    let exe_candidate = std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("resources").join(USER_DATABASE_FILE)));
+    exe_candidate.into_iter()
+        .chain([
+            Path::new("src-tauri/resources").join(USER_DATABASE_FILE),
+            Path::new("resources").join(USER_DATABASE_FILE),
+        ])
+        .find(|path| path.exists())
+}
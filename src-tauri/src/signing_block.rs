@@ -0,0 +1,748 @@
+//! APK签名块（APK Signing Block）解析器
+//!
+//! v2及以上的签名方案不再写入`META-INF/`，而是保存在ZIP中央目录之前的签名块中：
+//!
+//! ```text
+//! [文件条目] [APK签名块] [中央目录] [中央目录结束记录]
+//! ```
+//!
+//! 签名块由若干“长度-ID-值”对组成，这里解析其中的v2（0x7109871a）、
+//! v3（0xf05368c0）与v3.1（0x1b93ad61）签名方案块。
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x509_parser::prelude::FromDer;
+
+use crate::apk_parser::{ApkParser, ApkParserError, CertificateInfo};
+use crate::lineage::SigningCertificateLineage;
+use crate::pkcs7::{hex, oid_name};
+
+/// 签名块魔数
+const APK_SIG_BLOCK_MAGIC: &[u8; 16] = b"APK Sig Block 42";
+/// 签名块尾部（大小 + 魔数）的长度
+const APK_SIG_BLOCK_FOOTER_SIZE: u64 = 24;
+/// 签名块的最小长度：头部大小字段 + 尾部
+const APK_SIG_BLOCK_MIN_SIZE: u64 = 32;
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_MIN_SIZE: usize = 22;
+const ZIP_MAX_COMMENT_SIZE: usize = 0xFFFF;
+
+// 签名块中的ID
+pub const V2_BLOCK_ID: u32 = 0x7109_871a;
+pub const V3_BLOCK_ID: u32 = 0xf053_68c0;
+pub const V31_BLOCK_ID: u32 = 0x1b93_ad61;
+
+/// 已知的非签名方案块
+const KNOWN_BLOCK_NAMES: &[(u32, &str)] = &[
+    (V2_BLOCK_ID, "APK Signature Scheme v2"),
+    (V3_BLOCK_ID, "APK Signature Scheme v3"),
+    (V31_BLOCK_ID, "APK Signature Scheme v3.1"),
+    (0x4272_6577, "Verity padding"),
+    (0x6dff_800d, "Source stamp v2"),
+    (0x2b09_189e, "Source stamp v1"),
+    (0x504b_4453, "Dependency info"),
+    (0x2146_444e, "Google Play metadata"),
+    (0x7109_871f, "Channel info"),
+];
+
+// 签名数据中的附加属性ID
+pub const STRIPPING_PROTECTION_ATTR_ID: u32 = 0xbeef_f00d;
+pub const PROOF_OF_ROTATION_ATTR_ID: u32 = 0x3ba0_6f8c;
+pub const ROTATION_MIN_SDK_VERSION_ATTR_ID: u32 = 0x559f_8b02;
+pub const ROTATION_ON_DEV_RELEASE_ATTR_ID: u32 = 0xc2a6_b3ba;
+
+/// 返回签名算法ID的名称
+pub fn signature_algorithm_name(id: u32) -> String {
+    match id {
+        0x0101 => "RSASSA-PSS with SHA2-256".to_string(),
+        0x0102 => "RSASSA-PSS with SHA2-512".to_string(),
+        0x0103 => "RSASSA-PKCS1-v1_5 with SHA2-256".to_string(),
+        0x0104 => "RSASSA-PKCS1-v1_5 with SHA2-512".to_string(),
+        0x0201 => "ECDSA with SHA2-256".to_string(),
+        0x0202 => "ECDSA with SHA2-512".to_string(),
+        0x0301 => "DSA with SHA2-256".to_string(),
+        0x0421 => "RSASSA-PKCS1-v1_5 with SHA2-256 (verity)".to_string(),
+        0x0423 => "ECDSA with SHA2-256 (verity)".to_string(),
+        0x0425 => "DSA with SHA2-256 (verity)".to_string(),
+        other => format!("未知算法 0x{:04x}", other),
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> ApkParserError {
+    ApkParserError::InvalidSignature(msg.into())
+}
+
+/// ZIP各区段在文件中的位置
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ZipSections {
+    /// 中央目录偏移
+    pub central_directory_offset: u64,
+    /// 中央目录大小
+    pub central_directory_size: u64,
+    /// 中央目录结束记录偏移
+    pub eocd_offset: u64,
+    /// 中央目录结束记录（含注释）大小
+    pub eocd_size: u64,
+}
+
+/// 原始的APK签名块
+#[derive(Debug, Clone)]
+pub struct SigningBlock {
+    /// 签名块在文件中的偏移
+    pub offset: u64,
+    /// 签名块总大小（含头部的大小字段）
+    pub size: u64,
+    /// ZIP区段位置
+    pub sections: ZipSections,
+    /// ID-值对
+    pub pairs: Vec<(u32, Vec<u8>)>,
+}
+
+impl SigningBlock {
+    /// 返回指定ID的值
+    pub fn get(&self, id: u32) -> Option<&[u8]> {
+        self.pairs.iter().find(|(pair_id, _)| *pair_id == id).map(|(_, value)| value.as_slice())
+    }
+}
+
+/// 定位ZIP中央目录结束记录
+pub fn find_zip_sections<R: Read + Seek>(reader: &mut R) -> Result<ZipSections, ApkParserError> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    let tail_size = (file_size as usize).min(EOCD_MIN_SIZE + ZIP_MAX_COMMENT_SIZE);
+    if tail_size < EOCD_MIN_SIZE {
+        return Err(ApkParserError::InvalidApk("文件过小，不是有效的ZIP".to_string()));
+    }
+    reader.seek(SeekFrom::Start(file_size - tail_size as u64))?;
+    let mut tail = vec![0u8; tail_size];
+    reader.read_exact(&mut tail)?;
+
+    // 从后向前查找，并要求注释长度恰好延伸到文件末尾
+    let eocd_pos = (0..=tail_size - EOCD_MIN_SIZE)
+        .rev()
+        .find(|&pos| {
+            le_u32(&tail, pos) == Some(EOCD_SIGNATURE)
+                && le_u16(&tail, pos + 20).map(|len| pos + EOCD_MIN_SIZE + len as usize == tail_size) == Some(true)
+        })
+        .ok_or_else(|| ApkParserError::InvalidApk("找不到ZIP中央目录结束记录".to_string()))?;
+
+    let eocd = &tail[eocd_pos..];
+    let central_directory_size = le_u32(eocd, 12).unwrap_or(0) as u64;
+    let central_directory_offset = le_u32(eocd, 16).unwrap_or(0) as u64;
+    let eocd_offset = file_size - (tail_size - eocd_pos) as u64;
+    if central_directory_offset + central_directory_size > eocd_offset {
+        return Err(ApkParserError::InvalidApk("ZIP中央目录位置无效".to_string()));
+    }
+
+    Ok(ZipSections {
+        central_directory_offset,
+        central_directory_size,
+        eocd_offset,
+        eocd_size: file_size - eocd_offset,
+    })
+}
+
+/// 查找并读取APK签名块，不存在时返回None
+pub fn find_signing_block<R: Read + Seek>(reader: &mut R) -> Result<Option<SigningBlock>, ApkParserError> {
+    let sections = find_zip_sections(reader)?;
+    let cd_offset = sections.central_directory_offset;
+    if cd_offset < APK_SIG_BLOCK_MIN_SIZE {
+        return Ok(None);
+    }
+
+    reader.seek(SeekFrom::Start(cd_offset - APK_SIG_BLOCK_FOOTER_SIZE))?;
+    let mut footer = [0u8; APK_SIG_BLOCK_FOOTER_SIZE as usize];
+    reader.read_exact(&mut footer)?;
+    if &footer[8..] != APK_SIG_BLOCK_MAGIC {
+        return Ok(None);
+    }
+
+    let size_in_footer = u64::from_le_bytes(footer[..8].try_into().unwrap_or_default());
+    let total_size = size_in_footer
+        .checked_add(8)
+        .filter(|size| *size >= APK_SIG_BLOCK_MIN_SIZE && *size <= cd_offset)
+        .ok_or_else(|| invalid(format!("APK签名块大小无效: {}", size_in_footer)))?;
+    let offset = cd_offset - total_size;
+
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut block = vec![0u8; total_size as usize];
+    reader.read_exact(&mut block)?;
+    let size_in_header = u64::from_le_bytes(block[..8].try_into().unwrap_or_default());
+    if size_in_header != size_in_footer {
+        return Err(invalid("APK签名块头尾记录的大小不一致"));
+    }
+
+    // ID-值对位于头部大小字段与尾部之间
+    let pairs_data = &block[8..block.len() - APK_SIG_BLOCK_FOOTER_SIZE as usize];
+    let mut pairs = Vec::new();
+    let mut pos = 0usize;
+    while pos < pairs_data.len() {
+        let len = pairs_data
+            .get(pos..pos + 8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+            .ok_or_else(|| invalid("APK签名块中的ID-值对被截断"))?;
+        let start = pos + 8;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|end| len >= 4 && *end <= pairs_data.len())
+            .ok_or_else(|| invalid(format!("APK签名块中的ID-值对长度无效: {}", len)))?;
+        let id = le_u32(pairs_data, start).unwrap_or(0);
+        pairs.push((id, pairs_data[start + 4..end].to_vec()));
+        pos = end;
+    }
+
+    Ok(Some(SigningBlock {
+        offset,
+        size: total_size,
+        sections,
+        pairs,
+    }))
+}
+
+/// 签名者声明的内容摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemeDigest {
+    pub algorithm_id: u32,
+    pub algorithm: String,
+    /// 摘要值（十六进制）
+    pub digest: String,
+}
+
+/// 签名者的一个签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemeSignature {
+    pub algorithm_id: u32,
+    pub algorithm: String,
+    #[serde(skip)]
+    pub signature: Vec<u8>,
+}
+
+/// 签名数据中的附加属性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdditionalAttribute {
+    /// 属性ID（十六进制）
+    pub id: String,
+    /// 已知属性的名称
+    pub name: Option<String>,
+    /// 属性值：已知属性给出解释，其余为十六进制
+    pub value: String,
+    #[serde(skip)]
+    pub raw_id: u32,
+    #[serde(skip)]
+    pub raw_value: Vec<u8>,
+}
+
+/// 签名者的公钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemePublicKey {
+    /// 公钥算法
+    pub algorithm: String,
+    /// SubjectPublicKeyInfo的SHA-256摘要
+    pub sha256: String,
+    /// SubjectPublicKeyInfo的DER编码
+    #[serde(skip)]
+    pub der: Vec<u8>,
+}
+
+/// v2/v3/v3.1签名方案中的一个签名者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemeSigner {
+    /// 适用的最低SDK版本（仅v3/v3.1）
+    pub min_sdk_version: Option<u32>,
+    /// 适用的最高SDK版本（仅v3/v3.1）
+    pub max_sdk_version: Option<u32>,
+    pub digests: Vec<SchemeDigest>,
+    pub signatures: Vec<SchemeSignature>,
+    /// 证书，第一个为签名者证书
+    pub certificates: Vec<CertificateInfo>,
+    pub public_key: SchemePublicKey,
+    pub additional_attributes: Vec<AdditionalAttribute>,
+    /// v3/v3.1签名者的密钥轮换谱系
+    pub lineage: Option<SigningCertificateLineage>,
+    /// 被签名的原始数据
+    #[serde(skip)]
+    pub signed_data: Vec<u8>,
+    /// 证书的DER编码
+    #[serde(skip)]
+    pub certificates_der: Vec<Vec<u8>>,
+    /// 签名数据中记录的SDK范围（仅v3/v3.1，需与外层一致）
+    #[serde(skip)]
+    pub signed_sdk_range: Option<(u32, u32)>,
+    /// 解析该签名者时遇到的问题（如轮换证明格式错误），不影响其余字段
+    pub errors: Vec<String>,
+}
+
+impl SchemeSigner {
+    /// 查找指定ID的附加属性
+    pub fn attribute(&self, id: u32) -> Option<&AdditionalAttribute> {
+        self.additional_attributes.iter().find(|attr| attr.raw_id == id)
+    }
+}
+
+/// 签名块中的其他块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningBlockEntry {
+    /// 块ID（十六进制）
+    pub id: String,
+    pub name: Option<String>,
+    pub size: usize,
+}
+
+/// APK使用的签名方案
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningSchemes {
+    /// 是否存在v1（JAR）签名
+    pub v1: bool,
+    pub v2: bool,
+    pub v3: bool,
+    pub v3_1: bool,
+    /// 签名块偏移
+    pub signing_block_offset: Option<u64>,
+    /// 签名块大小
+    pub signing_block_size: Option<u64>,
+    pub v2_signers: Vec<SchemeSigner>,
+    pub v3_signers: Vec<SchemeSigner>,
+    pub v3_1_signers: Vec<SchemeSigner>,
+    /// 签名块中的其他块
+    pub other_blocks: Vec<SigningBlockEntry>,
+    /// 解析签名方案块时遇到的问题
+    pub errors: Vec<String>,
+}
+
+impl SigningSchemes {
+    /// 读取APK文件中的签名块并解析各签名方案
+    pub fn from_path<P: AsRef<Path>>(apk_path: P, v1: bool) -> Result<Self, ApkParserError> {
+        let mut file = File::open(apk_path.as_ref())?;
+        let block = find_signing_block(&mut file)?;
+        Ok(Self::from_block(block.as_ref(), v1))
+    }
+
+    /// 从已读取的签名块解析各签名方案
+    pub fn from_block(block: Option<&SigningBlock>, v1: bool) -> Self {
+        let mut schemes = SigningSchemes {
+            v1,
+            ..Default::default()
+        };
+        let block = match block {
+            Some(block) => block,
+            None => return schemes,
+        };
+        schemes.signing_block_offset = Some(block.offset);
+        schemes.signing_block_size = Some(block.size);
+
+        for (id, value) in &block.pairs {
+            let (label, with_sdk_range) = match *id {
+                V2_BLOCK_ID => ("v2", false),
+                V3_BLOCK_ID => ("v3", true),
+                V31_BLOCK_ID => ("v3.1", true),
+                _ => {
+                    schemes.other_blocks.push(SigningBlockEntry {
+                        id: format!("0x{:08x}", id),
+                        name: KNOWN_BLOCK_NAMES.iter().find(|(known, _)| known == id).map(|(_, name)| name.to_string()),
+                        size: value.len(),
+                    });
+                    continue;
+                }
+            };
+
+            let signers = match parse_scheme_block(value, with_sdk_range) {
+                Ok(signers) => signers,
+                Err(e) => {
+                    println!("WARN: 解析{}签名块失败: {}", label, e);
+                    schemes.errors.push(format!("{}: {}", label, e));
+                    Vec::new()
+                }
+            };
+            match *id {
+                V2_BLOCK_ID => {
+                    schemes.v2 = true;
+                    schemes.v2_signers = signers;
+                }
+                V3_BLOCK_ID => {
+                    schemes.v3 = true;
+                    schemes.v3_signers = signers;
+                }
+                _ => {
+                    schemes.v3_1 = true;
+                    schemes.v3_1_signers = signers;
+                }
+            }
+        }
+        schemes
+    }
+
+    /// 最高版本签名方案的签名者
+    pub fn preferred_signers(&self) -> &[SchemeSigner] {
+        [&self.v3_1_signers, &self.v3_signers, &self.v2_signers]
+            .into_iter()
+            .find(|signers| !signers.is_empty())
+            .map(|signers| signers.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// 读取一个以u32长度为前缀的字段
+pub(crate) fn read_length_prefixed<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a [u8], ApkParserError> {
+    let len = le_u32(data, *pos).ok_or_else(|| invalid("长度前缀被截断"))? as usize;
+    let start = *pos + 4;
+    let value = start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| invalid(format!("长度为{}的字段超出范围", len)))?;
+    *pos = start + len;
+    Ok(value)
+}
+
+/// 读取以u32长度为前缀的序列中的全部元素
+fn read_sequence(data: &[u8]) -> Result<Vec<&[u8]>, ApkParserError> {
+    let mut pos = 0;
+    let mut items = Vec::new();
+    while pos < data.len() {
+        items.push(read_length_prefixed(data, &mut pos)?);
+    }
+    Ok(items)
+}
+
+pub(crate) fn read_u32(data: &[u8], pos: &mut usize) -> Result<u32, ApkParserError> {
+    let value = le_u32(data, *pos).ok_or_else(|| invalid("u32字段被截断"))?;
+    *pos += 4;
+    Ok(value)
+}
+
+/// 解析签名方案块：长度前缀的签名者序列
+pub fn parse_scheme_block(value: &[u8], with_sdk_range: bool) -> Result<Vec<SchemeSigner>, ApkParserError> {
+    let mut pos = 0;
+    let signers = read_length_prefixed(value, &mut pos)?;
+    let signers = read_sequence(signers)?
+        .into_iter()
+        .map(|signer| parse_signer(signer, with_sdk_range))
+        .collect::<Result<Vec<_>, _>>()?;
+    if signers.is_empty() {
+        return Err(invalid("签名方案块中没有签名者"));
+    }
+    Ok(signers)
+}
+
+fn parse_signer(data: &[u8], with_sdk_range: bool) -> Result<SchemeSigner, ApkParserError> {
+    let mut pos = 0;
+    let signed_data = read_length_prefixed(data, &mut pos)?;
+    let sdk_range = if with_sdk_range {
+        Some((read_u32(data, &mut pos)?, read_u32(data, &mut pos)?))
+    } else {
+        None
+    };
+    let signatures = read_sequence(read_length_prefixed(data, &mut pos)?)?
+        .into_iter()
+        .map(|item| {
+            let mut item_pos = 0;
+            let algorithm_id = read_u32(item, &mut item_pos)?;
+            Ok(SchemeSignature {
+                algorithm_id,
+                algorithm: signature_algorithm_name(algorithm_id),
+                signature: read_length_prefixed(item, &mut item_pos)?.to_vec(),
+            })
+        })
+        .collect::<Result<Vec<_>, ApkParserError>>()?;
+    let public_key_der = read_length_prefixed(data, &mut pos)?;
+
+    // 签名数据：摘要、证书、（v3的SDK范围）、附加属性
+    let mut signed_pos = 0;
+    let digests = read_sequence(read_length_prefixed(signed_data, &mut signed_pos)?)?
+        .into_iter()
+        .map(|item| {
+            let mut item_pos = 0;
+            let algorithm_id = read_u32(item, &mut item_pos)?;
+            Ok(SchemeDigest {
+                algorithm_id,
+                algorithm: signature_algorithm_name(algorithm_id),
+                digest: hex(read_length_prefixed(item, &mut item_pos)?),
+            })
+        })
+        .collect::<Result<Vec<_>, ApkParserError>>()?;
+    let certificates_der: Vec<Vec<u8>> = read_sequence(read_length_prefixed(signed_data, &mut signed_pos)?)?
+        .into_iter()
+        .map(|cert| cert.to_vec())
+        .collect();
+    let signed_sdk_range = if with_sdk_range {
+        Some((read_u32(signed_data, &mut signed_pos)?, read_u32(signed_data, &mut signed_pos)?))
+    } else {
+        None
+    };
+    let additional_attributes = read_sequence(read_length_prefixed(signed_data, &mut signed_pos)?)?
+        .into_iter()
+        .map(|item| {
+            let id = le_u32(item, 0).ok_or_else(|| invalid("附加属性被截断"))?;
+            Ok(describe_attribute(id, &item[4..]))
+        })
+        .collect::<Result<Vec<_>, ApkParserError>>()?;
+
+    if certificates_der.is_empty() {
+        return Err(invalid("签名者没有证书"));
+    }
+    let certificates = certificates_der
+        .iter()
+        .map(|der| ApkParser::certificate_info(der))
+        .collect::<Result<Vec<_>, _>>()?;
+    // 轮换证明格式错误时保留签名者，只记录错误
+    let mut errors = Vec::new();
+    let lineage = additional_attributes
+        .iter()
+        .find(|attr| attr.raw_id == PROOF_OF_ROTATION_ATTR_ID)
+        .and_then(|attr| match SigningCertificateLineage::parse(&attr.raw_value) {
+            Ok(lineage) => Some(lineage),
+            Err(e) => {
+                println!("WARN: 解析轮换证明失败: {}", e);
+                errors.push(format!("轮换证明: {}", e));
+                None
+            }
+        });
+
+    Ok(SchemeSigner {
+        min_sdk_version: sdk_range.map(|(min, _)| min),
+        max_sdk_version: sdk_range.map(|(_, max)| max),
+        digests,
+        signatures,
+        certificates,
+        public_key: describe_public_key(public_key_der),
+        additional_attributes,
+        lineage,
+        signed_data: signed_data.to_vec(),
+        certificates_der,
+        signed_sdk_range,
+        errors,
+    })
+}
+
+pub(crate) fn describe_public_key(der: &[u8]) -> SchemePublicKey {
+    let algorithm = x509_parser::x509::SubjectPublicKeyInfo::from_der(der)
+        .map(|(_, spki)| oid_name(&spki.algorithm.algorithm.to_id_string()))
+        .unwrap_or_else(|_| "未知".to_string());
+    SchemePublicKey {
+        algorithm,
+        sha256: hex(&Sha256::digest(der)),
+        der: der.to_vec(),
+    }
+}
+
+fn describe_attribute(id: u32, value: &[u8]) -> AdditionalAttribute {
+    let as_u32 = || le_u32(value, 0).map(|v| v.to_string()).unwrap_or_else(|| hex(value));
+    let (name, display) = match id {
+        STRIPPING_PROTECTION_ATTR_ID => (
+            "Stripping protection",
+            le_u32(value, 0).map(|scheme| format!("v{}", scheme)).unwrap_or_else(|| hex(value)),
+        ),
+        PROOF_OF_ROTATION_ATTR_ID => ("Proof-of-rotation", format!("{} 字节", value.len())),
+        ROTATION_MIN_SDK_VERSION_ATTR_ID => ("Rotation min SDK version", as_u32()),
+        ROTATION_ON_DEV_RELEASE_ATTR_ID => ("Rotation on dev release", "true".to_string()),
+        _ => {
+            return AdditionalAttribute {
+                id: format!("0x{:08x}", id),
+                name: None,
+                value: hex(value),
+                raw_id: id,
+                raw_value: value.to_vec(),
+            }
+        }
+    };
+    AdditionalAttribute {
+        id: format!("0x{:08x}", id),
+        name: Some(name.to_string()),
+        value: display,
+        raw_id: id,
+        raw_value: value.to_vec(),
+    }
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+
+    const CERTIFICATE: &[u8] = include_bytes!("../tests/fixtures/signer-ec.der");
+
+    fn lp(data: &[u8]) -> Vec<u8> {
+        let mut out = (data.len() as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn seq(items: &[Vec<u8>]) -> Vec<u8> {
+        lp(&items.iter().flat_map(|item| lp(item)).collect::<Vec<u8>>())
+    }
+
+    fn subject_public_key() -> Vec<u8> {
+        let (_, cert) = x509_parser::parse_x509_certificate(CERTIFICATE).unwrap();
+        cert.tbs_certificate.subject_pki.raw.to_vec()
+    }
+
+    /// 构造一个签名者；`sdk_range`存在时按v3格式写入签名数据与外层
+    fn signer(sdk_range: Option<(u32, u32)>, attributes: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut signed_data = seq(&[[[0x01, 0x02, 0x00, 0x00].to_vec(), lp(&[0xaa; 32])].concat()]);
+        signed_data.extend(seq(&[CERTIFICATE.to_vec()]));
+        if let Some((min, max)) = sdk_range {
+            signed_data.extend(min.to_le_bytes());
+            signed_data.extend(max.to_le_bytes());
+        }
+        let attributes: Vec<Vec<u8>> = attributes.iter().map(|(id, value)| [id.to_le_bytes().to_vec(), value.clone()].concat()).collect();
+        signed_data.extend(seq(&attributes));
+
+        let mut out = lp(&signed_data);
+        if let Some((min, max)) = sdk_range {
+            out.extend(min.to_le_bytes());
+            out.extend(max.to_le_bytes());
+        }
+        out.extend(seq(&[[[0x01, 0x02, 0x00, 0x00].to_vec(), lp(&[0xbb; 8])].concat()]));
+        out.extend(lp(&subject_public_key()));
+        out
+    }
+
+    /// 签名块：`[大小][ID-值对][大小][魔数]`
+    fn signing_block(pairs: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let body: Vec<u8> = pairs
+            .iter()
+            .flat_map(|(id, value)| {
+                let mut pair = ((value.len() + 4) as u64).to_le_bytes().to_vec();
+                pair.extend(id.to_le_bytes());
+                pair.extend(value);
+                pair
+            })
+            .collect();
+        let size = (body.len() + 24) as u64;
+        [size.to_le_bytes().to_vec(), body, size.to_le_bytes().to_vec(), APK_SIG_BLOCK_MAGIC.to_vec()].concat()
+    }
+
+    /// 文件条目 + 签名块 + 中央目录 + 结束记录（带注释）
+    fn apk(block: &[u8], comment: &[u8]) -> Vec<u8> {
+        let entries = vec![b'E'; 100];
+        let cd = vec![b'C'; 46];
+        let mut eocd = vec![0u8; 22];
+        eocd[..4].copy_from_slice(b"PK\x05\x06");
+        eocd[12..16].copy_from_slice(&(cd.len() as u32).to_le_bytes());
+        eocd[16..20].copy_from_slice(&((entries.len() + block.len()) as u32).to_le_bytes());
+        eocd[20..22].copy_from_slice(&(comment.len() as u16).to_le_bytes());
+        [entries, block.to_vec(), cd, eocd, comment.to_vec()].concat()
+    }
+
+    #[test]
+    fn locates_signing_block_before_central_directory() {
+        let block = signing_block(&[(V2_BLOCK_ID, vec![1, 2, 3]), (0x4272_6577, vec![0; 8])]);
+        let data = apk(&block, b"comment");
+        let found = find_signing_block(&mut Cursor::new(&data)).unwrap().unwrap();
+
+        assert_eq!(found.offset, 100);
+        assert_eq!(found.size, block.len() as u64);
+        assert_eq!(found.sections.central_directory_offset, 100 + block.len() as u64);
+        assert_eq!(found.sections.central_directory_size, 46);
+        assert_eq!(found.sections.eocd_offset, 146 + block.len() as u64);
+        assert_eq!(found.sections.eocd_size, 22 + 7);
+        assert_eq!(found.get(V2_BLOCK_ID), Some(&[1u8, 2, 3][..]));
+        assert_eq!(found.pairs.len(), 2);
+    }
+
+    #[test]
+    fn missing_or_corrupt_signing_block() {
+        // 中央目录前没有魔数：没有签名块
+        let data = apk(&[0u8; 40], b"");
+        assert!(find_signing_block(&mut Cursor::new(&data)).unwrap().is_none());
+
+        // 头尾记录的大小不一致
+        let mut block = signing_block(&[(V2_BLOCK_ID, vec![1, 2, 3])]);
+        block[0] ^= 1;
+        assert!(find_signing_block(&mut Cursor::new(apk(&block, b""))).is_err());
+
+        // ID-值对长度超出签名块
+        let mut block = signing_block(&[(V2_BLOCK_ID, vec![1, 2, 3])]);
+        block[8] = 0x40;
+        assert!(find_signing_block(&mut Cursor::new(apk(&block, b""))).is_err());
+
+        // 没有结束记录
+        assert!(find_zip_sections(&mut Cursor::new(vec![0u8; 64])).is_err());
+    }
+
+    #[test]
+    fn parses_v2_signer() {
+        let block = seq(&[signer(None, &[(STRIPPING_PROTECTION_ATTR_ID, 3u32.to_le_bytes().to_vec())])]);
+        let signers = parse_scheme_block(&block, false).unwrap();
+        assert_eq!(signers.len(), 1);
+
+        let signer = &signers[0];
+        assert_eq!(signer.min_sdk_version, None);
+        assert_eq!(signer.signed_sdk_range, None);
+        assert_eq!(signer.digests.len(), 1);
+        assert_eq!(signer.digests[0].algorithm_id, 0x0201);
+        assert_eq!(signer.digests[0].digest, "aa".repeat(32));
+        assert_eq!(signer.signatures[0].signature, vec![0xbb; 8]);
+        assert_eq!(signer.certificates_der, vec![CERTIFICATE.to_vec()]);
+        assert!(signer.certificates[0].subject.contains("CN=Test Signer"));
+        assert_eq!(signer.public_key.der, subject_public_key());
+        assert_eq!(signer.public_key.sha256, hex(&Sha256::digest(subject_public_key())));
+        let attribute = signer.attribute(STRIPPING_PROTECTION_ATTR_ID).unwrap();
+        assert_eq!(attribute.value, "v3");
+        assert!(signer.lineage.is_none());
+        assert!(signer.errors.is_empty());
+    }
+
+    #[test]
+    fn parses_v3_signer_sdk_range() {
+        let block = seq(&[signer(Some((28, 32)), &[(ROTATION_MIN_SDK_VERSION_ATTR_ID, 33u32.to_le_bytes().to_vec())])]);
+        let parsed = &parse_scheme_block(&block, true).unwrap()[0];
+
+        assert_eq!(parsed.min_sdk_version, Some(28));
+        assert_eq!(parsed.max_sdk_version, Some(32));
+        assert_eq!(parsed.signed_sdk_range, Some((28, 32)));
+        assert_eq!(parsed.attribute(ROTATION_MIN_SDK_VERSION_ATTR_ID).unwrap().value, "33");
+
+        // 缺少SDK范围的签名者按v3格式解析时字段错位
+        let block = seq(&[signer(None, &[])]);
+        assert!(parse_scheme_block(&block, true).is_err());
+    }
+
+    #[test]
+    fn malformed_lineage_keeps_v3_signer() {
+        let block = seq(&[signer(Some((28, u32::MAX)), &[(PROOF_OF_ROTATION_ATTR_ID, vec![1, 0, 0])])]);
+        let signers = parse_scheme_block(&block, true).unwrap();
+
+        assert_eq!(signers.len(), 1);
+        assert!(signers[0].lineage.is_none());
+        assert_eq!(signers[0].errors.len(), 1);
+        assert!(signers[0].errors[0].starts_with("轮换证明"));
+        assert_eq!(signers[0].certificates_der, vec![CERTIFICATE.to_vec()]);
+    }
+
+    #[test]
+    fn scheme_blocks_from_signing_block() {
+        let v2 = seq(&[signer(None, &[])]);
+        let v3 = seq(&[signer(Some((24, u32::MAX)), &[])]);
+        let data = apk(&signing_block(&[(V2_BLOCK_ID, v2), (V3_BLOCK_ID, v3), (V31_BLOCK_ID, vec![0; 4]), (0x7109_871f, vec![0; 5])]), b"");
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&data).unwrap();
+        let block = find_signing_block(&mut file).unwrap();
+        let schemes = SigningSchemes::from_block(block.as_ref(), true);
+
+        assert!(schemes.v1 && schemes.v2 && schemes.v3 && schemes.v3_1);
+        assert_eq!(schemes.v2_signers.len(), 1);
+        assert_eq!(schemes.v3_signers[0].min_sdk_version, Some(24));
+        // 空的v3.1块只记录错误
+        assert!(schemes.v3_1_signers.is_empty());
+        assert_eq!(schemes.errors.len(), 1);
+        assert!(schemes.errors[0].starts_with("v3.1"));
+        assert_eq!(schemes.other_blocks.len(), 1);
+        assert_eq!(schemes.other_blocks[0].name.as_deref(), Some("Channel info"));
+        assert_eq!(schemes.preferred_signers().len(), 1);
+        assert_eq!(schemes.preferred_signers()[0].min_sdk_version, Some(24));
+    }
+}
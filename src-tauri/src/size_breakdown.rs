@@ -0,0 +1,250 @@
+//! APK体积分析
+//!
+//! 参照Android Studio的APK Analyzer，按路径逐级汇总压缩体积、解压体积与占比，
+//! 生成可供前端绘制树图的目录树。不压缩存储的条目会重新以deflate压缩来估算下载体积，
+//! 同时列出体积最大的条目，以及本可压缩却以不压缩方式存储的条目。
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::apk_parser::{ApkParser, ApkParserError};
+use crate::limits::ReadBudget;
+
+/// 默认列出的最大条目数
+pub const DEFAULT_TOP_N: usize = 20;
+
+/// 重新压缩后至少节省该字节数才视为不应以不压缩方式存储
+const MIN_SAVINGS: u64 = 1024;
+
+/// 重新压缩后至少节省的比例（百分比）
+const MIN_SAVINGS_PERCENT: u64 = 10;
+
+/// 目录树节点，文件为叶子节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeNode {
+    pub name: String,
+    /// 完整路径，目录以`/`结尾，根节点为空字符串
+    pub path: String,
+    pub is_directory: bool,
+    /// 节点下的文件数
+    pub entry_count: usize,
+    /// APK中的原始（压缩）体积
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    /// 估算的下载体积
+    pub download_size: u64,
+    /// 压缩体积占全部条目的百分比
+    pub percent: f64,
+    /// 子节点，按压缩体积从大到小排列
+    pub children: Vec<SizeNode>,
+}
+
+/// 单个条目的体积
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntrySize {
+    pub path: String,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub download_size: u64,
+    /// 是否以不压缩方式存储
+    pub stored: bool,
+}
+
+/// 不应以不压缩方式存储的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEntryIssue {
+    pub path: String,
+    pub size: u64,
+    /// 以deflate压缩后的估算体积
+    pub estimated_compressed_size: u64,
+    pub savings: u64,
+}
+
+/// APK体积分析报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeBreakdown {
+    /// APK文件大小
+    pub file_size: u64,
+    pub total_compressed_size: u64,
+    pub total_uncompressed_size: u64,
+    /// 估算的下载体积：各条目的下载体积加上ZIP头、中央目录与签名块等开销
+    pub estimated_download_size: u64,
+    pub root: SizeNode,
+    /// 压缩体积最大的条目
+    pub largest_entries: Vec<EntrySize>,
+    /// 本可压缩却以不压缩方式存储的条目
+    pub uncompressed_entries: Vec<StoredEntryIssue>,
+}
+
+/// 构建目录树时的中间节点
+#[derive(Default)]
+struct TreeBuilder {
+    entry_count: usize,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    download_size: u64,
+    is_directory: bool,
+    children: BTreeMap<String, TreeBuilder>,
+}
+
+impl TreeBuilder {
+    fn insert(&mut self, path: &str, entry: &EntrySize) {
+        self.add(entry);
+        let mut node = self;
+        let mut parts = path.split('/').filter(|part| !part.is_empty()).peekable();
+        while let Some(part) = parts.next() {
+            node = node.children.entry(part.to_string()).or_default();
+            node.is_directory = parts.peek().is_some();
+            node.add(entry);
+        }
+    }
+
+    fn add(&mut self, entry: &EntrySize) {
+        self.entry_count += 1;
+        self.compressed_size += entry.compressed_size;
+        self.uncompressed_size += entry.uncompressed_size;
+        self.download_size += entry.download_size;
+    }
+
+    fn build(self, name: String, path: String, total: u64) -> SizeNode {
+        let mut children: Vec<SizeNode> = self.children.into_iter()
+            .map(|(name, child)| {
+                let child_path = if child.is_directory { format!("{}{}/", path, name) } else { format!("{}{}", path, name) };
+                child.build(name, child_path, total)
+            })
+            .collect();
+        children.sort_by(|a, b| b.compressed_size.cmp(&a.compressed_size).then_with(|| a.name.cmp(&b.name)));
+        SizeNode {
+            name,
+            path,
+            is_directory: self.is_directory,
+            entry_count: self.entry_count,
+            compressed_size: self.compressed_size,
+            uncompressed_size: self.uncompressed_size,
+            download_size: self.download_size,
+            percent: if total == 0 { 0.0 } else { self.compressed_size as f64 * 100.0 / total as f64 },
+            children,
+        }
+    }
+}
+
+/// 打开APK文件并分析体积，供Tauri命令直接调用
+pub fn analyze_file(apk_path: &Path, top_n: usize) -> Result<SizeBreakdown, ApkParserError> {
+    let file_size = std::fs::metadata(apk_path)?.len();
+    let mut archive = ZipArchive::new(File::open(apk_path)?)?;
+    let budget = ReadBudget::default();
+    budget.check_entry_count(archive.len())?;
+    let extract_native_libs = ApkParser::read_manifest(&mut archive, &budget).ok()
+        .and_then(|manifest| manifest.application)
+        .and_then(|application| application.extract_native_libs);
+    Ok(analyze(&mut archive, file_size, extract_native_libs, top_n, &budget))
+}
+
+/// 分析已打开的APK
+///
+/// `extract_native_libs`为`Some(false)`时原生库必须以不压缩方式存储，不会被列为问题。
+/// 重新压缩读取的字节计入`budget`，超出后其余条目按原始体积估算。
+pub fn analyze(
+    archive: &mut ZipArchive<File>,
+    file_size: u64,
+    extract_native_libs: Option<bool>,
+    top_n: usize,
+    budget: &ReadBudget,
+) -> SizeBreakdown {
+    let mut entries = Vec::new();
+    let mut uncompressed_entries = Vec::new();
+
+    for i in 0..archive.len() {
+        let Ok(entry) = archive.by_index_raw(i) else {
+            continue;
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let path = entry.name().to_string();
+        let stored = entry.compression() == CompressionMethod::Stored;
+        let compressed_size = entry.compressed_size();
+        let uncompressed_size = entry.size();
+
+        // 已压缩的条目再压缩几乎没有收益，只对不压缩存储的条目重新压缩
+        let download_size = if stored && budget.charge(&path, compressed_size).is_ok() {
+            recompressed_size(entry).unwrap_or(compressed_size).min(compressed_size)
+        } else {
+            compressed_size
+        };
+
+        let savings = compressed_size - download_size;
+        if stored
+            && must_be_compressed(&path, extract_native_libs)
+            && savings >= MIN_SAVINGS
+            && savings.saturating_mul(100) >= compressed_size.saturating_mul(MIN_SAVINGS_PERCENT)
+        {
+            uncompressed_entries.push(StoredEntryIssue {
+                path: path.clone(),
+                size: uncompressed_size,
+                estimated_compressed_size: download_size,
+                savings,
+            });
+        }
+
+        entries.push(EntrySize { path, compressed_size, uncompressed_size, download_size, stored });
+    }
+
+    let mut tree = TreeBuilder { is_directory: true, ..Default::default() };
+    for entry in &entries {
+        tree.insert(&entry.path, entry);
+    }
+    let total_compressed_size = tree.compressed_size;
+    let total_uncompressed_size = tree.uncompressed_size;
+    let overhead = file_size.saturating_sub(total_compressed_size);
+    let estimated_download_size = tree.download_size + overhead;
+    let root = tree.build(String::new(), String::new(), total_compressed_size);
+
+    entries.sort_by(|a, b| b.compressed_size.cmp(&a.compressed_size).then_with(|| a.path.cmp(&b.path)));
+    entries.truncate(top_n);
+    uncompressed_entries.sort_by_key(|entry| std::cmp::Reverse(entry.savings));
+
+    SizeBreakdown {
+        file_size,
+        total_compressed_size,
+        total_uncompressed_size,
+        estimated_download_size,
+        root,
+        largest_entries: entries,
+        uncompressed_entries,
+    }
+}
+
+/// 以最高级别deflate压缩条目的原始数据，返回压缩后的字节数
+fn recompressed_size(entry: zip::read::ZipFile) -> io::Result<u64> {
+    let size = entry.compressed_size();
+    let mut encoder = DeflateEncoder::new(io::sink(), Compression::best());
+    io::copy(&mut entry.take(size), &mut encoder)?;
+    encoder.try_finish()?;
+    Ok(encoder.total_out())
+}
+
+/// 条目是否应当压缩存储
+///
+/// resources.arsc在targetSdk 30起必须不压缩存储；不压缩的DEX可被直接映射，是推荐做法；
+/// `extractNativeLibs=false`时原生库必须不压缩存储。
+fn must_be_compressed(path: &str, extract_native_libs: Option<bool>) -> bool {
+    if path == "resources.arsc" {
+        return false;
+    }
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    if file_name.starts_with("classes") && file_name.ends_with(".dex") && !path.contains('/') {
+        return false;
+    }
+    if path.starts_with("lib/") && file_name.ends_with(".so") && extract_native_libs == Some(false) {
+        return false;
+    }
+    true
+}
@@ -0,0 +1,1220 @@
+//! APK签名校验器
+//!
+//! 对v1（JAR）、v2、v3、v3.1签名做密码学校验：
+//! - v1：MANIFEST.MF中各条目的摘要、.SF对MANIFEST.MF的摘要，以及PKCS#7对.SF的签名；
+//! - v2/v3：对ZIP各区段的分块内容摘要，以及签名者对签名数据的签名；
+//! - 跨方案：防剥离（stripping protection）与回退（rollback）检查。
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use x509_parser::prelude::FromDer;
+use x509_parser::x509::SubjectPublicKeyInfo;
+use zip::ZipArchive;
+
+use crate::apk_parser::{ApkParser, ApkParserError};
+use crate::limits::ReadBudget;
+use crate::pkcs7::{SignedData, SignerInfo};
+use crate::signing_block::{
+    find_signing_block, SchemeSigner, SigningBlock, SigningSchemes, ROTATION_MIN_SDK_VERSION_ATTR_ID,
+    STRIPPING_PROTECTION_ATTR_ID,
+};
+
+/// 分块摘要的块大小（1 MiB）
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// verity摘要的Merkle树块大小（4 KiB）
+const VERITY_LOG2_BLOCK_SIZE: u8 = 12;
+/// verity摘要的盐，与apksig的`VerityTreeBuilder`一致为8个零字节
+const VERITY_SALT: [u8; 8] = [0; 8];
+
+const OID_RSA: &str = "1.2.840.113549.1.1.1";
+const OID_EC: &str = "1.2.840.10045.2.1";
+const OID_DSA: &str = "1.2.840.10040.4.1";
+const OID_P256: &str = "1.2.840.10045.3.1.7";
+const OID_P384: &str = "1.3.132.0.34";
+
+/// `.SF`主属性中声明APK还使用了哪些签名方案
+const APK_SIGNED_ATTRIBUTE: &str = "X-Android-APK-Signed";
+
+/// 单个签名方案的校验状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationStatus {
+    /// 校验通过
+    Passed,
+    /// 校验失败
+    Failed,
+    /// 使用了该方案，但签名算法均不受支持，未能校验
+    Unverified,
+    /// 未使用该方案
+    Absent,
+}
+
+/// 单个签名方案的校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemeVerification {
+    /// 方案名称：v1、v2、v3、v3.1
+    pub scheme: String,
+    pub status: VerificationStatus,
+    /// 导致失败的具体原因
+    pub errors: Vec<String>,
+    /// 不影响结果的提示
+    pub warnings: Vec<String>,
+}
+
+impl SchemeVerification {
+    pub(crate) fn new(scheme: &str) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            status: VerificationStatus::Absent,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 根据是否记录了错误确定状态
+    pub(crate) fn finish(mut self) -> Self {
+        self.status = if !self.errors.is_empty() {
+            VerificationStatus::Failed
+        } else if self.status == VerificationStatus::Unverified {
+            VerificationStatus::Unverified
+        } else {
+            VerificationStatus::Passed
+        };
+        self
+    }
+
+    /// 没有任何签名能被校验，记录原因；没有错误时结果为未校验而非通过
+    fn mark_unverified(&mut self, reason: String) {
+        self.warnings.push(reason);
+        self.status = VerificationStatus::Unverified;
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status == VerificationStatus::Passed
+    }
+}
+
+/// APK签名校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationResult {
+    /// 总体结论：至少一个方案通过，且没有方案或跨方案检查失败
+    pub verified: bool,
+    pub v1: SchemeVerification,
+    pub v2: SchemeVerification,
+    pub v3: SchemeVerification,
+    pub v3_1: SchemeVerification,
+    /// 跨方案检查（防剥离、回退）发现的问题
+    pub cross_scheme_errors: Vec<String>,
+}
+
+/// 校验APK文件的全部签名方案
+pub fn verify_apk<P: AsRef<Path>>(apk_path: P, budget: &ReadBudget) -> Result<VerificationResult, ApkParserError> {
+    let mut file = File::open(apk_path.as_ref())?;
+    // 签名块损坏时记为v2/v3失败，v1照常校验
+    let (block, block_error) = match find_signing_block(&mut file) {
+        Ok(block) => (block, None),
+        Err(e) => (None, Some(format!("无法读取APK签名块: {}", e))),
+    };
+    let mut archive = ZipArchive::new(File::open(apk_path.as_ref())?)?;
+
+    let v1_files = V1Files::read(&mut archive, budget)?;
+    // v1需要读取全部条目，使用独立的读取额度
+    let v1_budget = ReadBudget::new(budget.limits().clone());
+    let v1 = v1_files.as_ref().map(|files| verify_v1(files, &mut archive, &v1_budget));
+    let schemes = SigningSchemes::from_block(block.as_ref(), v1_files.is_some());
+
+    let mut digests = ContentDigests::default();
+    let mut scheme_results = Vec::new();
+    for (label, present, signers) in [
+        ("v2", schemes.v2, &schemes.v2_signers),
+        ("v3", schemes.v3, &schemes.v3_signers),
+        ("v3.1", schemes.v3_1, &schemes.v3_1_signers),
+    ] {
+        let mut result = SchemeVerification::new(label);
+        if present {
+            // 块存在但未能解析出签名者时，解析错误即为失败原因
+            result.errors.extend(schemes.errors.iter().filter(|e| e.starts_with(&format!("{}:", label))).cloned());
+            if let Some(block) = block.as_ref() {
+                for (index, signer) in signers.iter().enumerate() {
+                    verify_scheme_signer(signer, index, block, &mut file, &mut digests, &mut result);
+                }
+            }
+            result = result.finish();
+        } else if let (Some(error), "v2" | "v3") = (&block_error, label) {
+            result.errors.push(error.clone());
+            result = result.finish();
+        }
+        scheme_results.push(result);
+    }
+    let v3_1 = scheme_results.pop().unwrap_or_else(|| SchemeVerification::new("v3.1"));
+    let v3 = scheme_results.pop().unwrap_or_else(|| SchemeVerification::new("v3"));
+    let v2 = scheme_results.pop().unwrap_or_else(|| SchemeVerification::new("v2"));
+    let v1 = v1.unwrap_or_else(|| SchemeVerification::new("v1"));
+
+    let cross_scheme_errors = cross_scheme_checks(v1_files.as_ref(), &schemes);
+
+    let results = [&v1, &v2, &v3, &v3_1];
+    let verified = results.iter().any(|r| r.passed())
+        && results.iter().all(|r| r.status != VerificationStatus::Failed)
+        && cross_scheme_errors.is_empty();
+
+    Ok(VerificationResult {
+        verified,
+        v1,
+        v2,
+        v3,
+        v3_1,
+        cross_scheme_errors,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// 摘要与签名算法
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// 由JAR清单属性前缀（如`SHA-256`）或PKCS#7算法名得到摘要算法
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().replace('-', "").as_str() {
+            "MD5" => Some(Self::Md5),
+            "SHA1" => Some(Self::Sha1),
+            "SHA256" => Some(Self::Sha256),
+            "SHA384" => Some(Self::Sha384),
+            "SHA512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Md5 => md5::compute(data).0.to_vec(),
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAlgorithm {
+    RsaPkcs1,
+    RsaPss,
+    Ecdsa,
+    Dsa,
+}
+
+/// 签名校验未通过的原因
+#[derive(Debug)]
+enum SignatureError {
+    /// 算法不受支持，签名未被校验
+    Unsupported(String),
+    /// 签名无效或签名数据有误
+    Invalid(String),
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SignatureError::Unsupported(message) | SignatureError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for SignatureError {
+    fn from(message: String) -> Self {
+        SignatureError::Invalid(message)
+    }
+}
+
+/// 用SubjectPublicKeyInfo中的公钥校验签名
+fn verify_signature(
+    public_key_der: &[u8],
+    key_algorithm: KeyAlgorithm,
+    hash: HashAlgorithm,
+    message: &[u8],
+    signature_value: &[u8],
+) -> Result<(), SignatureError> {
+    let unsupported = |message: String| Err(SignatureError::Unsupported(message));
+    let (_, spki) = SubjectPublicKeyInfo::from_der(public_key_der).map_err(|e| format!("无法解析公钥: {}", e))?;
+    let key_oid = spki.algorithm.algorithm.to_id_string();
+    let key_bytes = spki.subject_public_key.data.as_ref();
+
+    let algorithm: &dyn VerificationAlgorithm = match (key_algorithm, key_oid.as_str()) {
+        (KeyAlgorithm::RsaPkcs1, OID_RSA) => match hash {
+            HashAlgorithm::Sha1 => &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+            HashAlgorithm::Sha256 => &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
+            HashAlgorithm::Sha384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+            HashAlgorithm::Sha512 => &signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY,
+            HashAlgorithm::Md5 => return unsupported("不支持校验MD5withRSA签名".to_string()),
+        },
+        (KeyAlgorithm::RsaPss, OID_RSA) => match hash {
+            HashAlgorithm::Sha256 => &signature::RSA_PSS_2048_8192_SHA256,
+            HashAlgorithm::Sha512 => &signature::RSA_PSS_2048_8192_SHA512,
+            _ => return unsupported(format!("不支持的RSASSA-PSS摘要算法{:?}", hash)),
+        },
+        (KeyAlgorithm::Ecdsa, OID_EC) => {
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|params| params.as_oid().ok())
+                .map(|oid| oid.to_id_string())
+                .unwrap_or_default();
+            match (curve.as_str(), hash) {
+                (OID_P256, HashAlgorithm::Sha256) => &signature::ECDSA_P256_SHA256_ASN1,
+                (OID_P256, HashAlgorithm::Sha384) => &signature::ECDSA_P256_SHA384_ASN1,
+                (OID_P384, HashAlgorithm::Sha256) => &signature::ECDSA_P384_SHA256_ASN1,
+                (OID_P384, HashAlgorithm::Sha384) => &signature::ECDSA_P384_SHA384_ASN1,
+                _ => return unsupported(format!("不支持的ECDSA曲线/摘要组合: {} / {:?}", curve, hash)),
+            }
+        }
+        (KeyAlgorithm::Dsa, OID_DSA) => return unsupported("不支持校验DSA签名".to_string()),
+        _ => return Err(SignatureError::Invalid(format!("签名算法与公钥类型{}不匹配", key_oid))),
+    };
+
+    UnparsedPublicKey::new(algorithm, key_bytes)
+        .verify(message, signature_value)
+        .map_err(|_| SignatureError::Invalid("签名值与公钥不匹配".to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// v1（JAR签名）
+// ---------------------------------------------------------------------------
+
+/// JAR清单中的一个段落
+struct ManifestSection {
+    /// 段落的原始字节（含结尾空行）
+    raw: Vec<u8>,
+    attributes: Vec<(String, String)>,
+}
+
+impl ManifestSection {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 该段落中的全部`<算法>-Digest<后缀>`属性
+    fn digests(&self, suffix: &str) -> Vec<(String, &str)> {
+        self.attributes
+            .iter()
+            .filter_map(|(key, value)| {
+                let algorithm = key.strip_suffix(suffix)?;
+                algorithm.strip_suffix("-Digest").map(|alg| (alg.to_string(), value.as_str()))
+            })
+            .collect()
+    }
+}
+
+/// 解析JAR清单（MANIFEST.MF或.SF），第一个段落为主属性
+fn parse_jar_manifest(data: &[u8]) -> Vec<ManifestSection> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    let mut attributes: Vec<(String, String)> = Vec::new();
+
+    while pos < data.len() {
+        let line_end = data[pos..].iter().position(|b| *b == b'\n').map(|i| pos + i + 1).unwrap_or(data.len());
+        let line = String::from_utf8_lossy(&data[pos..line_end]);
+        let line = line.trim_end_matches(['\r', '\n']);
+        pos = line_end;
+
+        if line.is_empty() {
+            sections.push(ManifestSection {
+                raw: data[start..pos].to_vec(),
+                attributes: std::mem::take(&mut attributes),
+            });
+            start = pos;
+        } else if let Some(continuation) = line.strip_prefix(' ') {
+            if let Some((_, value)) = attributes.last_mut() {
+                value.push_str(continuation);
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            attributes.push((key.trim().to_string(), value.trim_start().to_string()));
+        }
+    }
+    if start < data.len() {
+        sections.push(ManifestSection {
+            raw: data[start..].to_vec(),
+            attributes,
+        });
+    }
+    sections
+}
+
+/// 判断是否为不受MANIFEST.MF保护的签名相关文件
+fn is_jar_signature_file(name: &str) -> bool {
+    let file_name = match name.strip_prefix("META-INF/") {
+        Some(file_name) if !file_name.contains('/') => file_name.to_ascii_uppercase(),
+        _ => return false,
+    };
+    file_name == "MANIFEST.MF"
+        || file_name.starts_with("SIG-")
+        || [".SF", ".RSA", ".DSA", ".EC"].iter().any(|ext| file_name.ends_with(ext))
+}
+
+/// (签名块路径, 签名块内容, .SF路径, .SF内容)
+type V1Signer = (String, Vec<u8>, String, Option<Vec<u8>>);
+
+/// v1签名相关的文件
+struct V1Files {
+    manifest: Option<Vec<u8>>,
+    signers: Vec<V1Signer>,
+}
+
+impl V1Files {
+    /// 读取MANIFEST.MF与各签名块，不存在签名块时返回None
+    fn read(archive: &mut ZipArchive<File>, budget: &ReadBudget) -> Result<Option<Self>, ApkParserError> {
+        let names: Vec<String> = archive.file_names().map(|name| name.to_string()).collect();
+        let mut blocks: Vec<&String> = names.iter().filter(|name| ApkParser::is_signature_block_file(name)).collect();
+        if blocks.is_empty() {
+            return Ok(None);
+        }
+        blocks.sort();
+
+        let mut read = |name: &str| budget.read_entry(archive, name);
+
+        let manifest = names.iter().find(|name| name.as_str() == "META-INF/MANIFEST.MF").map(|name| read(name)).transpose()?;
+        let mut signers = Vec::new();
+        for block in blocks {
+            let stem = &block[..block.rfind('.').unwrap_or(block.len())];
+            let sf_path = format!("{}.SF", stem);
+            let sf = names.iter().find(|name| name.eq_ignore_ascii_case(&sf_path)).map(|name| read(name)).transpose()?;
+            signers.push((block.clone(), read(block)?, sf_path, sf));
+        }
+        Ok(Some(Self { manifest, signers }))
+    }
+
+    /// 各.SF主属性中`X-Android-APK-Signed`声明的方案编号
+    fn declared_schemes(&self) -> HashSet<u32> {
+        self.signers
+            .iter()
+            .filter_map(|(_, _, _, sf)| sf.as_ref())
+            .filter_map(|sf| parse_jar_manifest(sf).into_iter().next())
+            .filter_map(|main| main.get(APK_SIGNED_ATTRIBUTE).map(|value| value.to_string()))
+            .flat_map(|value| value.split(',').filter_map(|id| id.trim().parse().ok()).collect::<Vec<u32>>())
+            .collect()
+    }
+}
+
+fn verify_v1(files: &V1Files, archive: &mut ZipArchive<File>, budget: &ReadBudget) -> SchemeVerification {
+    let mut result = SchemeVerification::new("v1");
+    let manifest = match &files.manifest {
+        Some(manifest) => manifest,
+        None => {
+            result.errors.push("缺少META-INF/MANIFEST.MF".to_string());
+            return result.finish();
+        }
+    };
+    let sections = parse_jar_manifest(manifest);
+    let entry_sections: HashMap<&str, &ManifestSection> = sections
+        .iter()
+        .skip(1)
+        .filter_map(|section| section.get("Name").map(|name| (name, section)))
+        .collect();
+
+    let mut unsupported = 0;
+    for (block_path, block, sf_path, sf) in &files.signers {
+        let sf = match sf {
+            Some(sf) => sf,
+            None => {
+                result.errors.push(format!("{}: 缺少对应的签名文件{}", block_path, sf_path));
+                continue;
+            }
+        };
+        match verify_signature_block(block, sf) {
+            Ok(()) => {}
+            Err(SignatureError::Unsupported(e)) => {
+                result.warnings.push(format!("{}: 未校验签名: {}", block_path, e));
+                unsupported += 1;
+            }
+            Err(SignatureError::Invalid(e)) => result.errors.push(format!("{}: {}", block_path, e)),
+        }
+        for error in verify_signature_file(sf, manifest, &sections, &entry_sections) {
+            result.errors.push(format!("{}: {}", sf_path, error));
+        }
+    }
+
+    // 清单中的条目摘要与实际内容，以及未受保护的条目
+    let mut covered = HashSet::new();
+    for index in 0..archive.len() {
+        let entry = match archive.by_index(index) {
+            Ok(entry) => entry,
+            Err(e) => {
+                result.errors.push(format!("无法读取第{}个条目: {}", index, e));
+                continue;
+            }
+        };
+        let name = entry.name().to_string();
+        if entry.is_dir() || is_jar_signature_file(&name) {
+            continue;
+        }
+        let section = match entry_sections.get(name.as_str()) {
+            Some(section) => section,
+            None => {
+                result.errors.push(format!("{}: 未在MANIFEST.MF中签名", name));
+                continue;
+            }
+        };
+        covered.insert(name.clone());
+
+        let content = match budget.read(entry) {
+            Ok(content) => content,
+            Err(e) => {
+                result.errors.push(format!("{}: 读取失败: {}", name, e));
+                continue;
+            }
+        };
+        let digests = section.digests("");
+        if digests.is_empty() {
+            result.errors.push(format!("{}: MANIFEST.MF中没有摘要", name));
+        }
+        for (algorithm, expected) in digests {
+            match check_digest(&algorithm, expected, &content) {
+                Ok(()) => {}
+                Err(e) => result.errors.push(format!("{}: {}", name, e)),
+            }
+        }
+    }
+    for name in entry_sections.keys() {
+        if !covered.contains(*name) && !archive.file_names().any(|n| n == *name) {
+            result.errors.push(format!("{}: 在MANIFEST.MF中列出但APK中不存在", name));
+        }
+    }
+    if unsupported > 0 && unsupported == files.signers.len() {
+        result.mark_unverified("所有签名块的算法均不受支持，v1签名未被校验".to_string());
+    }
+    result.finish()
+}
+
+/// 按清单中的算法名与Base64摘要校验数据
+fn check_digest(algorithm: &str, expected_base64: &str, data: &[u8]) -> Result<(), String> {
+    let hash = HashAlgorithm::from_name(algorithm).ok_or_else(|| format!("不支持的摘要算法{}", algorithm))?;
+    let expected = BASE64.decode(expected_base64.trim()).map_err(|_| format!("{}摘要不是有效的Base64", algorithm))?;
+    if hash.digest(data) == expected {
+        Ok(())
+    } else {
+        Err(format!("{}摘要不匹配", algorithm))
+    }
+}
+
+/// 校验.SF对MANIFEST.MF的摘要
+fn verify_signature_file(
+    sf: &[u8],
+    manifest: &[u8],
+    sections: &[ManifestSection],
+    entry_sections: &HashMap<&str, &ManifestSection>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    let sf_sections = parse_jar_manifest(sf);
+    let main = match sf_sections.first() {
+        Some(main) => main,
+        None => return vec!["签名文件为空".to_string()],
+    };
+
+    // 整个清单的摘要匹配时无需逐条检查
+    let whole = main.digests("-Manifest");
+    if !whole.is_empty() && whole.iter().all(|(alg, digest)| check_digest(alg, digest, manifest).is_ok()) {
+        return errors;
+    }
+
+    // 否则主属性与每个条目段落都必须由签名文件单独覆盖
+    if let Some(main_section) = sections.first() {
+        for (algorithm, digest) in main.digests("-Manifest-Main-Attributes") {
+            if let Err(e) = check_digest(&algorithm, digest, &main_section.raw) {
+                errors.push(format!("清单主属性{}", e));
+            }
+        }
+    }
+    let mut signed_names = HashSet::new();
+    for section in sf_sections.iter().skip(1) {
+        let name = match section.get("Name") {
+            Some(name) => name,
+            None => continue,
+        };
+        let manifest_section = match entry_sections.get(name) {
+            Some(manifest_section) => manifest_section,
+            None => {
+                errors.push(format!("{}: 在签名文件中列出但不在MANIFEST.MF中", name));
+                continue;
+            }
+        };
+        signed_names.insert(name);
+        for (algorithm, digest) in section.digests("") {
+            if let Err(e) = check_digest(&algorithm, digest, &manifest_section.raw) {
+                errors.push(format!("{}: 清单段落{}", name, e));
+            }
+        }
+    }
+    if !whole.is_empty() && signed_names.is_empty() {
+        errors.push("MANIFEST.MF的摘要不匹配".to_string());
+    } else {
+        for name in entry_sections.keys().filter(|name| !signed_names.contains(*name)) {
+            errors.push(format!("{}: 未被签名文件覆盖", name));
+        }
+    }
+    errors
+}
+
+/// 校验PKCS#7签名块对.SF的签名
+fn verify_signature_block(block: &[u8], sf: &[u8]) -> Result<(), SignatureError> {
+    let signed_data = SignedData::parse(block).map_err(|e| e.to_string())?;
+    let signer = signed_data.signers.first().ok_or_else(|| "签名块中没有签名者".to_string())?;
+    if signed_data.signers.len() > 1 {
+        return Err(SignatureError::Invalid("签名块中包含多个签名者".to_string()));
+    }
+    let cert_der = signed_data
+        .signer_certificate(signer)
+        .ok_or_else(|| "找不到签名者的证书".to_string())?;
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der).map_err(|e| format!("无法解析签名者证书: {}", e))?;
+
+    let digest = HashAlgorithm::from_name(&signer.digest_algorithm)
+        .ok_or_else(|| SignatureError::Unsupported(format!("不支持的摘要算法{}", signer.digest_algorithm)))?;
+    let message = match &signer.signed_attributes {
+        Some(attributes) => {
+            let expected = signer.message_digest.as_deref().ok_or_else(|| "签名属性中缺少messageDigest".to_string())?;
+            if crate::pkcs7::hex(&digest.digest(sf)) != expected {
+                return Err(SignatureError::Invalid("签名属性中的messageDigest与.SF不匹配".to_string()));
+            }
+            attributes.as_slice()
+        }
+        None => sf,
+    };
+
+    let (key_algorithm, hash) = pkcs7_signature_algorithm(signer, digest)?;
+    verify_signature(cert.tbs_certificate.subject_pki.raw, key_algorithm, hash, message, &signer.signature)
+}
+
+/// 由签名者的签名算法名得到公钥算法与摘要算法
+fn pkcs7_signature_algorithm(signer: &SignerInfo, digest: HashAlgorithm) -> Result<(KeyAlgorithm, HashAlgorithm), SignatureError> {
+    let name = signer.signature_algorithm.as_str();
+    let unsupported = || SignatureError::Unsupported(format!("不支持的签名算法{}", name));
+    let (hash_name, key_name) = match name.split_once("with") {
+        Some((hash, key)) => (Some(hash), key),
+        None => (None, name),
+    };
+    let key = match key_name {
+        "RSA" => KeyAlgorithm::RsaPkcs1,
+        "ECDSA" | "EC" => KeyAlgorithm::Ecdsa,
+        "DSA" => KeyAlgorithm::Dsa,
+        _ => return Err(unsupported()),
+    };
+    let hash = match hash_name {
+        Some(hash_name) => HashAlgorithm::from_name(hash_name).ok_or_else(unsupported)?,
+        None => digest,
+    };
+    Ok((key, hash))
+}
+
+// ---------------------------------------------------------------------------
+// v2 / v3 / v3.1
+// ---------------------------------------------------------------------------
+
+/// 签名方案算法ID对应的公钥算法、摘要算法，以及是否为verity摘要
+fn scheme_algorithm(id: u32) -> Option<(KeyAlgorithm, HashAlgorithm, bool)> {
+    match id {
+        0x0101 => Some((KeyAlgorithm::RsaPss, HashAlgorithm::Sha256, false)),
+        0x0102 => Some((KeyAlgorithm::RsaPss, HashAlgorithm::Sha512, false)),
+        0x0103 => Some((KeyAlgorithm::RsaPkcs1, HashAlgorithm::Sha256, false)),
+        0x0104 => Some((KeyAlgorithm::RsaPkcs1, HashAlgorithm::Sha512, false)),
+        0x0201 => Some((KeyAlgorithm::Ecdsa, HashAlgorithm::Sha256, false)),
+        0x0202 => Some((KeyAlgorithm::Ecdsa, HashAlgorithm::Sha512, false)),
+        0x0301 => Some((KeyAlgorithm::Dsa, HashAlgorithm::Sha256, false)),
+        0x0421 => Some((KeyAlgorithm::RsaPkcs1, HashAlgorithm::Sha256, true)),
+        0x0423 => Some((KeyAlgorithm::Ecdsa, HashAlgorithm::Sha256, true)),
+        0x0425 => Some((KeyAlgorithm::Dsa, HashAlgorithm::Sha256, true)),
+        _ => None,
+    }
+}
+
+/// 按签名方案算法ID校验签名，供轮换证明等结构复用
+pub(crate) fn verify_scheme_signature(public_key_der: &[u8], algorithm_id: u32, message: &[u8], signature_value: &[u8]) -> Result<(), String> {
+    let (key_algorithm, hash, _) = scheme_algorithm(algorithm_id)
+        .ok_or_else(|| format!("未知的签名算法0x{:04x}", algorithm_id))?;
+    verify_signature(public_key_der, key_algorithm, hash, message, signature_value).map_err(|e| e.to_string())
+}
+
+/// 已计算的分块内容摘要缓存
+#[derive(Default)]
+struct ContentDigests {
+    computed: HashMap<HashAlgorithm, Result<Vec<u8>, String>>,
+    verity: Option<Result<Vec<u8>, String>>,
+}
+
+impl ContentDigests {
+    fn get(&mut self, hash: HashAlgorithm, file: &mut File, block: &SigningBlock) -> Result<Vec<u8>, String> {
+        self.computed
+            .entry(hash)
+            .or_insert_with(|| {
+                let result = match hash {
+                    HashAlgorithm::Sha256 => chunked_digest::<Sha256>(file, block),
+                    HashAlgorithm::Sha512 => chunked_digest::<Sha512>(file, block),
+                    _ => return Err("不支持的分块摘要算法".to_string()),
+                };
+                result.map_err(|e| format!("计算内容摘要失败: {}", e))
+            })
+            .clone()
+    }
+
+    fn get_verity(&mut self, file: &mut File, block: &SigningBlock) -> Result<Vec<u8>, String> {
+        self.verity
+            .get_or_insert_with(|| verity_digest(file, block).map_err(|e| format!("计算verity摘要失败: {}", e)))
+            .clone()
+    }
+}
+
+/// 计算verity签名算法的内容摘要：
+/// 对去掉签名块后的APK（文件条目、中央目录、偏移修正后的中央目录结束记录）
+/// 以4 KiB块、8字节零盐计算fs-verity格式的Merkle树根哈希，摘要为根哈希 || 数据长度（u64小端）。
+/// 签名块必须从4 KiB边界开始。
+pub fn verity_digest(file: &mut File, block: &SigningBlock) -> std::io::Result<Vec<u8>> {
+    let sections = &block.sections;
+    if block.offset % (1 << VERITY_LOG2_BLOCK_SIZE) != 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "签名块未按4096字节对齐"));
+    }
+    let mut eocd = vec![0u8; sections.eocd_size as usize];
+    file.seek(SeekFrom::Start(sections.eocd_offset))?;
+    file.read_exact(&mut eocd)?;
+    if eocd.len() >= 20 {
+        eocd[16..20].copy_from_slice(&(block.offset as u32).to_le_bytes());
+    }
+
+    // 文件条目与中央目录直接从文件读取，不整体载入内存
+    let sections_to_read = [(0, block.offset), (sections.central_directory_offset, sections.central_directory_size)];
+    let data_size = block.offset + sections.central_directory_size + eocd.len() as u64;
+    let mut data = SectionReader { file, sections: &sections_to_read, index: 0, remaining: None }.chain(eocd.as_slice());
+    let mut digest = crate::idsig::merkle_root_hash(&mut data, VERITY_LOG2_BLOCK_SIZE, &VERITY_SALT)?;
+    digest.extend_from_slice(&data_size.to_le_bytes());
+    Ok(digest)
+}
+
+/// 依次读取文件中的若干区段
+struct SectionReader<'a> {
+    file: &'a mut File,
+    sections: &'a [(u64, u64)],
+    index: usize,
+    /// 当前区段剩余的字节数，尚未定位到区段起点时为None
+    remaining: Option<u64>,
+}
+
+impl Read for SectionReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while let Some(&(offset, len)) = self.sections.get(self.index) {
+            let remaining = match self.remaining {
+                Some(remaining) => remaining,
+                None => {
+                    self.file.seek(SeekFrom::Start(offset))?;
+                    len
+                }
+            };
+            if remaining == 0 {
+                self.index += 1;
+                self.remaining = None;
+                continue;
+            }
+            let size = remaining.min(buf.len() as u64) as usize;
+            let read = self.file.read(&mut buf[..size])?;
+            if read == 0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "APK内容不完整"));
+            }
+            self.remaining = Some(remaining - read as u64);
+            return Ok(read);
+        }
+        Ok(0)
+    }
+}
+
+/// 计算v2/v3的分块内容摘要：
+/// 依次对“文件条目”、“中央目录”、“中央目录结束记录”按1 MiB分块，
+/// 块摘要为H(0xa5 || 长度 || 数据)，顶层摘要为H(0x5a || 块数 || 块摘要...)。
+/// 中央目录结束记录中的中央目录偏移需替换为签名块的偏移。
+pub fn chunked_digest<D: Digest>(file: &mut File, block: &SigningBlock) -> std::io::Result<Vec<u8>> {
+    let sections = &block.sections;
+    let mut eocd = vec![0u8; sections.eocd_size as usize];
+    file.seek(SeekFrom::Start(sections.eocd_offset))?;
+    file.read_exact(&mut eocd)?;
+    if eocd.len() >= 20 {
+        eocd[16..20].copy_from_slice(&(block.offset as u32).to_le_bytes());
+    }
+
+    let mut chunk_digests: Vec<u8> = Vec::new();
+    let mut chunk_count: u32 = 0;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut digest_chunk = |chunk: &[u8]| {
+        let mut hasher = D::new();
+        hasher.update([0xa5u8]);
+        hasher.update((chunk.len() as u32).to_le_bytes());
+        hasher.update(chunk);
+        chunk_digests.extend_from_slice(&hasher.finalize());
+        chunk_count += 1;
+    };
+
+    for (start, len) in [
+        (0, block.offset),
+        (sections.central_directory_offset, sections.central_directory_size),
+    ] {
+        file.seek(SeekFrom::Start(start))?;
+        let mut remaining = len;
+        while remaining > 0 {
+            let size = remaining.min(CHUNK_SIZE as u64) as usize;
+            file.read_exact(&mut buffer[..size])?;
+            digest_chunk(&buffer[..size]);
+            remaining -= size as u64;
+        }
+    }
+    for chunk in eocd.chunks(CHUNK_SIZE) {
+        digest_chunk(chunk);
+    }
+
+    let mut hasher = D::new();
+    hasher.update([0x5au8]);
+    hasher.update(chunk_count.to_le_bytes());
+    hasher.update(&chunk_digests);
+    Ok(hasher.finalize().to_vec())
+}
+
+fn verify_scheme_signer(
+    signer: &SchemeSigner,
+    index: usize,
+    block: &SigningBlock,
+    file: &mut File,
+    digests: &mut ContentDigests,
+    result: &mut SchemeVerification,
+) {
+    let prefix = format!("签名者#{}", index + 1);
+    let mut error = |message: String| result.errors.push(format!("{}: {}", prefix, message));
+
+    if signer.signatures.is_empty() {
+        error("没有签名".to_string());
+    }
+    for signer_error in &signer.errors {
+        error(signer_error.clone());
+    }
+
+    // 签名数据中的证书公钥必须与签名者公钥一致
+    match signer.certificates_der.first().map(|der| x509_parser::parse_x509_certificate(der)) {
+        Some(Ok((_, cert))) if cert.tbs_certificate.subject_pki.raw == signer.public_key.der.as_slice() => {}
+        Some(Ok(_)) => error("签名者公钥与证书公钥不一致".to_string()),
+        Some(Err(e)) => error(format!("无法解析签名者证书: {}", e)),
+        None => error("没有证书".to_string()),
+    }
+
+    // 签名数据中摘要的算法列表须与签名的算法列表一致
+    let signature_algorithms: Vec<u32> = signer.signatures.iter().map(|s| s.algorithm_id).collect();
+    let digest_algorithms: Vec<u32> = signer.digests.iter().map(|d| d.algorithm_id).collect();
+    if signature_algorithms != digest_algorithms {
+        error("签名算法列表与摘要算法列表不一致".to_string());
+    }
+
+    if let (Some(outer), Some(signed)) = (
+        signer.min_sdk_version.zip(signer.max_sdk_version),
+        signer.signed_sdk_range,
+    ) {
+        if outer != signed {
+            error(format!("签名数据中的SDK范围{:?}与签名者声明的{:?}不一致", signed, outer));
+        }
+    }
+
+    // 轮换谱系须校验通过，且以签名者证书结尾
+    if let Some(lineage) = &signer.lineage {
+        for lineage_error in &lineage.errors {
+            error(format!("轮换证明: {}", lineage_error));
+        }
+        if lineage.current().map(|node| &node.certificate_der) != signer.certificates_der.first() {
+            error("轮换证明的最新证书与签名者证书不一致".to_string());
+        }
+    }
+
+    let mut verified_any = false;
+    let mut warnings = Vec::new();
+    for scheme_signature in &signer.signatures {
+        let (key_algorithm, hash, verity) = match scheme_algorithm(scheme_signature.algorithm_id) {
+            Some(algorithm) => algorithm,
+            None => {
+                warnings.push(format!("{}: 跳过未知签名算法{}", prefix, scheme_signature.algorithm));
+                continue;
+            }
+        };
+        match verify_signature(
+            &signer.public_key.der,
+            key_algorithm,
+            hash,
+            &signer.signed_data,
+            &scheme_signature.signature,
+        ) {
+            Ok(()) => {}
+            Err(SignatureError::Unsupported(e)) => {
+                warnings.push(format!("{}: 未校验{}签名: {}", prefix, scheme_signature.algorithm, e));
+                continue;
+            }
+            Err(SignatureError::Invalid(e)) => {
+                error(format!("{}签名校验失败: {}", scheme_signature.algorithm, e));
+                continue;
+            }
+        }
+
+        let expected = signer
+            .digests
+            .iter()
+            .find(|d| d.algorithm_id == scheme_signature.algorithm_id)
+            .map(|d| d.digest.clone());
+        let actual = if verity { digests.get_verity(file, block) } else { digests.get(hash, file, block) };
+        match (expected, actual) {
+            (Some(expected), Ok(actual)) if crate::pkcs7::hex(&actual) == expected => verified_any = true,
+            (Some(_), Ok(_)) => error(format!("{}内容摘要不匹配，APK内容已被修改", scheme_signature.algorithm)),
+            (None, _) => error(format!("缺少{}的内容摘要", scheme_signature.algorithm)),
+            (_, Err(e)) => error(e),
+        }
+    }
+    let unchecked = !verified_any && !signer.signatures.is_empty() && warnings.len() == signer.signatures.len();
+    result.warnings.extend(warnings);
+    if unchecked {
+        result.mark_unverified(format!("{}: 没有可校验的签名", prefix));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 跨方案检查
+// ---------------------------------------------------------------------------
+
+/// 证书DER的集合，用于比较各方案的签名者
+fn signer_certificates(signers: &[SchemeSigner]) -> Vec<Vec<u8>> {
+    let mut certs: Vec<Vec<u8>> = signers.iter().filter_map(|s| s.certificates_der.first().cloned()).collect();
+    certs.sort();
+    certs
+}
+
+fn cross_scheme_checks(v1_files: Option<&V1Files>, schemes: &SigningSchemes) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    // 防剥离：v1声明的方案必须存在
+    if let Some(files) = v1_files {
+        for scheme in files.declared_schemes() {
+            let present = match scheme {
+                2 => schemes.v2,
+                3 => schemes.v3,
+                _ => true,
+            };
+            if !present {
+                errors.push(format!("v1签名声明APK还使用了v{}签名，但签名块中不存在，可能已被剥离", scheme));
+            }
+        }
+    }
+
+    // 防剥离：v2签名者的stripping protection属性声明了v3
+    for signer in &schemes.v2_signers {
+        if let Some(attr) = signer.attribute(STRIPPING_PROTECTION_ATTR_ID) {
+            let scheme = attr.raw_value.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+            if scheme == Some(3) && !schemes.v3 {
+                errors.push("v2签名声明APK还使用了v3签名，但v3签名块不存在，可能已被剥离".to_string());
+            }
+        }
+    }
+
+    // 防剥离/回退：v3签名者声明了v3.1的轮换最低SDK
+    for signer in &schemes.v3_signers {
+        if let Some(attr) = signer.attribute(ROTATION_MIN_SDK_VERSION_ATTR_ID) {
+            let rotation_min_sdk = attr.raw_value.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+            if !schemes.v3_1 {
+                errors.push("v3签名声明了v3.1密钥轮换，但v3.1签名块不存在，可能已被剥离".to_string());
+            } else if schemes.v3_1_signers.iter().map(|s| s.min_sdk_version).min().flatten() != rotation_min_sdk {
+                errors.push("v3签名声明的轮换最低SDK与v3.1签名者的最低SDK不一致".to_string());
+            }
+        }
+    }
+    if schemes.v3_1 && !schemes.v3 {
+        errors.push("存在v3.1签名块但缺少v3签名块".to_string());
+    }
+
+    // 回退：同一方案内多个签名者的SDK范围不能重叠
+    for (label, signers) in [("v3", &schemes.v3_signers), ("v3.1", &schemes.v3_1_signers)] {
+        let mut ranges: Vec<(u32, u32)> = signers
+            .iter()
+            .filter_map(|s| s.min_sdk_version.zip(s.max_sdk_version))
+            .collect();
+        ranges.sort();
+        if ranges.windows(2).any(|pair| pair[1].0 <= pair[0].1) {
+            errors.push(format!("{}签名者的SDK范围存在重叠", label));
+        }
+    }
+
+    // 回退：旧方案的签名者应与v2一致，避免旧系统信任不同的密钥
+    let v2_certs = signer_certificates(&schemes.v2_signers);
+    if let Some(files) = v1_files {
+        if schemes.v2 && !v2_certs.is_empty() {
+            let mut v1_certs: Vec<Vec<u8>> = files
+                .signers
+                .iter()
+                .filter_map(|(_, block, _, _)| {
+                    let signed_data = SignedData::parse(block).ok()?;
+                    let signer = signed_data.signers.first()?;
+                    signed_data.signer_certificate(signer).map(|der| der.to_vec())
+                })
+                .collect();
+            v1_certs.sort();
+            if !v1_certs.is_empty() && v1_certs != v2_certs {
+                errors.push("v1与v2的签名者证书不一致".to_string());
+            }
+        }
+    }
+    // 回退：旧方案的签名者必须是新方案签名者本身，或位于其轮换谱系中
+    let older_schemes = [("v2", &schemes.v2_signers), ("v3", &schemes.v3_signers)];
+    let newer_schemes = [("v3", &schemes.v3_signers), ("v3.1", &schemes.v3_1_signers)];
+    for ((older_label, older), (newer_label, newer)) in older_schemes.iter().zip(newer_schemes.iter()) {
+        let older_certs = signer_certificates(older);
+        if older_certs.is_empty() || newer.is_empty() {
+            continue;
+        }
+        for signer in newer.iter() {
+            let matches = older_certs.iter().all(|cert| match &signer.lineage {
+                Some(lineage) => lineage.contains(cert),
+                None => signer.certificates_der.first() == Some(cert),
+            });
+            if !matches {
+                errors.push(match signer.lineage {
+                    Some(_) => format!("{}签名者不在{}签名者的轮换谱系中", older_label, newer_label),
+                    None => format!("{}与{}的签名者证书不一致，且{}未提供密钥轮换证明", older_label, newer_label, newer_label),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::signing_block::{AdditionalAttribute, SchemePublicKey, ZipSections};
+
+    /// 1 MiB + 4 KiB的文件条目、64字节签名块、中央目录与结束记录
+    fn signed_layout() -> (File, SigningBlock) {
+        let entries: Vec<u8> = (0..CHUNK_SIZE + 4096).map(|i| (i * 7) as u8).collect();
+        let block = [b'B'; 64];
+        let cd = [b'C'; 46];
+        let mut eocd = vec![0u8; 22];
+        eocd[..4].copy_from_slice(b"PK\x05\x06");
+        eocd[12..16].copy_from_slice(&(cd.len() as u32).to_le_bytes());
+        eocd[16..20].copy_from_slice(&((entries.len() + block.len()) as u32).to_le_bytes());
+
+        let mut file = tempfile::tempfile().unwrap();
+        for part in [&entries[..], &block, &cd, &eocd] {
+            file.write_all(part).unwrap();
+        }
+        let cd_offset = (entries.len() + block.len()) as u64;
+        let signing_block = SigningBlock {
+            offset: entries.len() as u64,
+            size: block.len() as u64,
+            sections: ZipSections {
+                central_directory_offset: cd_offset,
+                central_directory_size: cd.len() as u64,
+                eocd_offset: cd_offset + cd.len() as u64,
+                eocd_size: eocd.len() as u64,
+            },
+            pairs: Vec::new(),
+        };
+        (file, signing_block)
+    }
+
+    // 以下期望值按apksig的ApkSigningBlockUtils与VerityTreeBuilder的构造方式独立计算
+    #[test]
+    fn chunked_digest_vectors() {
+        let (mut file, block) = signed_layout();
+        assert_eq!(
+            crate::pkcs7::hex(&chunked_digest::<Sha256>(&mut file, &block).unwrap()),
+            "227cf1653f28aa39924ccc209adfb2a9a3c768a72e77455693482193f94ecadd"
+        );
+        assert_eq!(
+            crate::pkcs7::hex(&chunked_digest::<Sha512>(&mut file, &block).unwrap()),
+            "6ef30eab939db69435c7a70cdc502511149d3e3dae0bb80ac9e2be059b0b01ba\
+             3a609f46d331b4297511cb1c1c3884e530b8f8cc7ebc057b28954c321280b180"
+        );
+    }
+
+    #[test]
+    fn verity_digest_uses_zero_salt() {
+        let (mut file, block) = signed_layout();
+        assert_eq!(
+            crate::pkcs7::hex(&verity_digest(&mut file, &block).unwrap()),
+            "2a300179b456605dd112089ae123b4f6f5a79a5358ebb8f2bc777cb4bcb457314410100000000000"
+        );
+    }
+
+    #[test]
+    fn verity_digest_requires_aligned_signing_block() {
+        let (mut file, mut block) = signed_layout();
+        block.offset -= 1;
+        assert!(verity_digest(&mut file, &block).is_err());
+    }
+
+    fn sha256_base64(data: &[u8]) -> String {
+        BASE64.encode(Sha256::digest(data))
+    }
+
+    const MANIFEST_MAIN: &str = "Manifest-Version: 1.0\r\nCreated-By: test\r\n\r\n";
+    const MANIFEST_ENTRY_A: &str = "Name: a.txt\r\nSHA-256-Digest: AAAA\r\n\r\n";
+    const MANIFEST_ENTRY_B: &str = "Name: b.txt\r\nSHA-256-Digest: BBBB\r\n\r\n";
+
+    fn check_sf(sf: &str) -> Vec<String> {
+        let manifest = format!("{}{}{}", MANIFEST_MAIN, MANIFEST_ENTRY_A, MANIFEST_ENTRY_B);
+        let sections = parse_jar_manifest(manifest.as_bytes());
+        let entry_sections: HashMap<&str, &ManifestSection> = sections
+            .iter()
+            .skip(1)
+            .filter_map(|section| section.get("Name").map(|name| (name, section)))
+            .collect();
+        verify_signature_file(sf.as_bytes(), manifest.as_bytes(), &sections, &entry_sections)
+    }
+
+    fn sf_entry(name: &str, section: &str) -> String {
+        format!("Name: {}\r\nSHA-256-Digest: {}\r\n\r\n", name, sha256_base64(section.as_bytes()))
+    }
+
+    #[test]
+    fn signature_file_whole_manifest_digest() {
+        let manifest = format!("{}{}{}", MANIFEST_MAIN, MANIFEST_ENTRY_A, MANIFEST_ENTRY_B);
+        let sf = format!("Signature-Version: 1.0\r\nSHA-256-Digest-Manifest: {}\r\n\r\n", sha256_base64(manifest.as_bytes()));
+        assert!(check_sf(&sf).is_empty());
+    }
+
+    #[test]
+    fn signature_file_falls_back_to_sections() {
+        // 整体摘要不匹配时，主属性与每个段落须分别匹配
+        let sf = format!(
+            "Signature-Version: 1.0\r\nSHA-256-Digest-Manifest-Main-Attributes: {}\r\n\r\n{}{}",
+            sha256_base64(MANIFEST_MAIN.as_bytes()),
+            sf_entry("a.txt", MANIFEST_ENTRY_A),
+            sf_entry("b.txt", MANIFEST_ENTRY_B),
+        );
+        assert!(check_sf(&sf).is_empty());
+
+        let sf = format!(
+            "Signature-Version: 1.0\r\nSHA-256-Digest-Manifest-Main-Attributes: {}\r\n\r\n{}{}",
+            sha256_base64(b"tampered"),
+            sf_entry("a.txt", MANIFEST_ENTRY_B),
+            sf_entry("c.txt", MANIFEST_ENTRY_B),
+        );
+        let errors = check_sf(&sf);
+        assert!(errors.iter().any(|e| e.starts_with("清单主属性")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("a.txt: 清单段落")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("c.txt: 在签名文件中列出")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e == "b.txt: 未被签名文件覆盖"), "{:?}", errors);
+    }
+
+    #[test]
+    fn signature_file_with_wrong_whole_digest_and_no_sections() {
+        let sf = format!("Signature-Version: 1.0\r\nSHA-256-Digest-Manifest: {}\r\n\r\n", sha256_base64(b"other"));
+        assert_eq!(check_sf(&sf), ["MANIFEST.MF的摘要不匹配"]);
+    }
+
+    fn attribute(id: u32, value: u32) -> AdditionalAttribute {
+        AdditionalAttribute {
+            id: format!("0x{:08x}", id),
+            name: None,
+            value: value.to_string(),
+            raw_id: id,
+            raw_value: value.to_le_bytes().to_vec(),
+        }
+    }
+
+    fn scheme_signer(certificate: &[u8], sdk_range: Option<(u32, u32)>, attributes: Vec<AdditionalAttribute>) -> SchemeSigner {
+        SchemeSigner {
+            min_sdk_version: sdk_range.map(|range| range.0),
+            max_sdk_version: sdk_range.map(|range| range.1),
+            digests: Vec::new(),
+            signatures: Vec::new(),
+            certificates: Vec::new(),
+            public_key: SchemePublicKey { algorithm: "RSA".to_string(), sha256: String::new(), der: Vec::new() },
+            additional_attributes: attributes,
+            lineage: None,
+            signed_data: Vec::new(),
+            certificates_der: vec![certificate.to_vec()],
+            signed_sdk_range: sdk_range,
+            errors: Vec::new(),
+        }
+    }
+
+    fn v1_declaring(schemes: &str) -> V1Files {
+        let sf = format!("Signature-Version: 1.0\r\nX-Android-APK-Signed: {}\r\n\r\n", schemes);
+        V1Files {
+            manifest: None,
+            signers: vec![("META-INF/CERT.RSA".to_string(), Vec::new(), "META-INF/CERT.SF".to_string(), Some(sf.into_bytes()))],
+        }
+    }
+
+    #[test]
+    fn stripping_protection() {
+        let v1_only = SigningSchemes { v1: true, ..Default::default() };
+        // 声明的方案来自集合，顺序不固定
+        let mut errors = cross_scheme_checks(Some(&v1_declaring("2, 3")), &v1_only);
+        errors.sort();
+        assert_eq!(errors, [
+            "v1签名声明APK还使用了v2签名，但签名块中不存在，可能已被剥离",
+            "v1签名声明APK还使用了v3签名，但签名块中不存在，可能已被剥离",
+        ]);
+
+        let v2_only = SigningSchemes {
+            v2: true,
+            v2_signers: vec![scheme_signer(b"cert", None, vec![attribute(STRIPPING_PROTECTION_ATTR_ID, 3)])],
+            ..Default::default()
+        };
+        assert_eq!(cross_scheme_checks(None, &v2_only), ["v2签名声明APK还使用了v3签名，但v3签名块不存在，可能已被剥离"]);
+
+        let v3_without_v31 = SigningSchemes {
+            v3: true,
+            v3_signers: vec![scheme_signer(b"cert", Some((24, 32)), vec![attribute(ROTATION_MIN_SDK_VERSION_ATTR_ID, 33)])],
+            ..Default::default()
+        };
+        assert_eq!(cross_scheme_checks(None, &v3_without_v31), ["v3签名声明了v3.1密钥轮换，但v3.1签名块不存在，可能已被剥离"]);
+    }
+
+    #[test]
+    fn rollback_protection() {
+        let consistent = SigningSchemes {
+            v2: true,
+            v3: true,
+            v2_signers: vec![scheme_signer(b"cert", None, Vec::new())],
+            v3_signers: vec![scheme_signer(b"cert", Some((24, u32::MAX)), Vec::new())],
+            ..Default::default()
+        };
+        assert!(cross_scheme_checks(Some(&v1_declaring("2, 3")), &consistent).is_empty());
+
+        let different_key = SigningSchemes {
+            v3_signers: vec![scheme_signer(b"other", Some((24, u32::MAX)), Vec::new())],
+            ..consistent.clone()
+        };
+        assert_eq!(cross_scheme_checks(None, &different_key), ["v2与v3的签名者证书不一致，且v3未提供密钥轮换证明"]);
+
+        let overlapping = SigningSchemes {
+            v3_signers: vec![
+                scheme_signer(b"cert", Some((24, 30)), Vec::new()),
+                scheme_signer(b"cert", Some((28, u32::MAX)), Vec::new()),
+            ],
+            ..consistent
+        };
+        assert_eq!(cross_scheme_checks(None, &overlapping), ["v3签名者的SDK范围存在重叠"]);
+    }
+}
@@ -0,0 +1,667 @@
+//! ZIP结构异常与篡改检查
+//!
+//! 直接按字节解析中央目录结束记录、中央目录和本地文件头，不依赖`zip` crate，
+//! 因此在`ZipArchive::new`失败时同样可用。恶意软件和对抗分析工具的APK常利用
+//! `zip` crate会默默容忍的ZIP特性，这里逐项报告：重复条目名、本地文件头与中央目录
+//! 不一致、伪加密标志、不支持或伪造的压缩方法、条目重叠与条目间的多余数据、
+//! 文件前缀/注释/尾部数据（包括Janus式的DEX前缀）以及路径穿越条目名。
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apk_parser::ApkParserError;
+use crate::cert_audit::Severity;
+use crate::limits::ReadBudget;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+const LOCAL_HEADER_SIZE: usize = 30;
+const CENTRAL_HEADER_SIZE: usize = 46;
+const EOCD_MIN_SIZE: usize = 22;
+const ZIP64_LOCATOR_SIZE: usize = 20;
+const ZIP64_EOCD_SIZE: usize = 56;
+const ZIP_MAX_COMMENT_SIZE: usize = 0xFFFF;
+
+const APK_SIG_BLOCK_MAGIC: &[u8; 16] = b"APK Sig Block 42";
+
+const FLAG_ENCRYPTED: u16 = 0x0001;
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+const FLAG_STRONG_ENCRYPTION: u16 = 0x0040;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// 单条ZIP结构异常
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZipAnomaly {
+    /// 异常类型：duplicate_entry、header_mismatch、encryption_flag、unsupported_compression、
+    /// overlapping_entries、gap_data、prefix_data、janus_dex_prefix、shifted_offsets、trailing_data、
+    /// archive_comment、path_traversal、missing_local_header、entry_count_mismatch、
+    /// central_directory_gap、invalid_central_directory
+    pub kind: String,
+    pub severity: Severity,
+    /// 涉及的条目名
+    pub entry: Option<String>,
+    /// 文件中的偏移
+    pub offset: Option<u64>,
+    pub message: String,
+}
+
+/// 从中央目录读取的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawZipEntry {
+    pub name: String,
+    pub flags: u16,
+    pub compression_method: u16,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    /// 本地文件头偏移
+    pub local_header_offset: u64,
+    /// 数据起始偏移，本地文件头无效时为None
+    pub data_offset: Option<u64>,
+    /// DOS格式的修改时间与日期
+    pub dos_time: u16,
+    pub dos_date: u16,
+}
+
+/// ZIP结构检查报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZipAuditReport {
+    pub file_size: u64,
+    /// 中央目录中的条目数
+    pub entry_count: usize,
+    pub central_directory_offset: Option<u64>,
+    pub central_directory_size: Option<u64>,
+    /// APK签名块的偏移与大小
+    pub signing_block: Option<(u64, u64)>,
+    pub zip64: bool,
+    pub entries: Vec<RawZipEntry>,
+    pub anomalies: Vec<ZipAnomaly>,
+    /// 异常中的最高风险等级
+    pub highest_severity: Option<Severity>,
+}
+
+impl ZipAuditReport {
+    fn push(&mut self, kind: &str, severity: Severity, entry: Option<&str>, offset: Option<u64>, message: String) {
+        self.anomalies.push(ZipAnomaly {
+            kind: kind.to_string(),
+            severity,
+            entry: entry.map(String::from),
+            offset,
+            message,
+        });
+    }
+}
+
+/// 检查APK文件的ZIP结构
+///
+/// 只定位读取结束记录、中央目录与各本地文件头，不加载条目数据，读取量计入预算。
+pub fn inspect(apk_path: &Path, budget: &ReadBudget) -> Result<ZipAuditReport, ApkParserError> {
+    budget.check()?;
+    let file = File::open(apk_path)?;
+    let len = file.metadata()?.len();
+    let source = Source::File { file, len, budget, context: apk_path.display().to_string() };
+    let mut report = ZipAuditReport { file_size: len, ..Default::default() };
+    audit(&source, &mut report)?;
+    report.highest_severity = report.anomalies.iter().map(|a| a.severity).max();
+    Ok(report)
+}
+
+/// 检查内存中ZIP数据的结构
+pub fn inspect_bytes(data: &[u8]) -> ZipAuditReport {
+    let mut report = ZipAuditReport {
+        file_size: data.len() as u64,
+        ..Default::default()
+    };
+    // 内存数据的读取不会失败
+    let _ = audit(&Source::Bytes(data), &mut report);
+    report.highest_severity = report.anomalies.iter().map(|a| a.severity).max();
+    report
+}
+
+/// 待检查的ZIP数据：内存中的字节，或按偏移读取的文件
+enum Source<'a> {
+    Bytes(&'a [u8]),
+    File { file: File, len: u64, budget: &'a ReadBudget, context: String },
+}
+
+impl Source<'_> {
+    fn len(&self) -> u64 {
+        match self {
+            Source::Bytes(data) => data.len() as u64,
+            Source::File { len, .. } => *len,
+        }
+    }
+
+    /// 读取从`offset`开始的至多`len`字节，超出数据末尾的部分被截掉
+    fn read(&self, offset: u64, len: usize) -> Result<Cow<'_, [u8]>, ApkParserError> {
+        let available = self.len().saturating_sub(offset);
+        let len = (len as u64).min(available);
+        match self {
+            Source::Bytes(data) => Ok(Cow::Borrowed(&data[offset.min(data.len() as u64) as usize..][..len as usize])),
+            Source::File { file, budget, context, .. } => {
+                budget.charge(context, len)?;
+                let mut file: &File = file;
+                let mut data = Vec::new();
+                file.seek(SeekFrom::Start(offset))?;
+                file.take(len).read_to_end(&mut data)?;
+                Ok(Cow::Owned(data))
+            }
+        }
+    }
+
+    fn u32(&self, offset: u64) -> Result<Option<u32>, ApkParserError> {
+        Ok(le_u32(&self.read(offset, 4)?, 0))
+    }
+}
+
+fn audit(source: &Source, report: &mut ZipAuditReport) -> Result<(), ApkParserError> {
+    let size = source.len();
+    // 中央目录结束记录：从后向前查找，允许其后存在多余数据
+    let tail_start = size.saturating_sub((EOCD_MIN_SIZE + ZIP_MAX_COMMENT_SIZE) as u64);
+    let tail = source.read(tail_start, (size - tail_start) as usize)?;
+    let Some(eocd) = (0..=tail.len().saturating_sub(EOCD_MIN_SIZE))
+        .rev()
+        .find(|&pos| le_u32(&tail, pos) == Some(EOCD_SIGNATURE))
+    else {
+        report.push("invalid_central_directory", Severity::Critical, None, None, "找不到ZIP中央目录结束记录".to_string());
+        return Ok(());
+    };
+    let eocd_pos = tail_start + eocd as u64;
+
+    let comment_len = le_u16(&tail, eocd + 20).unwrap_or(0) as u64;
+    let eocd_end = eocd_pos + EOCD_MIN_SIZE as u64 + comment_len;
+    if comment_len > 0 {
+        report.push("archive_comment", Severity::Info, None, Some(eocd_pos + EOCD_MIN_SIZE as u64),
+            format!("ZIP注释长度为 {} 字节", comment_len));
+    }
+    if eocd_end < size {
+        report.push("trailing_data", Severity::Medium, None, Some(eocd_end),
+            format!("中央目录结束记录之后有 {} 字节多余数据", size - eocd_end));
+    } else if eocd_end > size {
+        report.push("invalid_central_directory", Severity::High, None, Some(eocd_pos),
+            "ZIP注释长度超出文件末尾".to_string());
+    }
+
+    let mut total_entries = le_u16(&tail, eocd + 10).unwrap_or(0) as u64;
+    let mut cd_size = le_u32(&tail, eocd + 12).unwrap_or(0) as u64;
+    let mut cd_offset = le_u32(&tail, eocd + 16).unwrap_or(0) as u64;
+    // 中央目录（或ZIP64结束记录）应紧接在结束记录之前
+    let mut cd_end_expected = eocd_pos;
+
+    if let Some(locator) = eocd_pos.checked_sub(ZIP64_LOCATOR_SIZE as u64) {
+        let locator_data = source.read(locator, ZIP64_LOCATOR_SIZE)?;
+        if le_u32(&locator_data, 0) == Some(ZIP64_LOCATOR_SIGNATURE) {
+            let record_data = match le_u64(&locator_data, 8) {
+                Some(record) => Some((record, source.read(record, ZIP64_EOCD_SIZE)?)),
+                None => None,
+            };
+            match record_data.filter(|(_, data)| le_u32(data, 0) == Some(ZIP64_EOCD_SIGNATURE)) {
+                Some((record, data)) => {
+                    report.zip64 = true;
+                    total_entries = le_u64(&data, 32).unwrap_or(total_entries);
+                    cd_size = le_u64(&data, 40).unwrap_or(cd_size);
+                    cd_offset = le_u64(&data, 48).unwrap_or(cd_offset);
+                    cd_end_expected = record;
+                }
+                None => report.push("invalid_central_directory", Severity::High, None, Some(locator),
+                    "ZIP64定位记录指向的结束记录无效".to_string()),
+            }
+        }
+    }
+
+    // 在ZIP前附加数据而未修正偏移时，中央目录实际紧邻结束记录，其余偏移需整体平移
+    let mut shift = 0;
+    if source.u32(cd_offset)? != Some(CENTRAL_HEADER_SIGNATURE) {
+        if let Some(actual) = cd_end_expected.checked_sub(cd_size).filter(|actual| *actual > cd_offset) {
+            if source.u32(actual)? == Some(CENTRAL_HEADER_SIGNATURE) {
+                shift = actual - cd_offset;
+                cd_offset = actual;
+                report.push("shifted_offsets", Severity::Medium, None, Some(0),
+                    format!("ZIP内记录的偏移未计入文件开头的 {} 字节附加数据", shift));
+            }
+        }
+    }
+
+    report.central_directory_offset = Some(cd_offset);
+    report.central_directory_size = Some(cd_size);
+    if cd_offset.saturating_add(cd_size) > cd_end_expected {
+        report.push("invalid_central_directory", Severity::Critical, None, Some(cd_offset),
+            format!("中央目录（偏移 {}，大小 {}）超出结束记录位置 {}", cd_offset, cd_size, cd_end_expected));
+        return Ok(());
+    }
+    // 上面已排除越界，此处相加不会溢出
+    let cd_end = cd_offset + cd_size;
+    if cd_end < cd_end_expected {
+        report.push("central_directory_gap", Severity::Medium, None, Some(cd_end),
+            format!("中央目录与结束记录之间有 {} 字节多余数据", cd_end_expected - cd_end));
+    }
+
+    // APK签名块位于最后一个条目的数据与中央目录之间
+    let mut entries_end = cd_offset;
+    if cd_offset >= 32 {
+        let footer = source.read(cd_offset - 24, 24)?;
+        if footer.get(8..) == Some(APK_SIG_BLOCK_MAGIC) {
+            if let Some(size) = le_u64(&footer, 0).and_then(|size| size.checked_add(8)).filter(|size| *size <= cd_offset) {
+                report.signing_block = Some((cd_offset - size, size));
+                entries_end = cd_offset - size;
+            }
+        }
+    }
+
+    // 中央目录不超过文件大小，整体读取
+    let central = source.read(cd_offset, cd_size as usize)?;
+    parse_central_directory(&central, cd_offset, shift, report);
+    if report.entries.len() as u64 != total_entries {
+        report.push("entry_count_mismatch", Severity::Medium, None, Some(eocd_pos),
+            format!("结束记录声明 {} 个条目，中央目录中解析到 {} 个", total_entries, report.entries.len()));
+    }
+    report.entry_count = report.entries.len();
+
+    check_names(report);
+    check_local_headers(source, report)?;
+    check_layout(source, entries_end, report)
+}
+
+/// 解析从文件偏移`base`处读出的中央目录
+fn parse_central_directory(data: &[u8], base: u64, shift: u64, report: &mut ZipAuditReport) {
+    let mut pos = 0;
+    while pos + CENTRAL_HEADER_SIZE <= data.len() {
+        if le_u32(data, pos) != Some(CENTRAL_HEADER_SIGNATURE) {
+            report.push("invalid_central_directory", Severity::High, None, Some(base + pos as u64),
+                "中央目录中出现无效的文件头签名".to_string());
+            return;
+        }
+        let field16 = |offset: usize| le_u16(data, pos + offset).unwrap_or(0);
+        let field32 = |offset: usize| le_u32(data, pos + offset).unwrap_or(0);
+        let name_len = field16(28) as usize;
+        let extra_len = field16(30) as usize;
+        let comment_len = field16(32) as usize;
+        let name_start = pos + CENTRAL_HEADER_SIZE;
+        let Some(name) = data.get(name_start..name_start + name_len) else {
+            report.push("invalid_central_directory", Severity::High, None, Some(base + pos as u64), "中央目录条目被截断".to_string());
+            return;
+        };
+        let extra = data.get(name_start + name_len..name_start + name_len + extra_len).unwrap_or_default();
+
+        let mut entry = RawZipEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            flags: field16(8),
+            compression_method: field16(10),
+            dos_time: field16(12),
+            dos_date: field16(14),
+            crc32: field32(16),
+            compressed_size: field32(20) as u64,
+            uncompressed_size: field32(24) as u64,
+            local_header_offset: field32(42) as u64,
+            data_offset: None,
+        };
+        apply_zip64_extra(extra, &mut entry);
+        entry.local_header_offset = entry.local_header_offset.saturating_add(shift);
+        report.entries.push(entry);
+        pos = name_start + name_len + extra_len + comment_len;
+    }
+}
+
+/// 用ZIP64扩展字段替换值为0xFFFFFFFF的大小与偏移
+fn apply_zip64_extra(extra: &[u8], entry: &mut RawZipEntry) {
+    let mut pos = 0;
+    while pos + 4 <= extra.len() {
+        let id = le_u16(extra, pos).unwrap_or(0);
+        let len = le_u16(extra, pos + 2).unwrap_or(0) as usize;
+        if id == ZIP64_EXTRA_ID {
+            let mut field = pos + 4;
+            for value in [&mut entry.uncompressed_size, &mut entry.compressed_size, &mut entry.local_header_offset] {
+                if *value == 0xFFFF_FFFF {
+                    if let Some(v) = le_u64(extra, field) {
+                        *value = v;
+                    }
+                    field += 8;
+                }
+            }
+        }
+        pos += 4 + len;
+    }
+}
+
+fn check_names(report: &mut ZipAuditReport) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for entry in &report.entries {
+        *seen.entry(entry.name.clone()).or_default() += 1;
+    }
+    let mut duplicates: Vec<(String, usize)> = seen.into_iter().filter(|(_, count)| *count > 1).collect();
+    duplicates.sort();
+    for (name, count) in duplicates {
+        // 重复的清单或DEX可让安装器与分析工具看到不同的内容
+        let severity = if name == "AndroidManifest.xml" || name.ends_with(".dex") { Severity::Critical } else { Severity::High };
+        report.push("duplicate_entry", severity, Some(&name), None, format!("条目名重复出现 {} 次", count));
+    }
+
+    let traversal: Vec<String> = report.entries.iter()
+        .map(|entry| entry.name.clone())
+        .filter(|name| {
+            let normalized = name.replace('\\', "/");
+            normalized.starts_with('/') || normalized.split('/').any(|part| part == "..")
+        })
+        .collect();
+    for name in traversal {
+        report.push("path_traversal", Severity::High, Some(&name), None, "条目名包含路径穿越或绝对路径".to_string());
+    }
+}
+
+fn check_local_headers(source: &Source, report: &mut ZipAuditReport) -> Result<(), ApkParserError> {
+    let mut anomalies = Vec::new();
+    for entry in &mut report.entries {
+        let name = Some(entry.name.as_str());
+        if entry.flags & (FLAG_ENCRYPTED | FLAG_STRONG_ENCRYPTION) != 0 {
+            // APK条目不允许加密，设置加密位常用于干扰反编译工具（伪加密）
+            anomalies.push(("encryption_flag", Severity::High, name.map(String::from), None,
+                format!("中央目录设置了加密标志位 0x{:04x}", entry.flags)));
+        }
+        if !matches!(entry.compression_method, METHOD_STORED | METHOD_DEFLATED) {
+            anomalies.push(("unsupported_compression", Severity::High, name.map(String::from), None,
+                format!("压缩方法 {} 不被Android支持", entry.compression_method)));
+        } else if entry.compression_method == METHOD_STORED && entry.compressed_size != entry.uncompressed_size {
+            anomalies.push(("header_mismatch", Severity::High, name.map(String::from), None,
+                format!("未压缩条目的压缩大小 {} 与原始大小 {} 不同", entry.compressed_size, entry.uncompressed_size)));
+        }
+
+        let header = source.read(entry.local_header_offset, LOCAL_HEADER_SIZE)?;
+        if le_u32(&header, 0) != Some(LOCAL_HEADER_SIGNATURE) {
+            anomalies.push(("missing_local_header", Severity::High, name.map(String::from), Some(entry.local_header_offset),
+                "中央目录指向的位置没有本地文件头".to_string()));
+            continue;
+        }
+        let field16 = |offset: usize| le_u16(&header, offset).unwrap_or(0);
+        let field32 = |offset: usize| le_u32(&header, offset).unwrap_or(0);
+        let name_len = field16(26) as usize;
+        let extra_len = field16(28) as usize;
+        let name_start = entry.local_header_offset + LOCAL_HEADER_SIZE as u64;
+        let local_name = source.read(name_start, name_len)?;
+        // 名称被文件末尾截断时视为空
+        let local_name = if local_name.len() == name_len { String::from_utf8_lossy(&local_name).into_owned() } else { String::new() };
+        entry.data_offset = Some(name_start + (name_len + extra_len) as u64);
+
+        let mut mismatches = Vec::new();
+        if local_name != entry.name {
+            mismatches.push(format!("本地文件头中的名称为“{}”", local_name));
+        }
+        if (field16(6) ^ entry.flags) & (FLAG_ENCRYPTED | FLAG_STRONG_ENCRYPTION) != 0 {
+            mismatches.push(format!("加密标志位不同（本地 0x{:04x}，中央目录 0x{:04x}）", field16(6), entry.flags));
+        }
+        if field16(8) != entry.compression_method {
+            mismatches.push(format!("压缩方法不同（本地 {}，中央目录 {}）", field16(8), entry.compression_method));
+        }
+        // 使用数据描述符时本地文件头中的大小与CRC可以为0
+        if field16(6) & FLAG_DATA_DESCRIPTOR == 0 {
+            let (crc, compressed, uncompressed) = (field32(14), field32(18) as u64, field32(22) as u64);
+            if crc != entry.crc32 {
+                mismatches.push(format!("CRC不同（本地 {:08x}，中央目录 {:08x}）", crc, entry.crc32));
+            }
+            if compressed != 0xFFFF_FFFF && (compressed != entry.compressed_size || uncompressed != entry.uncompressed_size) {
+                mismatches.push(format!("大小不同（本地 {}/{}，中央目录 {}/{}）",
+                    compressed, uncompressed, entry.compressed_size, entry.uncompressed_size));
+            }
+        }
+        if !mismatches.is_empty() {
+            anomalies.push(("header_mismatch", Severity::High, name.map(String::from), Some(entry.local_header_offset),
+                format!("本地文件头与中央目录不一致：{}", mismatches.join("；"))));
+        }
+    }
+    for (kind, severity, entry, offset, message) in anomalies {
+        report.push(kind, severity, entry.as_deref(), offset, message);
+    }
+    Ok(())
+}
+
+/// 检查条目的排布：文件前缀、条目重叠、条目之间及最后一个条目之后的多余数据
+fn check_layout(source: &Source, entries_end: u64, report: &mut ZipAuditReport) -> Result<(), ApkParserError> {
+    let mut spans: Vec<(u64, u64, String)> = Vec::new();
+    for entry in &report.entries {
+        let Some(data_offset) = entry.data_offset else {
+            continue;
+        };
+        // 大小来自文件，可能被伪造为接近u64上限
+        let mut end = data_offset.saturating_add(entry.compressed_size);
+        if entry.flags & FLAG_DATA_DESCRIPTOR != 0 {
+            let has_signature = source.u32(end)? == Some(DATA_DESCRIPTOR_SIGNATURE);
+            end = end.saturating_add(if has_signature { 16 } else { 12 });
+        }
+        spans.push((entry.local_header_offset, end, entry.name.clone()));
+    }
+    spans.sort();
+
+    match spans.first() {
+        Some((first, _, _)) if *first > 0 => {
+            let prefix = source.read(0, (*first).min(4) as usize)?;
+            if prefix.starts_with(b"dex\n") {
+                report.push("janus_dex_prefix", Severity::Critical, None, Some(0),
+                    format!("文件开头附加了 {} 字节的DEX数据（Janus漏洞，CVE-2017-13156）", first));
+            } else {
+                report.push("prefix_data", Severity::Medium, None, Some(0),
+                    format!("第一个本地文件头之前有 {} 字节数据", first));
+            }
+        }
+        _ => {}
+    }
+
+    let mut anomalies = Vec::new();
+    let mut previous: Option<&(u64, u64, String)> = None;
+    for span in &spans {
+        if let Some((_, prev_end, prev_name)) = previous {
+            let (start, _, name) = span;
+            if start < prev_end {
+                anomalies.push(("overlapping_entries", Severity::High, name.clone(), *start,
+                    format!("与条目“{}”的数据重叠 {} 字节", prev_name, prev_end - start)));
+            } else if start > prev_end {
+                anomalies.push(("gap_data", Severity::Medium, prev_name.clone(), *prev_end,
+                    format!("该条目之后有 {} 字节未被任何条目引用的数据", start - prev_end)));
+            }
+        }
+        if previous.map_or(true, |(_, prev_end, _)| span.1 > *prev_end) {
+            previous = Some(span);
+        }
+    }
+    if let Some((_, last_end, last_name)) = previous {
+        if *last_end < entries_end {
+            anomalies.push(("gap_data", Severity::Medium, last_name.clone(), *last_end,
+                format!("最后一个条目与中央目录之间有 {} 字节多余数据", entries_end - last_end)));
+        } else if *last_end > entries_end {
+            anomalies.push(("overlapping_entries", Severity::High, last_name.clone(), *last_end,
+                "条目数据延伸进签名块或中央目录".to_string()));
+        }
+    }
+    for (kind, severity, entry, offset, message) in anomalies {
+        report.push(kind, severity, Some(&entry), Some(offset), message);
+    }
+    Ok(())
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn le_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset.checked_add(8)?).and_then(|b| b.try_into().ok()).map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// 由未压缩条目构成的ZIP，`zip64`为true时大小与偏移放在ZIP64扩展字段中，并写入ZIP64结束记录
+    fn build_zip(entries: &[(&str, &[u8])], zip64: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+        for (name, content) in entries {
+            let offset = data.len() as u32;
+            let (size_field, offset_field) = if zip64 { (0xFFFF_FFFF, 0xFFFF_FFFF) } else { (content.len() as u32, offset) };
+            let mut extra = Vec::new();
+            if zip64 {
+                extra.extend(ZIP64_EXTRA_ID.to_le_bytes());
+                extra.extend(24u16.to_le_bytes());
+                extra.extend((content.len() as u64).to_le_bytes());
+                extra.extend((content.len() as u64).to_le_bytes());
+                extra.extend((offset as u64).to_le_bytes());
+            }
+
+            data.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            data.extend([0; 4]);
+            data.extend(size_field.to_le_bytes());
+            data.extend(size_field.to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend(0u16.to_le_bytes());
+            data.extend(name.as_bytes());
+            data.extend(*content);
+
+            central.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            central.extend([20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend([0; 4]);
+            central.extend(size_field.to_le_bytes());
+            central.extend(size_field.to_le_bytes());
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend((extra.len() as u16).to_le_bytes());
+            central.extend([0; 10]);
+            central.extend(offset_field.to_le_bytes());
+            central.extend(name.as_bytes());
+            central.extend(extra);
+        }
+
+        let cd_offset = data.len();
+        let cd_size = central.len();
+        data.extend(central);
+        if zip64 {
+            let record = data.len();
+            data.extend(ZIP64_EOCD_SIGNATURE.to_le_bytes());
+            data.extend(44u64.to_le_bytes());
+            data.extend([45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            data.extend((entries.len() as u64).to_le_bytes());
+            data.extend((entries.len() as u64).to_le_bytes());
+            data.extend((cd_size as u64).to_le_bytes());
+            data.extend((cd_offset as u64).to_le_bytes());
+            data.extend(ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+            data.extend(0u32.to_le_bytes());
+            data.extend((record as u64).to_le_bytes());
+            data.extend(1u32.to_le_bytes());
+        }
+        let (count, cd_size, cd_offset) = if zip64 {
+            (0xFFFF, 0xFFFF_FFFF, 0xFFFF_FFFF)
+        } else {
+            (entries.len() as u16, cd_size as u32, cd_offset as u32)
+        };
+        data.extend(EOCD_SIGNATURE.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(count.to_le_bytes());
+        data.extend(count.to_le_bytes());
+        data.extend(cd_size.to_le_bytes());
+        data.extend(cd_offset.to_le_bytes());
+        data.extend(0u16.to_le_bytes());
+        data
+    }
+
+    fn kinds(report: &ZipAuditReport) -> Vec<&str> {
+        report.anomalies.iter().map(|anomaly| anomaly.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn well_formed_archive_has_no_anomalies() {
+        let report = inspect_bytes(&build_zip(&[("AndroidManifest.xml", b"manifest"), ("classes.dex", b"dex\n035")], false));
+        assert_eq!(report.entry_count, 2);
+        assert!(report.anomalies.is_empty(), "{:?}", report.anomalies);
+        assert_eq!(report.highest_severity, None);
+    }
+
+    #[test]
+    fn truncated_end_of_central_directory_is_reported() {
+        let data = build_zip(&[("a.txt", b"hello")], false);
+        let report = inspect_bytes(&data[..data.len() - 10]);
+        assert_eq!(kinds(&report), ["invalid_central_directory"]);
+        assert_eq!(report.highest_severity, Some(Severity::Critical));
+        assert!(report.entries.is_empty());
+
+        // 注释长度指向文件末尾之外
+        let mut data = data;
+        let len = data.len();
+        data[len - 2..].copy_from_slice(&100u16.to_le_bytes());
+        let report = inspect_bytes(&data);
+        assert!(kinds(&report).contains(&"invalid_central_directory"));
+    }
+
+    #[test]
+    fn overlapping_entries_are_reported() {
+        let mut data = build_zip(&[("a.txt", b"hello"), ("b.txt", b"world")], false);
+        // 第一个条目的大小延伸进第二个条目的本地文件头，本地头与中央目录保持一致
+        let cd_offset = le_u32(&data, data.len() - 6).unwrap() as usize;
+        for offset in [18, 22, cd_offset + 20, cd_offset + 24] {
+            put_u32(&mut data, offset, 20);
+        }
+        let report = inspect_bytes(&data);
+        let overlap = report.anomalies.iter().find(|anomaly| anomaly.kind == "overlapping_entries").unwrap();
+        assert_eq!(overlap.entry.as_deref(), Some("b.txt"));
+        assert_eq!(overlap.severity, Severity::High);
+    }
+
+    #[test]
+    fn zip64_records_are_followed() {
+        let report = inspect_bytes(&build_zip(&[("a.txt", b"hello"), ("b.txt", b"world!")], true));
+        assert!(report.zip64);
+        assert_eq!(report.entry_count, 2);
+        assert!(report.anomalies.is_empty(), "{:?}", report.anomalies);
+        let second = &report.entries[1];
+        assert_eq!(second.compressed_size, 6);
+        assert_eq!(second.local_header_offset, 40);
+        assert_eq!(second.data_offset, Some(40 + LOCAL_HEADER_SIZE as u64 + 5));
+    }
+
+    #[test]
+    fn inspect_reads_only_structures_from_file() {
+        let content = vec![0x5a; 256 * 1024];
+        let data = build_zip(&[("AndroidManifest.xml", b"manifest"), ("assets/big.bin", &content)], false);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &data).unwrap();
+
+        let budget = ReadBudget::default();
+        let report = inspect(file.path(), &budget).unwrap();
+        assert_eq!(report.file_size, data.len() as u64);
+        assert_eq!(report.entry_count, 2);
+        assert!(report.anomalies.is_empty(), "{:?}", report.anomalies);
+        assert_eq!(report.entries[1].data_offset, inspect_bytes(&data).entries[1].data_offset);
+        // 条目数据不计入读取量
+        assert!(budget.total_read() < content.len() as u64, "{}", budget.total_read());
+    }
+
+    #[test]
+    fn zip64_sizes_near_limit_do_not_overflow() {
+        let mut data = build_zip(&[("a.txt", b"hello")], true);
+        let record = data.len() - EOCD_MIN_SIZE - ZIP64_LOCATOR_SIZE - 56;
+        let cd_offset = le_u64(&data, record + 48).unwrap() as usize;
+        // 设置数据描述符标志，并把ZIP64扩展字段中的压缩大小改为接近u64上限
+        data[cd_offset + 8] |= FLAG_DATA_DESCRIPTOR as u8;
+        let extra = cd_offset + CENTRAL_HEADER_SIZE + 5;
+        data[extra + 12..extra + 20].copy_from_slice(&(u64::MAX - 4).to_le_bytes());
+        let report = inspect_bytes(&data);
+        assert_eq!(report.entries[0].compressed_size, u64::MAX - 4);
+        assert!(kinds(&report).contains(&"overlapping_entries"));
+    }
+}